@@ -1,28 +1,32 @@
+use super::loader::IncompatibleAbi;
 use super::test_highlight;
 use std::fmt::Write;
 use std::io;
-use tree_sitter::QueryError;
+use tree_sitter::{QueryError, QueryErrorKind};
 
+/// A chain of error messages, from the innermost cause to the outermost context. If the
+/// error was caused by loading a language with an unsupported ABI version, the original
+/// `IncompatibleAbi` is kept too.
 #[derive(Debug)]
-pub struct Error(pub Vec<String>);
+pub struct Error(pub Vec<String>, Option<IncompatibleAbi>);
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 impl Error {
     pub fn grammar(message: &str) -> Self {
-        Error(vec![format!("Grammar error: {}", message)])
+        Error(vec![format!("Grammar error: {}", message)], None)
     }
 
     pub fn regex(message: &str) -> Self {
-        Error(vec![format!("Regex error: {}", message)])
+        Error(vec![format!("Regex error: {}", message)], None)
     }
 
     pub fn undefined_symbol(name: &str) -> Self {
-        Error(vec![format!("Undefined symbol `{}`", name)])
+        Error(vec![format!("Undefined symbol `{}`", name)], None)
     }
 
     pub fn new(message: String) -> Self {
-        Error(vec![message])
+        Error(vec![message], None)
     }
 
     pub fn err<T>(message: String) -> Result<T> {
@@ -39,6 +43,10 @@ impl Error {
         }
     }
 
+    pub fn incompatible_abi(&self) -> Option<&IncompatibleAbi> {
+        self.1.as_ref()
+    }
+
     pub fn message(&self) -> String {
         let mut result = self.0.last().unwrap().clone();
         if self.0.len() > 1 {
@@ -53,24 +61,28 @@ impl Error {
 
 impl<'a> From<QueryError> for Error {
     fn from(error: QueryError) -> Self {
-        match error {
-            QueryError::Capture(row, c) => Error::new(format!(
+        let row = error.row + 1;
+        let message = error.message;
+        match error.kind {
+            QueryErrorKind::Capture => Error::new(format!(
                 "Query error on line {}: Invalid capture name {}",
-                row, c
+                row, message
             )),
-            QueryError::Field(row, f) => Error::new(format!(
+            QueryErrorKind::Field => Error::new(format!(
                 "Query error on line {}: Invalid field name {}",
-                row, f
+                row, message
             )),
-            QueryError::NodeType(row, t) => Error::new(format!(
+            QueryErrorKind::NodeType => Error::new(format!(
                 "Query error on line {}. Invalid node type {}",
-                row, t
+                row, message
             )),
-            QueryError::Syntax(row, l) => Error::new(format!(
+            QueryErrorKind::Syntax => Error::new(format!(
                 "Query error on line {}. Invalid syntax:\n{}",
-                row, l
+                row, message
             )),
-            QueryError::Predicate(p) => Error::new(format!("Query error: {}", p)),
+            QueryErrorKind::Predicate => {
+                Error::new(format!("Query error on line {}: {}", row, message))
+            }
         }
     }
 }
@@ -123,6 +135,12 @@ impl From<test_highlight::Failure> for Error {
     }
 }
 
+impl From<IncompatibleAbi> for Error {
+    fn from(error: IncompatibleAbi) -> Self {
+        Error(vec![error.message()], Some(error))
+    }
+}
+
 impl From<String> for Error {
     fn from(error: String) -> Self {
         Error::new(error)
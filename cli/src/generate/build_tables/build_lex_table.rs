@@ -5,7 +5,7 @@ use crate::generate::grammars::{LexicalGrammar, SyntaxGrammar};
 use crate::generate::nfa::NfaCursor;
 use crate::generate::rules::{Symbol, TokenSet};
 use crate::generate::tables::{AdvanceAction, LexState, LexTable, ParseStateId, ParseTable};
-use log::info;
+use log::debug;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
 use std::mem;
@@ -17,6 +17,7 @@ pub(crate) fn build_lex_table(
     keywords: &TokenSet,
     coincident_token_index: &CoincidentTokenIndex,
     token_conflict_map: &TokenConflictMap,
+    minimize: bool,
 ) -> (LexTable, LexTable) {
     let keyword_lex_table;
     if syntax_grammar.word_token.is_some() {
@@ -76,7 +77,9 @@ pub(crate) fn build_lex_table(
     }
 
     let mut table = builder.table;
-    minimize_lex_table(&mut table, parse_table);
+    if minimize {
+        minimize_lex_table(&mut table, parse_table);
+    }
     sort_states(&mut table, parse_table);
     (table, keyword_lex_table)
 }
@@ -122,7 +125,7 @@ impl<'a> LexTableBuilder<'a> {
         let (state_id, is_new) = self.add_state(nfa_states, eof_valid);
 
         if is_new {
-            info!(
+            debug!(
                 "entry point state: {}, tokens: {:?}",
                 state_id,
                 tokens
@@ -1,4 +1,4 @@
-use super::item::{ParseItem, ParseItemSet, ParseItemSetCore};
+use super::item::{ParseItem, ParseItemDisplay, ParseItemSet, ParseItemSetCore};
 use super::item_set_builder::ParseItemSetBuilder;
 use crate::error::{Error, Result};
 use crate::generate::grammars::{
@@ -11,11 +11,15 @@ use crate::generate::tables::{
     ProductionInfo, ProductionInfoId,
 };
 use core::ops::Range;
+use log::trace;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Write;
 use std::u32;
 
+// The number of core items shown for each state when tracing state construction.
+const TRACED_ITEM_COUNT: usize = 3;
+
 // For conflict reporting, each parse state is associated with an example
 // sequence of symbols that could lead to that parse state.
 type SymbolSequence = Vec<Symbol>;
@@ -44,6 +48,7 @@ struct ParseTableBuilder<'a> {
     parse_state_info_by_id: Vec<ParseStateInfo<'a>>,
     parse_state_queue: VecDeque<ParseStateQueueEntry>,
     non_terminal_extra_states: Vec<(Symbol, usize)>,
+    trace_states_interval: Option<usize>,
     parse_table: ParseTable,
 }
 
@@ -147,6 +152,12 @@ impl<'a> ParseTableBuilder<'a> {
                 };
 
                 let state_id = self.parse_table.states.len();
+                trace!(
+                    "add parse state {} - {} items, core {}",
+                    state_id,
+                    v.key().entries.len(),
+                    core_id
+                );
                 self.parse_state_info_by_id
                     .push((preceding_symbols.clone(), v.key().clone()));
 
@@ -164,6 +175,9 @@ impl<'a> ParseTableBuilder<'a> {
                     preceding_auxiliary_symbols: preceding_auxiliary_symbols.clone(),
                 });
                 v.insert(state_id);
+                if is_traced_state(state_id, self.trace_states_interval) {
+                    self.trace_state(state_id);
+                }
                 state_id
             }
         }
@@ -798,6 +812,35 @@ impl<'a> ParseTableBuilder<'a> {
         }
     }
 
+    fn trace_state(&self, state_id: ParseStateId) {
+        let item_set = &self.parse_state_info_by_id[state_id].1;
+        let mut line = format!("state {}:", state_id);
+        if item_set.entries.is_empty() {
+            line += " (no items)";
+        }
+        for (i, (item, _)) in item_set.entries.iter().enumerate() {
+            if i == TRACED_ITEM_COUNT {
+                write!(
+                    &mut line,
+                    " ... ({} more)",
+                    item_set.entries.len() - TRACED_ITEM_COUNT
+                )
+                .unwrap();
+                break;
+            }
+            if i > 0 {
+                line += ";";
+            }
+            write!(
+                &mut line,
+                " {}",
+                ParseItemDisplay(item, self.syntax_grammar, self.lexical_grammar)
+            )
+            .unwrap();
+        }
+        eprintln!("{}", line);
+    }
+
     fn symbol_name(&self, symbol: &Symbol) -> String {
         match symbol.kind {
             SymbolType::End => "EOF".to_string(),
@@ -817,6 +860,15 @@ impl<'a> ParseTableBuilder<'a> {
     }
 }
 
+// When states are traced at an interval, only every `interval`th state is printed,
+// starting with the first one.
+fn is_traced_state(state_id: ParseStateId, interval: Option<usize>) -> bool {
+    match interval {
+        Some(interval) => state_id % interval == 0,
+        None => false,
+    }
+}
+
 fn populate_following_tokens(
     result: &mut Vec<TokenSet>,
     grammar: &SyntaxGrammar,
@@ -858,6 +910,7 @@ pub(crate) fn build_parse_table<'a>(
     lexical_grammar: &'a LexicalGrammar,
     inlines: &'a InlinedProductionMap,
     variable_info: &'a Vec<VariableInfo>,
+    trace_states_interval: Option<usize>,
 ) -> Result<(ParseTable, Vec<TokenSet>, Vec<ParseStateInfo<'a>>)> {
     let item_set_builder = ParseItemSetBuilder::new(syntax_grammar, lexical_grammar, inlines);
     let mut following_tokens = vec![TokenSet::new(); lexical_grammar.variables.len()];
@@ -874,6 +927,7 @@ pub(crate) fn build_parse_table<'a>(
         item_set_builder,
         variable_info,
         non_terminal_extra_states: Vec::new(),
+        trace_states_interval,
         state_ids_by_item_set: HashMap::new(),
         core_ids_by_core: HashMap::new(),
         parse_state_info_by_id: Vec::new(),
@@ -890,3 +944,20 @@ pub(crate) fn build_parse_table<'a>(
 
     Ok((table, following_tokens, item_sets))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_traced_state() {
+        let traced_states = |interval| {
+            (0..10)
+                .filter(|state_id| is_traced_state(*state_id, interval))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(traced_states(None), Vec::<usize>::new());
+        assert_eq!(traced_states(Some(1)), (0..10).collect::<Vec<_>>());
+        assert_eq!(traced_states(Some(4)), vec![0, 4, 8]);
+    }
+}
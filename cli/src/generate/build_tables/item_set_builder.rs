@@ -31,6 +31,42 @@ fn find_or_push<T: Eq>(vector: &mut Vec<T>, value: T) {
     }
 }
 
+// Compute the FIRST set of each of the grammar's variables, in order. The FIRST set
+// of a non-terminal `i` is the union of the following sets:
+// * the set of all terminals that appear at the beginings of i's productions
+// * the FIRST sets of all the non-terminals that appear at the beginnings
+//   of i's productions
+//
+// Rather than computing these sets using recursion, we use an explicit stack
+// called `symbols_to_process`.
+pub(crate) fn compute_first_sets(syntax_grammar: &SyntaxGrammar) -> Vec<TokenSet> {
+    let mut result = Vec::with_capacity(syntax_grammar.variables.len());
+    let mut symbols_to_process = Vec::new();
+    let mut processed_non_terminals = HashSet::new();
+    for i in 0..syntax_grammar.variables.len() {
+        let mut first_set = TokenSet::new();
+        processed_non_terminals.clear();
+        symbols_to_process.clear();
+        symbols_to_process.push(Symbol::non_terminal(i));
+        while let Some(current_symbol) = symbols_to_process.pop() {
+            if current_symbol.is_terminal() || current_symbol.is_external() {
+                first_set.insert(current_symbol);
+            } else if processed_non_terminals.insert(current_symbol) {
+                for production in syntax_grammar.variables[current_symbol.index]
+                    .productions
+                    .iter()
+                {
+                    if let Some(step) = production.steps.first() {
+                        symbols_to_process.push(step.symbol);
+                    }
+                }
+            }
+        }
+        result.push(first_set);
+    }
+    result
+}
+
 impl<'a> ParseItemSetBuilder<'a> {
     pub fn new(
         syntax_grammar: &'a SyntaxGrammar,
@@ -68,38 +104,15 @@ impl<'a> ParseItemSetBuilder<'a> {
             result.last_sets.insert(symbol, set);
         }
 
-        // The FIRST set of a non-terminal `i` is the union of the following sets:
-        // * the set of all terminals that appear at the beginings of i's productions
-        // * the FIRST sets of all the non-terminals that appear at the beginnings
-        //   of i's productions
-        //
-        // Rather than computing these sets using recursion, we use an explicit stack
-        // called `symbols_to_process`.
+        for (i, first_set) in compute_first_sets(syntax_grammar).into_iter().enumerate() {
+            result.first_sets.insert(Symbol::non_terminal(i), first_set);
+        }
+
+        // The LAST set of a non-terminal is defined in a similar way to the FIRST set.
         let mut symbols_to_process = Vec::new();
         let mut processed_non_terminals = HashSet::new();
         for i in 0..syntax_grammar.variables.len() {
             let symbol = Symbol::non_terminal(i);
-
-            let first_set = &mut result.first_sets.entry(symbol).or_insert(TokenSet::new());
-            processed_non_terminals.clear();
-            symbols_to_process.clear();
-            symbols_to_process.push(symbol);
-            while let Some(current_symbol) = symbols_to_process.pop() {
-                if current_symbol.is_terminal() || current_symbol.is_external() {
-                    first_set.insert(current_symbol);
-                } else if processed_non_terminals.insert(current_symbol) {
-                    for production in syntax_grammar.variables[current_symbol.index]
-                        .productions
-                        .iter()
-                    {
-                        if let Some(step) = production.steps.first() {
-                            symbols_to_process.push(step.symbol);
-                        }
-                    }
-                }
-            }
-
-            // The LAST set is defined in a similar way to the FIRST set.
             let last_set = &mut result.last_sets.entry(symbol).or_insert(TokenSet::new());
             processed_non_terminals.clear();
             symbols_to_process.clear();
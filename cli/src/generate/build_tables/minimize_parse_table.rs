@@ -5,7 +5,7 @@ use crate::generate::rules::{AliasMap, Symbol, TokenSet};
 use crate::generate::tables::{
     GotoAction, ParseAction, ParseState, ParseStateId, ParseTable, ParseTableEntry,
 };
-use log::info;
+use log::debug;
 use std::collections::{HashMap, HashSet};
 use std::mem;
 
@@ -250,7 +250,7 @@ impl<'a> Minimizer<'a> {
                         let group1 = group_ids_by_state_id[*s1];
                         let group2 = group_ids_by_state_id[*s2];
                         if group1 != group2 {
-                            info!(
+                            debug!(
                                 "split states {} {} - successors for {} are split: {} {}",
                                 state1.id,
                                 state2.id,
@@ -273,7 +273,7 @@ impl<'a> Minimizer<'a> {
                         let group1 = group_ids_by_state_id[*s1];
                         let group2 = group_ids_by_state_id[*s2];
                         if group1 != group2 {
-                            info!(
+                            debug!(
                                 "split states {} {} - successors for {} are split: {} {}",
                                 state1.id,
                                 state2.id,
@@ -305,7 +305,7 @@ impl<'a> Minimizer<'a> {
         let actions1 = &entry1.actions;
         let actions2 = &entry2.actions;
         if actions1.len() != actions2.len() {
-            info!(
+            debug!(
                 "split states {} {} - differing action counts for token {}",
                 state_id1,
                 state_id2,
@@ -334,7 +334,7 @@ impl<'a> Minimizer<'a> {
                 if group1 == group2 && is_repetition1 == is_repetition2 {
                     continue;
                 } else {
-                    info!(
+                    debug!(
                         "split states {} {} - successors for {} are split: {} {}",
                         state_id1,
                         state_id2,
@@ -345,7 +345,7 @@ impl<'a> Minimizer<'a> {
                     return true;
                 }
             } else if action1 != action2 {
-                info!(
+                debug!(
                     "split states {} {} - unequal actions for {}",
                     state_id1,
                     state_id2,
@@ -368,7 +368,7 @@ impl<'a> Minimizer<'a> {
         // Do not add external tokens; they could conflict lexically with any of the state's
         // existing lookahead tokens.
         if new_token.is_external() {
-            info!(
+            debug!(
                 "split states {} {} - external token {}",
                 left_id,
                 right_id,
@@ -385,7 +385,7 @@ impl<'a> Minimizer<'a> {
             .iter()
             .any(|external| external.corresponding_internal_token == Some(new_token))
         {
-            info!(
+            debug!(
                 "split states {} {} - internal/external token {}",
                 left_id,
                 right_id,
@@ -408,7 +408,7 @@ impl<'a> Minimizer<'a> {
                             .token_conflict_map
                             .does_match_same_string(new_token.index, token.index))
                 {
-                    info!(
+                    debug!(
                         "split states {} {} - token {} conflicts with {}",
                         left_id,
                         right_id,
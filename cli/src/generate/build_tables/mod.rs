@@ -11,13 +11,16 @@ use self::build_parse_table::{build_parse_table, ParseStateInfo};
 use self::coincident_tokens::CoincidentTokenIndex;
 use self::minimize_parse_table::minimize_parse_table;
 use self::token_conflicts::TokenConflictMap;
+
+pub(crate) use self::item_set_builder::compute_first_sets;
 use crate::error::Result;
 use crate::generate::grammars::{InlinedProductionMap, LexicalGrammar, SyntaxGrammar};
 use crate::generate::nfa::{CharacterSet, NfaCursor};
 use crate::generate::node_types::VariableInfo;
 use crate::generate::rules::{AliasMap, Symbol, SymbolType, TokenSet};
 use crate::generate::tables::{LexTable, ParseAction, ParseTable, ParseTableEntry};
-use log::info;
+use crate::generate::CompileOptions;
+use log::debug;
 use std::collections::{BTreeSet, HashMap};
 
 pub(crate) fn build_tables(
@@ -26,10 +29,15 @@ pub(crate) fn build_tables(
     simple_aliases: &AliasMap,
     variable_info: &Vec<VariableInfo>,
     inlines: &InlinedProductionMap,
-    report_symbol_name: Option<&str>,
+    options: &CompileOptions,
 ) -> Result<(ParseTable, LexTable, LexTable, Option<Symbol>)> {
-    let (mut parse_table, following_tokens, parse_state_info) =
-        build_parse_table(syntax_grammar, lexical_grammar, inlines, variable_info)?;
+    let (mut parse_table, following_tokens, parse_state_info) = build_parse_table(
+        syntax_grammar,
+        lexical_grammar,
+        inlines,
+        variable_info,
+        options.trace_states,
+    )?;
     let token_conflict_map = TokenConflictMap::new(lexical_grammar, following_tokens);
     let coincident_token_index = CoincidentTokenIndex::new(&parse_table, lexical_grammar);
     let keywords = identify_keywords(
@@ -48,14 +56,16 @@ pub(crate) fn build_tables(
         &keywords,
     );
     populate_used_symbols(&mut parse_table, syntax_grammar, lexical_grammar);
-    minimize_parse_table(
-        &mut parse_table,
-        syntax_grammar,
-        lexical_grammar,
-        simple_aliases,
-        &token_conflict_map,
-        &keywords,
-    );
+    if options.optimize {
+        minimize_parse_table(
+            &mut parse_table,
+            syntax_grammar,
+            lexical_grammar,
+            simple_aliases,
+            &token_conflict_map,
+            &keywords,
+        );
+    }
     let (main_lex_table, keyword_lex_table) = build_lex_table(
         &mut parse_table,
         syntax_grammar,
@@ -63,11 +73,12 @@ pub(crate) fn build_tables(
         &keywords,
         &coincident_token_index,
         &token_conflict_map,
+        options.optimize,
     );
     populate_external_lex_states(&mut parse_table, syntax_grammar);
     mark_fragile_tokens(&mut parse_table, lexical_grammar, &token_conflict_map);
 
-    if let Some(report_symbol_name) = report_symbol_name {
+    if let Some(report_symbol_name) = &options.report_symbol_name {
         report_state_info(
             &syntax_grammar,
             &lexical_grammar,
@@ -108,7 +119,7 @@ fn populate_error_state(
             if conflicts_with_other_tokens {
                 None
             } else {
-                info!(
+                debug!(
                     "error recovery - token {} has no conflicts",
                     lexical_grammar.variables[i].name
                 );
@@ -132,7 +143,7 @@ fn populate_error_state(
                     !coincident_token_index.contains(symbol, *t)
                         && token_conflict_map.does_conflict(symbol.index, t.index)
                 }) {
-                    info!(
+                    debug!(
                         "error recovery - exclude token {} because of conflict with {}",
                         lexical_grammar.variables[i].name, lexical_grammar.variables[t.index].name
                     );
@@ -140,7 +151,7 @@ fn populate_error_state(
                 }
             }
         }
-        info!(
+        debug!(
             "error recovery - include token {}",
             lexical_grammar.variables[i].name
         );
@@ -273,7 +284,7 @@ fn identify_keywords(
                 && token_conflict_map.does_match_same_string(i, word_token.index)
                 && !token_conflict_map.does_match_different_string(i, word_token.index)
             {
-                info!(
+                debug!(
                     "Keywords - add candidate {}",
                     lexical_grammar.variables[i].name
                 );
@@ -292,7 +303,7 @@ fn identify_keywords(
                 if other_token != *token
                     && token_conflict_map.does_match_same_string(other_token.index, token.index)
                 {
-                    info!(
+                    debug!(
                         "Keywords - exclude {} because it matches the same string as {}",
                         lexical_grammar.variables[token.index].name,
                         lexical_grammar.variables[other_token.index].name
@@ -334,7 +345,7 @@ fn identify_keywords(
                     word_token.index,
                     other_index,
                 ) {
-                    info!(
+                    debug!(
                         "Keywords - exclude {} because of conflict with {}",
                         lexical_grammar.variables[token.index].name,
                         lexical_grammar.variables[other_index].name
@@ -343,7 +354,7 @@ fn identify_keywords(
                 }
             }
 
-            info!(
+            debug!(
                 "Keywords - include {}",
                 lexical_grammar.variables[token.index].name,
             );
@@ -269,6 +269,11 @@ function grammar(baseGrammar, options) {
     throw new Error("Grammar's 'name' property must not start with a digit and cannot contain non-word characters.");
   }
 
+  const version = options.version;
+  if (version != null && typeof version !== "string") {
+    throw new Error("Grammar's 'version' property must be a string.");
+  }
+
   let rules = Object.assign({}, baseGrammar.rules);
   if (options.rules) {
     if (typeof options.rules !== "object") {
@@ -361,7 +366,7 @@ function grammar(baseGrammar, options) {
     throw new Error("Grammar must have at least one rule.");
   }
 
-  return {name, word, rules, extras, conflicts, externals, inline, supertypes};
+  return {name, version, word, rules, extras, conflicts, externals, inline, supertypes};
 }
 
 function checkArguments(ruleCount, caller, callerName, suffix = '') {
@@ -1,5 +1,7 @@
 use super::nfa::Nfa;
 use super::rules::{Alias, Associativity, Rule, Symbol};
+use crate::error::{Error, Result};
+use semver::Version;
 use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -29,6 +31,7 @@ pub(crate) struct InputGrammar {
     pub variables_to_inline: Vec<String>,
     pub supertype_symbols: Vec<String>,
     pub word_token: Option<String>,
+    pub version: Option<Version>,
 }
 
 // Extracted lexical grammar
@@ -93,6 +96,7 @@ pub(crate) struct SyntaxGrammar {
     pub supertype_symbols: Vec<Symbol>,
     pub variables_to_inline: Vec<Symbol>,
     pub word_token: Option<Symbol>,
+    pub grammar_version: Option<Version>,
 }
 
 #[cfg(test)]
@@ -196,6 +200,39 @@ impl VariableType {
     }
 }
 
+impl InputGrammar {
+    /// Check that no variable's rule can only match the empty string, which usually
+    /// indicates a missing rule body. The start rule is exempt, because a grammar
+    /// whose start rule is blank is still a valid grammar for the empty document.
+    pub fn validate_no_empty_variables(&self) -> Result<()> {
+        let empty_variable_names = self
+            .variables
+            .iter()
+            .skip(1)
+            .filter(|variable| rule_is_blank(&variable.rule))
+            .map(|variable| variable.name.as_str())
+            .collect::<Vec<_>>();
+        if empty_variable_names.is_empty() {
+            Ok(())
+        } else {
+            Error::err(format!(
+                "The following rules only match the empty string: {}",
+                empty_variable_names.join(", ")
+            ))
+        }
+    }
+}
+
+fn rule_is_blank(rule: &Rule) -> bool {
+    match rule {
+        Rule::Blank => true,
+        Rule::Choice(elements) | Rule::Seq(elements) => elements.iter().all(rule_is_blank),
+        Rule::Repeat(content) => rule_is_blank(content),
+        Rule::RepeatMin { rule, .. } | Rule::Metadata { rule, .. } => rule_is_blank(rule),
+        _ => false,
+    }
+}
+
 impl LexicalGrammar {
     pub fn variable_indices_for_nfa_states<'a>(
         &'a self,
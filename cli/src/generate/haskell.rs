@@ -0,0 +1,43 @@
+const LANGUAGE_HS_TEMPLATE: &str = include_str!("./templates/Language.hs");
+const PARSER_NAME_PLACEHOLDER: &str = "PARSER_NAME";
+const MODULE_NAME_PLACEHOLDER: &str = "MODULE_NAME";
+
+// Generate a Haskell module that imports the parser's language function from C, along
+// with wrappers for looking up the language's node types and field names.
+pub(crate) fn language_hs(parser_name: &str) -> String {
+    LANGUAGE_HS_TEMPLATE
+        .replace(MODULE_NAME_PLACEHOLDER, &module_name(parser_name))
+        .replace(PARSER_NAME_PLACEHOLDER, parser_name)
+}
+
+// Convert a parser name like `c_sharp` to a Haskell module name like `CSharp`.
+pub(crate) fn module_name(parser_name: &str) -> String {
+    let mut result = String::new();
+    for word in parser_name.split('_').filter(|word| !word.is_empty()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.extend(chars);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_hs() {
+        assert_eq!(module_name("json"), "Json");
+        assert_eq!(module_name("c_sharp"), "CSharp");
+
+        let module = language_hs("c_sharp");
+        assert!(module.contains("module TreeSitter.CSharp\n"));
+        assert!(module.contains(
+            "foreign import ccall unsafe \"tree_sitter_c_sharp\"\n  tree_sitter_c_sharp :: IO (Ptr TSLanguage)"
+        ));
+        assert!(!module.contains(PARSER_NAME_PLACEHOLDER));
+        assert!(!module.contains(MODULE_NAME_PLACEHOLDER));
+    }
+}
@@ -0,0 +1,154 @@
+use serde_json::{json, Map, Value};
+
+// The types of rule objects, and the properties that each type requires in addition
+// to `type`, matching the rules that `parse_grammar` accepts.
+const RULE_TYPES: [(&str, &[&str]); 17] = [
+    ("ALIAS", &["content", "named", "value"]),
+    ("BLANK", &[]),
+    ("STRING", &["value"]),
+    ("PATTERN", &["value"]),
+    ("SYMBOL", &["name"]),
+    ("CHOICE", &["members"]),
+    ("FIELD", &["name", "content"]),
+    ("SEQ", &["members"]),
+    ("REPEAT", &["content"]),
+    ("REPEAT1", &["content"]),
+    ("REPEAT_MIN", &["min", "content"]),
+    ("PREC_DYNAMIC", &["value", "content"]),
+    ("PREC_LEFT", &["value", "content"]),
+    ("PREC_RIGHT", &["value", "content"]),
+    ("PREC", &["value", "content"]),
+    ("TOKEN", &["content"]),
+    ("IMMEDIATE_TOKEN", &["content"]),
+];
+
+// Produce a JSON Schema for the grammar object that a `grammar.js` file exports, as
+// it is serialized to `src/grammar.json`, so that editors can validate grammars.
+pub(crate) fn grammar_json_schema() -> String {
+    let rule_variants = RULE_TYPES
+        .iter()
+        .map(|(rule_type, properties)| rule_schema(rule_type, properties))
+        .collect::<Vec<_>>();
+    let rule = json!({"$ref": "#/definitions/rule"});
+    let name = json!({"type": "string"});
+
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Tree-sitter grammar",
+        "type": "object",
+        "required": ["name", "rules"],
+        "properties": {
+            "name": {
+                "description": "The name of the language",
+                "type": "string",
+                "pattern": "^[a-zA-Z_]\\w*$"
+            },
+            "rules": {
+                "description": "The grammar's rules, keyed by name. The first rule is the start rule",
+                "type": "object",
+                "minProperties": 1,
+                "additionalProperties": {"$ref": "#/definitions/rule"}
+            },
+            "extras": list_schema(
+                "The tokens that may appear anywhere in the language",
+                rule.clone()
+            ),
+            "externals": list_schema("The tokens that are produced by the external scanner", rule),
+            "conflicts": list_schema(
+                "The sets of rules that are allowed to conflict",
+                json!({"type": "array", "items": name.clone()})
+            ),
+            "inline": list_schema(
+                "The rules that are replaced with their definitions wherever they are used",
+                name.clone()
+            ),
+            "supertypes": list_schema(
+                "The hidden rules that are treated as supertypes of their alternatives",
+                name
+            ),
+            "word": {
+                "description": "The name of the token that matches keywords",
+                "type": "string"
+            },
+            "version": {
+                "description": "The version of the grammar, as a semantic version",
+                "type": "string"
+            }
+        },
+        "definitions": {
+            "rule": {"oneOf": rule_variants}
+        }
+    });
+    serde_json::to_string_pretty(&schema).unwrap() + "\n"
+}
+
+fn list_schema(description: &str, items: Value) -> Value {
+    json!({"description": description, "type": "array", "items": items})
+}
+
+fn rule_schema(rule_type: &str, required_properties: &[&str]) -> Value {
+    let mut properties = Map::new();
+    properties.insert("type".to_string(), json!({ "const": rule_type }));
+    for property in required_properties {
+        let property_schema = match *property {
+            "content" => json!({"$ref": "#/definitions/rule"}),
+            "members" => json!({"type": "array", "items": {"$ref": "#/definitions/rule"}}),
+            "named" => json!({"type": "boolean"}),
+            "min" => json!({"type": "integer", "minimum": 0}),
+            "value" if rule_type.starts_with("PREC") => json!({"type": "integer"}),
+            _ => json!({"type": "string"}),
+        };
+        properties.insert(property.to_string(), property_schema);
+    }
+    let mut required = vec!["type"];
+    required.extend_from_slice(required_properties);
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grammar_json_schema() {
+        let schema: Value = serde_json::from_str(&grammar_json_schema()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(
+            properties.keys().collect::<Vec<_>>(),
+            &[
+                "name",
+                "rules",
+                "extras",
+                "externals",
+                "conflicts",
+                "inline",
+                "supertypes",
+                "word",
+                "version"
+            ]
+        );
+
+        let rule_variants = schema["definitions"]["rule"]["oneOf"].as_array().unwrap();
+        assert_eq!(rule_variants.len(), RULE_TYPES.len());
+        let prec_left = rule_variants
+            .iter()
+            .find(|variant| variant["properties"]["type"]["const"] == "PREC_LEFT")
+            .unwrap();
+        assert_eq!(
+            prec_left,
+            &json!({
+                "type": "object",
+                "required": ["type", "value", "content"],
+                "properties": {
+                    "type": {"const": "PREC_LEFT"},
+                    "value": {"type": "integer"},
+                    "content": {"$ref": "#/definitions/rule"}
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use super::haskell::module_name;
+use std::path::PathBuf;
+
+const CARGO_TOML_TEMPLATE: &str = include_str!("./templates/cargo.toml");
+const BUILD_RS_TEMPLATE: &str = include_str!("./templates/build.rs");
+const LIB_RS_TEMPLATE: &str = include_str!("./templates/lib.rs");
+const SETUP_PY_TEMPLATE: &str = include_str!("./templates/setup.py");
+const INIT_PY_TEMPLATE: &str = include_str!("./templates/__init__.py");
+const PY_BINDING_C_TEMPLATE: &str = include_str!("./templates/py-binding.c");
+const POM_XML_TEMPLATE: &str = include_str!("./templates/pom.xml");
+const LANGUAGE_JAVA_TEMPLATE: &str = include_str!("./templates/Language.java");
+const JNI_BINDING_C_TEMPLATE: &str = include_str!("./templates/jni-binding.c");
+const PARSER_H_TEMPLATE: &str = include_str!("./templates/parser.h");
+
+const KEBAB_PARSER_NAME_PLACEHOLDER: &str = "KEBAB_PARSER_NAME";
+const CAMEL_PARSER_NAME_PLACEHOLDER: &str = "CAMEL_PARSER_NAME";
+const UPPER_PARSER_NAME_PLACEHOLDER: &str = "UPPER_PARSER_NAME";
+const PARSER_NAME_PLACEHOLDER: &str = "PARSER_NAME";
+const TREE_SITTER_VERSION_PLACEHOLDER: &str = "TREE_SITTER_VERSION";
+
+// The languages for which binding boilerplate can be generated.
+pub const BINDING_LANGUAGES: [&str; 3] = ["rust", "python", "java"];
+
+// Get the files that make up the bindings for the given language, as paths
+// relative to the grammar's repository, paired with their contents.
+pub(crate) fn binding_files(binding_language: &str, parser_name: &str) -> Vec<(PathBuf, String)> {
+    let files: Vec<(PathBuf, &str)> = match binding_language {
+        "rust" => vec![
+            (PathBuf::from("Cargo.toml"), CARGO_TOML_TEMPLATE),
+            (
+                ["bindings", "rust", "build.rs"].iter().collect(),
+                BUILD_RS_TEMPLATE,
+            ),
+            (
+                ["bindings", "rust", "lib.rs"].iter().collect(),
+                LIB_RS_TEMPLATE,
+            ),
+        ],
+        "python" => {
+            let package_path = PathBuf::from("bindings")
+                .join("python")
+                .join(format!("tree_sitter_{}", parser_name));
+            vec![
+                (PathBuf::from("setup.py"), SETUP_PY_TEMPLATE),
+                (package_path.join("__init__.py"), INIT_PY_TEMPLATE),
+                (package_path.join("binding.c"), PY_BINDING_C_TEMPLATE),
+            ]
+        }
+        "java" => {
+            let java_path = PathBuf::from("bindings").join("java");
+            let source_path = java_path.join("src").join("main");
+            let class_name = format!("{}.java", module_name(parser_name));
+            vec![
+                (java_path.join("pom.xml"), POM_XML_TEMPLATE),
+                (
+                    source_path.join("java").join("treesitter").join(class_name),
+                    LANGUAGE_JAVA_TEMPLATE,
+                ),
+                (
+                    source_path.join("c").join("binding.c"),
+                    JNI_BINDING_C_TEMPLATE,
+                ),
+            ]
+        }
+        _ => Vec::new(),
+    };
+    files
+        .into_iter()
+        .map(|(path, template)| (path, render_template(template, parser_name)))
+        .collect()
+}
+
+// Get the contents of a C header that declares the function that returns the
+// parser's language, for C and C++ code that compiles the generated `parser.c`.
+pub(crate) fn c_header(parser_name: &str) -> String {
+    render_template(PARSER_H_TEMPLATE, parser_name)
+}
+
+fn render_template(template: &str, parser_name: &str) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let minor_version = version.rsplitn(2, '.').last().unwrap_or(version);
+    template
+        .replace(
+            KEBAB_PARSER_NAME_PLACEHOLDER,
+            &parser_name.replace('_', "-"),
+        )
+        .replace(CAMEL_PARSER_NAME_PLACEHOLDER, &module_name(parser_name))
+        .replace(UPPER_PARSER_NAME_PLACEHOLDER, &parser_name.to_uppercase())
+        .replace(PARSER_NAME_PLACEHOLDER, parser_name)
+        .replace(TREE_SITTER_VERSION_PLACEHOLDER, minor_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_files() {
+        for binding_language in &BINDING_LANGUAGES {
+            let files = binding_files(binding_language, "c_sharp");
+            assert_eq!(files.len(), 3);
+            for (path, contents) in files {
+                assert!(!contents.contains(PARSER_NAME_PLACEHOLDER), "{:?}", path);
+                assert!(
+                    !contents.contains(TREE_SITTER_VERSION_PLACEHOLDER),
+                    "{:?}",
+                    path
+                );
+            }
+        }
+
+        let files = binding_files("rust", "c_sharp");
+        assert!(files[0].1.contains("name = \"tree-sitter-c-sharp\"\n"));
+        assert!(files[2].1.contains("fn tree_sitter_c_sharp() -> Language;"));
+
+        let files = binding_files("python", "c_sharp");
+        assert_eq!(
+            files[1].0,
+            PathBuf::from("bindings/python/tree_sitter_c_sharp/__init__.py")
+        );
+
+        let files = binding_files("java", "c_sharp");
+        assert_eq!(
+            files[1].0,
+            PathBuf::from("bindings/java/src/main/java/treesitter/CSharp.java")
+        );
+        assert!(files[1].1.contains("public final class CSharp {"));
+        assert!(files[2].1.contains("Java_treesitter_CSharp_language"));
+        assert!(files[0]
+            .1
+            .contains("<native.library>libtree-sitter-c-sharp.dylib</native.library>"));
+        assert!(files[0]
+            .1
+            .contains("<exists>${basedir}/../../src/scanner.c</exists>"));
+
+        assert!(binding_files("cobol", "c_sharp").is_empty());
+    }
+
+    #[test]
+    fn test_c_header() {
+        let header = c_header("c_sharp");
+        assert!(header.starts_with("#ifndef TREE_SITTER_C_SHARP_H_\n"));
+        assert!(header.contains("#include \"tree_sitter/api.h\"\n"));
+        assert!(header.contains("extern const TSLanguage *tree_sitter_c_sharp(void);\n"));
+        assert!(!header.contains(PARSER_NAME_PLACEHOLDER));
+    }
+}
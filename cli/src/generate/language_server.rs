@@ -0,0 +1,189 @@
+use super::grammars::InputGrammar;
+use super::node_types::NodeInfoJSON;
+use super::word_token::grammar_keywords;
+use crate::error::Result;
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+
+/// The data about a grammar that a language server needs in order to provide
+/// features like completion, hover descriptions and semantic highlighting, gathered
+/// from the grammar, its `node-types.json` file and its highlight query.
+#[derive(Debug, Serialize)]
+pub struct LanguageServerGrammarInfo {
+    pub name: String,
+    /// The contents of the grammar's `node-types.json` file.
+    pub node_types: serde_json::Value,
+    /// The names of every field in the grammar, sorted.
+    pub field_names: Vec<String>,
+    /// The subtypes of each supertype, keyed by the supertype's name.
+    pub supertypes: BTreeMap<String, Vec<String>>,
+    /// The names of the captures in the highlight query, in the order that they
+    /// first appear.
+    pub highlight_capture_names: Vec<String>,
+    /// The keyword-like strings in the grammar, sorted.
+    pub keywords: Vec<String>,
+}
+
+impl LanguageServerGrammarInfo {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap() + "\n"
+    }
+}
+
+pub(crate) fn export_for_language_server(
+    grammar: &InputGrammar,
+    node_types_json: &str,
+    highlights_query: Option<&str>,
+) -> Result<LanguageServerGrammarInfo> {
+    let node_types: Vec<NodeInfoJSON> = serde_json::from_str(node_types_json)?;
+
+    let mut field_names = node_types
+        .iter()
+        .flat_map(|node_type| node_type.fields.iter().flat_map(|fields| fields.keys()))
+        .cloned()
+        .collect::<Vec<_>>();
+    field_names.sort_unstable();
+    field_names.dedup();
+
+    let supertypes = node_types
+        .iter()
+        .filter_map(|node_type| {
+            let subtypes = node_type.subtypes.as_ref()?;
+            let subtype_names = subtypes.iter().map(|subtype| subtype.kind.clone());
+            Some((node_type.kind.clone(), subtype_names.collect()))
+        })
+        .collect();
+
+    Ok(LanguageServerGrammarInfo {
+        name: grammar.name.clone(),
+        node_types: serde_json::from_str(node_types_json)?,
+        field_names,
+        supertypes,
+        highlight_capture_names: highlights_query.map(capture_names).unwrap_or_default(),
+        keywords: grammar_keywords(grammar)
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    })
+}
+
+// Find the names of the captures in a query's source, without compiling the query,
+// skipping over its comments and string literals.
+fn capture_names(source: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '@' => {
+                let mut name = String::new();
+                while let Some(c) = chars.peek() {
+                    if c.is_alphanumeric() || "_-.".contains(*c) {
+                        name.push(*c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if !name.is_empty() && !result.contains(&name) {
+                    result.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::grammars::Variable;
+    use crate::generate::rules::Rule;
+
+    #[test]
+    fn test_export_for_language_server() {
+        let grammar = InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::seq(vec![Rule::string("let"), Rule::named("identifier")]),
+                        Rule::seq(vec![Rule::string("if"), Rule::string("(")]),
+                        Rule::string("let"),
+                    ])),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        };
+        let node_types_json = r#"[
+            {
+                "type": "_expression",
+                "named": true,
+                "subtypes": [{"type": "call", "named": true}, {"type": "identifier", "named": true}]
+            },
+            {
+                "type": "call",
+                "named": true,
+                "fields": {
+                    "function": {"multiple": false, "required": true, "types": []},
+                    "arguments": {"multiple": false, "required": true, "types": []}
+                }
+            },
+            {
+                "type": "assignment",
+                "named": true,
+                "fields": {
+                    "left": {"multiple": false, "required": true, "types": []},
+                    "function": {"multiple": false, "required": false, "types": []}
+                }
+            }
+        ]"#;
+        let highlights_query = concat!(
+            "; A comment with an @ignored capture name\n",
+            "(call function: (identifier) @function.call)\n",
+            "((identifier) @variable (#eq? @variable \"@not-a-capture\"))\n",
+            "(identifier) @variable\n",
+        );
+
+        let info =
+            export_for_language_server(&grammar, node_types_json, Some(highlights_query)).unwrap();
+        assert_eq!(info.name, "the_language");
+        assert_eq!(info.node_types.as_array().unwrap().len(), 3);
+        assert_eq!(info.field_names, &["arguments", "function", "left"]);
+        assert_eq!(
+            info.supertypes.into_iter().collect::<Vec<_>>(),
+            &[(
+                "_expression".to_string(),
+                vec!["call".to_string(), "identifier".to_string()]
+            )]
+        );
+        assert_eq!(info.highlight_capture_names, &["function.call", "variable"]);
+        assert_eq!(info.keywords, &["if", "let"]);
+    }
+}
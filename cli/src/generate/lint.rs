@@ -0,0 +1,235 @@
+use super::grammars::InputGrammar;
+use super::prepare_grammar::{find_unused_rules, prepare_grammar};
+use super::word_token::{token_pattern, word_token_candidates};
+use super::{generate_parser_for_grammar_with_opts, CompileOptions};
+use regex::Regex;
+use serde_derive::Serialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+/// A problem that `tree-sitter lint-grammar` found in a grammar.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    /// The name of the lint that produced the finding, such as `unused-rule`.
+    pub lint: &'static str,
+    pub severity: LintSeverity,
+    /// The name of the grammar rule that the finding is about, if any.
+    pub rule: Option<String>,
+    /// The 1-based line of the grammar file where the rule is defined, if the
+    /// definition could be found.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// The findings of every lint for a single grammar file.
+#[derive(Debug, Serialize)]
+pub struct LintReport {
+    pub path: String,
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == LintSeverity::Error)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintSeverity::Warning => write!(f, "warning"),
+            LintSeverity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in &self.findings {
+            write!(f, "{}", self.path)?;
+            if let Some(line) = finding.line {
+                write!(f, ":{}", line)?;
+            }
+            writeln!(
+                f,
+                ": {}[{}]: {}",
+                finding.severity, finding.lint, finding.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Run every lint against the grammar. If the grammar can't be generated at all,
+// the generation error is the only finding, because the other lints rely on the
+// grammar's rules being valid.
+pub(crate) fn lint_grammar(grammar: &InputGrammar) -> Vec<LintFinding> {
+    if let Err(error) = check_generation(grammar) {
+        return vec![LintFinding {
+            lint: "invalid-grammar",
+            severity: LintSeverity::Error,
+            rule: None,
+            line: None,
+            message: error.trim_end().to_string(),
+        }];
+    }
+
+    let mut result = Vec::new();
+    for name in find_unused_rules(grammar).unwrap_or_default() {
+        result.push(LintFinding {
+            lint: "unused-rule",
+            severity: LintSeverity::Warning,
+            message: format!(
+                "The rule `{}` can't be reached from the start rule, the extras, or the word token",
+                name
+            ),
+            rule: Some(name),
+            line: None,
+        });
+    }
+
+    if grammar.word_token.is_none() {
+        if let Some(candidate) = word_token_candidates(grammar).into_iter().next() {
+            result.push(LintFinding {
+                lint: "missing-word-token",
+                severity: LintSeverity::Warning,
+                rule: None,
+                line: None,
+                message: format!(
+                    "The grammar has no word token. Setting `word: $ => $.{}` would make keywords faster to lex and prevent them from matching the start of identifiers",
+                    candidate
+                ),
+            });
+        }
+    }
+
+    for variable in &grammar.variables {
+        let matches_empty_string = token_pattern(&variable.rule)
+            .and_then(|pattern| Regex::new(&format!("^(?:{})$", pattern)).ok())
+            .map(|regex| regex.is_match(""))
+            .unwrap_or(false);
+        if matches_empty_string {
+            result.push(LintFinding {
+                lint: "empty-token",
+                severity: LintSeverity::Warning,
+                rule: Some(variable.name.clone()),
+                line: None,
+                message: format!(
+                    "The token `{}` matches the empty string. Its pattern should require at least one character",
+                    variable.name
+                ),
+            });
+        }
+    }
+    result
+}
+
+fn check_generation(grammar: &InputGrammar) -> Result<(), String> {
+    let (syntax_grammar, lexical_grammar, inlines, simple_aliases) =
+        prepare_grammar(grammar).map_err(|e| e.message())?;
+    let options = CompileOptions::builder().optimize(false).build();
+    generate_parser_for_grammar_with_opts(
+        &grammar.name,
+        syntax_grammar,
+        lexical_grammar,
+        inlines,
+        simple_aliases,
+        &options,
+    )
+    .map_err(|e| e.message())?;
+    Ok(())
+}
+
+// Find the 1-based line where the rule with the given name is defined in the
+// source of a `grammar.js` or `grammar.json` file.
+pub(crate) fn rule_definition_line(source: &str, name: &str) -> Option<usize> {
+    let regex = Regex::new(&format!(r#"^\s*["']?{}["']?\s*:"#, regex::escape(name))).unwrap();
+    source
+        .lines()
+        .position(|line| regex.is_match(line))
+        .map(|index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::grammars::Variable;
+    use crate::generate::rules::Rule;
+
+    #[test]
+    fn test_lint_grammar() {
+        let mut grammar = InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::seq(vec![Rule::string("var"), Rule::named("identifier")]),
+                        Rule::named("spaces"),
+                    ])),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-zA-Z_]\\w*")),
+                Variable::named("spaces", Rule::pattern("_*")),
+                Variable::named("number", Rule::pattern("\\d+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        };
+
+        let findings = lint_grammar(&grammar);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|finding| (finding.lint, finding.rule.as_ref().map(String::as_str)))
+                .collect::<Vec<_>>(),
+            &[
+                ("unused-rule", Some("number")),
+                ("missing-word-token", None),
+                ("empty-token", Some("spaces")),
+            ]
+        );
+        assert!(findings
+            .iter()
+            .all(|finding| finding.severity == LintSeverity::Warning));
+
+        // A syntactic rule that matches the empty string makes generation fail.
+        grammar.variables.push(Variable::named(
+            "nothing",
+            Rule::choice(vec![Rule::string("x"), Rule::Blank]),
+        ));
+        grammar.variables[0].rule = Rule::seq(vec![Rule::named("nothing"), Rule::string("y")]);
+        let findings = lint_grammar(&grammar);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].lint, "invalid-grammar");
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+        assert!(findings[0]
+            .message
+            .contains("`nothing` matches the empty string"));
+    }
+
+    #[test]
+    fn test_rule_definition_line() {
+        let source = "module.exports = grammar({\n  rules: {\n    program: $ => repeat($.item),\n\n    item: $ => 'x',\n    \"quoted\": $ => 'y',\n  }\n});\n";
+        assert_eq!(rule_definition_line(source, "program"), Some(3));
+        assert_eq!(rule_definition_line(source, "item"), Some(5));
+        assert_eq!(rule_definition_line(source, "quoted"), Some(6));
+        assert_eq!(rule_definition_line(source, "it"), None);
+    }
+}
@@ -0,0 +1,129 @@
+use super::grammars::InputGrammar;
+use super::rules::Rule;
+use crate::error::{Error, Result};
+
+// Render a Mermaid flowchart of the grammar's variables, with an arrow from each
+// variable to each of the variables and external tokens that it refers to.
+// External tokens are drawn as parallelograms.
+pub(crate) fn render_mermaid_diagram(grammar: &InputGrammar) -> Result<String> {
+    let mut result = "flowchart TD\n".to_string();
+    for (i, variable) in grammar.variables.iter().enumerate() {
+        result += &format!("  v{}[\"{}\"]\n", i, escape(&variable.name));
+    }
+    for (i, token) in grammar.external_tokens.iter().enumerate() {
+        let name = match token {
+            Rule::NamedSymbol(name) => name.as_str(),
+            _ => "",
+        };
+        result += &format!("  e{}[/\"{}\"/]\n", i, escape(name));
+    }
+    for (i, variable) in grammar.variables.iter().enumerate() {
+        let mut names = Vec::new();
+        add_referenced_names(&variable.rule, &mut names);
+        let mut targets = Vec::new();
+        for name in names {
+            let target = node_id(grammar, name).ok_or_else(|| Error::undefined_symbol(name))?;
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for target in targets {
+            result += &format!("  v{} --> {}\n", i, target);
+        }
+    }
+    Ok(result)
+}
+
+// Find the flowchart node for a name, preferring variables over external tokens
+// like symbol interning does.
+fn node_id(grammar: &InputGrammar, name: &str) -> Option<String> {
+    if let Some(i) = grammar.variables.iter().position(|v| v.name == name) {
+        return Some(format!("v{}", i));
+    }
+    grammar
+        .external_tokens
+        .iter()
+        .position(|token| *token == Rule::NamedSymbol(name.to_string()))
+        .map(|i| format!("e{}", i))
+}
+
+fn add_referenced_names<'a>(rule: &'a Rule, result: &mut Vec<&'a str>) {
+    match rule {
+        Rule::NamedSymbol(name) => result.push(name),
+        Rule::Choice(members) | Rule::Seq(members) => {
+            for member in members {
+                add_referenced_names(member, result);
+            }
+        }
+        Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+            add_referenced_names(rule, result)
+        }
+        _ => {}
+    }
+}
+
+// Mermaid labels can't contain quotes, but they can contain HTML entity codes.
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::grammars::Variable;
+
+    #[test]
+    fn test_mermaid_diagram() {
+        let grammar = InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("_statement"),
+                        Rule::named("identifier"),
+                    ])),
+                ),
+                Variable::named(
+                    "_statement",
+                    Rule::seq(vec![
+                        Rule::field("callee".to_string(), Rule::named("identifier")),
+                        Rule::string("<"),
+                        Rule::named("heredoc"),
+                        Rule::named("identifier"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: vec![Rule::named("heredoc")],
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        };
+
+        assert_eq!(
+            render_mermaid_diagram(&grammar).unwrap(),
+            concat!(
+                "flowchart TD\n",
+                "  v0[\"program\"]\n",
+                "  v1[\"_statement\"]\n",
+                "  v2[\"identifier\"]\n",
+                "  e0[/\"heredoc\"/]\n",
+                "  v0 --> v1\n",
+                "  v0 --> v2\n",
+                "  v1 --> v2\n",
+                "  v1 --> e0\n",
+            )
+        );
+
+        let mut grammar = grammar;
+        grammar.variables[2].rule = Rule::named("number");
+        assert_eq!(
+            render_mermaid_diagram(&grammar).unwrap_err().message(),
+            "Undefined symbol `number`"
+        );
+    }
+}
@@ -1,115 +1,309 @@
 mod build_tables;
 mod dedup;
 mod grammars;
+mod haskell;
+mod json_schema;
+mod language_bindings;
+mod language_server;
+mod lint;
+mod mermaid_diagram;
 mod nfa;
 mod node_types;
 mod npm_files;
 pub mod parse_grammar;
 mod prepare_grammar;
+mod railroad_diagrams;
 mod render;
 mod rules;
 mod tables;
+mod typescript;
+mod word_token;
 
-use self::build_tables::build_tables;
-use self::grammars::{InlinedProductionMap, LexicalGrammar, SyntaxGrammar};
+use self::build_tables::{build_tables, compute_first_sets};
+use self::grammars::{InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar};
+use self::json_schema::grammar_json_schema;
+use self::language_bindings::{binding_files, c_header};
+use self::language_server::export_for_language_server;
+use self::lint::{lint_grammar, rule_definition_line};
+use self::mermaid_diagram::render_mermaid_diagram;
+use self::node_types::{find_node_type_inconsistencies, NodeInfoJSON};
 use self::parse_grammar::parse_grammar;
-use self::prepare_grammar::prepare_grammar;
+use self::prepare_grammar::{
+    find_references, prepare_grammar, prepare_grammar_with_opts, summarize_variables,
+};
+use self::railroad_diagrams::render_railroad_diagrams;
 use self::render::render_c_code;
-use self::rules::AliasMap;
+use self::rules::{AliasMap, SymbolType};
+use self::typescript::generate_typescript_declarations;
+use self::word_token::word_token_candidates;
 use crate::error::{Error, Result};
 use lazy_static::lazy_static;
+use log::info;
 use regex::{Regex, RegexBuilder};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Instant;
+use tree_sitter::Language;
+
+pub use self::language_bindings::BINDING_LANGUAGES;
+pub use self::language_server::LanguageServerGrammarInfo;
+pub use self::lint::{LintFinding, LintReport, LintSeverity};
 
 lazy_static! {
     static ref JSON_COMMENT_REGEX: Regex = RegexBuilder::new("^\\s*//.*")
         .multi_line(true)
         .build()
         .unwrap();
+    static ref TOKEN_COUNT_REGEX: Regex = Regex::new(r"\bTOKEN_COUNT\s*=\s*(\d+)").unwrap();
 }
 
-const NEW_HEADER_PARTS: [&'static str; 2] = [
-    "
+// The oldest ABI version for which parsers can still be generated.
+pub const MIN_ABI_VERSION: usize = 10;
+
+// Parts of `parser.h` that were introduced after `MIN_ABI_VERSION`, paired with the
+// ABI version that introduced them. They are removed when generating for an older ABI.
+const NEW_HEADER_PARTS: [(usize, &'static str); 4] = [
+    (
+        11,
+        "
   uint32_t large_state_count;
   const uint16_t *small_parse_table;
   const uint32_t *small_parse_table_map;
   const TSSymbol *public_symbol_map;",
-    "
+    ),
+    (
+        12,
+        "
+  const char *name;
+  const char *grammar_version;
+  uint32_t state_count;
+  const TSFieldMapSlice *node_type_field_slices;
+  const TSNodeTypeField *node_type_fields;
+  const TSFieldMapSlice *node_type_child_field_slices;
+  const TSFieldId *node_type_child_fields;",
+    ),
+    (
+        12,
+        "
+
+typedef struct {
+  TSFieldId field_id;
+  bool multiple;
+} TSNodeTypeField;",
+    ),
+    (
+        11,
+        "
 #define SMALL_STATE(id) id - LARGE_STATE_COUNT
 ",
+    ),
 ];
 
 struct GeneratedParser {
     c_code: String,
     node_types_json: String,
+    symbol_map_json: String,
+}
+
+/// The options that control how a parser is generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// The ABI version of the generated parser.
+    pub abi_version: usize,
+    /// The name of a rule whose parse states should be reported while building the
+    /// parse table. An empty name reports the states of every rule.
+    pub report_symbol_name: Option<String>,
+    /// If set, each newly discovered parse state whose id is a multiple of this
+    /// interval is printed while the parse table is built.
+    pub trace_states: Option<usize>,
+    /// The directory where the generated source files are written. By default, this
+    /// is the `src` directory within the grammar's repository.
+    pub output_dir: Option<PathBuf>,
+    /// Whether to minimize the parse table and the lex table. Skipping this makes
+    /// generation faster, but the resulting parser is larger and slower, so it
+    /// should not be used in production.
+    pub optimize: bool,
+    /// Whether to merge hidden rules whose definitions are identical before building
+    /// the parse table. This reduces the number of parse states for some grammars.
+    pub minimize_grammar: bool,
+    /// Whether to check that the `TOKEN_COUNT` constant in the grammar's external
+    /// scanner matches the number of external tokens in the grammar.
+    pub validate_externals: bool,
+    /// Whether to choose a word token for grammars that don't specify one, from the
+    /// rules that look like identifiers.
+    pub infer_word_token: bool,
+    /// If set, a JSON file that maps each symbol's numeric id to its name is written
+    /// to this path.
+    pub symbol_map_path: Option<PathBuf>,
+}
+
+/// A builder for [CompileOptions].
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptionsBuilder {
+    options: CompileOptions,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            abi_version: tree_sitter::LANGUAGE_VERSION,
+            report_symbol_name: None,
+            trace_states: None,
+            output_dir: None,
+            optimize: true,
+            minimize_grammar: false,
+            validate_externals: false,
+            infer_word_token: false,
+            symbol_map_path: None,
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn builder() -> CompileOptionsBuilder {
+        CompileOptionsBuilder::default()
+    }
+}
+
+impl CompileOptionsBuilder {
+    pub fn abi_version(mut self, abi_version: usize) -> Self {
+        self.options.abi_version = abi_version;
+        self
+    }
+
+    pub fn report_symbol_name(mut self, name: Option<&str>) -> Self {
+        self.options.report_symbol_name = name.map(String::from);
+        self
+    }
+
+    pub fn trace_states(mut self, interval: Option<usize>) -> Self {
+        self.options.trace_states = interval;
+        self
+    }
+
+    pub fn output_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.output_dir = Some(path.into());
+        self
+    }
+
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.options.optimize = optimize;
+        self
+    }
+
+    pub fn minimize_grammar(mut self, minimize_grammar: bool) -> Self {
+        self.options.minimize_grammar = minimize_grammar;
+        self
+    }
+
+    pub fn validate_externals(mut self, validate_externals: bool) -> Self {
+        self.options.validate_externals = validate_externals;
+        self
+    }
+
+    pub fn infer_word_token(mut self, infer_word_token: bool) -> Self {
+        self.options.infer_word_token = infer_word_token;
+        self
+    }
+
+    pub fn symbol_map_path(mut self, path: Option<&Path>) -> Self {
+        self.options.symbol_map_path = path.map(PathBuf::from);
+        self
+    }
+
+    pub fn build(self) -> CompileOptions {
+        self.options
+    }
 }
 
 pub fn generate_parser_in_directory(
     repo_path: &PathBuf,
     grammar_path: Option<&str>,
-    next_abi: bool,
-    report_symbol_name: Option<&str>,
+    options: &CompileOptions,
 ) -> Result<()> {
-    let src_path = repo_path.join("src");
+    let abi_version = options.abi_version;
+    if !(MIN_ABI_VERSION..=tree_sitter::LANGUAGE_VERSION).contains(&abi_version) {
+        return Error::err(format!(
+            "ABI version {} is not supported. Supported versions are {} through {}",
+            abi_version,
+            MIN_ABI_VERSION,
+            tree_sitter::LANGUAGE_VERSION
+        ));
+    }
+    if abi_version < tree_sitter::LANGUAGE_VERSION {
+        eprintln!(
+            "Warning: ABI version {} is deprecated. The current ABI version is {}",
+            abi_version,
+            tree_sitter::LANGUAGE_VERSION
+        );
+    }
+
+    let src_path = options
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| repo_path.join("src"));
     let header_path = src_path.join("tree_sitter");
 
     // Ensure that the output directories exist.
     fs::create_dir_all(&src_path)?;
     fs::create_dir_all(&header_path)?;
 
-    // Read the grammar.json.
-    let grammar_json;
-    match grammar_path {
-        Some(path) => {
-            grammar_json = load_grammar_file(path.as_ref())?;
-        }
-        None => {
-            let grammar_js_path = grammar_path.map_or(repo_path.join("grammar.js"), |s| s.into());
-            grammar_json = load_grammar_file(&grammar_js_path)?;
-            fs::write(&src_path.join("grammar.json"), &grammar_json)?;
-        }
-    }
+    // Read the grammar file.
+    let grammar_file_path = find_grammar_file(repo_path, grammar_path);
 
     // Parse and preprocess the grammar.
-    let input_grammar = parse_grammar(&grammar_json)?;
+    let start_time = Instant::now();
+    let grammar_json = load_grammar_file(&grammar_file_path)?;
+    if grammar_path.is_none() {
+        fs::write(&src_path.join("grammar.json"), &grammar_json)?;
+    }
+    let mut input_grammar = parse_grammar(&grammar_json)?;
+    if options.validate_externals {
+        validate_external_token_count(&repo_path.join("src"), input_grammar.external_tokens.len())?;
+    }
+    if options.infer_word_token && input_grammar.word_token.is_none() {
+        input_grammar.word_token = infer_word_token(&input_grammar);
+    }
     let (syntax_grammar, lexical_grammar, inlines, simple_aliases) =
-        prepare_grammar(&input_grammar)?;
+        prepare_grammar_with_opts(&input_grammar, options.minimize_grammar)?;
+    info!(
+        "load and prepare grammar - {} ms",
+        start_time.elapsed().as_millis()
+    );
     let language_name = input_grammar.name;
 
     // Generate the parser and related files.
     let GeneratedParser {
         c_code,
         node_types_json,
+        symbol_map_json,
     } = generate_parser_for_grammar_with_opts(
         &language_name,
         syntax_grammar,
         lexical_grammar,
         inlines,
         simple_aliases,
-        next_abi,
-        report_symbol_name,
+        options,
     )?;
 
     write_file(&src_path.join("parser.c"), c_code)?;
     write_file(&src_path.join("node-types.json"), node_types_json)?;
+    if let Some(symbol_map_path) = &options.symbol_map_path {
+        write_file(symbol_map_path, symbol_map_json)?;
+    }
 
-    if next_abi {
-        write_file(&header_path.join("parser.h"), tree_sitter::PARSER_HEADER)?;
-    } else {
-        let mut header = tree_sitter::PARSER_HEADER.to_string();
-
-        for part in &NEW_HEADER_PARTS {
+    let mut header = tree_sitter::PARSER_HEADER.to_string();
+    for (version, part) in &NEW_HEADER_PARTS {
+        if abi_version < *version {
             let pos = header
                 .find(part)
                 .expect("Missing expected part of parser.h header");
             header.replace_range(pos..(pos + part.len()), "");
         }
-
-        write_file(&header_path.join("parser.h"), header)?;
     }
+    write_file(&header_path.join("parser.h"), header)?;
 
     ensure_file(&repo_path.join("index.js"), || {
         npm_files::index_js(&language_name)
@@ -124,6 +318,343 @@ pub fn generate_parser_in_directory(
     Ok(())
 }
 
+// Choose the most referenced of the grammar's identifier-like rules as its word
+// token, warning about the choice when there are several candidates.
+fn infer_word_token(grammar: &InputGrammar) -> Option<String> {
+    let candidates = word_token_candidates(grammar);
+    if candidates.len() > 1 {
+        eprintln!(
+            "Warning: Found multiple candidates for the word token: {}. Using `{}`",
+            candidates.join(", "),
+            candidates[0]
+        );
+    }
+    candidates.into_iter().next()
+}
+
+// Describe every place where the grammar's rules refer directly to the rule with
+// the given name, one reference per line.
+pub fn show_references_in_directory(
+    repo_path: &Path,
+    grammar_path: Option<&str>,
+    name: &str,
+) -> Result<String> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    let mut result = String::new();
+    for (rule_name, context) in find_references(&input_grammar, name)? {
+        result += &format!("{}: {}\n", rule_name, context);
+    }
+    Ok(result)
+}
+
+// Describe each of the grammar's rules as a table, with the rule's index, name and
+// kind, the start of its definition, the number of rules that refer to it, the
+// minimum length of its text, or `-` if it can't match any finite text, how deeply
+// its definition is nested, and the number of strings and patterns in it.
+pub fn grammar_info_in_directory(repo_path: &Path, grammar_path: Option<&str>) -> Result<String> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    let summaries = summarize_variables(&input_grammar)?;
+    let name_width = summaries
+        .iter()
+        .map(|summary| summary.name.chars().count())
+        .chain(Some("name".len()))
+        .max()
+        .unwrap();
+    let rule_width = summaries
+        .iter()
+        .map(|summary| summary.rule_summary.chars().count())
+        .chain(Some("rule".len()))
+        .max()
+        .unwrap();
+    let mut result = format!(
+        "{:<5}  {:<name_width$}  {:<9}  {:<rule_width$}  {:<10}  {:<10}  {:<5}  {}\n",
+        "index",
+        "name",
+        "kind",
+        "rule",
+        "references",
+        "min length",
+        "depth",
+        "terminals",
+        name_width = name_width,
+        rule_width = rule_width
+    );
+    for (i, summary) in summaries.iter().enumerate() {
+        result += &format!(
+            "{:<5}  {:<name_width$}  {:<9}  {:<rule_width$}  {:<10}  {:<10}  {:<5}  {}\n",
+            i,
+            summary.name,
+            format!("{:?}", summary.kind),
+            summary.rule_summary,
+            summary.reference_count,
+            summary
+                .minimum_length
+                .map_or("-".to_string(), |length| length.to_string()),
+            summary.depth,
+            summary.terminal_count,
+            name_width = name_width,
+            rule_width = rule_width
+        );
+    }
+    Ok(result)
+}
+
+// Describe the FIRST set of each of the grammar's variables, after the grammar has
+// been prepared for generating a parser, one variable per line.
+pub fn first_sets_in_directory(repo_path: &Path, grammar_path: Option<&str>) -> Result<String> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    let (syntax_grammar, lexical_grammar, _, _) = prepare_grammar(&input_grammar)?;
+    Ok(render_first_sets(&syntax_grammar, &lexical_grammar))
+}
+
+fn render_first_sets(syntax_grammar: &SyntaxGrammar, lexical_grammar: &LexicalGrammar) -> String {
+    let mut result = String::new();
+    for (variable, first_set) in syntax_grammar
+        .variables
+        .iter()
+        .zip(compute_first_sets(syntax_grammar))
+    {
+        let token_names = first_set
+            .iter()
+            .map(|symbol| match symbol.kind {
+                SymbolType::External => syntax_grammar.external_tokens[symbol.index].name.as_str(),
+                SymbolType::End => "end",
+                _ => lexical_grammar.variables[symbol.index].name.as_str(),
+            })
+            .collect::<Vec<_>>();
+        if token_names.is_empty() {
+            result += &format!("{}: {{}}\n", variable.name);
+        } else {
+            result += &format!("{}: {{ {} }}\n", variable.name, token_names.join(", "));
+        }
+    }
+    result
+}
+
+// Run every lint against the grammar. The findings about a rule are given the line
+// of the rule's definition.
+pub fn lint_grammar_in_directory(
+    repo_path: &Path,
+    grammar_path: Option<&str>,
+) -> Result<LintReport> {
+    // Node resolves relative paths against its module directory, not the current
+    // directory.
+    let grammar_file_path = repo_path.join(find_grammar_file(repo_path, grammar_path));
+    let input_grammar = load_input_grammar(repo_path, grammar_file_path.to_str())?;
+    let mut findings = lint_grammar(&input_grammar);
+    let source = fs::read_to_string(&grammar_file_path)?;
+    for finding in &mut findings {
+        if let Some(rule) = &finding.rule {
+            finding.line = rule_definition_line(&source, rule);
+        }
+    }
+    Ok(LintReport {
+        path: grammar_file_path.to_string_lossy().to_string(),
+        findings,
+    })
+}
+
+// Write an HTML page to the given path, with a railroad diagram for each of the
+// grammar's rules.
+pub fn generate_railroad_diagrams_in_directory(
+    repo_path: &Path,
+    grammar_path: Option<&str>,
+    output_path: &Path,
+) -> Result<()> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    write_file(output_path, render_railroad_diagrams(&input_grammar)?)
+}
+
+// Write a Mermaid flowchart of the references between the grammar's rules to the
+// given path. In a Markdown file, the flowchart is placed in a `mermaid` code block,
+// so that it is rendered as a diagram on GitHub. An existing Markdown file keeps
+// everything outside of the block between its `<!-- mermaid:start -->` and
+// `<!-- mermaid:end -->` markers, and is left alone if it has no such block.
+pub fn generate_mermaid_diagram_in_directory(
+    repo_path: &Path,
+    grammar_path: Option<&str>,
+    output_path: &Path,
+) -> Result<()> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    let mut diagram = render_mermaid_diagram(&input_grammar)?;
+    if output_path.extension().and_then(|e| e.to_str()) == Some("md") {
+        let block = format!(
+            "{}\n```mermaid\n{}```\n{}",
+            MERMAID_START_MARKER, diagram, MERMAID_END_MARKER
+        );
+        diagram = if output_path.exists() {
+            let contents = fs::read_to_string(output_path)?;
+            replace_mermaid_block(&contents, &block).ok_or_else(|| {
+                Error::new(format!(
+                    "{:?} has no {} ... {} block to replace",
+                    output_path.file_name().unwrap(),
+                    MERMAID_START_MARKER,
+                    MERMAID_END_MARKER
+                ))
+            })?
+        } else {
+            block + "\n"
+        };
+    }
+    write_file(output_path, diagram)
+}
+
+const MERMAID_START_MARKER: &str = "<!-- mermaid:start -->";
+const MERMAID_END_MARKER: &str = "<!-- mermaid:end -->";
+
+// Replace the text from the start marker through the end marker with the given
+// block, returning `None` if the markers are not found in order.
+fn replace_mermaid_block(contents: &str, block: &str) -> Option<String> {
+    let start = contents.find(MERMAID_START_MARKER)?;
+    let end = contents[start..].find(MERMAID_END_MARKER)? + start + MERMAID_END_MARKER.len();
+    Some(format!(
+        "{}{}{}",
+        &contents[..start],
+        block,
+        &contents[end..]
+    ))
+}
+
+// Write a C header that declares the grammar's language function to the given path,
+// for C and C++ code that compiles the generated parser.
+pub fn generate_c_header_in_directory(
+    repo_path: &Path,
+    grammar_path: Option<&str>,
+    output_path: &Path,
+) -> Result<()> {
+    let input_grammar = load_input_grammar(repo_path, grammar_path)?;
+    write_file(output_path, c_header(&input_grammar.name))
+}
+
+// Generate a `types.d.ts` file from the `node-types.json` file that was written by a
+// previous call to `generate_parser_in_directory`.
+pub fn generate_typescript_in_directory(repo_path: &Path) -> Result<()> {
+    let src_path = repo_path.join("src");
+    let node_types_path = src_path.join("node-types.json");
+    let node_types_json = fs::read_to_string(&node_types_path).map_err(Error::wrap(|| {
+        format!(
+            "Failed to read {:?}. Run `tree-sitter generate` first",
+            node_types_path
+        )
+    }))?;
+    let declarations = generate_typescript_declarations(&node_types_json)?;
+    write_file(&src_path.join("types.d.ts"), declarations)
+}
+
+// Write a JSON Schema for grammars to `src/grammar-schema.json`, so that editors can
+// validate the grammar. The schema is the same for every grammar.
+pub fn generate_json_schema_in_directory(repo_path: &Path) -> Result<()> {
+    let src_path = repo_path.join("src");
+    fs::create_dir_all(&src_path)?;
+    write_file(&src_path.join("grammar-schema.json"), grammar_json_schema())
+}
+
+// Check that the `node-types.json` file that was written by a previous call to
+// `generate_parser_in_directory` is consistent with the compiled language, listing
+// every inconsistency in the error.
+pub fn check_node_types_in_directory(repo_path: &Path, language: Language) -> Result<()> {
+    let node_types_path = repo_path.join("src").join("node-types.json");
+    let node_types_json = fs::read_to_string(&node_types_path).map_err(Error::wrap(|| {
+        format!(
+            "Failed to read {:?}. Run `tree-sitter generate` first",
+            node_types_path
+        )
+    }))?;
+    let node_types = serde_json::from_str::<Vec<NodeInfoJSON>>(&node_types_json)?;
+    let inconsistencies = find_node_type_inconsistencies(language, &node_types);
+    if inconsistencies.is_empty() {
+        Ok(())
+    } else {
+        Error::err(format!(
+            "The node types in {:?} don't match the language:\n  {}",
+            node_types_path,
+            inconsistencies.join("\n  ")
+        ))
+    }
+}
+
+// Gather the data that a language server needs about the grammar, from the
+// `grammar.json` and `node-types.json` files that were written by a previous call to
+// `generate_parser_in_directory`, and from the grammar's highlight query, if it has
+// one.
+pub fn language_server_info_in_directory(repo_path: &Path) -> Result<LanguageServerGrammarInfo> {
+    let src_path = repo_path.join("src");
+    let read_generated_file = |name: &str| {
+        let path = src_path.join(name);
+        fs::read_to_string(&path).map_err(Error::wrap(|| {
+            format!(
+                "Failed to read {:?}. Run `tree-sitter generate` first",
+                path
+            )
+        }))
+    };
+    let input_grammar = parse_grammar(&read_generated_file("grammar.json")?)?;
+    let node_types_json = read_generated_file("node-types.json")?;
+    let highlights_query =
+        fs::read_to_string(repo_path.join("queries").join("highlights.scm")).ok();
+    export_for_language_server(
+        &input_grammar,
+        &node_types_json,
+        highlights_query.as_deref(),
+    )
+}
+
+// Generate a Haskell module for the grammar in the `bindings/haskell` directory,
+// using the language name from the `grammar.json` file that was written by a
+// previous call to `generate_parser_in_directory`.
+pub fn generate_haskell_bindings_in_directory(repo_path: &Path) -> Result<()> {
+    let grammar_json_path = repo_path.join("src").join("grammar.json");
+    let grammar_json = fs::read_to_string(&grammar_json_path).map_err(Error::wrap(|| {
+        format!(
+            "Failed to read {:?}. Run `tree-sitter generate` first",
+            grammar_json_path
+        )
+    }))?;
+    let language_name = parse_grammar(&grammar_json)?.name;
+    let bindings_path = repo_path
+        .join("bindings")
+        .join("haskell")
+        .join("TreeSitter");
+    fs::create_dir_all(&bindings_path)?;
+    write_file(
+        &bindings_path.join(format!("{}.hs", haskell::module_name(&language_name))),
+        haskell::language_hs(&language_name),
+    )
+}
+
+// Generate the boilerplate for using the grammar from the given language, using the
+// language name from the `grammar.json` file that was written by a previous call to
+// `generate_parser_in_directory`. Files that already exist are left unchanged.
+pub fn generate_language_bindings_in_directory(
+    repo_path: &Path,
+    binding_language: &str,
+) -> Result<()> {
+    let grammar_json_path = repo_path.join("src").join("grammar.json");
+    let grammar_json = fs::read_to_string(&grammar_json_path).map_err(Error::wrap(|| {
+        format!(
+            "Failed to read {:?}. Run `tree-sitter generate` first",
+            grammar_json_path
+        )
+    }))?;
+    let language_name = parse_grammar(&grammar_json)?.name;
+    let files = binding_files(binding_language, &language_name);
+    if files.is_empty() {
+        return Error::err(format!(
+            "Unknown binding language '{}'. Expected one of: {}",
+            binding_language,
+            BINDING_LANGUAGES.join(", ")
+        ));
+    }
+    for (path, contents) in files {
+        let path = repo_path.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        ensure_file(&path, || &contents)?;
+    }
+    Ok(())
+}
+
 pub fn generate_parser_for_grammar(grammar_json: &str) -> Result<(String, String)> {
     let grammar_json = JSON_COMMENT_REGEX.replace_all(grammar_json, "\n");
     let input_grammar = parse_grammar(&grammar_json)?;
@@ -135,8 +666,7 @@ pub fn generate_parser_for_grammar(grammar_json: &str) -> Result<(String, String
         lexical_grammar,
         inlines,
         simple_aliases,
-        true,
-        None,
+        &CompileOptions::default(),
     )?;
     Ok((input_grammar.name, parser.c_code))
 }
@@ -147,9 +677,9 @@ fn generate_parser_for_grammar_with_opts(
     lexical_grammar: LexicalGrammar,
     inlines: InlinedProductionMap,
     simple_aliases: AliasMap,
-    next_abi: bool,
-    report_symbol_name: Option<&str>,
+    options: &CompileOptions,
 ) -> Result<GeneratedParser> {
+    let start_time = Instant::now();
     let variable_info =
         node_types::get_variable_info(&syntax_grammar, &lexical_grammar, &simple_aliases)?;
     let node_types_json = node_types::generate_node_types_json(
@@ -158,15 +688,28 @@ fn generate_parser_for_grammar_with_opts(
         &simple_aliases,
         &variable_info,
     );
+    info!(
+        "compute node types - {} ms",
+        start_time.elapsed().as_millis()
+    );
+
+    let start_time = Instant::now();
     let (parse_table, main_lex_table, keyword_lex_table, keyword_capture_token) = build_tables(
         &syntax_grammar,
         &lexical_grammar,
         &simple_aliases,
         &variable_info,
         &inlines,
-        report_symbol_name,
+        options,
     )?;
-    let c_code = render_c_code(
+    info!(
+        "build tables - {} ms, {} parse states",
+        start_time.elapsed().as_millis(),
+        parse_table.states.len()
+    );
+
+    let start_time = Instant::now();
+    let (c_code, symbol_map_json) = render_c_code(
         name,
         parse_table,
         main_lex_table,
@@ -175,11 +718,62 @@ fn generate_parser_for_grammar_with_opts(
         syntax_grammar,
         lexical_grammar,
         simple_aliases,
-        next_abi,
+        &variable_info,
+        options.abi_version,
     );
+    info!("render C code - {} ms", start_time.elapsed().as_millis());
     Ok(GeneratedParser {
         c_code,
         node_types_json: serde_json::to_string_pretty(&node_types_json).unwrap(),
+        symbol_map_json,
+    })
+}
+
+fn find_grammar_file(repo_path: &Path, grammar_path: Option<&str>) -> PathBuf {
+    grammar_path.map_or(repo_path.join("grammar.js"), PathBuf::from)
+}
+
+fn load_input_grammar(repo_path: &Path, grammar_path: Option<&str>) -> Result<InputGrammar> {
+    parse_grammar(&load_grammar_file(&find_grammar_file(repo_path, grammar_path))?)
+}
+
+// Check the `TOKEN_COUNT = N` constant in the external scanner in the given directory
+// against the number of external tokens in the grammar.
+fn validate_external_token_count(src_path: &Path, external_token_count: usize) -> Result<()> {
+    let scanner_path = ["scanner.c", "scanner.cc"]
+        .iter()
+        .map(|name| src_path.join(name))
+        .find(|path| path.exists());
+    let scanner_path = match scanner_path {
+        Some(path) => path,
+        None if external_token_count == 0 => return Ok(()),
+        None => {
+            return Error::err(format!(
+                "The grammar has {} external tokens, but no external scanner was found in {:?}",
+                external_token_count, src_path
+            ))
+        }
+    };
+    let scanner_source = fs::read_to_string(&scanner_path)
+        .map_err(Error::wrap(|| format!("Failed to read {:?}", scanner_path)))?;
+    match scanner_token_count(&scanner_source) {
+        Some(count) if count == external_token_count => Ok(()),
+        Some(count) => Error::err(format!(
+            "The grammar has {} external tokens, but {:?} declares TOKEN_COUNT = {}",
+            external_token_count, scanner_path, count
+        )),
+        None => Error::err(format!(
+            "Failed to find a `TOKEN_COUNT = N` constant in {:?}",
+            scanner_path
+        )),
+    }
+}
+
+fn scanner_token_count(scanner_source: &str) -> Option<usize> {
+    scanner_source.lines().find_map(|line| {
+        TOKEN_COUNT_REGEX
+            .captures(line)
+            .and_then(|captures| captures[1].parse().ok())
     })
 }
 
@@ -238,3 +832,87 @@ fn ensure_file<T: AsRef<[u8]>>(path: &PathBuf, f: impl Fn() -> T) -> Result<()>
         write_file(path, f().as_ref())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_first_sets() {
+        let grammar = parse_grammar(
+            r#"{
+                "name": "first_sets",
+                "rules": {
+                    "program": {
+                        "type": "REPEAT",
+                        "content": {
+                            "type": "CHOICE",
+                            "members": [
+                                {"type": "SYMBOL", "name": "call"},
+                                {"type": "SYMBOL", "name": "number"}
+                            ]
+                        }
+                    },
+                    "call": {
+                        "type": "SEQ",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "STRING", "value": "("},
+                            {"type": "STRING", "value": ")"}
+                        ]
+                    },
+                    "word": {"type": "PATTERN", "value": "[a-z]+"},
+                    "number": {"type": "PATTERN", "value": "[0-9]+"}
+                }
+            }"#,
+        )
+        .unwrap();
+        let (syntax_grammar, lexical_grammar, _, _) = prepare_grammar(&grammar).unwrap();
+        assert_eq!(
+            render_first_sets(&syntax_grammar, &lexical_grammar),
+            concat!(
+                "program: { word, number }\n",
+                "call: { word }\n",
+                "program_repeat1: { word, number }\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_scanner_token_count() {
+        assert_eq!(
+            scanner_token_count(
+                "enum TokenType {\n  STRING,\n  COMMENT,\n};\n\nstatic const int TOKEN_COUNT = 2;\n"
+            ),
+            Some(2)
+        );
+        assert_eq!(
+            scanner_token_count("enum TokenType { STRING, COMMENT, TOKEN_COUNT=2 };"),
+            Some(2)
+        );
+        assert_eq!(
+            scanner_token_count("enum TokenType { STRING, COMMENT, TOKEN_COUNT };"),
+            None
+        );
+        assert_eq!(scanner_token_count("int MAX_TOKEN_COUNT = 5;"), None);
+    }
+
+    #[test]
+    fn test_replace_mermaid_block() {
+        assert_eq!(
+            replace_mermaid_block(
+                "# Grammar\n<!-- mermaid:start -->\nold\n<!-- mermaid:end -->\nMore text\n",
+                "<!-- mermaid:start -->\nnew\n<!-- mermaid:end -->"
+            ),
+            Some(
+                "# Grammar\n<!-- mermaid:start -->\nnew\n<!-- mermaid:end -->\nMore text\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(replace_mermaid_block("# Grammar\n", "new"), None);
+        assert_eq!(
+            replace_mermaid_block("<!-- mermaid:end -->\n<!-- mermaid:start -->\n", "new"),
+            None
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use super::grammars::{LexicalGrammar, SyntaxGrammar, VariableType};
 use super::rules::{Alias, AliasMap, Symbol, SymbolType};
 use crate::error::{Error, Result};
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
+use tree_sitter::Language;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum ChildType {
@@ -24,31 +25,31 @@ pub(crate) struct VariableInfo {
     pub has_multi_step_production: bool,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, Default, PartialOrd, Ord)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Default, PartialOrd, Ord)]
 pub(crate) struct NodeInfoJSON {
     #[serde(rename = "type")]
-    kind: String,
-    named: bool,
+    pub(crate) kind: String,
+    pub(crate) named: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    fields: Option<BTreeMap<String, FieldInfoJSON>>,
+    pub(crate) fields: Option<BTreeMap<String, FieldInfoJSON>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    children: Option<FieldInfoJSON>,
+    pub(crate) children: Option<FieldInfoJSON>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    subtypes: Option<Vec<NodeTypeJSON>>,
+    pub(crate) subtypes: Option<Vec<NodeTypeJSON>>,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct NodeTypeJSON {
     #[serde(rename = "type")]
-    kind: String,
-    named: bool,
+    pub(crate) kind: String,
+    pub(crate) named: bool,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct FieldInfoJSON {
-    multiple: bool,
-    required: bool,
-    types: Vec<NodeTypeJSON>,
+    pub(crate) multiple: bool,
+    pub(crate) required: bool,
+    pub(crate) types: Vec<NodeTypeJSON>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -91,6 +92,10 @@ impl ChildQuantity {
         }
     }
 
+    pub(crate) fn is_multiple(&self) -> bool {
+        self.multiple
+    }
+
     fn append(&mut self, other: ChildQuantity) {
         if other.exists {
             if self.exists || other.multiple {
@@ -651,6 +656,90 @@ pub(crate) fn generate_node_types_json(
     result
 }
 
+// Find the ways in which the contents of a `node-types.json` file disagree with a
+// compiled language: node types and fields that the language doesn't have, and
+// supertypes whose subtypes aren't node types of the language.
+pub(crate) fn find_node_type_inconsistencies(
+    language: Language,
+    node_types: &[NodeInfoJSON],
+) -> Vec<String> {
+    let mut result = Vec::new();
+    let supertypes = supertype_names(language);
+    let has_node_type = |kind: &str, named: bool| {
+        language
+            .node_kind_id_for_type_checked(kind, named)
+            .is_some()
+            || (named && supertypes.contains(kind))
+    };
+    let check_child_types = |result: &mut Vec<String>, parent: &str, types: &[NodeTypeJSON]| {
+        for child_type in types {
+            if !has_node_type(&child_type.kind, child_type.named) {
+                result.push(format!(
+                    "{} refers to the node type {}, which the language doesn't have",
+                    describe_node_type(parent, true),
+                    describe_node_type(&child_type.kind, child_type.named)
+                ));
+            }
+        }
+    };
+
+    for node_type in node_types {
+        let description = describe_node_type(&node_type.kind, node_type.named);
+        if let Some(subtypes) = &node_type.subtypes {
+            if !supertypes.contains(node_type.kind.as_str()) {
+                result.push(format!(
+                    "{} is not a supertype in the language",
+                    description
+                ));
+            }
+            if subtypes
+                .iter()
+                .any(|subtype| subtype.kind == node_type.kind)
+            {
+                result.push(format!("{} is listed as its own subtype", description));
+            }
+            check_child_types(&mut result, &node_type.kind, subtypes);
+            continue;
+        }
+
+        if !has_node_type(&node_type.kind, node_type.named) {
+            result.push(format!(
+                "{} is not a node type in the language",
+                description
+            ));
+        }
+        for (field_name, field) in node_type.fields.iter().flatten() {
+            if language.field_id_for_name(field_name).is_none() {
+                result.push(format!(
+                    "The field `{}` of {} is not a field in the language",
+                    field_name, description
+                ));
+            }
+            check_child_types(&mut result, &node_type.kind, &field.types);
+        }
+        if let Some(children) = &node_type.children {
+            check_child_types(&mut result, &node_type.kind, &children.types);
+        }
+    }
+    result
+}
+
+// Supertypes are hidden, so they can't be looked up by name like other node types.
+fn supertype_names(language: Language) -> HashSet<&'static str> {
+    (0..language.node_kind_count() as u16)
+        .filter(|id| language.symbol_metadata(*id).supertype)
+        .filter_map(|id| language.node_kind_for_id(id))
+        .collect()
+}
+
+fn describe_node_type(kind: &str, named: bool) -> String {
+    if named {
+        format!("`{}`", kind)
+    } else {
+        format!("`\"{}\"`", kind)
+    }
+}
+
 fn process_supertypes(
     info: &mut FieldInfoJSON,
     subtype_map: &HashMap<NodeTypeJSON, Vec<NodeTypeJSON>>,
@@ -716,6 +805,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -802,6 +892,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec!["_v2".to_string()],
             variables: vec![
                 Variable {
@@ -889,6 +980,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -983,6 +1075,7 @@ mod tests {
         let node_types = get_node_types(InputGrammar {
             name: String::new(),
             word_token: None,
+            version: None,
             extra_symbols: Vec::new(),
             external_tokens: Vec::new(),
             expected_conflicts: Vec::new(),
@@ -1042,6 +1135,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -1118,6 +1212,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -1186,6 +1281,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -1312,6 +1408,7 @@ mod tests {
             expected_conflicts: Vec::new(),
             variables_to_inline: Vec::new(),
             word_token: None,
+            version: None,
             supertype_symbols: vec![],
             variables: vec![
                 Variable {
@@ -1,6 +1,7 @@
 use super::grammars::{InputGrammar, Variable, VariableType};
 use super::rules::Rule;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use semver::Version;
 use serde_derive::Deserialize;
 use serde_json::{Map, Value};
 
@@ -39,6 +40,10 @@ enum RuleJSON {
     REPEAT1 {
         content: Box<RuleJSON>,
     },
+    REPEAT_MIN {
+        min: usize,
+        content: Box<RuleJSON>,
+    },
     PREC_DYNAMIC {
         value: i32,
         content: Box<RuleJSON>,
@@ -73,6 +78,7 @@ pub(crate) struct GrammarJSON {
     inline: Option<Vec<String>>,
     supertypes: Option<Vec<String>>,
     word: Option<String>,
+    version: Option<String>,
 }
 
 pub(crate) fn parse_grammar(input: &str) -> Result<InputGrammar> {
@@ -102,6 +108,13 @@ pub(crate) fn parse_grammar(input: &str) -> Result<InputGrammar> {
     let expected_conflicts = grammar_json.conflicts.unwrap_or(Vec::new());
     let variables_to_inline = grammar_json.inline.unwrap_or(Vec::new());
     let supertype_symbols = grammar_json.supertypes.unwrap_or(Vec::new());
+    let version = match grammar_json.version {
+        Some(version) => Some(
+            Version::parse(&version)
+                .map_err(|e| Error::new(format!("Invalid grammar version '{}': {}", version, e)))?,
+        ),
+        None => None,
+    };
 
     Ok(InputGrammar {
         name: grammar_json.name,
@@ -112,6 +125,7 @@ pub(crate) fn parse_grammar(input: &str) -> Result<InputGrammar> {
         external_tokens,
         supertype_symbols,
         variables_to_inline,
+        version,
     })
 }
 
@@ -133,6 +147,7 @@ fn parse_rule(json: RuleJSON) -> Rule {
         RuleJSON::REPEAT { content } => {
             Rule::choice(vec![Rule::repeat(parse_rule(*content)), Rule::Blank])
         }
+        RuleJSON::REPEAT_MIN { min, content } => Rule::repeat_min(parse_rule(*content), min),
         RuleJSON::PREC { value, content } => Rule::prec(value, parse_rule(*content)),
         RuleJSON::PREC_LEFT { value, content } => Rule::prec_left(value, parse_rule(*content)),
         RuleJSON::PREC_RIGHT { value, content } => Rule::prec_right(value, parse_rule(*content)),
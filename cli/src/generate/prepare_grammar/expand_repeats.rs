@@ -289,6 +289,7 @@ mod tests {
             variables_to_inline: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            grammar_version: None,
         }
     }
 }
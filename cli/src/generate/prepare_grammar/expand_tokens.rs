@@ -44,7 +44,7 @@ fn get_completion_precedence(rule: &Rule) -> i32 {
     }
 }
 
-fn preprocess_regex(content: &str) -> String {
+pub(super) fn preprocess_regex(content: &str) -> String {
     let content = CURLY_BRACE_REGEX.replace(content, "$1\\{$2\\}");
     let mut result = String::with_capacity(content.len());
     let mut is_escaped = false;
@@ -503,6 +503,16 @@ mod tests {
                 separators: vec![],
                 examples: vec![("abcd", Some((0, "abc"))), ("ab", None)],
             },
+            // case-insensitive string
+            Row {
+                rules: vec![Rule::string_case_insensitive("end-if")],
+                separators: vec![],
+                examples: vec![
+                    ("End-If;", Some((0, "End-If"))),
+                    ("END-IF", Some((0, "END-IF"))),
+                    ("endxif", None),
+                ],
+            },
             // complex rule containing strings and regexes
             Row {
                 rules: vec![Rule::repeat(Rule::seq(vec![
@@ -152,6 +152,7 @@ mod tests {
             supertype_symbols: Vec::new(),
             external_tokens: Vec::new(),
             word_token: None,
+            grammar_version: None,
         };
 
         let lexical_grammar = LexicalGrammar {
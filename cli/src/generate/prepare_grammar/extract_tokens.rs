@@ -155,6 +155,7 @@ pub(super) fn extract_tokens(
             supertype_symbols,
             external_tokens,
             word_token,
+            grammar_version: grammar.grammar_version,
         },
         ExtractedLexicalGrammar {
             variables: lexical_variables,
@@ -498,6 +499,7 @@ mod test {
             variables_to_inline: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            grammar_version: None,
         }
     }
 }
@@ -205,6 +205,7 @@ unless they are used only as the grammar's start rule.
         external_tokens: grammar.external_tokens,
         supertype_symbols: grammar.supertype_symbols,
         word_token: grammar.word_token,
+        grammar_version: grammar.grammar_version,
         variables,
     })
 }
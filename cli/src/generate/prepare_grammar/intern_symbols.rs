@@ -1,8 +1,13 @@
+use super::rule_references::add_referenced_symbols;
 use super::InternedGrammar;
 use crate::error::{Error, Result};
 use crate::generate::grammars::{InputGrammar, Variable, VariableType};
 use crate::generate::rules::{Rule, Symbol};
 
+// Repetitions with a minimum count are expanded by copying their content, so the
+// count is limited to keep the grammar from growing too large.
+const MAX_REPEAT_MIN: usize = 10;
+
 pub(super) fn intern_symbols(grammar: &InputGrammar) -> Result<InternedGrammar> {
     let interner = Interner { grammar };
 
@@ -10,6 +15,8 @@ pub(super) fn intern_symbols(grammar: &InputGrammar) -> Result<InternedGrammar>
         return Error::err("A grammar's start rule must be visible.".to_string());
     }
 
+    grammar.validate_no_empty_variables()?;
+
     let mut variables = Vec::with_capacity(grammar.variables.len());
     for variable in grammar.variables.iter() {
         variables.push(Variable {
@@ -73,7 +80,7 @@ pub(super) fn intern_symbols(grammar: &InputGrammar) -> Result<InternedGrammar>
         );
     }
 
-    Ok(InternedGrammar {
+    let result = InternedGrammar {
         variables,
         external_tokens,
         extra_symbols,
@@ -81,7 +88,120 @@ pub(super) fn intern_symbols(grammar: &InputGrammar) -> Result<InternedGrammar>
         variables_to_inline,
         supertype_symbols,
         word_token,
-    })
+        grammar_version: grammar.version.clone(),
+    };
+    validate_start_rule_is_not_reentrant(&result)?;
+    Ok(result)
+}
+
+// Check that the start rule can't match itself without consuming any tokens, as
+// in `program: $ => choice($.program, ...)`. That kind of cycle causes infinite
+// loops during error recovery.
+fn validate_start_rule_is_not_reentrant(grammar: &InternedGrammar) -> Result<()> {
+    // The start rule can only be reentered if it refers back to itself somehow.
+    match grammar.variables.first() {
+        Some(start_variable) if start_variable.is_recursive(grammar) => {}
+        _ => return Ok(()),
+    }
+
+    // Find the variables that can match the empty string.
+    let mut nullable = vec![false; grammar.variables.len()];
+    let mut done = false;
+    while !done {
+        done = true;
+        for (i, variable) in grammar.variables.iter().enumerate() {
+            if !nullable[i] && rule_is_nullable(&variable.rule, &nullable) {
+                nullable[i] = true;
+                done = false;
+            }
+        }
+    }
+
+    // A rule that can match the empty string is rejected when the grammar is
+    // flattened, unless only the start rule can match it. That error explains the
+    // problem better, so there is no need to search for a cycle.
+    let mut referenced_symbols = Vec::new();
+    for variable in &grammar.variables {
+        add_referenced_symbols(&variable.rule, &mut referenced_symbols);
+    }
+    if referenced_symbols
+        .iter()
+        .any(|symbol| symbol.is_non_terminal() && nullable[symbol.index])
+    {
+        return Ok(());
+    }
+
+    // Search for a path from the start rule back to itself, where each rule can
+    // match the next one without consuming any other tokens.
+    let mut predecessors = vec![None; grammar.variables.len()];
+    let mut stack = vec![0];
+    while let Some(i) = stack.pop() {
+        let mut successors = Vec::new();
+        add_unit_references(&grammar.variables[i].rule, &nullable, &mut successors);
+        for successor in successors {
+            if successor == 0 {
+                let mut path = Vec::new();
+                let mut j = i;
+                while j != 0 {
+                    path.push(grammar.variables[j].name.as_str());
+                    j = predecessors[j].unwrap();
+                }
+                path.push(&grammar.variables[0].name);
+                path.reverse();
+                path.push(&grammar.variables[0].name);
+                return Error::err(format!(
+                    "The start rule `{}` can match itself without consuming any tokens: {}",
+                    grammar.variables[0].name,
+                    path.join(" -> ")
+                ));
+            }
+            if predecessors[successor].is_none() {
+                predecessors[successor] = Some(i);
+                stack.push(successor);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn rule_is_nullable(rule: &Rule, nullable: &[bool]) -> bool {
+    match rule {
+        Rule::Blank => true,
+        Rule::Symbol(symbol) => symbol.is_non_terminal() && nullable[symbol.index],
+        Rule::Choice(elements) => elements.iter().any(|e| rule_is_nullable(e, nullable)),
+        Rule::Seq(elements) => elements.iter().all(|e| rule_is_nullable(e, nullable)),
+        Rule::Repeat(content) => rule_is_nullable(content, nullable),
+        Rule::Metadata { params, .. } if params.is_token => false,
+        Rule::Metadata { rule, .. } => rule_is_nullable(rule, nullable),
+        _ => false,
+    }
+}
+
+// Find the variables that the given rule can match in their entirety, without
+// consuming any other tokens.
+fn add_unit_references(rule: &Rule, nullable: &[bool], result: &mut Vec<usize>) {
+    match rule {
+        Rule::Symbol(symbol) if symbol.is_non_terminal() => result.push(symbol.index),
+        Rule::Choice(elements) => {
+            for element in elements {
+                add_unit_references(element, nullable, result);
+            }
+        }
+        Rule::Seq(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                let others_are_nullable = elements
+                    .iter()
+                    .enumerate()
+                    .all(|(j, other)| i == j || rule_is_nullable(other, nullable));
+                if others_are_nullable {
+                    add_unit_references(element, nullable, result);
+                }
+            }
+        }
+        Rule::Repeat(content) => add_unit_references(content, nullable, result),
+        Rule::Metadata { rule, .. } => add_unit_references(rule, nullable, result),
+        _ => {}
+    }
 }
 
 struct Interner<'a> {
@@ -103,9 +223,29 @@ impl<'a> Interner<'a> {
                 for element in elements {
                     result.push(self.intern_rule(element)?);
                 }
-                Ok(Rule::Seq(result))
+                // A `RepeatMin` step expands to a sequence, which is spliced into this one.
+                Ok(Rule::seq(result))
             }
             Rule::Repeat(content) => Ok(Rule::Repeat(Box::new(self.intern_rule(content)?))),
+            Rule::RepeatMin { rule, min } => {
+                if *min > MAX_REPEAT_MIN {
+                    return Error::err(format!(
+                        "A repetition's minimum count must be at most {}, but it is {}",
+                        MAX_REPEAT_MIN, min
+                    ));
+                }
+                // A `Repeat` rule matches one or more occurrences of its content.
+                let rule = self.intern_rule(rule)?;
+                if *min == 0 {
+                    Ok(Rule::choice(vec![Rule::repeat(rule), Rule::Blank]))
+                } else if *min == 1 {
+                    Ok(Rule::repeat(rule))
+                } else {
+                    let mut elements = vec![rule.clone(); *min - 1];
+                    elements.push(Rule::repeat(rule));
+                    Ok(Rule::seq(elements))
+                }
+            }
             Rule::Metadata { rule, params } => Ok(Rule::Metadata {
                 rule: Box::new(self.intern_rule(rule)?),
                 params: params.clone(),
@@ -222,6 +362,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_repeat_min_expansion() {
+        let grammar = intern_symbols(&build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::seq(vec![
+                    Rule::repeat_min(Rule::named("y"), 0),
+                    Rule::repeat_min(Rule::named("y"), 1),
+                    Rule::repeat_min(Rule::named("y"), 3),
+                ]),
+            ),
+            Variable::named("y", Rule::string("a")),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            grammar.variables[0].rule,
+            Rule::seq(vec![
+                Rule::choice(vec![Rule::repeat(Rule::non_terminal(1)), Rule::Blank]),
+                Rule::repeat(Rule::non_terminal(1)),
+                Rule::seq(vec![
+                    Rule::non_terminal(1),
+                    Rule::non_terminal(1),
+                    Rule::repeat(Rule::non_terminal(1)),
+                ]),
+            ])
+        );
+
+        let result = intern_symbols(&build_grammar(vec![
+            Variable::named("x", Rule::repeat_min(Rule::named("y"), 11)),
+            Variable::named("y", Rule::string("a")),
+        ]));
+        match result {
+            Err(e) => assert_eq!(
+                e.message(),
+                "A repetition's minimum count must be at most 10, but it is 11"
+            ),
+            _ => panic!("Expected an error but got none"),
+        }
+    }
+
     #[test]
     fn test_grammar_with_undefined_symbols() {
         let result = intern_symbols(&build_grammar(vec![Variable::named("x", Rule::named("y"))]));
@@ -232,6 +413,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_grammar_with_empty_variables() {
+        let result = intern_symbols(&build_grammar(vec![
+            Variable::named("x", Rule::choice(vec![Rule::named("y"), Rule::named("z")])),
+            Variable::named("y", Rule::Blank),
+            Variable::named(
+                "z",
+                Rule::seq(vec![Rule::Blank, Rule::repeat(Rule::prec(1, Rule::Blank))]),
+            ),
+        ]));
+
+        match result {
+            Err(e) => assert_eq!(
+                e.message(),
+                "The following rules only match the empty string: y, z"
+            ),
+            _ => panic!("Expected an error but got none"),
+        }
+
+        // A blank start rule is allowed.
+        assert!(intern_symbols(&build_grammar(vec![Variable::named("x", Rule::Blank)])).is_ok());
+    }
+
+    #[test]
+    fn test_grammar_with_reentrant_start_rule() {
+        let result = intern_symbols(&build_grammar(vec![
+            Variable::named(
+                "program",
+                Rule::choice(vec![Rule::named("a"), Rule::named("b")]),
+            ),
+            Variable::named(
+                "a",
+                Rule::seq(vec![
+                    Rule::choice(vec![Rule::string("("), Rule::Blank]),
+                    Rule::named("program"),
+                ]),
+            ),
+            Variable::named("b", Rule::string("b")),
+        ]));
+
+        match result {
+            Err(e) => assert_eq!(
+                e.message(),
+                "The start rule `program` can match itself without consuming any tokens: program -> a -> program"
+            ),
+            _ => panic!("Expected an error but got none"),
+        }
+
+        // Recursion is allowed when the recursive branch consumes a token.
+        assert!(intern_symbols(&build_grammar(vec![
+            Variable::named(
+                "program",
+                Rule::choice(vec![Rule::named("a"), Rule::named("b")])
+            ),
+            Variable::named(
+                "a",
+                Rule::seq(vec![Rule::named("program"), Rule::string(";")]),
+            ),
+            Variable::named("b", Rule::string("b")),
+        ]))
+        .is_ok());
+
+        // A cycle through a rule that matches the empty string is left for the
+        // empty string check, which reports that rule instead.
+        assert!(intern_symbols(&build_grammar(vec![
+            Variable::named("rule_1", Rule::named("rule_2")),
+            Variable::named(
+                "rule_2",
+                Rule::choice(vec![Rule::named("rule_1"), Rule::Blank])
+            ),
+        ]))
+        .is_ok());
+    }
+
     fn build_grammar(variables: Vec<Variable>) -> InputGrammar {
         InputGrammar {
             variables,
@@ -242,6 +497,7 @@ mod tests {
             variables_to_inline: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            version: None,
         }
     }
 }
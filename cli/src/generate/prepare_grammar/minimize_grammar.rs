@@ -0,0 +1,270 @@
+use super::InternedGrammar;
+use crate::generate::grammars::{Variable, VariableType};
+use crate::generate::rules::{Rule, Symbol};
+use std::collections::HashMap;
+
+// Merge hidden variables whose rules are identical. Such variables match the same
+// strings and produce the same (invisible) nodes, so every reference to a duplicate
+// can be pointed at the first variable with the same rule, and the duplicate can
+// be removed. This reduces the number of LR states and avoids needless conflicts.
+//
+// Variables that are visible, or that are referred to by name elsewhere in the
+// grammar (supertypes, inlined rules, the word token, and external tokens), are
+// never merged.
+pub(super) fn minimize_grammar(grammar: &InternedGrammar) -> InternedGrammar {
+    let variable_count = grammar.variables.len();
+
+    let mut is_mergeable = grammar
+        .variables
+        .iter()
+        .enumerate()
+        .map(|(i, variable)| i > 0 && variable.kind == VariableType::Hidden)
+        .collect::<Vec<_>>();
+    let protected_symbols = grammar
+        .supertype_symbols
+        .iter()
+        .chain(grammar.variables_to_inline.iter())
+        .chain(grammar.word_token.iter())
+        .cloned()
+        .chain(grammar.external_tokens.iter().filter_map(|t| match t.rule {
+            Rule::Symbol(symbol) => Some(symbol),
+            _ => None,
+        }));
+    for symbol in protected_symbols {
+        if symbol.is_non_terminal() {
+            is_mergeable[symbol.index] = false;
+        }
+    }
+
+    // Merging two variables can make other variables identical, so repeat until
+    // no more duplicates are found.
+    let mut canonical_indices = (0..variable_count).collect::<Vec<_>>();
+    loop {
+        let mut first_index_by_rule = HashMap::new();
+        let mut merged_any = false;
+        for (i, variable) in grammar.variables.iter().enumerate() {
+            if !is_mergeable[i] || canonical_indices[i] != i {
+                continue;
+            }
+            let rule = replace_symbols_in_rule(&variable.rule, &canonical_indices);
+            match first_index_by_rule.get(&rule) {
+                Some(first_index) => {
+                    canonical_indices[i] = *first_index;
+                    merged_any = true;
+                }
+                None => {
+                    first_index_by_rule.insert(rule, i);
+                }
+            }
+        }
+        if !merged_any {
+            break;
+        }
+        for i in 0..variable_count {
+            canonical_indices[i] = canonical_indices[canonical_indices[i]];
+        }
+    }
+
+    // Compute the new index of each variable, accounting for the removed ones.
+    let mut new_indices = Vec::with_capacity(variable_count);
+    let mut removed_count = 0;
+    for i in 0..variable_count {
+        if canonical_indices[i] == i {
+            new_indices.push(i - removed_count);
+        } else {
+            new_indices.push(new_indices[canonical_indices[i]]);
+            removed_count += 1;
+        }
+    }
+
+    let replace_symbol = |symbol: &Symbol| {
+        if symbol.is_non_terminal() {
+            Symbol::non_terminal(new_indices[symbol.index])
+        } else {
+            *symbol
+        }
+    };
+
+    let mut expected_conflicts = Vec::new();
+    for conflict in &grammar.expected_conflicts {
+        let mut conflict = conflict.iter().map(replace_symbol).collect::<Vec<_>>();
+        conflict.sort_unstable();
+        conflict.dedup();
+        if !expected_conflicts.contains(&conflict) {
+            expected_conflicts.push(conflict);
+        }
+    }
+
+    InternedGrammar {
+        variables: grammar
+            .variables
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| canonical_indices[*i] == *i)
+            .map(|(_, variable)| Variable {
+                name: variable.name.clone(),
+                kind: variable.kind,
+                rule: replace_symbols_in_rule(&variable.rule, &new_indices),
+            })
+            .collect(),
+        extra_symbols: grammar
+            .extra_symbols
+            .iter()
+            .map(|rule| replace_symbols_in_rule(rule, &new_indices))
+            .collect(),
+        expected_conflicts,
+        external_tokens: grammar
+            .external_tokens
+            .iter()
+            .map(|token| Variable {
+                name: token.name.clone(),
+                kind: token.kind,
+                rule: replace_symbols_in_rule(&token.rule, &new_indices),
+            })
+            .collect(),
+        variables_to_inline: grammar
+            .variables_to_inline
+            .iter()
+            .map(replace_symbol)
+            .collect(),
+        supertype_symbols: grammar
+            .supertype_symbols
+            .iter()
+            .map(replace_symbol)
+            .collect(),
+        word_token: grammar.word_token.as_ref().map(replace_symbol),
+        grammar_version: grammar.grammar_version.clone(),
+    }
+}
+
+fn replace_symbols_in_rule(rule: &Rule, new_indices: &Vec<usize>) -> Rule {
+    match rule {
+        Rule::Symbol(symbol) if symbol.is_non_terminal() => {
+            Rule::Symbol(Symbol::non_terminal(new_indices[symbol.index]))
+        }
+        Rule::Choice(elements) => Rule::Choice(
+            elements
+                .iter()
+                .map(|e| replace_symbols_in_rule(e, new_indices))
+                .collect(),
+        ),
+        Rule::Seq(elements) => Rule::Seq(
+            elements
+                .iter()
+                .map(|e| replace_symbols_in_rule(e, new_indices))
+                .collect(),
+        ),
+        Rule::Repeat(content) => {
+            Rule::Repeat(Box::new(replace_symbols_in_rule(content, new_indices)))
+        }
+        Rule::Metadata { rule, params } => Rule::Metadata {
+            params: params.clone(),
+            rule: Box::new(replace_symbols_in_rule(rule, new_indices)),
+        },
+        _ => rule.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merging_identical_hidden_variables() {
+        let grammar = minimize_grammar(&build_grammar(vec![
+            Variable::named(
+                "a",
+                Rule::seq(vec![
+                    Rule::non_terminal(1),
+                    Rule::non_terminal(2),
+                    Rule::non_terminal(3),
+                    Rule::non_terminal(4),
+                    Rule::non_terminal(5),
+                ]),
+            ),
+            Variable::hidden("_b", Rule::seq(vec![Rule::terminal(0), Rule::terminal(1)])),
+            Variable::hidden("_c", Rule::seq(vec![Rule::terminal(0), Rule::terminal(1)])),
+            Variable::hidden("_d", Rule::repeat(Rule::non_terminal(1))),
+            Variable::hidden("_e", Rule::repeat(Rule::non_terminal(2))),
+            Variable::named("f", Rule::seq(vec![Rule::terminal(0), Rule::terminal(1)])),
+        ]));
+
+        // `_c` is identical to `_b`, which makes `_e` identical to `_d`. The visible
+        // variable `f` is not merged.
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named(
+                    "a",
+                    Rule::seq(vec![
+                        Rule::non_terminal(1),
+                        Rule::non_terminal(1),
+                        Rule::non_terminal(2),
+                        Rule::non_terminal(2),
+                        Rule::non_terminal(3),
+                    ])
+                ),
+                Variable::hidden("_b", Rule::seq(vec![Rule::terminal(0), Rule::terminal(1)])),
+                Variable::hidden("_d", Rule::repeat(Rule::non_terminal(1))),
+                Variable::named("f", Rule::seq(vec![Rule::terminal(0), Rule::terminal(1)])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merging_does_not_affect_inlined_variables() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "a",
+                Rule::seq(vec![
+                    Rule::non_terminal(1),
+                    Rule::non_terminal(2),
+                    Rule::non_terminal(3),
+                ]),
+            ),
+            Variable::hidden("_b", Rule::terminal(0)),
+            Variable::hidden("_c", Rule::terminal(0)),
+            Variable::hidden("_d", Rule::terminal(0)),
+        ]);
+        input_grammar.variables_to_inline = vec![Symbol::non_terminal(3)];
+        input_grammar.expected_conflicts = vec![
+            vec![Symbol::non_terminal(1), Symbol::non_terminal(3)],
+            vec![Symbol::non_terminal(2), Symbol::non_terminal(3)],
+        ];
+
+        let grammar = minimize_grammar(&input_grammar);
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named(
+                    "a",
+                    Rule::seq(vec![
+                        Rule::non_terminal(1),
+                        Rule::non_terminal(1),
+                        Rule::non_terminal(2),
+                    ])
+                ),
+                Variable::hidden("_b", Rule::terminal(0)),
+                Variable::hidden("_d", Rule::terminal(0)),
+            ]
+        );
+        assert_eq!(grammar.variables_to_inline, vec![Symbol::non_terminal(2)]);
+        assert_eq!(
+            grammar.expected_conflicts,
+            vec![vec![Symbol::non_terminal(1), Symbol::non_terminal(2)]]
+        );
+    }
+
+    fn build_grammar(variables: Vec<Variable>) -> InternedGrammar {
+        InternedGrammar {
+            variables,
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            grammar_version: None,
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::expand_tokens::preprocess_regex;
+use super::InternedGrammar;
+use crate::generate::rules::{Rule, Symbol, SymbolType};
+use regex_syntax::ast::{parse, Ast, RepetitionKind, RepetitionRange};
+
+impl InternedGrammar {
+    // Compute the minimum number of characters in the text of a node produced by the
+    // given variable. Returns `None` if the variable can't produce any finite text,
+    // because every alternative of the rule refers to the variable itself. External
+    // tokens are assumed to be able to match the empty string.
+    pub(crate) fn compute_minimum_input_length(&self, symbol: Symbol) -> Option<usize> {
+        match symbol.kind {
+            SymbolType::NonTerminal => self.minimum_input_lengths()[symbol.index],
+            SymbolType::External => Some(0),
+            _ => None,
+        }
+    }
+
+    // Compute the minimum input length of every variable, by repeatedly computing the
+    // length of each rule from the lengths found so far, until none of them decrease.
+    // The lengths of variables that can't produce finite text are never found.
+    pub(crate) fn minimum_input_lengths(&self) -> Vec<Option<usize>> {
+        let mut result = vec![None; self.variables.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, variable) in self.variables.iter().enumerate() {
+                let length = rule_minimum_length(&variable.rule, &result);
+                if length.is_some() && (result[i].is_none() || length < result[i]) {
+                    result[i] = length;
+                    changed = true;
+                }
+            }
+        }
+        result
+    }
+}
+
+fn rule_minimum_length(rule: &Rule, variable_lengths: &[Option<usize>]) -> Option<usize> {
+    match rule {
+        Rule::Blank => Some(0),
+        Rule::String(value) => Some(value.chars().count()),
+        Rule::Pattern(value) => {
+            let ast = parse::Parser::new().parse(&preprocess_regex(value)).ok()?;
+            Some(regex_minimum_length(&ast))
+        }
+        Rule::Symbol(symbol) => match symbol.kind {
+            SymbolType::NonTerminal => variable_lengths[symbol.index],
+            _ => Some(0),
+        },
+        Rule::Choice(members) => members
+            .iter()
+            .filter_map(|member| rule_minimum_length(member, variable_lengths))
+            .min(),
+        Rule::Seq(members) => members
+            .iter()
+            .map(|member| rule_minimum_length(member, variable_lengths))
+            .sum(),
+        Rule::Metadata { rule, .. } | Rule::Repeat(rule) => {
+            rule_minimum_length(rule, variable_lengths)
+        }
+        Rule::RepeatMin { rule, min } => {
+            if *min == 0 {
+                Some(0)
+            } else {
+                rule_minimum_length(rule, variable_lengths).map(|length| length * min)
+            }
+        }
+        Rule::NamedSymbol(_) => None,
+    }
+}
+
+fn regex_minimum_length(ast: &Ast) -> usize {
+    match ast {
+        Ast::Empty(_) | Ast::Flags(_) | Ast::Assertion(_) => 0,
+        Ast::Literal(_) | Ast::Dot(_) | Ast::Class(_) => 1,
+        Ast::Repetition(repetition) => {
+            let min = match repetition.op.kind {
+                RepetitionKind::ZeroOrOne | RepetitionKind::ZeroOrMore => 0,
+                RepetitionKind::OneOrMore => 1,
+                RepetitionKind::Range(RepetitionRange::Exactly(min))
+                | RepetitionKind::Range(RepetitionRange::AtLeast(min))
+                | RepetitionKind::Range(RepetitionRange::Bounded(min, _)) => min as usize,
+            };
+            min * regex_minimum_length(&repetition.ast)
+        }
+        Ast::Group(group) => regex_minimum_length(&group.ast),
+        Ast::Alternation(alternation) => alternation
+            .asts
+            .iter()
+            .map(regex_minimum_length)
+            .min()
+            .unwrap_or(0),
+        Ast::Concat(concat) => concat.asts.iter().map(regex_minimum_length).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::intern_symbols::intern_symbols;
+    use crate::generate::grammars::{InputGrammar, Variable};
+    use crate::generate::rules::{Rule, Symbol};
+
+    #[test]
+    fn test_minimum_input_lengths() {
+        let grammar = intern_symbols(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::choice(vec![Rule::repeat(Rule::named("statement")), Rule::Blank]),
+                ),
+                Variable::named(
+                    "statement",
+                    Rule::seq(vec![
+                        Rule::named("expression"),
+                        Rule::string(";"),
+                        Rule::named("heredoc"),
+                    ]),
+                ),
+                Variable::named(
+                    "expression",
+                    Rule::choice(vec![
+                        Rule::named("call"),
+                        Rule::named("identifier"),
+                        Rule::named("number"),
+                    ]),
+                ),
+                Variable::named(
+                    "call",
+                    Rule::seq(vec![Rule::named("expression"), Rule::string("()")]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]{3,}")),
+                Variable::named("number", Rule::pattern("\\d+(\\.\\d+)?|0x")),
+                Variable::named(
+                    "infinite",
+                    Rule::seq(vec![Rule::string("("), Rule::named("infinite")]),
+                ),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: vec![Rule::named("heredoc")],
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            grammar.minimum_input_lengths(),
+            &[Some(0), Some(2), Some(1), Some(3), Some(3), Some(1), None]
+        );
+        assert_eq!(
+            grammar.compute_minimum_input_length(Symbol::non_terminal(3)),
+            Some(3)
+        );
+        assert_eq!(
+            grammar.compute_minimum_input_length(Symbol::non_terminal(6)),
+            None
+        );
+        assert_eq!(
+            grammar.compute_minimum_input_length(Symbol::external(0)),
+            Some(0)
+        );
+    }
+}
@@ -4,7 +4,13 @@ mod extract_simple_aliases;
 mod extract_tokens;
 mod flatten_grammar;
 mod intern_symbols;
+mod minimize_grammar;
+mod minimum_lengths;
 mod process_inlines;
+mod recursive_variables;
+mod rule_references;
+mod unused_rules;
+mod variable_summaries;
 
 use self::expand_repeats::expand_repeats;
 pub(crate) use self::expand_tokens::expand_tokens;
@@ -12,12 +18,16 @@ use self::extract_simple_aliases::extract_simple_aliases;
 use self::extract_tokens::extract_tokens;
 use self::flatten_grammar::flatten_grammar;
 use self::intern_symbols::intern_symbols;
+use self::minimize_grammar::minimize_grammar;
 use self::process_inlines::process_inlines;
-use crate::error::Result;
+pub(crate) use self::rule_references::RuleContext;
+pub(crate) use self::variable_summaries::VariableSummary;
+use crate::error::{Error, Result};
 use crate::generate::grammars::{
     ExternalToken, InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar, Variable,
 };
-use crate::generate::rules::{AliasMap, Rule, Symbol};
+use crate::generate::rules::{AliasMap, Rule, Symbol, SymbolType};
+use semver::Version;
 
 pub(crate) struct IntermediateGrammar<T, U> {
     variables: Vec<Variable>,
@@ -27,10 +37,23 @@ pub(crate) struct IntermediateGrammar<T, U> {
     variables_to_inline: Vec<Symbol>,
     supertype_symbols: Vec<Symbol>,
     word_token: Option<Symbol>,
+    grammar_version: Option<Version>,
 }
 
 pub(crate) type InternedGrammar = IntermediateGrammar<Rule, Variable>;
 
+impl InternedGrammar {
+    // Look up the rule or external token that the given symbol refers to. Terminal
+    // symbols have no variables until the grammar's tokens are extracted.
+    pub(crate) fn variable_for_symbol(&self, symbol: Symbol) -> Option<&Variable> {
+        match symbol.kind {
+            SymbolType::NonTerminal => self.variables.get(symbol.index),
+            SymbolType::External => self.external_tokens.get(symbol.index),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) type ExtractedSyntaxGrammar = IntermediateGrammar<Symbol, ExternalToken>;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -47,7 +70,24 @@ pub(crate) fn prepare_grammar(
     InlinedProductionMap,
     AliasMap,
 )> {
-    let interned_grammar = intern_symbols(input_grammar)?;
+    prepare_grammar_with_opts(input_grammar, false)
+}
+
+// Prepare the grammar, first merging any hidden rules that are identical if
+// `minimize` is set. This changes the symbols in the generated parser.
+pub(crate) fn prepare_grammar_with_opts(
+    input_grammar: &InputGrammar,
+    minimize: bool,
+) -> Result<(
+    SyntaxGrammar,
+    LexicalGrammar,
+    InlinedProductionMap,
+    AliasMap,
+)> {
+    let mut interned_grammar = intern_symbols(input_grammar)?;
+    if minimize {
+        interned_grammar = minimize_grammar(&interned_grammar);
+    }
     let (syntax_grammar, lexical_grammar) = extract_tokens(interned_grammar)?;
     let syntax_grammar = expand_repeats(syntax_grammar);
     let mut syntax_grammar = flatten_grammar(syntax_grammar)?;
@@ -56,3 +96,53 @@ pub(crate) fn prepare_grammar(
     let inlines = process_inlines(&syntax_grammar);
     Ok((syntax_grammar, lexical_grammar, inlines, simple_aliases))
 }
+
+// Find the rules that refer directly to the rule or external token with the given
+// name, returning the name of each referencing rule along with the position of the
+// reference within it.
+pub(crate) fn find_references(
+    input_grammar: &InputGrammar,
+    name: &str,
+) -> Result<Vec<(String, RuleContext)>> {
+    let interned_grammar = intern_symbols(input_grammar)?;
+    let symbol = if let Some(index) = interned_grammar
+        .variables
+        .iter()
+        .position(|variable| variable.name == name)
+    {
+        Symbol::non_terminal(index)
+    } else if let Some(index) = interned_grammar
+        .external_tokens
+        .iter()
+        .position(|token| token.name == name)
+    {
+        Symbol::external(index)
+    } else {
+        return Err(Error::undefined_symbol(name));
+    };
+    Ok(interned_grammar
+        .find_rules_referencing(symbol)
+        .into_iter()
+        .map(|(symbol, context)| {
+            (
+                interned_grammar.variables[symbol.index].name.clone(),
+                context,
+            )
+        })
+        .collect())
+}
+
+// Summarize each of the grammar's rules after its symbols have been interned.
+pub(crate) fn summarize_variables(input_grammar: &InputGrammar) -> Result<Vec<VariableSummary>> {
+    Ok(intern_symbols(input_grammar)?.variable_summaries())
+}
+
+// Find the names of the grammar's rules that can't be reached from its start rule,
+// its extras, or its word token.
+pub(crate) fn find_unused_rules(input_grammar: &InputGrammar) -> Result<Vec<String>> {
+    Ok(intern_symbols(input_grammar)?
+        .unused_variable_names()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
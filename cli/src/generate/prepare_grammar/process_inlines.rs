@@ -200,6 +200,7 @@ mod tests {
             external_tokens: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            grammar_version: None,
             variables_to_inline: vec![Symbol::non_terminal(1)],
             variables: vec![
                 SyntaxVariable {
@@ -331,6 +332,7 @@ mod tests {
             external_tokens: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            grammar_version: None,
         };
         let inline_map = process_inlines(&grammar);
 
@@ -433,6 +435,7 @@ mod tests {
             external_tokens: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            grammar_version: None,
         };
 
         let inline_map = process_inlines(&grammar);
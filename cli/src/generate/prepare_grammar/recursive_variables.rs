@@ -0,0 +1,87 @@
+use super::rule_references::add_referenced_symbols;
+use super::InternedGrammar;
+use crate::generate::grammars::Variable;
+use crate::generate::rules::SymbolType;
+
+impl Variable {
+    // Check whether this variable's rule can lead back to the variable itself,
+    // either directly or through references to other rules. The variable is found
+    // in the grammar by its name.
+    pub(crate) fn is_recursive(&self, grammar: &InternedGrammar) -> bool {
+        let index = match grammar.variables.iter().position(|v| v.name == self.name) {
+            Some(index) => index,
+            None => return false,
+        };
+        let mut is_visited = vec![false; grammar.variables.len()];
+        let mut stack = Vec::new();
+        add_referenced_symbols(&self.rule, &mut stack);
+        while let Some(symbol) = stack.pop() {
+            if symbol.kind != SymbolType::NonTerminal || is_visited[symbol.index] {
+                continue;
+            }
+            if symbol.index == index {
+                return true;
+            }
+            is_visited[symbol.index] = true;
+            add_referenced_symbols(&grammar.variables[symbol.index].rule, &mut stack);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::intern_symbols::intern_symbols;
+    use crate::generate::grammars::{InputGrammar, Variable};
+    use crate::generate::rules::Rule;
+
+    #[test]
+    fn test_is_recursive() {
+        let grammar = intern_symbols(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named("program", Rule::repeat(Rule::named("statement"))),
+                Variable::named(
+                    "statement",
+                    Rule::choice(vec![Rule::named("block"), Rule::named("expression")]),
+                ),
+                Variable::named(
+                    "block",
+                    Rule::seq(vec![
+                        Rule::string("{"),
+                        Rule::repeat(Rule::named("statement")),
+                        Rule::string("}"),
+                    ]),
+                ),
+                Variable::named(
+                    "expression",
+                    Rule::choice(vec![
+                        Rule::seq(vec![
+                            Rule::named("expression"),
+                            Rule::string("+"),
+                            Rule::named("identifier"),
+                        ]),
+                        Rule::named("identifier"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+
+        let recursive_names = grammar
+            .variables
+            .iter()
+            .filter(|variable| variable.is_recursive(&grammar))
+            .map(|variable| variable.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(recursive_names, &["statement", "block", "expression"]);
+    }
+}
@@ -0,0 +1,235 @@
+use super::InternedGrammar;
+use crate::generate::rules::{Rule, Symbol};
+use std::fmt;
+
+// One step on the path from the top of a rule to a reference within it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RuleContextStep {
+    Choice(usize),
+    Optional,
+    Seq(usize),
+    Repeat,
+    Field(String),
+    Alias(String),
+    Precedence(i32),
+    Token,
+}
+
+// The position of a symbol reference within a rule, described as the chain of
+// enclosing rules, from the outermost to the innermost.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RuleContext {
+    pub steps: Vec<RuleContextStep>,
+}
+
+impl InternedGrammar {
+    // Find every direct reference to the given symbol within the grammar's rules.
+    // A rule that refers to the symbol several times is listed once per reference.
+    pub(crate) fn find_rules_referencing(&self, symbol: Symbol) -> Vec<(Symbol, RuleContext)> {
+        let mut result = Vec::new();
+        for (i, variable) in self.variables.iter().enumerate() {
+            if !variable.rule.contains_symbol(symbol) {
+                continue;
+            }
+            let mut context = RuleContext::default();
+            let mut contexts = Vec::new();
+            find_references_in_rule(&variable.rule, symbol, &mut context, &mut contexts);
+            result.extend(
+                contexts
+                    .into_iter()
+                    .map(|context| (Symbol::non_terminal(i), context)),
+            );
+        }
+        result
+    }
+}
+
+fn find_references_in_rule(
+    rule: &Rule,
+    symbol: Symbol,
+    context: &mut RuleContext,
+    result: &mut Vec<RuleContext>,
+) {
+    match rule {
+        Rule::Symbol(s) if *s == symbol => result.push(context.clone()),
+        // Optional rules are represented as a choice with a blank member.
+        Rule::Choice(members) if members.len() == 2 && members[1] == Rule::Blank => {
+            context.steps.push(RuleContextStep::Optional);
+            find_references_in_rule(&members[0], symbol, context, result);
+            context.steps.pop();
+        }
+        Rule::Choice(members) => {
+            for (i, member) in members.iter().enumerate() {
+                context.steps.push(RuleContextStep::Choice(i));
+                find_references_in_rule(member, symbol, context, result);
+                context.steps.pop();
+            }
+        }
+        Rule::Seq(members) => {
+            for (i, member) in members.iter().enumerate() {
+                context.steps.push(RuleContextStep::Seq(i));
+                find_references_in_rule(member, symbol, context, result);
+                context.steps.pop();
+            }
+        }
+        Rule::Repeat(content) => {
+            context.steps.push(RuleContextStep::Repeat);
+            find_references_in_rule(content, symbol, context, result);
+            context.steps.pop();
+        }
+        Rule::Metadata { params, rule } => {
+            let mut step_count = 0;
+            if let Some(field_name) = &params.field_name {
+                context
+                    .steps
+                    .push(RuleContextStep::Field(field_name.clone()));
+                step_count += 1;
+            }
+            if let Some(alias) = &params.alias {
+                context
+                    .steps
+                    .push(RuleContextStep::Alias(alias.value.clone()));
+                step_count += 1;
+            }
+            if let Some(precedence) = params.precedence {
+                context.steps.push(RuleContextStep::Precedence(precedence));
+                step_count += 1;
+            }
+            if params.is_token {
+                context.steps.push(RuleContextStep::Token);
+                step_count += 1;
+            }
+            find_references_in_rule(rule, symbol, context, result);
+            context.steps.truncate(context.steps.len() - step_count);
+        }
+        _ => {}
+    }
+}
+
+// Collect every symbol that a rule refers to directly, in the order that they
+// appear.
+pub(super) fn add_referenced_symbols(rule: &Rule, result: &mut Vec<Symbol>) {
+    match rule {
+        Rule::Symbol(symbol) => result.push(*symbol),
+        Rule::Choice(members) | Rule::Seq(members) => {
+            for member in members {
+                add_referenced_symbols(member, result);
+            }
+        }
+        Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+            add_referenced_symbols(rule, result)
+        }
+        _ => {}
+    }
+}
+
+impl fmt::Display for RuleContextStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleContextStep::Choice(i) => write!(f, "choice[{}]", i),
+            RuleContextStep::Optional => write!(f, "optional"),
+            RuleContextStep::Seq(i) => write!(f, "seq[{}]", i),
+            RuleContextStep::Repeat => write!(f, "repeat"),
+            RuleContextStep::Field(name) => write!(f, "field({})", name),
+            RuleContextStep::Alias(value) => write!(f, "alias({})", value),
+            RuleContextStep::Precedence(value) => write!(f, "prec({})", value),
+            RuleContextStep::Token => write!(f, "token"),
+        }
+    }
+}
+
+impl fmt::Display for RuleContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.steps.is_empty() {
+            return write!(f, "(entire rule)");
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " > ")?;
+            }
+            write!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::intern_symbols::intern_symbols;
+    use super::*;
+    use crate::generate::grammars::{InputGrammar, Variable};
+
+    #[test]
+    fn test_find_rules_referencing() {
+        let grammar = intern_symbols(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::choice(vec![
+                        Rule::repeat(Rule::choice(vec![
+                            Rule::named("statement"),
+                            Rule::named("expression"),
+                        ])),
+                        Rule::Blank,
+                    ]),
+                ),
+                Variable::named(
+                    "statement",
+                    Rule::seq(vec![Rule::named("expression"), Rule::string(";")]),
+                ),
+                Variable::named(
+                    "expression",
+                    Rule::choice(vec![
+                        Rule::named("identifier"),
+                        Rule::prec_left(
+                            1,
+                            Rule::seq(vec![
+                                Rule::field("left".to_string(), Rule::named("expression")),
+                                Rule::string("+"),
+                                Rule::field("right".to_string(), Rule::named("expression")),
+                            ]),
+                        ),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("\\w+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+
+        let references = grammar.find_rules_referencing(Symbol::non_terminal(2));
+        assert_eq!(
+            references
+                .iter()
+                .map(|(symbol, context)| (*symbol, context.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Symbol::non_terminal(0),
+                    "optional > repeat > choice[1]".to_string()
+                ),
+                (Symbol::non_terminal(1), "seq[0]".to_string()),
+                (
+                    Symbol::non_terminal(2),
+                    "choice[1] > prec(1) > seq[0] > field(left)".to_string()
+                ),
+                (
+                    Symbol::non_terminal(2),
+                    "choice[1] > prec(1) > seq[2] > field(right)".to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(
+            grammar.find_rules_referencing(Symbol::non_terminal(0)),
+            Vec::new()
+        );
+    }
+}
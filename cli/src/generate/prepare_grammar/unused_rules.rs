@@ -0,0 +1,72 @@
+use super::InternedGrammar;
+use crate::generate::rules::{Rule, Symbol, SymbolType};
+
+impl InternedGrammar {
+    // Find the names of the variables that can't be reached from the start rule, the
+    // extras, or the word token, in the order that they are defined.
+    pub(crate) fn unused_variable_names(&self) -> Vec<&str> {
+        let variable_count = self.variables.len();
+        let referenced_variables = |rule: &Rule| {
+            (0..variable_count)
+                .filter(|index| rule.contains_symbol(Symbol::non_terminal(*index)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut is_used = vec![false; variable_count];
+        let mut stack = vec![0];
+        for rule in &self.extra_symbols {
+            stack.extend(referenced_variables(rule));
+        }
+        stack.extend(
+            self.word_token
+                .iter()
+                .filter(|symbol| symbol.kind == SymbolType::NonTerminal)
+                .map(|symbol| symbol.index),
+        );
+        while let Some(index) = stack.pop() {
+            if is_used[index] {
+                continue;
+            }
+            is_used[index] = true;
+            stack.extend(referenced_variables(&self.variables[index].rule));
+        }
+        self.variables
+            .iter()
+            .zip(is_used)
+            .filter(|(_, is_used)| !is_used)
+            .map(|(variable, _)| variable.name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::intern_symbols::intern_symbols;
+    use crate::generate::grammars::{InputGrammar, Variable};
+    use crate::generate::rules::Rule;
+
+    #[test]
+    fn test_unused_variable_names() {
+        let grammar = intern_symbols(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named("program", Rule::repeat(Rule::named("statement"))),
+                Variable::named("statement", Rule::named("identifier")),
+                Variable::named("comment", Rule::pattern("#.*")),
+                Variable::named("unused", Rule::named("also_unused")),
+                Variable::named("also_unused", Rule::named("unused")),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: vec![Rule::named("comment")],
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+
+        assert_eq!(grammar.unused_variable_names(), &["unused", "also_unused"]);
+    }
+}
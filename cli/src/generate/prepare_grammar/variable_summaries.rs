@@ -0,0 +1,220 @@
+use super::InternedGrammar;
+use crate::generate::grammars::VariableType;
+use crate::generate::rules::{Rule, Symbol};
+use std::collections::HashSet;
+
+const RULE_SUMMARY_LENGTH: usize = 60;
+
+// A short description of one of a grammar's variables, for inspecting the grammar
+// without generating a parser.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct VariableSummary {
+    pub name: String,
+    pub kind: VariableType,
+    pub rule_summary: String,
+    pub reference_count: usize,
+    pub minimum_length: Option<usize>,
+    pub depth: usize,
+    pub terminal_count: usize,
+}
+
+impl InternedGrammar {
+    // Summarize each of the grammar's variables, in order. The rule summary is the
+    // start of the rule's BNF, the reference count is the number of distinct rules
+    // that refer to the variable, the minimum length is the one computed by
+    // `compute_minimum_input_length`, the depth is the rule's nesting depth, and the
+    // terminal count is the number of strings and patterns in the rule.
+    pub(crate) fn variable_summaries(&self) -> Vec<VariableSummary> {
+        self.variables
+            .iter()
+            .enumerate()
+            .map(|(i, variable)| {
+                let mut bnf = String::new();
+                self.write_bnf(&variable.rule, false, &mut bnf);
+                let mut rule_summary = bnf.chars().take(RULE_SUMMARY_LENGTH).collect::<String>();
+                if bnf.chars().count() > RULE_SUMMARY_LENGTH {
+                    rule_summary += "...";
+                }
+                let referencing_symbols = self
+                    .find_rules_referencing(Symbol::non_terminal(i))
+                    .into_iter()
+                    .map(|(symbol, _)| symbol)
+                    .collect::<HashSet<_>>();
+                VariableSummary {
+                    name: variable.name.clone(),
+                    kind: variable.kind,
+                    rule_summary,
+                    reference_count: referencing_symbols.len(),
+                    minimum_length: self.compute_minimum_input_length(Symbol::non_terminal(i)),
+                    depth: variable.rule.max_depth(),
+                    terminal_count: variable.rule.count_terminals(),
+                }
+            })
+            .collect()
+    }
+
+    fn write_bnf(&self, rule: &Rule, is_nested: bool, result: &mut String) {
+        match rule {
+            Rule::Blank => result.push('ε'),
+            Rule::String(value) => result.push_str(&format!("{:?}", value)),
+            Rule::Pattern(value) => result.push_str(&format!("/{}/", value)),
+            Rule::NamedSymbol(name) => result.push_str(name),
+            Rule::Symbol(symbol) => match self.variable_for_symbol(*symbol) {
+                Some(variable) => result.push_str(&variable.name),
+                None => result.push('?'),
+            },
+            Rule::Choice(members) if members.len() == 2 && members[1] == Rule::Blank => {
+                if let Rule::Repeat(content) = &members[0] {
+                    self.write_bnf(content, true, result);
+                    result.push('*');
+                } else {
+                    self.write_bnf(&members[0], true, result);
+                    result.push('?');
+                }
+            }
+            Rule::Choice(members) => {
+                if is_nested {
+                    result.push('(');
+                }
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        result.push_str(" | ");
+                    }
+                    self.write_bnf(member, false, result);
+                }
+                if is_nested {
+                    result.push(')');
+                }
+            }
+            Rule::Seq(members) => {
+                if is_nested {
+                    result.push('(');
+                }
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        result.push(' ');
+                    }
+                    self.write_bnf(member, true, result);
+                }
+                if is_nested {
+                    result.push(')');
+                }
+            }
+            Rule::Repeat(content) => {
+                self.write_bnf(content, true, result);
+                result.push('+');
+            }
+            Rule::RepeatMin { rule, min } => {
+                self.write_bnf(rule, true, result);
+                result.push_str(&format!("{{{},}}", min));
+            }
+            Rule::Metadata { params, rule } => {
+                if let Some(field_name) = &params.field_name {
+                    result.push_str(field_name);
+                    result.push_str(": ");
+                    self.write_bnf(rule, true, result);
+                } else {
+                    self.write_bnf(rule, is_nested, result);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::intern_symbols::intern_symbols;
+    use super::*;
+    use crate::generate::grammars::{InputGrammar, Variable};
+
+    #[test]
+    fn test_variable_summaries() {
+        let grammar = intern_symbols(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::choice(vec![Rule::repeat(Rule::named("_statement")), Rule::Blank]),
+                ),
+                Variable::named(
+                    "_statement",
+                    Rule::seq(vec![
+                        Rule::choice(vec![Rule::named("call"), Rule::named("identifier")]),
+                        Rule::choice(vec![Rule::string(";"), Rule::Blank]),
+                    ]),
+                ),
+                Variable::named(
+                    "call",
+                    Rule::seq(vec![
+                        Rule::field("function".to_string(), Rule::named("identifier")),
+                        Rule::string("("),
+                        Rule::choice(vec![
+                            Rule::seq(vec![
+                                Rule::named("identifier"),
+                                Rule::repeat(Rule::seq(vec![
+                                    Rule::string(","),
+                                    Rule::named("identifier"),
+                                ])),
+                            ]),
+                            Rule::Blank,
+                        ]),
+                        Rule::string(")"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            grammar.variable_summaries(),
+            vec![
+                VariableSummary {
+                    name: "program".to_string(),
+                    kind: VariableType::Named,
+                    rule_summary: "_statement*".to_string(),
+                    reference_count: 0,
+                    minimum_length: Some(0),
+                    depth: 3,
+                    terminal_count: 0,
+                },
+                VariableSummary {
+                    name: "_statement".to_string(),
+                    kind: VariableType::Hidden,
+                    rule_summary: "(call | identifier) \";\"?".to_string(),
+                    reference_count: 1,
+                    minimum_length: Some(1),
+                    depth: 3,
+                    terminal_count: 1,
+                },
+                VariableSummary {
+                    name: "call".to_string(),
+                    kind: VariableType::Named,
+                    rule_summary:
+                        "function: identifier \"(\" (identifier (\",\" identifier)+)? \")\""
+                            .to_string(),
+                    reference_count: 1,
+                    minimum_length: Some(3),
+                    depth: 6,
+                    terminal_count: 3,
+                },
+                VariableSummary {
+                    name: "identifier".to_string(),
+                    kind: VariableType::Named,
+                    rule_summary: "/[a-z]+/".to_string(),
+                    reference_count: 2,
+                    minimum_length: Some(1),
+                    depth: 1,
+                    terminal_count: 1,
+                },
+            ]
+        );
+    }
+}
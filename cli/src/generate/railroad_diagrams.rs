@@ -0,0 +1,315 @@
+use super::grammars::InputGrammar;
+use super::rules::Rule;
+use crate::error::{Error, Result};
+
+const CHAR_WIDTH: i32 = 8;
+const BOX_PADDING: i32 = 10;
+const BOX_HALF_HEIGHT: i32 = 11;
+const SEQUENCE_GAP: i32 = 10;
+const ARC_RADIUS: i32 = 10;
+const VERTICAL_GAP: i32 = 8;
+const LABEL_HEIGHT: i32 = 14;
+const MARGIN: i32 = 20;
+
+const STYLE: &str = "
+body { font-family: sans-serif; }
+svg.railroad path { fill: none; stroke: #333; stroke-width: 1.5; }
+svg.railroad rect { fill: #fff; stroke: #333; stroke-width: 1.5; }
+svg.railroad rect.terminal { fill: #efe; }
+svg.railroad rect.pattern { fill: #ffe; }
+svg.railroad rect.external { fill: #eee; stroke-dasharray: 4 2; }
+svg.railroad text { font-family: monospace; font-size: 13px; text-anchor: middle; }
+svg.railroad text.label { font-style: italic; text-anchor: start; }
+svg.railroad a text { fill: #06c; }
+";
+
+// A laid-out part of a railroad diagram. Its track enters on the left and leaves on
+// the right at `y = 0`, and its SVG is drawn between `y = -up` and `y = down`.
+struct Diagram {
+    width: i32,
+    up: i32,
+    down: i32,
+    svg: String,
+}
+
+// Render an HTML page with an SVG railroad diagram for each of the grammar's
+// variables. References to other variables link to their diagrams.
+pub(crate) fn render_railroad_diagrams(grammar: &InputGrammar) -> Result<String> {
+    let mut result = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>{1}</style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(&grammar.name),
+        STYLE
+    );
+    for variable in &grammar.variables {
+        let diagram = rule_diagram(grammar, &variable.rule)?;
+        let width = diagram.width + 2 * MARGIN;
+        let height = diagram.up + diagram.down + 2 * MARGIN;
+        result += &format!(
+            "<h2 id=\"{0}\">{0}</h2>\n<svg class=\"railroad\" width=\"{1}\" height=\"{2}\" viewBox=\"0 0 {1} {2}\">",
+            escape(&variable.name),
+            width,
+            height,
+        );
+        result += &format!(
+            "<g transform=\"translate({} {})\"><path d=\"M-10 -8v16M-10 0h10M{} 0h10m0 -8v16\"/>{}</g>",
+            MARGIN,
+            MARGIN + diagram.up,
+            diagram.width,
+            diagram.svg
+        );
+        result += "</svg>\n";
+    }
+    result += "</body>\n</html>\n";
+    Ok(result)
+}
+
+fn rule_diagram(grammar: &InputGrammar, rule: &Rule) -> Result<Diagram> {
+    Ok(match rule {
+        Rule::Blank => line(SEQUENCE_GAP),
+        Rule::String(value) => text_box(&format!("\"{}\"", value), "terminal", None),
+        Rule::Pattern(value) => text_box(&format!("/{}/", value), "pattern", None),
+        Rule::NamedSymbol(name) => {
+            if grammar.variables.iter().any(|v| v.name == *name) {
+                text_box(name, "nonterminal", Some(name))
+            } else if grammar.external_tokens.contains(rule) {
+                text_box(name, "external", None)
+            } else {
+                return Err(Error::undefined_symbol(name));
+            }
+        }
+        Rule::Symbol(_) => text_box("?", "nonterminal", None),
+        Rule::Choice(members) => choice(
+            members
+                .iter()
+                .map(|member| match member {
+                    Rule::Blank => Ok(line(0)),
+                    _ => rule_diagram(grammar, member),
+                })
+                .collect::<Result<_>>()?,
+        ),
+        Rule::Seq(members) => sequence(
+            members
+                .iter()
+                .map(|member| rule_diagram(grammar, member))
+                .collect::<Result<_>>()?,
+        ),
+        Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => repeat(rule_diagram(grammar, rule)?),
+        Rule::Metadata { params, rule } => {
+            let diagram = rule_diagram(grammar, rule)?;
+            match &params.field_name {
+                Some(field_name) => labeled(diagram, &format!("{}:", field_name)),
+                None => diagram,
+            }
+        }
+    })
+}
+
+fn line(width: i32) -> Diagram {
+    Diagram {
+        width,
+        up: 0,
+        down: 0,
+        svg: format!("<path d=\"M0 0h{}\"/>", width),
+    }
+}
+
+fn text_box(text: &str, class: &str, link: Option<&str>) -> Diagram {
+    let width = text.chars().count() as i32 * CHAR_WIDTH + 2 * BOX_PADDING;
+    let corner_radius = if class == "nonterminal" {
+        0
+    } else {
+        BOX_HALF_HEIGHT
+    };
+    let mut svg = format!(
+        "<rect class=\"{}\" x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/><text x=\"{}\" y=\"4\">{}</text>",
+        class,
+        -BOX_HALF_HEIGHT,
+        width,
+        2 * BOX_HALF_HEIGHT,
+        corner_radius,
+        width / 2,
+        escape(text)
+    );
+    if let Some(link) = link {
+        svg = format!("<a href=\"#{}\">{}</a>", escape(link), svg);
+    }
+    Diagram {
+        width,
+        up: BOX_HALF_HEIGHT,
+        down: BOX_HALF_HEIGHT,
+        svg,
+    }
+}
+
+// Place a label above a diagram, such as the name of the field that it belongs to.
+fn labeled(diagram: Diagram, label: &str) -> Diagram {
+    let label_width = label.chars().count() as i32 * CHAR_WIDTH;
+    let width = diagram.width.max(label_width);
+    let mut svg = format!(
+        "{}<text class=\"label\" x=\"0\" y=\"{}\">{}</text>",
+        diagram.svg,
+        -diagram.up - 4,
+        escape(label)
+    );
+    if width > diagram.width {
+        svg += &format!("<path d=\"M{} 0H{}\"/>", diagram.width, width);
+    }
+    Diagram {
+        width,
+        up: diagram.up + LABEL_HEIGHT,
+        down: diagram.down,
+        svg,
+    }
+}
+
+fn sequence(items: Vec<Diagram>) -> Diagram {
+    let mut result = line(0);
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            result.svg += &format!("<path d=\"M{} 0h{}\"/>", result.width, SEQUENCE_GAP);
+            result.width += SEQUENCE_GAP;
+        }
+        result.svg += &translate(&item, result.width, 0);
+        result.width += item.width;
+        result.up = result.up.max(item.up);
+        result.down = result.down.max(item.down);
+    }
+    result
+}
+
+// Stack the alternatives vertically, with the first one on the main track, and
+// connect each of the others to the main track on both sides.
+fn choice(mut alternatives: Vec<Diagram>) -> Diagram {
+    if alternatives.len() <= 1 {
+        return alternatives.pop().unwrap_or_else(|| line(0));
+    }
+    let r = ARC_RADIUS;
+    let inner_width = alternatives.iter().map(|a| a.width).max().unwrap_or(0);
+    let width = inner_width + 4 * r;
+    let mut svg = String::new();
+    let mut y = 0;
+    let mut down = 0;
+    for (i, alternative) in alternatives.iter().enumerate() {
+        let end = 2 * r + alternative.width;
+        if i == 0 {
+            svg += &format!("<path d=\"M0 0h{}\"/>", 2 * r);
+            svg += &translate(alternative, 2 * r, 0);
+            svg += &format!("<path d=\"M{} 0H{}\"/>", end, width);
+        } else {
+            y = (down + VERTICAL_GAP + alternative.up).max(2 * r);
+            svg += &format!(
+                "<path d=\"M0 0q{0} 0 {0} {0}V{1}q0 {0} {0} {0}\"/>",
+                r,
+                y - r
+            );
+            svg += &translate(alternative, 2 * r, y);
+            svg += &format!(
+                "<path d=\"M{0} {1}H{2}q{3} 0 {3} -{3}V{3}q0 -{3} {3} -{3}\"/>",
+                end,
+                y,
+                width - 2 * r,
+                r
+            );
+        }
+        down = y + alternative.down;
+    }
+    Diagram {
+        width,
+        up: alternatives[0].up,
+        down,
+        svg,
+    }
+}
+
+// Draw the item on the main track, with a track below it that loops back to the
+// item's start.
+fn repeat(item: Diagram) -> Diagram {
+    let r = ARC_RADIUS;
+    let width = item.width + 2 * r;
+    let loop_y = (item.down + VERTICAL_GAP).max(2 * r);
+    let mut svg = format!("<path d=\"M0 0h{}\"/>", r);
+    svg += &translate(&item, r, 0);
+    svg += &format!(
+        "<path d=\"M{0} 0h{1}M{0} 0q{1} 0 {1} {1}V{2}q0 {1} -{1} {1}H{1}q-{1} 0 -{1} -{1}V{1}q0 -{1} {1} -{1}\"/>",
+        r + item.width,
+        r,
+        loop_y - r
+    );
+    Diagram {
+        width,
+        up: item.up,
+        down: loop_y,
+        svg,
+    }
+}
+
+fn translate(diagram: &Diagram, x: i32, y: i32) -> String {
+    format!(
+        "<g transform=\"translate({} {})\">{}</g>",
+        x, y, diagram.svg
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::grammars::Variable;
+    use regex::Regex;
+
+    #[test]
+    fn test_railroad_diagrams_html() {
+        let html = render_railroad_diagrams(&InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::choice(vec![Rule::repeat(Rule::named("_statement")), Rule::Blank]),
+                ),
+                Variable::named(
+                    "_statement",
+                    Rule::seq(vec![
+                        Rule::field("callee".to_string(), Rule::named("identifier")),
+                        Rule::string("<"),
+                        Rule::named("heredoc"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: vec![Rule::named("heredoc")],
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        })
+        .unwrap();
+        assert!(html.contains("<title>the_language</title>"));
+        assert_eq!(html.matches("<svg class=\"railroad\"").count(), 3);
+        assert!(html.contains(">callee:</text>"));
+        assert!(html.contains(">&quot;&lt;&quot;</text>"));
+        assert!(html.contains("<rect class=\"external\""));
+        assert!(html.contains(">/[a-z]+/</text>"));
+
+        // Every link refers to one of the diagrams.
+        let ids = Regex::new("id=\"([^\"]+)\"").unwrap();
+        let ids = ids
+            .captures_iter(&html)
+            .map(|c| c[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, &["program", "_statement", "identifier"]);
+        let links = Regex::new("href=\"#([^\"]+)\"").unwrap();
+        let links = links
+            .captures_iter(&html)
+            .map(|c| c[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(links, &["_statement", "identifier"]);
+    }
+}
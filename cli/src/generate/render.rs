@@ -1,5 +1,6 @@
 use super::grammars::{ExternalToken, LexicalGrammar, SyntaxGrammar, VariableType};
 use super::nfa::CharacterSet;
+use super::node_types::VariableInfo;
 use super::rules::{Alias, AliasMap, Symbol, SymbolType};
 use super::tables::{
     AdvanceAction, FieldLocation, GotoAction, LexState, LexTable, ParseAction, ParseTable,
@@ -11,9 +12,8 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write;
 use std::mem::swap;
 
-// Currently, the library supports a new ABI version that has not yet been
-// stabilized, and the parser generation does not use it by default.
-const STABLE_LANGUAGE_VERSION: usize = tree_sitter::LANGUAGE_VERSION - 1;
+const ABI_VERSION_WITH_SMALL_STATES: usize = 11;
+const ABI_VERSION_WITH_LANGUAGE_INFO: usize = 12;
 
 const LARGE_CHARACTER_RANGE_COUNT: usize = 8;
 
@@ -71,7 +71,8 @@ struct Generator {
     alias_ids: HashMap<Alias, String>,
     alias_map: BTreeMap<Alias, Option<Symbol>>,
     field_names: Vec<String>,
-    next_abi: bool,
+    node_type_fields: Vec<Vec<(String, bool)>>,
+    abi_version: usize,
 }
 
 struct TransitionSummary {
@@ -88,7 +89,7 @@ struct LargeCharacterSetInfo {
 }
 
 impl Generator {
-    fn generate(mut self) -> String {
+    fn generate(mut self) -> (String, String) {
         self.init();
         self.add_includes();
         self.add_pragmas();
@@ -96,16 +97,21 @@ impl Generator {
         self.add_symbol_enum();
         self.add_symbol_names_list();
 
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             self.add_unique_symbol_map();
         }
 
         self.add_symbol_metadata_list();
+        let symbol_map_json = self.symbol_map_json();
 
         if !self.field_names.is_empty() {
             self.add_field_name_enum();
             self.add_field_name_names_list();
             self.add_field_sequences();
+            if self.abi_version >= ABI_VERSION_WITH_LANGUAGE_INFO {
+                self.add_node_type_fields();
+                self.add_node_type_child_fields();
+            }
         }
 
         if !self.parse_table.production_infos.is_empty() {
@@ -133,7 +139,7 @@ impl Generator {
         self.add_parse_table();
         self.add_parser_export();
 
-        self.buffer
+        (self.buffer, symbol_map_json)
     }
 
     fn init(&mut self) {
@@ -177,7 +183,7 @@ impl Generator {
         // If we are opting in to the new unstable language ABI, then use the concept of
         // "small parse states". Otherwise, use the same representation for all parse
         // states.
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             let threshold = cmp::min(SMALL_STATE_THRESHOLD, self.parse_table.symbols.len() / 2);
             self.large_state_count = self
                 .parse_table
@@ -241,15 +247,7 @@ impl Generator {
             })
             .count();
 
-        if self.next_abi {
-            add_line!(
-                self,
-                "#define LANGUAGE_VERSION {}",
-                tree_sitter::LANGUAGE_VERSION
-            );
-        } else {
-            add_line!(self, "#define LANGUAGE_VERSION {}", STABLE_LANGUAGE_VERSION);
-        }
+        add_line!(self, "#define LANGUAGE_VERSION {}", self.abi_version);
 
         add_line!(
             self,
@@ -257,7 +255,7 @@ impl Generator {
             self.parse_table.states.len()
         );
 
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             add_line!(self, "#define LARGE_STATE_COUNT {}", self.large_state_count);
         }
 
@@ -452,6 +450,9 @@ impl Generator {
                     VariableType::Hidden => {
                         add_line!(self, ".visible = false,");
                         add_line!(self, ".named = true,");
+                        if self.syntax_grammar.supertype_symbols.contains(symbol) {
+                            add_line!(self, ".supertype = true,");
+                        }
                     }
                     VariableType::Auxiliary => {
                         add_line!(self, ".visible = false,");
@@ -477,6 +478,36 @@ impl Generator {
         add_line!(self, "");
     }
 
+    // Describe each symbol's numeric id, name and metadata, as they appear in the
+    // `ts_symbol_names` and `ts_symbol_metadata` arrays.
+    fn symbol_map_json(&self) -> String {
+        let mut result = serde_json::Map::new();
+        for symbol in &self.parse_table.symbols {
+            let (name, is_named) = match self.simple_aliases.get(symbol) {
+                Some(alias) => (alias.value.as_str(), alias.is_named),
+                None => match self.metadata_for_symbol(*symbol) {
+                    (name, VariableType::Named) | (name, VariableType::Hidden) => (name, true),
+                    (name, _) => (name, false),
+                },
+            };
+            result.insert(
+                self.symbol_order[symbol].to_string(),
+                serde_json::json!({ "name": name, "named": is_named }),
+            );
+        }
+        let mut id = self.symbol_order.len();
+        for (alias, symbol) in &self.alias_map {
+            if symbol.is_none() {
+                result.insert(
+                    id.to_string(),
+                    serde_json::json!({ "name": alias.value, "named": alias.is_named }),
+                );
+                id += 1;
+            }
+        }
+        serde_json::to_string_pretty(&result).unwrap() + "\n"
+    }
+
     fn add_alias_sequences(&mut self) {
         add_line!(
             self,
@@ -585,6 +616,167 @@ impl Generator {
         add_line!(self, "");
     }
 
+    // For each node type with fields, list the fields that the grammar declares for
+    // it, along with whether each of them can contain multiple nodes.
+    fn add_node_type_fields(&mut self) {
+        let mut slices = Vec::new();
+        let mut entries = Vec::new();
+        for symbol in &self.parse_table.symbols {
+            if !symbol.is_non_terminal() {
+                continue;
+            }
+            let fields = self.node_type_fields[symbol.index]
+                .iter()
+                .filter(|(name, _)| self.field_names.contains(name))
+                .collect::<Vec<_>>();
+            if !fields.is_empty() {
+                slices.push((*symbol, entries.len(), fields.len()));
+                entries.extend(fields);
+            }
+        }
+
+        add_line!(
+            self,
+            "static const TSFieldMapSlice ts_node_type_field_slices[SYMBOL_COUNT + ALIAS_COUNT] = {{",
+        );
+        indent!(self);
+        for (symbol, index, length) in slices {
+            add_line!(
+                self,
+                "[{}] = {{.index = {}, .length = {}}},",
+                self.symbol_ids[&symbol],
+                index,
+                length
+            );
+        }
+        dedent!(self);
+        add_line!(self, "}};");
+        add_line!(self, "");
+
+        add_line!(
+            self,
+            "static const TSNodeTypeField ts_node_type_fields[] = {{"
+        );
+        indent!(self);
+        for (field_name, multiple) in entries {
+            add_line!(self, "{{{}, {}}},", self.field_id(field_name), multiple);
+        }
+        dedent!(self);
+        add_line!(self, "}};");
+        add_line!(self, "");
+    }
+
+    // For each node type with fields, list the field that the grammar assigns to each
+    // position in the node's children, counted the way `ts_node_child` counts them.
+    // The first entry is unused, so that the list is never empty.
+    fn add_node_type_child_fields(&mut self) {
+        let mut slices = Vec::new();
+        let mut entries = vec![None];
+        for symbol in &self.parse_table.symbols {
+            if !symbol.is_non_terminal() {
+                continue;
+            }
+            let fields = self.child_fields_for_variable(symbol.index);
+            if !fields.is_empty() {
+                slices.push((*symbol, entries.len(), fields.len()));
+                entries.extend(fields);
+            }
+        }
+
+        add_line!(
+            self,
+            "static const TSFieldMapSlice ts_node_type_child_field_slices[SYMBOL_COUNT + ALIAS_COUNT] = {{",
+        );
+        indent!(self);
+        for (symbol, index, length) in slices {
+            add_line!(
+                self,
+                "[{}] = {{.index = {}, .length = {}}},",
+                self.symbol_ids[&symbol],
+                index,
+                length
+            );
+        }
+        dedent!(self);
+        add_line!(self, "}};");
+        add_line!(self, "");
+
+        add_line!(
+            self,
+            "static const TSFieldId ts_node_type_child_fields[] = {{"
+        );
+        indent!(self);
+        for field_name in entries {
+            match field_name {
+                Some(field_name) => {
+                    add_line!(self, "{},", self.field_id(&field_name));
+                }
+                None => {
+                    add_line!(self, "0,");
+                }
+            }
+        }
+        dedent!(self);
+        add_line!(self, "}};");
+        add_line!(self, "");
+    }
+
+    // Find the field at each child position of the given variable's nodes, or `None`
+    // if there is no field there, or if different productions assign different
+    // fields to it. A hidden non-terminal contributes a varying number of children,
+    // so a production's positions are only known up to the first one. Trailing
+    // positions without a field are omitted.
+    fn child_fields_for_variable(&self, index: usize) -> Vec<Option<String>> {
+        let mut fields: Vec<Option<&String>> = Vec::new();
+        let mut conflicts = Vec::new();
+        for production in &self.syntax_grammar.variables[index].productions {
+            let mut child_index = 0;
+            for step in &production.steps {
+                if step.alias.is_none() && !self.symbol_is_visible(step.symbol) {
+                    if step.symbol.is_non_terminal() {
+                        break;
+                    }
+                    continue;
+                }
+                if let Some(field_name) = &step.field_name {
+                    if fields.len() <= child_index {
+                        fields.resize(child_index + 1, None);
+                        conflicts.resize(child_index + 1, false);
+                    }
+                    match fields[child_index] {
+                        None => fields[child_index] = Some(field_name),
+                        Some(existing) if existing != field_name => conflicts[child_index] = true,
+                        _ => {}
+                    }
+                }
+                child_index += 1;
+            }
+        }
+
+        let mut result = fields
+            .into_iter()
+            .zip(conflicts)
+            .map(|(field_name, conflict)| if conflict { None } else { field_name.cloned() })
+            .collect::<Vec<_>>();
+        while result.last() == Some(&None) {
+            result.pop();
+        }
+        result
+    }
+
+    fn symbol_is_visible(&self, symbol: Symbol) -> bool {
+        if self.simple_aliases.contains_key(&symbol) {
+            return true;
+        }
+        if self.syntax_grammar.variables_to_inline.contains(&symbol) {
+            return false;
+        }
+        match self.metadata_for_symbol(symbol).1 {
+            VariableType::Named | VariableType::Anonymous => true,
+            VariableType::Hidden | VariableType::Auxiliary => false,
+        }
+    }
+
     fn add_lex_function(
         &mut self,
         name: &str,
@@ -691,7 +883,7 @@ impl Generator {
         indent!(self);
         add_line!(self, "START_LEXER();");
 
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             add_line!(self, "eof = lexer->eof(lexer);");
         } else {
             add_line!(self, "eof = lookahead == 0;");
@@ -968,7 +1160,7 @@ impl Generator {
         add_line!(
             self,
             "static uint16_t ts_parse_table[{}][SYMBOL_COUNT] = {{",
-            if self.next_abi {
+            if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
                 "LARGE_STATE_COUNT"
             } else {
                 "STATE_COUNT"
@@ -1225,7 +1417,7 @@ impl Generator {
         add_line!(self, ".alias_count = ALIAS_COUNT,");
         add_line!(self, ".token_count = TOKEN_COUNT,");
 
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             add_line!(self, ".large_state_count = LARGE_STATE_COUNT,");
         }
 
@@ -1250,10 +1442,18 @@ impl Generator {
         add_line!(self, ".lex_modes = ts_lex_modes,");
         add_line!(self, ".symbol_names = ts_symbol_names,");
 
-        if self.next_abi {
+        if self.abi_version >= ABI_VERSION_WITH_SMALL_STATES {
             add_line!(self, ".public_symbol_map = ts_symbol_map,");
         }
 
+        if self.abi_version >= ABI_VERSION_WITH_LANGUAGE_INFO {
+            add_line!(self, ".name = \"{}\",", self.language_name);
+            if let Some(version) = &self.syntax_grammar.grammar_version {
+                add_line!(self, ".grammar_version = \"{}\",", version);
+            }
+            add_line!(self, ".state_count = STATE_COUNT,");
+        }
+
         if !self.parse_table.production_infos.is_empty() {
             add_line!(
                 self,
@@ -1273,6 +1473,15 @@ impl Generator {
                 self,
                 ".field_map_entries = (const TSFieldMapEntry *)ts_field_map_entries,"
             );
+            if self.abi_version >= ABI_VERSION_WITH_LANGUAGE_INFO {
+                add_line!(self, ".node_type_field_slices = ts_node_type_field_slices,");
+                add_line!(self, ".node_type_fields = ts_node_type_fields,");
+                add_line!(
+                    self,
+                    ".node_type_child_field_slices = ts_node_type_child_field_slices,"
+                );
+                add_line!(self, ".node_type_child_fields = ts_node_type_child_fields,");
+            }
         }
 
         add_line!(
@@ -1511,8 +1720,13 @@ impl Generator {
 /// * `simple_aliases` - A map describing the global rename rules that should apply.
 ///    the keys are symbols that are *always* aliased in the same way, and the values
 ///    are the aliases that are applied to those symbols.
-/// * `next_abi` - A boolean indicating whether to opt into the new, unstable parse
-///    table format. This is mainly used for testing, when developing Tree-sitter itself.
+/// * `variable_info` - Information about the fields and children of each of the
+///    syntax grammar's variables, indexed by variable.
+/// * `abi_version` - The ABI version of the generated parser. This determines which
+///    parse table format is used, and which fields of `TSLanguage` are populated.
+///
+/// Returns the C code along with a JSON object that maps each symbol's numeric id to
+/// its name and whether it is named.
 pub(crate) fn render_c_code(
     name: &str,
     parse_table: ParseTable,
@@ -1522,8 +1736,22 @@ pub(crate) fn render_c_code(
     syntax_grammar: SyntaxGrammar,
     lexical_grammar: LexicalGrammar,
     simple_aliases: AliasMap,
-    next_abi: bool,
-) -> String {
+    variable_info: &[VariableInfo],
+    abi_version: usize,
+) -> (String, String) {
+    let node_type_fields = variable_info
+        .iter()
+        .map(|info| {
+            let mut fields = info
+                .fields
+                .iter()
+                .map(|(name, field)| (name.clone(), field.quantity.is_multiple()))
+                .collect::<Vec<_>>();
+            fields.sort_unstable();
+            fields
+        })
+        .collect();
+
     Generator {
         buffer: String::new(),
         indent_level: 0,
@@ -1541,7 +1769,8 @@ pub(crate) fn render_c_code(
         alias_ids: HashMap::new(),
         alias_map: BTreeMap::new(),
         field_names: Vec::new(),
-        next_abi,
+        node_type_fields,
+        abi_version,
     }
     .generate()
 }
@@ -57,6 +57,12 @@ pub(crate) enum Rule {
         rule: Box<Rule>,
     },
     Repeat(Box<Rule>),
+    // A repetition with a minimum number of occurrences. This is expanded into
+    // other rules when the grammar's symbols are interned.
+    RepeatMin {
+        rule: Box<Rule>,
+        min: usize,
+    },
     Seq(Vec<Rule>),
 }
 
@@ -127,16 +133,96 @@ impl Rule {
         Rule::Repeat(Box::new(rule))
     }
 
+    pub fn repeat_min(rule: Rule, min: usize) -> Self {
+        Rule::RepeatMin {
+            rule: Box::new(rule),
+            min,
+        }
+    }
+
     pub fn choice(rules: Vec<Rule>) -> Self {
         let mut elements = Vec::with_capacity(rules.len());
-        for rule in rules {
-            choice_helper(&mut elements, rule);
+        for alternative in rules.iter().flat_map(Rule::flatten_choices) {
+            if !elements.contains(&alternative) {
+                elements.push(alternative);
+            }
         }
         Rule::Choice(elements)
     }
 
     pub fn seq(rules: Vec<Rule>) -> Self {
-        Rule::Seq(rules)
+        Rule::Seq(rules.iter().flat_map(Rule::flatten_seqs).collect())
+    }
+
+    // Get the alternatives of this rule, with the alternatives of any nested choices
+    // listed in place of those choices. A rule that isn't a choice is its own only
+    // alternative.
+    pub fn flatten_choices(&self) -> Vec<Rule> {
+        let mut result = Vec::new();
+        flatten_helper(self, &mut result, |rule| match rule {
+            Rule::Choice(members) => Some(members),
+            _ => None,
+        });
+        result
+    }
+
+    // Get the steps of this rule, with the steps of any nested sequences listed in
+    // place of those sequences. A rule that isn't a sequence is its own only step.
+    pub fn flatten_seqs(&self) -> Vec<Rule> {
+        let mut result = Vec::new();
+        flatten_helper(self, &mut result, |rule| match rule {
+            Rule::Seq(members) => Some(members),
+            _ => None,
+        });
+        result
+    }
+
+    // Check whether this rule or any rule nested within it is the given symbol. Named
+    // symbols haven't been resolved to symbols yet, so they never match.
+    pub fn contains_symbol(&self, symbol: Symbol) -> bool {
+        match self {
+            Rule::Symbol(s) => *s == symbol,
+            Rule::Choice(members) | Rule::Seq(members) => {
+                members.iter().any(|member| member.contains_symbol(symbol))
+            }
+            Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+                rule.contains_symbol(symbol)
+            }
+            Rule::Blank | Rule::String(_) | Rule::Pattern(_) | Rule::NamedSymbol(_) => false,
+        }
+    }
+
+    // Get the number of levels of nesting in this rule. Rules with no nested rules
+    // have a depth of 1.
+    pub fn max_depth(&self) -> usize {
+        match self {
+            Rule::Choice(members) | Rule::Seq(members) => {
+                1 + members.iter().map(Rule::max_depth).max().unwrap_or(0)
+            }
+            Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+                1 + rule.max_depth()
+            }
+            Rule::Blank
+            | Rule::String(_)
+            | Rule::Pattern(_)
+            | Rule::NamedSymbol(_)
+            | Rule::Symbol(_) => 1,
+        }
+    }
+
+    // Count the strings and patterns within this rule. Symbols aren't counted, even
+    // if they refer to tokens.
+    pub fn count_terminals(&self) -> usize {
+        match self {
+            Rule::String(_) | Rule::Pattern(_) => 1,
+            Rule::Choice(members) | Rule::Seq(members) => {
+                members.iter().map(Rule::count_terminals).sum()
+            }
+            Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+                rule.count_terminals()
+            }
+            Rule::Blank | Rule::NamedSymbol(_) | Rule::Symbol(_) => 0,
+        }
     }
 }
 
@@ -175,6 +261,23 @@ impl Rule {
     pub fn pattern(value: &'static str) -> Self {
         Rule::Pattern(value.to_string())
     }
+
+    // Match the given string regardless of the case of its ASCII letters, using a
+    // pattern with a character class for each letter, such as `[sS][eE][lL]`.
+    pub fn string_case_insensitive(value: &str) -> Self {
+        let mut pattern = String::new();
+        for c in value.chars() {
+            if c.is_ascii_alphabetic() {
+                pattern.push('[');
+                pattern.push(c.to_ascii_lowercase());
+                pattern.push(c.to_ascii_uppercase());
+                pattern.push(']');
+            } else {
+                pattern += &regex::escape(&c.to_string());
+            }
+        }
+        Rule::Pattern(pattern)
+    }
 }
 
 impl Symbol {
@@ -392,17 +495,93 @@ fn add_metadata<T: FnOnce(&mut MetadataParams)>(input: Rule, f: T) -> Rule {
     }
 }
 
-fn choice_helper(result: &mut Vec<Rule>, rule: Rule) {
-    match rule {
-        Rule::Choice(elements) => {
-            for element in elements {
-                choice_helper(result, element);
-            }
-        }
-        _ => {
-            if !result.contains(&rule) {
-                result.push(rule);
+fn flatten_helper(rule: &Rule, result: &mut Vec<Rule>, members: fn(&Rule) -> Option<&Vec<Rule>>) {
+    match members(rule) {
+        Some(members_of_rule) => {
+            for member in members_of_rule {
+                flatten_helper(member, result, members);
             }
         }
+        None => result.push(rule.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten_choices_and_seqs() {
+        let rule = Rule::Choice(vec![
+            Rule::Choice(vec![Rule::named("a"), Rule::Choice(vec![Rule::named("b")])]),
+            Rule::Seq(vec![Rule::Seq(vec![Rule::named("c")]), Rule::named("d")]),
+            Rule::named("a"),
+        ]);
+        assert_eq!(
+            rule.flatten_choices(),
+            &[
+                Rule::named("a"),
+                Rule::named("b"),
+                Rule::Seq(vec![Rule::Seq(vec![Rule::named("c")]), Rule::named("d")]),
+                Rule::named("a"),
+            ]
+        );
+        assert_eq!(rule.flatten_seqs(), &[rule.clone()]);
+        assert_eq!(
+            rule.flatten_choices()[2].flatten_seqs(),
+            &[Rule::named("c"), Rule::named("d")]
+        );
+    }
+
+    #[test]
+    fn test_contains_symbol() {
+        let rule = Rule::seq(vec![
+            Rule::string("("),
+            Rule::choice(vec![
+                Rule::repeat(Rule::non_terminal(1)),
+                Rule::prec(2, Rule::terminal(3)),
+            ]),
+            Rule::named("a"),
+        ]);
+        assert!(rule.contains_symbol(Symbol::non_terminal(1)));
+        assert!(rule.contains_symbol(Symbol::terminal(3)));
+        assert!(!rule.contains_symbol(Symbol::terminal(1)));
+        assert!(!rule.contains_symbol(Symbol::non_terminal(0)));
+        assert!(!Rule::Blank.contains_symbol(Symbol::non_terminal(0)));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        assert_eq!(Rule::string("a").max_depth(), 1);
+        assert_eq!(Rule::seq(vec![]).max_depth(), 1);
+        assert_eq!(
+            Rule::seq(vec![
+                Rule::string("("),
+                Rule::choice(vec![
+                    Rule::repeat(Rule::non_terminal(1)),
+                    Rule::prec(2, Rule::terminal(3)),
+                ]),
+            ])
+            .max_depth(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_count_terminals() {
+        assert_eq!(Rule::Blank.count_terminals(), 0);
+        assert_eq!(
+            Rule::seq(vec![
+                Rule::string("("),
+                Rule::choice(vec![
+                    Rule::repeat(Rule::pattern("[a-z]+")),
+                    Rule::prec(2, Rule::terminal(3)),
+                    Rule::named("a"),
+                ]),
+                Rule::token(Rule::seq(vec![Rule::string(")"), Rule::pattern("\\s*")])),
+            ])
+            .count_terminals(),
+            4
+        );
     }
 }
@@ -0,0 +1,100 @@
+{-# LANGUAGE ForeignFunctionInterface #-}
+
+-- This file is generated by `tree-sitter generate --emit-haskell-bindings`.
+
+module TreeSitter.MODULE_NAME
+  ( TSLanguage
+  , Language (..)
+  , language
+  , languageVersion
+  , symbolCount
+  , symbolName
+  , symbolForName
+  , fieldCount
+  , fieldName
+  , fieldForName
+  ) where
+
+import Data.Word (Word16, Word32)
+import Foreign.C.String (CString, peekCString, withCStringLen)
+import Foreign.C.Types (CBool)
+import Foreign.Ptr (Ptr, nullPtr)
+import System.IO.Unsafe (unsafePerformIO)
+
+-- | The tree-sitter C library's language type.
+data TSLanguage
+
+-- | A parsed language that can be passed to the tree-sitter C library.
+newtype Language = Language (Ptr TSLanguage)
+
+foreign import ccall unsafe "tree_sitter_PARSER_NAME"
+  tree_sitter_PARSER_NAME :: IO (Ptr TSLanguage)
+
+foreign import ccall unsafe "ts_language_version"
+  ts_language_version :: Ptr TSLanguage -> IO Word32
+
+foreign import ccall unsafe "ts_language_symbol_count"
+  ts_language_symbol_count :: Ptr TSLanguage -> IO Word32
+
+foreign import ccall unsafe "ts_language_symbol_name"
+  ts_language_symbol_name :: Ptr TSLanguage -> Word16 -> IO CString
+
+foreign import ccall unsafe "ts_language_symbol_for_name"
+  ts_language_symbol_for_name :: Ptr TSLanguage -> CString -> Word32 -> CBool -> IO Word16
+
+foreign import ccall unsafe "ts_language_field_count"
+  ts_language_field_count :: Ptr TSLanguage -> IO Word32
+
+foreign import ccall unsafe "ts_language_field_name_for_id"
+  ts_language_field_name_for_id :: Ptr TSLanguage -> Word16 -> IO CString
+
+foreign import ccall unsafe "ts_language_field_id_for_name"
+  ts_language_field_id_for_name :: Ptr TSLanguage -> CString -> Word32 -> IO Word16
+
+-- | The PARSER_NAME language.
+language :: Language
+language = Language (unsafePerformIO tree_sitter_PARSER_NAME)
+{-# NOINLINE language #-}
+
+-- | The ABI version of the tree-sitter library that the parser was generated for.
+languageVersion :: Language -> Word32
+languageVersion (Language ptr) = unsafePerformIO (ts_language_version ptr)
+
+-- | The number of distinct node types in the language.
+symbolCount :: Language -> Word32
+symbolCount (Language ptr) = unsafePerformIO (ts_language_symbol_count ptr)
+
+-- | The node type for the given numerical id.
+symbolName :: Language -> Word16 -> Maybe String
+symbolName (Language ptr) symbol =
+  unsafePerformIO (ts_language_symbol_name ptr symbol >>= peekNullableCString)
+
+-- | The numerical id for the given node type, and whether the node type is named.
+symbolForName :: Language -> String -> Bool -> Maybe Word16
+symbolForName (Language ptr) name isNamed =
+  unsafePerformIO $
+    withCStringLen name $ \(string, len) -> do
+      symbol <- ts_language_symbol_for_name ptr string (fromIntegral len) (if isNamed then 1 else 0)
+      pure (if symbol == 0 then Nothing else Just symbol)
+
+-- | The number of distinct field names in the language.
+fieldCount :: Language -> Word32
+fieldCount (Language ptr) = unsafePerformIO (ts_language_field_count ptr)
+
+-- | The field name for the given numerical id.
+fieldName :: Language -> Word16 -> Maybe String
+fieldName (Language ptr) field =
+  unsafePerformIO (ts_language_field_name_for_id ptr field >>= peekNullableCString)
+
+-- | The numerical id for the given field name.
+fieldForName :: Language -> String -> Maybe Word16
+fieldForName (Language ptr) name =
+  unsafePerformIO $
+    withCStringLen name $ \(string, len) -> do
+      field <- ts_language_field_id_for_name ptr string (fromIntegral len)
+      pure (if field == 0 then Nothing else Just field)
+
+peekNullableCString :: CString -> IO (Maybe String)
+peekNullableCString string
+  | string == nullPtr = pure Nothing
+  | otherwise = Just <$> peekCString string
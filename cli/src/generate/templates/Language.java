@@ -0,0 +1,18 @@
+package treesitter;
+
+/**
+ * The PARSER_NAME grammar for the tree-sitter parsing library.
+ */
+public final class CAMEL_PARSER_NAME {
+  static {
+    System.loadLibrary("tree-sitter-KEBAB_PARSER_NAME");
+  }
+
+  private CAMEL_PARSER_NAME() {}
+
+  /**
+   * Get the address of the tree-sitter language for this grammar, which can be
+   * passed to the tree-sitter library's Java bindings.
+   */
+  public static native long language();
+}
@@ -0,0 +1,5 @@
+"""PARSER_NAME grammar for the tree-sitter parsing library"""
+
+from ._binding import language
+
+__all__ = ["language"]
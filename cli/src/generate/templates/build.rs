@@ -0,0 +1,33 @@
+use std::path::Path;
+
+fn main() {
+    let src_dir = Path::new("src");
+
+    let mut c_config = cc::Build::new();
+    c_config.include(&src_dir);
+    c_config
+        .flag_if_supported("-Wno-unused-parameter")
+        .flag_if_supported("-Wno-unused-but-set-variable")
+        .flag_if_supported("-Wno-trigraphs");
+    let parser_path = src_dir.join("parser.c");
+    c_config.file(&parser_path);
+    println!("cargo:rerun-if-changed={}", parser_path.to_str().unwrap());
+
+    // Compile an external scanner, if the grammar has one.
+    let scanner_path = src_dir.join("scanner.c");
+    if scanner_path.exists() {
+        c_config.file(&scanner_path);
+        println!("cargo:rerun-if-changed={}", scanner_path.to_str().unwrap());
+    }
+    c_config.compile("parser");
+
+    let scanner_path = src_dir.join("scanner.cc");
+    if scanner_path.exists() {
+        cc::Build::new()
+            .cpp(true)
+            .include(&src_dir)
+            .file(&scanner_path)
+            .compile("scanner");
+        println!("cargo:rerun-if-changed={}", scanner_path.to_str().unwrap());
+    }
+}
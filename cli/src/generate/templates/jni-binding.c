@@ -0,0 +1,9 @@
+#include <jni.h>
+
+typedef struct TSLanguage TSLanguage;
+
+TSLanguage *tree_sitter_PARSER_NAME(void);
+
+JNIEXPORT jlong JNICALL Java_treesitter_CAMEL_PARSER_NAME_language(JNIEnv *env, jclass class) {
+  return (jlong)tree_sitter_PARSER_NAME();
+}
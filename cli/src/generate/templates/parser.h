@@ -0,0 +1,16 @@
+#ifndef TREE_SITTER_UPPER_PARSER_NAME_H_
+#define TREE_SITTER_UPPER_PARSER_NAME_H_
+
+#include "tree_sitter/api.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+extern const TSLanguage *tree_sitter_PARSER_NAME(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif  // TREE_SITTER_UPPER_PARSER_NAME_H_
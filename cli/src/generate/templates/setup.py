@@ -0,0 +1,28 @@
+from os.path import exists
+from platform import system
+
+from setuptools import Extension, find_packages, setup
+
+sources = [
+    "bindings/python/tree_sitter_PARSER_NAME/binding.c",
+    "src/parser.c",
+]
+if exists("src/scanner.c"):
+    sources.append("src/scanner.c")
+
+setup(
+    name="tree-sitter-KEBAB_PARSER_NAME",
+    version="0.0.1",
+    description="PARSER_NAME grammar for the tree-sitter parsing library",
+    packages=find_packages("bindings/python"),
+    package_dir={"": "bindings/python"},
+    ext_modules=[
+        Extension(
+            name="tree_sitter_PARSER_NAME._binding",
+            sources=sources,
+            include_dirs=["src"],
+            extra_compile_args=["-std=c99"] if system() != "Windows" else [],
+        )
+    ],
+    zip_safe=False,
+)
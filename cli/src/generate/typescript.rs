@@ -0,0 +1,343 @@
+use super::node_types::{FieldInfoJSON, NodeInfoJSON, NodeTypeJSON};
+use crate::error::Result;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+const HEADER: &str =
+    "// This file is generated by `tree-sitter generate --emit-node-types-typescript`.
+
+export interface SyntaxNodeBase {
+  type: string;
+  text: string;
+  isNamed: boolean;
+  startIndex: number;
+  endIndex: number;
+  parent: SyntaxNode | null;
+  children: SyntaxNode[];
+  namedChildren: NamedNode[];
+}
+
+export interface UnnamedNode<T extends string = string> extends SyntaxNodeBase {
+  type: T;
+  isNamed: false;
+}
+
+export type SyntaxNode = NamedNode | UnnamedNode;
+";
+
+// Generate TypeScript declarations from the contents of a `node-types.json` file.
+// Every named node type gets an interface with an accessor method for each of its
+// fields, and every supertype becomes a union of its subtypes.
+pub(crate) fn generate_typescript_declarations(node_types_json: &str) -> Result<String> {
+    let node_types: Vec<NodeInfoJSON> = serde_json::from_str(node_types_json)?;
+    let mut result = HEADER.to_string();
+    let type_names = unique_type_names(&node_types);
+
+    let concrete_type_names = node_types
+        .iter()
+        .filter(|node_type| node_type.named && node_type.subtypes.is_none())
+        .map(|node_type| &type_names[node_type.kind.as_str()])
+        .collect::<Vec<_>>();
+    result += "\nexport type NamedNode =";
+    if concrete_type_names.is_empty() {
+        result += " never";
+    }
+    for name in concrete_type_names {
+        write!(&mut result, "\n  | {}", name).unwrap();
+    }
+    result += ";\n";
+
+    for node_type in node_types.iter().filter(|node_type| node_type.named) {
+        result.push('\n');
+        if let Some(subtypes) = &node_type.subtypes {
+            writeln!(
+                &mut result,
+                "export type {} = {};",
+                type_names[node_type.kind.as_str()],
+                type_union(subtypes, &type_names)
+            )
+            .unwrap();
+            continue;
+        }
+
+        writeln!(
+            &mut result,
+            "export interface {} extends SyntaxNodeBase {{",
+            type_names[node_type.kind.as_str()]
+        )
+        .unwrap();
+        writeln!(&mut result, "  type: {};", string_literal(&node_type.kind)).unwrap();
+        writeln!(&mut result, "  isNamed: true;").unwrap();
+        if let Some(fields) = &node_type.fields {
+            for (field_name, field) in fields {
+                writeln!(
+                    &mut result,
+                    "  {};",
+                    field_accessor(field_name, field, &type_names)
+                )
+                .unwrap();
+            }
+        }
+        result += "}\n";
+    }
+
+    Ok(result)
+}
+
+fn field_accessor(
+    field_name: &str,
+    field: &FieldInfoJSON,
+    type_names: &HashMap<&str, String>,
+) -> String {
+    let name = camel_case(field_name);
+    let types = type_union(&field.types, type_names);
+    if field.multiple {
+        if field.types.len() > 1 {
+            format!("{}Nodes(): ({})[]", name, types)
+        } else {
+            format!("{}Nodes(): {}[]", name, types)
+        }
+    } else if field.required {
+        format!("{}Node(): {}", name, types)
+    } else {
+        format!("{}Node(): {} | null", name, types)
+    }
+}
+
+fn type_union(types: &[NodeTypeJSON], type_names: &HashMap<&str, String>) -> String {
+    if types.is_empty() {
+        return "never".to_string();
+    }
+    types
+        .iter()
+        .map(|node_type| {
+            if node_type.named {
+                type_names
+                    .get(node_type.kind.as_str())
+                    .cloned()
+                    .unwrap_or_else(|| type_name(&node_type.kind))
+            } else {
+                format!("UnnamedNode<{}>", string_literal(&node_type.kind))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// Assign an interface name to every named node type. Distinct node types can map
+// to the same name (e.g. `_expression` and `expression`), so every name after the
+// first one gets a numeric suffix.
+fn unique_type_names(node_types: &[NodeInfoJSON]) -> HashMap<&str, String> {
+    let mut result = HashMap::new();
+    let mut used_names = HashSet::new();
+    for node_type in node_types.iter().filter(|node_type| node_type.named) {
+        if result.contains_key(node_type.kind.as_str()) {
+            continue;
+        }
+        let base_name = type_name(&node_type.kind);
+        let base_name = &base_name[..base_name.len() - "Node".len()];
+        let mut name = format!("{}Node", base_name);
+        let mut suffix = 2;
+        while !used_names.insert(name.clone()) {
+            name = format!("{}{}Node", base_name, suffix);
+            suffix += 1;
+        }
+        result.insert(node_type.kind.as_str(), name);
+    }
+    result
+}
+
+// Convert a node type like `_binary_expression` to an interface name like
+// `BinaryExpressionNode`. Characters that can't appear in an identifier are
+// spelled out, so that `+=` becomes `PlusEqNode`.
+fn type_name(kind: &str) -> String {
+    let mut result = String::new();
+    let mut start_word = true;
+    for c in kind.chars() {
+        if c.is_alphanumeric() {
+            if start_word {
+                result.extend(c.to_uppercase());
+            } else {
+                result.push(c);
+            }
+            start_word = false;
+        } else {
+            if let Some(word) = punctuation_name(c) {
+                result += word;
+            }
+            start_word = true;
+        }
+    }
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result + "Node"
+}
+
+fn punctuation_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '~' => "Tilde",
+        '`' => "Bquote",
+        '!' => "Bang",
+        '@' => "At",
+        '#' => "Pound",
+        '$' => "Dollar",
+        '%' => "Percent",
+        '^' => "Caret",
+        '&' => "Amp",
+        '*' => "Star",
+        '(' => "Lparen",
+        ')' => "Rparen",
+        '-' => "Dash",
+        '+' => "Plus",
+        '=' => "Eq",
+        '{' => "Lbrace",
+        '}' => "Rbrace",
+        '[' => "Lbrack",
+        ']' => "Rbrack",
+        '\\' => "Bslash",
+        '|' => "Pipe",
+        ':' => "Colon",
+        ';' => "Semi",
+        '"' => "Dquote",
+        '\'' => "Squote",
+        '<' => "Lt",
+        '>' => "Gt",
+        ',' => "Comma",
+        '.' => "Dot",
+        '?' => "Qmark",
+        '/' => "Slash",
+        _ => return None,
+    })
+}
+
+fn camel_case(name: &str) -> String {
+    let type_name = type_name(name);
+    let mut chars = type_name[..type_name.len() - "Node".len()].chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_typescript_declarations() {
+        let declarations = generate_typescript_declarations(
+            r#"[
+                {
+                    "type": "_expression",
+                    "named": true,
+                    "subtypes": [
+                        {"type": "binary_expression", "named": true},
+                        {"type": "identifier", "named": true}
+                    ]
+                },
+                {
+                    "type": "binary_expression",
+                    "named": true,
+                    "fields": {
+                        "left": {
+                            "multiple": false,
+                            "required": true,
+                            "types": [{"type": "_expression", "named": true}]
+                        },
+                        "operator": {
+                            "multiple": false,
+                            "required": false,
+                            "types": [
+                                {"type": "+", "named": false},
+                                {"type": "\"", "named": false}
+                            ]
+                        },
+                        "type_arguments": {
+                            "multiple": true,
+                            "required": false,
+                            "types": [{"type": "identifier", "named": true}]
+                        }
+                    }
+                },
+                {"type": "identifier", "named": true},
+                {"type": "+", "named": false}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            &declarations[HEADER.len()..],
+            r#"
+export type NamedNode =
+  | BinaryExpressionNode
+  | IdentifierNode;
+
+export type ExpressionNode = BinaryExpressionNode | IdentifierNode;
+
+export interface BinaryExpressionNode extends SyntaxNodeBase {
+  type: "binary_expression";
+  isNamed: true;
+  leftNode(): ExpressionNode;
+  operatorNode(): UnnamedNode<"+"> | UnnamedNode<"\""> | null;
+  typeArgumentsNodes(): IdentifierNode[];
+}
+
+export interface IdentifierNode extends SyntaxNodeBase {
+  type: "identifier";
+  isNamed: true;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_declarations_with_colliding_names() {
+        let declarations = generate_typescript_declarations(
+            r#"[
+                {
+                    "type": "_expression",
+                    "named": true,
+                    "subtypes": [{"type": "expression", "named": true}]
+                },
+                {"type": "expression", "named": true},
+                {"type": "_expression_", "named": true}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            &declarations[HEADER.len()..],
+            r#"
+export type NamedNode =
+  | Expression2Node
+  | Expression3Node;
+
+export type ExpressionNode = Expression2Node;
+
+export interface Expression2Node extends SyntaxNodeBase {
+  type: "expression";
+  isNamed: true;
+}
+
+export interface Expression3Node extends SyntaxNodeBase {
+  type: "_expression_";
+  isNamed: true;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_type_name_sanitizes_non_identifier_characters() {
+        assert_eq!(type_name("+="), "PlusEqNode");
+        assert_eq!(type_name("<=>"), "LtEqGtNode");
+        assert_eq!(type_name("list-item"), "ListDashItemNode");
+        assert_eq!(type_name("2d_point"), "_2dPointNode");
+        assert_eq!(type_name("_binary_expression"), "BinaryExpressionNode");
+    }
+}
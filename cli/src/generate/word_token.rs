@@ -0,0 +1,156 @@
+use super::grammars::InputGrammar;
+use super::rules::Rule;
+use regex::Regex;
+use std::collections::HashMap;
+
+// Strings that any reasonable identifier token should match.
+const IDENTIFIER_SAMPLES: [&str; 3] = ["x", "identifier", "camelCase"];
+
+// Strings that an identifier token should not match.
+const NON_IDENTIFIER_SAMPLES: [&str; 3] = ["", "0", "a b"];
+
+// Find the grammar's rules that could serve as its word token, with the most
+// referenced rules first. A candidate is a rule that consists of a single pattern,
+// which matches identifiers along with every keyword in the grammar.
+pub(crate) fn word_token_candidates(grammar: &InputGrammar) -> Vec<String> {
+    let mut keywords = Vec::new();
+    let mut reference_counts = HashMap::new();
+    for variable in &grammar.variables {
+        walk_rule(&variable.rule, &mut keywords, &mut reference_counts);
+    }
+    for rule in &grammar.extra_symbols {
+        walk_rule(rule, &mut keywords, &mut reference_counts);
+    }
+
+    let mut candidates = grammar
+        .variables
+        .iter()
+        .enumerate()
+        .filter(|(_, variable)| match token_pattern(&variable.rule) {
+            Some(pattern) => is_identifier_pattern(pattern, &keywords),
+            None => false,
+        })
+        .map(|(i, variable)| {
+            let count = reference_counts.get(variable.name.as_str()).cloned();
+            (count.unwrap_or(0), i, variable.name.clone())
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_unstable_by(|(a_count, a_index, _), (b_count, b_index, _)| {
+        b_count.cmp(a_count).then(a_index.cmp(b_index))
+    });
+    candidates.into_iter().map(|(_, _, name)| name).collect()
+}
+
+// Find the keyword-like strings in the grammar's rules, sorted and without
+// duplicates.
+pub(crate) fn grammar_keywords(grammar: &InputGrammar) -> Vec<&str> {
+    let mut keywords = Vec::new();
+    let mut reference_counts = HashMap::new();
+    for variable in &grammar.variables {
+        walk_rule(&variable.rule, &mut keywords, &mut reference_counts);
+    }
+    keywords.sort_unstable();
+    keywords.dedup();
+    keywords
+}
+
+// Collect the keyword-like strings in a rule, and count its references to other
+// rules.
+fn walk_rule<'a>(
+    rule: &'a Rule,
+    keywords: &mut Vec<&'a str>,
+    reference_counts: &mut HashMap<&'a str, usize>,
+) {
+    match rule {
+        Rule::String(value) => {
+            let mut chars = value.chars();
+            let is_keyword = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if is_keyword {
+                keywords.push(value);
+            }
+        }
+        Rule::NamedSymbol(name) => *reference_counts.entry(name.as_str()).or_insert(0) += 1,
+        Rule::Choice(members) | Rule::Seq(members) => {
+            for member in members {
+                walk_rule(member, keywords, reference_counts);
+            }
+        }
+        Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::RepeatMin { rule, .. } => {
+            walk_rule(rule, keywords, reference_counts)
+        }
+        Rule::Blank | Rule::Pattern(_) | Rule::Symbol(_) => {}
+    }
+}
+
+// Get the pattern that a rule consists of, if it is a single pattern.
+pub(crate) fn token_pattern(rule: &Rule) -> Option<&str> {
+    match rule {
+        Rule::Pattern(value) => Some(value),
+        Rule::Metadata { params, rule } if params.field_name.is_none() => token_pattern(rule),
+        _ => None,
+    }
+}
+
+fn is_identifier_pattern(pattern: &str, keywords: &[&str]) -> bool {
+    let regex = match Regex::new(&format!("^(?:{})$", pattern)) {
+        Ok(regex) => regex,
+        Err(_) => return false,
+    };
+    IDENTIFIER_SAMPLES
+        .iter()
+        .chain(keywords)
+        .all(|sample| regex.is_match(sample))
+        && !NON_IDENTIFIER_SAMPLES
+            .iter()
+            .any(|sample| regex.is_match(sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::grammars::Variable;
+
+    #[test]
+    fn test_word_token_candidates() {
+        let grammar = InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("declaration"),
+                        Rule::named("identifier"),
+                        Rule::named("type_identifier"),
+                    ])),
+                ),
+                Variable::named(
+                    "declaration",
+                    Rule::seq(vec![
+                        Rule::string("var"),
+                        Rule::named("identifier"),
+                        Rule::string("="),
+                        Rule::named("number"),
+                    ]),
+                ),
+                Variable::named("type_identifier", Rule::pattern("[a-zA-Z_]\\w*")),
+                Variable::named("identifier", Rule::token(Rule::pattern("[a-zA-Z_]\\w*"))),
+                Variable::named("lowercase_name", Rule::pattern("[a-u]+")),
+                Variable::named("number", Rule::pattern("\\d+")),
+                Variable::named("word", Rule::pattern("\\w*")),
+            ],
+            extra_symbols: Vec::new(),
+            external_tokens: Vec::new(),
+            expected_conflicts: Vec::new(),
+            variables_to_inline: Vec::new(),
+            supertype_symbols: Vec::new(),
+            word_token: None,
+            version: None,
+        };
+
+        assert_eq!(
+            word_token_candidates(&grammar),
+            &["identifier", "type_identifier"]
+        );
+    }
+}
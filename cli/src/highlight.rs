@@ -1,14 +1,17 @@
 use super::util;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::loader::Loader;
 use ansi_term::Color;
 use lazy_static::lazy_static;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::Write;
 use std::time::Instant;
-use std::{fs, io, path, str, usize};
+use std::{fs, io, ops, path, str, usize};
+use tree_sitter::{Language, Parser};
 use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer};
 
 pub const HTML_HEADER: &'static str = "
@@ -277,6 +280,7 @@ pub fn ansi(
     theme: &Theme,
     source: &[u8],
     config: &HighlightConfiguration,
+    injections: &[(ops::Range<usize>, &HighlightConfiguration)],
     print_time: bool,
 ) -> Result<()> {
     let stdout = io::stdout();
@@ -285,9 +289,13 @@ pub fn ansi(
     let cancellation_flag = util::cancel_on_stdin();
     let mut highlighter = Highlighter::new();
 
-    let events = highlighter.highlight(config, source, Some(&cancellation_flag), |string| {
-        loader.highlight_config_for_injection_string(string)
-    })?;
+    let events = highlighter.highlight_with_injections(
+        config,
+        source,
+        Some(&cancellation_flag),
+        injections,
+        |string| loader.highlight_config_for_injection_string(string),
+    )?;
 
     let mut style_stack = vec![theme.default_style().ansi];
     for event in events {
@@ -315,11 +323,127 @@ pub fn ansi(
     Ok(())
 }
 
+// Print the highlighted source code one line at a time. After each line, print the
+// named nodes of the syntax tree that start on that line, indented by their depth.
+pub fn ansi_with_tree(
+    loader: &Loader,
+    theme: &Theme,
+    source: &[u8],
+    language: Language,
+    config: &HighlightConfiguration,
+    injections: &[(ops::Range<usize>, &HighlightConfiguration)],
+    print_time: bool,
+) -> Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let time = Instant::now();
+    let cancellation_flag = util::cancel_on_stdin();
+    let mut highlighter = Highlighter::new();
+
+    let events = highlighter.highlight_with_injections(
+        config,
+        source,
+        Some(&cancellation_flag),
+        injections,
+        |string| loader.highlight_config_for_injection_string(string),
+    )?;
+
+    // Paint each line separately, so that no style carries over into the tree.
+    let mut lines = vec![Vec::new()];
+    let mut style_stack = vec![theme.default_style().ansi];
+    for event in events {
+        match event? {
+            HighlightEvent::HighlightStart(highlight) => {
+                style_stack.push(theme.styles[highlight.0].ansi);
+            }
+            HighlightEvent::HighlightEnd => {
+                style_stack.pop();
+            }
+            HighlightEvent::Source { start, end } => {
+                let style = style_stack.last().unwrap();
+                for (i, text) in source[start..end].split(|c| *c == b'\n').enumerate() {
+                    if i > 0 {
+                        lines.push(Vec::new());
+                    }
+                    style.paint(text).write_to(lines.last_mut().unwrap())?;
+                }
+            }
+        }
+    }
+    if source.ends_with(b"\n") {
+        lines.pop();
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|e| e.to_string())?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| Error::new("Failed to parse".to_string()))?;
+    let mut nodes_by_row = vec![Vec::new(); lines.len()];
+    let mut cursor = tree.walk();
+    let mut depth = 0;
+    loop {
+        let node = cursor.node();
+        if node.is_named() {
+            let start = node.start_position();
+            let end = node.end_position();
+            let mut description = "  ".repeat(depth);
+            if let Some(field_name) = cursor.field_name() {
+                description += field_name;
+                description += ": ";
+            }
+            description += &format!(
+                "({}) [{}, {}] - [{}, {}]",
+                node.kind(),
+                start.row,
+                start.column,
+                end.row,
+                end.column
+            );
+            if let Some(row) = nodes_by_row.get_mut(start.row) {
+                row.push(description);
+            }
+        }
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break;
+            }
+            depth -= 1;
+        }
+        if depth == 0 {
+            break;
+        }
+    }
+
+    let tree_style = ansi_term::Style::new().dimmed();
+    let width = lines.len().to_string().len();
+    for (i, (line, nodes)) in lines.iter().zip(nodes_by_row).enumerate() {
+        write!(&mut stdout, "{:>width$} | ", i + 1, width = width)?;
+        stdout.write_all(line)?;
+        writeln!(&mut stdout)?;
+        for node in nodes {
+            let node = tree_style.paint(node);
+            writeln!(&mut stdout, "{:>width$} | {}", "", node, width = width)?;
+        }
+    }
+
+    if print_time {
+        eprintln!("Time: {}ms", time.elapsed().as_millis());
+    }
+
+    Ok(())
+}
+
 pub fn html(
     loader: &Loader,
     theme: &Theme,
     source: &[u8],
     config: &HighlightConfiguration,
+    injections: &[(ops::Range<usize>, &HighlightConfiguration)],
     print_time: bool,
 ) -> Result<()> {
     use std::io::Write;
@@ -330,9 +454,13 @@ pub fn html(
     let cancellation_flag = util::cancel_on_stdin();
     let mut highlighter = Highlighter::new();
 
-    let events = highlighter.highlight(config, source, Some(&cancellation_flag), |string| {
-        loader.highlight_config_for_injection_string(string)
-    })?;
+    let events = highlighter.highlight_with_injections(
+        config,
+        source,
+        Some(&cancellation_flag),
+        injections,
+        |string| loader.highlight_config_for_injection_string(string),
+    )?;
 
     let mut renderer = HtmlRenderer::new();
     renderer.render(events, source, &move |highlight| {
@@ -361,3 +489,29 @@ pub fn html(
 
     Ok(())
 }
+
+/// Highlight the given source code without printing it, and return the names of
+/// the injected languages that its injection queries refer to, but which the loader
+/// can't find a highlighting configuration for.
+pub fn unresolved_injection_languages(
+    loader: &Loader,
+    source: &[u8],
+    config: &HighlightConfiguration,
+) -> Result<Vec<String>> {
+    let unresolved = RefCell::new(Vec::<String>::new());
+    let mut highlighter = Highlighter::new();
+    let events = highlighter.highlight(config, source, None, |string| {
+        let result = loader.highlight_config_for_injection_string(string);
+        if result.is_none() {
+            let mut unresolved = unresolved.borrow_mut();
+            if !unresolved.iter().any(|name| name == string) {
+                unresolved.push(string.to_string());
+            }
+        }
+        result
+    })?;
+    for event in events {
+        event?;
+    }
+    Ok(unresolved.into_inner())
+}
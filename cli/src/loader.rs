@@ -1,4 +1,5 @@
 use super::error::{Error, Result};
+use lazy_static::lazy_static;
 use libloading::{Library, Symbol};
 use once_cell::unsync::OnceCell;
 use regex::{Regex, RegexBuilder};
@@ -6,10 +7,11 @@ use serde_derive::Deserialize;
 use std::collections::HashMap;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::time::SystemTime;
-use std::{fs, mem};
+use std::{env, fs, mem};
 use tree_sitter::Language;
 use tree_sitter_highlight::HighlightConfiguration;
 use tree_sitter_tags::TagsConfiguration;
@@ -38,6 +40,7 @@ pub struct LanguageConfiguration<'a> {
     tags_config: OnceCell<Option<TagsConfiguration>>,
     highlight_names: &'a Mutex<Vec<String>>,
     use_all_highlight_names: bool,
+    highlight_pattern_scope: Option<String>,
 }
 
 pub struct Loader {
@@ -47,6 +50,15 @@ pub struct Loader {
     language_configuration_ids_by_file_type: HashMap<String, Vec<usize>>,
     highlight_names: Box<Mutex<Vec<String>>>,
     use_all_highlight_names: bool,
+    highlight_pattern_scope: Option<String>,
+}
+
+/// An error indicating that a language was generated with an ABI version that this
+/// version of the library can't use.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IncompatibleAbi {
+    pub language_name: String,
+    pub version: usize,
 }
 
 unsafe impl Send for Loader {}
@@ -61,6 +73,7 @@ impl Loader {
             language_configuration_ids_by_file_type: HashMap::new(),
             highlight_names: Box::new(Mutex::new(Vec::new())),
             use_all_highlight_names: true,
+            highlight_pattern_scope: None,
         }
     }
 
@@ -71,6 +84,12 @@ impl Loader {
         highlights.extend(names.iter().cloned());
     }
 
+    // Only use the highlight patterns that follow a `; scope: <name>` comment with the
+    // given name. This must be called before any languages are found.
+    pub fn configure_highlight_pattern_scope(&mut self, scope: &str) {
+        self.highlight_pattern_scope = Some(scope.to_string());
+    }
+
     pub fn highlight_names(&self) -> Vec<String> {
         self.highlight_names.lock().unwrap().clone()
     }
@@ -285,78 +304,51 @@ impl Loader {
         )?;
 
         if recompile {
-            let mut config = cc::Build::new();
-            config
-                .cpp(true)
-                .opt_level(2)
-                .cargo_metadata(false)
-                .target(BUILD_TARGET)
-                .host(BUILD_TARGET);
-            let compiler = config.get_compiler();
-            let mut command = Command::new(compiler.path());
-            for (key, value) in compiler.env() {
-                command.env(key, value);
-            }
-
-            if cfg!(windows) {
-                command
-                    .args(&["/nologo", "/LD", "/I"])
-                    .arg(header_path)
-                    .arg("/Od")
-                    .arg(parser_path);
-                if let Some(scanner_path) = scanner_path.as_ref() {
-                    command.arg(scanner_path);
-                }
-                command
-                    .arg("/link")
-                    .arg(format!("/out:{}", library_path.to_str().unwrap()));
-            } else {
-                command
-                    .arg("-shared")
-                    .arg("-fPIC")
-                    .arg("-fno-exceptions")
-                    .arg("-g")
-                    .arg("-I")
-                    .arg(header_path)
-                    .arg("-o")
-                    .arg(&library_path)
-                    .arg("-O2");
-                if let Some(scanner_path) = scanner_path.as_ref() {
-                    if scanner_path.extension() == Some("c".as_ref()) {
-                        command.arg("-xc").arg("-std=c99").arg(scanner_path);
-                    } else {
-                        command.arg(scanner_path);
-                    }
-                }
-                command.arg("-xc").arg(parser_path);
-            }
-
-            let output = command
-                .output()
-                .map_err(Error::wrap(|| "Failed to execute C compiler"))?;
-            if !output.status.success() {
-                return Err(Error::new(format!(
-                    "Parser compilation failed.\nStdout: {}\nStderr: {}",
-                    String::from_utf8_lossy(&output.stdout),
-                    String::from_utf8_lossy(&output.stderr)
-                )));
-            }
+            compile_library(header_path, parser_path, scanner_path, &library_path)?;
         }
+        load_library(name, &library_path)
+    }
 
-        let library = Library::new(&library_path).map_err(Error::wrap(|| {
-            format!("Error opening dynamic library {:?}", &library_path)
+    /// Compile the C source of a generated parser and load the resulting language,
+    /// without requiring the source to be written to a grammar repository first.
+    ///
+    /// The source is compiled in a scratch directory that is removed once the
+    /// library has been loaded. Loaded libraries are never unloaded, so the
+    /// language remains valid for the rest of the process.
+    pub fn load_language_from_c_source(&self, source: &str) -> Result<Language> {
+        lazy_static! {
+            static ref LANGUAGE_FN_REGEX: Regex =
+                Regex::new(r"TSLanguage\s*\*\s*tree_sitter_(\w+)\s*\(").unwrap();
+        }
+        static SCRATCH_DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let name = LANGUAGE_FN_REGEX
+            .captures(source)
+            .map(|captures| captures[1].to_string())
+            .ok_or_else(|| Error::new("The source does not define a language function".into()))?;
+        let scratch_path = env::temp_dir().join(format!(
+            "tree-sitter-{}-{}-{}",
+            name,
+            process::id(),
+            SCRATCH_DIR_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let header_path = scratch_path.join("tree_sitter");
+        fs::create_dir_all(&header_path).map_err(Error::wrap(|| {
+            format!("Failed to create {:?}", scratch_path)
         }))?;
-        let language_fn_name = format!("tree_sitter_{}", replace_dashes_with_underscores(name));
-        let language = unsafe {
-            let language_fn: Symbol<unsafe extern "C" fn() -> Language> = library
-                .get(language_fn_name.as_bytes())
-                .map_err(Error::wrap(|| {
-                    format!("Failed to load symbol {}", language_fn_name)
-                }))?;
-            language_fn()
-        };
-        mem::forget(library);
-        Ok(language)
+
+        let parser_path = scratch_path.join("parser.c");
+        let mut library_path = scratch_path.join(&name);
+        library_path.set_extension(DYLIB_EXTENSION);
+        let result = fs::write(&parser_path, source)
+            .and_then(|_| fs::write(header_path.join("parser.h"), tree_sitter::PARSER_HEADER))
+            .map_err(Error::wrap(|| "Failed to write the parser source"))
+            .and_then(|_| compile_library(&scratch_path, &parser_path, &None, &library_path))
+            .and_then(|_| load_library(&name, &library_path));
+
+        // On Windows, the library can't be removed while it is loaded.
+        fs::remove_dir_all(&scratch_path).ok();
+        result
     }
 
     pub fn highlight_config_for_injection_string<'a>(
@@ -490,6 +482,7 @@ impl Loader {
                         tags_config: OnceCell::new(),
                         highlight_names: &*self.highlight_names,
                         use_all_highlight_names: self.use_all_highlight_names,
+                        highlight_pattern_scope: self.highlight_pattern_scope.clone(),
                     };
 
                     for file_type in &configuration.file_types {
@@ -524,6 +517,7 @@ impl Loader {
                 tags_config: OnceCell::new(),
                 highlight_names: &*self.highlight_names,
                 use_all_highlight_names: self.use_all_highlight_names,
+                highlight_pattern_scope: self.highlight_pattern_scope.clone(),
             };
             self.language_configurations
                 .push(unsafe { mem::transmute(configuration) });
@@ -539,12 +533,27 @@ impl Loader {
     }
 }
 
+impl IncompatibleAbi {
+    pub fn message(&self) -> String {
+        format!(
+            "Language {} has incompatible ABI version {}. Expected minimum {}, maximum {}",
+            self.language_name,
+            self.version,
+            tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+            tree_sitter::LANGUAGE_VERSION
+        )
+    }
+}
+
 impl<'a> LanguageConfiguration<'a> {
     pub fn highlight_config(&self, language: Language) -> Result<Option<&HighlightConfiguration>> {
         self.highlight_config
             .get_or_try_init(|| {
-                let highlights_query =
+                let mut highlights_query =
                     self.read_queries(&self.highlights_filenames, "highlights.scm")?;
+                if let Some(scope) = &self.highlight_pattern_scope {
+                    highlights_query = query_patterns_in_scope(&highlights_query, scope);
+                }
                 let injections_query =
                     self.read_queries(&self.injections_filenames, "injections.scm")?;
                 let locals_query = self.read_queries(&self.locals_filenames, "locals.scm")?;
@@ -618,6 +627,123 @@ impl<'a> LanguageConfiguration<'a> {
     }
 }
 
+// Remove the patterns from a query that don't belong to the given scope. A comment
+// like `; scope: <name>` assigns each of the patterns after it, up to the next such
+// comment, to the named scope. The removed lines are left blank, so that the rows in
+// any errors in the query still match its source.
+fn query_patterns_in_scope(source: &str, scope: &str) -> String {
+    let mut in_scope = false;
+    let mut result = String::with_capacity(source.len());
+    for line in source.split('\n') {
+        let line_start = line.trim_start();
+        if line_start.starts_with(';') {
+            let comment = line_start.trim_start_matches(';').trim_start();
+            if let Some(name) = comment.strip_prefix("scope:") {
+                in_scope = name.trim() == scope;
+            }
+        }
+        if in_scope {
+            result += line;
+        }
+        result.push('\n');
+    }
+    result.pop();
+    result
+}
+
+fn compile_library(
+    header_path: &Path,
+    parser_path: &Path,
+    scanner_path: &Option<PathBuf>,
+    library_path: &Path,
+) -> Result<()> {
+    let mut config = cc::Build::new();
+    config
+        .cpp(true)
+        .opt_level(2)
+        .cargo_metadata(false)
+        .target(BUILD_TARGET)
+        .host(BUILD_TARGET);
+    let compiler = config.get_compiler();
+    let mut command = Command::new(compiler.path());
+    for (key, value) in compiler.env() {
+        command.env(key, value);
+    }
+
+    if cfg!(windows) {
+        command
+            .args(&["/nologo", "/LD", "/I"])
+            .arg(header_path)
+            .arg("/Od")
+            .arg(parser_path);
+        if let Some(scanner_path) = scanner_path.as_ref() {
+            command.arg(scanner_path);
+        }
+        command
+            .arg("/link")
+            .arg(format!("/out:{}", library_path.to_str().unwrap()));
+    } else {
+        command
+            .arg("-shared")
+            .arg("-fPIC")
+            .arg("-fno-exceptions")
+            .arg("-g")
+            .arg("-I")
+            .arg(header_path)
+            .arg("-o")
+            .arg(library_path)
+            .arg("-O2");
+        if let Some(scanner_path) = scanner_path.as_ref() {
+            if scanner_path.extension() == Some("c".as_ref()) {
+                command.arg("-xc").arg("-std=c99").arg(scanner_path);
+            } else {
+                command.arg(scanner_path);
+            }
+        }
+        command.arg("-xc").arg(parser_path);
+    }
+
+    let output = command
+        .output()
+        .map_err(Error::wrap(|| "Failed to execute C compiler"))?;
+    if !output.status.success() {
+        return Err(Error::new(format!(
+            "Parser compilation failed.\nStdout: {}\nStderr: {}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(())
+}
+
+fn load_library(name: &str, library_path: &Path) -> Result<Language> {
+    let library = Library::new(library_path).map_err(Error::wrap(|| {
+        format!("Error opening dynamic library {:?}", library_path)
+    }))?;
+    let language_fn_name = format!("tree_sitter_{}", replace_dashes_with_underscores(name));
+    let language = unsafe {
+        let language_fn: Symbol<unsafe extern "C" fn() -> Language> = library
+            .get(language_fn_name.as_bytes())
+            .map_err(Error::wrap(|| {
+                format!("Failed to load symbol {}", language_fn_name)
+            }))?;
+        language_fn()
+    };
+    mem::forget(library);
+
+    if !language.check_version_compatibility(
+        tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION as u32,
+        tree_sitter::LANGUAGE_VERSION as u32,
+    ) {
+        return Err(IncompatibleAbi {
+            language_name: name.to_string(),
+            version: language.abi_version() as usize,
+        }
+        .into());
+    }
+    Ok(language)
+}
+
 fn needs_recompile(
     lib_path: &Path,
     parser_c_path: &Path,
@@ -653,3 +779,38 @@ fn replace_dashes_with_underscores(name: &str) -> String {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_patterns_in_scope() {
+        let source = concat!(
+            "(identifier) @variable\n",
+            "; scope: function\n",
+            "(parameter) @variable.parameter\n",
+            ";; scope: top-level\n",
+            "(call) @function\n",
+            "; scope: function\n",
+            "(return) @keyword",
+        );
+        assert_eq!(
+            query_patterns_in_scope(source, "function"),
+            concat!(
+                "\n",
+                "; scope: function\n",
+                "(parameter) @variable.parameter\n",
+                "\n",
+                "\n",
+                "; scope: function\n",
+                "(return) @keyword",
+            )
+        );
+        assert_eq!(
+            query_patterns_in_scope(source, "top-level"),
+            "\n\n\n;; scope: top-level\n(call) @function\n\n"
+        );
+        assert_eq!(query_patterns_in_scope(source, "class"), "\n\n\n\n\n\n");
+    }
+}
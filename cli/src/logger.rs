@@ -23,7 +23,7 @@ impl Log for Logger {
     fn flush(&self) {}
 }
 
-pub fn init() {
+pub fn init(level: LevelFilter) {
     log::set_boxed_logger(Box::new(Logger { filter: None })).unwrap();
-    log::set_max_level(LevelFilter::Info);
+    log::set_max_level(level);
 }
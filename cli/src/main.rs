@@ -1,7 +1,8 @@
 use clap::{App, AppSettings, Arg, SubCommand};
 use error::Error;
 use glob::glob;
-use std::path::Path;
+use log::LevelFilter;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::{env, fs, u64};
 use tree_sitter::Language;
@@ -40,15 +41,174 @@ fn run() -> error::Result<()> {
             SubCommand::with_name("generate")
                 .about("Generate a parser")
                 .arg(Arg::with_name("grammar-path").index(1))
-                .arg(Arg::with_name("log").long("log"))
-                .arg(Arg::with_name("prev-abi").long("prev-abi"))
+                .arg(
+                    Arg::with_name("log")
+                        .long("log")
+                        .help("Log the details of building the parse tables, like `--log-level debug`"),
+                )
+                .arg(
+                    Arg::with_name("log-level")
+                        .long("log-level")
+                        .value_name("level")
+                        .help("Log the stages of generation, or at `trace`, every parse state")
+                        .takes_value(true)
+                        .possible_values(&["trace", "debug", "info", "warn", "error"]),
+                )
+                .arg(
+                    Arg::with_name("prev-abi")
+                        .long("prev-abi")
+                        .conflicts_with("abi-version"),
+                )
+                .arg(
+                    Arg::with_name("abi-version")
+                        .long("abi-version")
+                        .value_name("N")
+                        .takes_value(true),
+                )
                 .arg(
                     Arg::with_name("report-states-for-rule")
                         .long("report-states-for-rule")
                         .value_name("rule-name")
                         .takes_value(true),
                 )
-                .arg(Arg::with_name("no-minimize").long("no-minimize")),
+                .arg(
+                    Arg::with_name("trace-states")
+                        .long("trace-states")
+                        .help("Print each parse state as it is discovered"),
+                )
+                .arg(
+                    Arg::with_name("trace-states-interval")
+                        .long("trace-states-interval")
+                        .value_name("N")
+                        .takes_value(true)
+                        .requires("trace-states")
+                        .help("Only print every Nth parse state when tracing"),
+                )
+                .arg(
+                    Arg::with_name("no-optimize")
+                        .long("no-optimize")
+                        .alias("no-minimize")
+                        .help("Skip minimizing the parse tables (not for production use)"),
+                )
+                .arg(
+                    Arg::with_name("minimize-grammar")
+                        .long("minimize-grammar")
+                        .help("Merge hidden rules that have identical definitions"),
+                )
+                .arg(
+                    Arg::with_name("validate-externals")
+                        .long("validate-externals")
+                        .help("Check that the external scanner's TOKEN_COUNT matches the grammar"),
+                )
+                .arg(
+                    Arg::with_name("infer-word-token")
+                        .long("infer-word-token")
+                        .help("Use an identifier-like rule as the word token if none is set"),
+                )
+                .arg(
+                    Arg::with_name("verify-roundtrip")
+                        .long("verify-roundtrip")
+                        .help("Run the grammar's corpus tests with the generated parser"),
+                )
+                .arg(
+                    Arg::with_name("validate-corpus-coverage")
+                        .long("validate-corpus-coverage")
+                        .value_name("percent")
+                        .takes_value(true)
+                        .help("Run the corpus tests, and exit with status 3 if they cover less than this percentage of the rules"),
+                )
+                .arg(
+                    Arg::with_name("check-node-types")
+                        .long("check-node-types")
+                        .help("Check that src/node-types.json matches the compiled parser, which requires a C compiler"),
+                )
+                .arg(
+                    Arg::with_name("emit-node-types-typescript")
+                        .long("emit-node-types-typescript")
+                        .help("Generate src/types.d.ts from an existing src/node-types.json"),
+                )
+                .arg(
+                    Arg::with_name("emit-json-schema")
+                        .long("emit-json-schema")
+                        .help("Generate src/grammar-schema.json, a JSON Schema for grammars"),
+                )
+                .arg(
+                    Arg::with_name("emit-language-server-info")
+                        .long("emit-language-server-info")
+                        .help("Generate src/language-server-info.json from the existing generated files and queries"),
+                )
+                .arg(
+                    Arg::with_name("emit-haskell-bindings")
+                        .long("emit-haskell-bindings")
+                        .help("Generate a Haskell module for the parser in bindings/haskell"),
+                )
+                .arg(
+                    Arg::with_name("emit-language-bindings")
+                        .long("emit-language-bindings")
+                        .value_name("language")
+                        .takes_value(true)
+                        .possible_values(&generate::BINDING_LANGUAGES)
+                        .help("Generate the boilerplate for using the parser from a language"),
+                )
+                .arg(
+                    Arg::with_name("show-references")
+                        .long("show-references")
+                        .value_name("rule-name")
+                        .takes_value(true)
+                        .help("List the rules that refer to the given rule, without generating"),
+                )
+                .arg(
+                    Arg::with_name("emit-railroad-diagram")
+                        .long("emit-railroad-diagram")
+                        .value_name("output.html")
+                        .takes_value(true)
+                        .help("Write railroad diagrams of the grammar's rules, without generating"),
+                )
+                .arg(
+                    Arg::with_name("symbol-map")
+                        .long("symbol-map")
+                        .value_name("symbol_map.json")
+                        .takes_value(true)
+                        .help("Write a JSON file that maps each symbol's numeric id to its name"),
+                )
+                .arg(
+                    Arg::with_name("emit-mermaid")
+                        .long("emit-mermaid")
+                        .value_name("output.md")
+                        .takes_value(true)
+                        .help("Write a Mermaid flowchart of the rules, without generating"),
+                )
+                .arg(
+                    Arg::with_name("emit-c-header")
+                        .long("emit-c-header")
+                        .value_name("parser.h")
+                        .takes_value(true)
+                        .help("Write a C header that declares the language function, without generating"),
+                )
+                .arg(
+                    Arg::with_name("grammar-info")
+                        .long("grammar-info")
+                        .help("Print a summary of the grammar's rules, without generating"),
+                )
+                .arg(
+                    Arg::with_name("print-first-sets")
+                        .long("print-first-sets")
+                        .help("Print the FIRST set of each rule, without generating"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lint-grammar")
+                .about("Check a grammar for problems without generating a parser")
+                .arg(Arg::with_name("grammar-path").index(1))
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("format")
+                        .help("The format in which to print the findings")
+                        .takes_value(true)
+                        .possible_values(&["default", "json"])
+                        .default_value("default"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("parse")
@@ -57,7 +217,7 @@ fn run() -> error::Result<()> {
                     Arg::with_name("path")
                         .index(1)
                         .multiple(true)
-                        .required(true),
+                        .required_unless("compare"),
                 )
                 .arg(Arg::with_name("scope").long("scope").takes_value(true))
                 .arg(Arg::with_name("debug").long("debug").short("d"))
@@ -66,6 +226,117 @@ fn run() -> error::Result<()> {
                 .arg(Arg::with_name("time").long("time").short("t"))
                 .arg(Arg::with_name("allow-cancellation").long("cancel"))
                 .arg(Arg::with_name("timeout").long("timeout").takes_value(true))
+                .arg(
+                    Arg::with_name("max-error-recovery-nodes")
+                        .long("max-error-recovery-nodes")
+                        .value_name("N")
+                        .help("Stop inserting MISSING nodes after N have been inserted")
+                        .takes_value(true)
+                        .default_value("1000"),
+                )
+                .arg(
+                    Arg::with_name("error-format")
+                        .long("error-format")
+                        .value_name("format")
+                        .help("The format in which to report syntax errors")
+                        .takes_value(true)
+                        .possible_values(&["default", "gcc"])
+                        .default_value("default"),
+                )
+                .arg(
+                    Arg::with_name("select-fields")
+                        .long("select-fields")
+                        .value_name("fields")
+                        .help("Only print children in the given comma-separated fields")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("select-depth")
+                        .long("select-depth")
+                        .value_name("N")
+                        .help("Only print the first N levels of the tree")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("extract-nodes")
+                        .long("extract-nodes")
+                        .value_name("types")
+                        .help("Only print the nodes of the given comma-separated types")
+                        .takes_value(true)
+                        .conflicts_with_all(&["format", "count", "json-tokens", "pretty-print"]),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("format")
+                        .help("The format in which to print the tree [default: sexp]")
+                        .takes_value(true)
+                        .possible_values(&["sexp", "graphml"])
+                        .conflicts_with_all(&["count", "json-tokens", "pretty-print"]),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .help("Print the number of nodes of each type instead of the tree")
+                        .conflicts_with_all(&["json-tokens", "pretty-print"]),
+                )
+                .arg(
+                    Arg::with_name("coverage")
+                        .long("coverage")
+                        .help("Print the fraction of the file that was parsed without errors"),
+                )
+                .arg(
+                    Arg::with_name("check-coverage")
+                        .long("check-coverage")
+                        .value_name("grammar-dir")
+                        .help("Print how many times each of the grammar's rules appears in the trees")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("report-ambiguities")
+                        .long("report-ambiguities")
+                        .help("Report the nodes that the grammar allows more than one valid parse for"),
+                )
+                .arg(
+                    Arg::with_name("json-tokens")
+                        .long("json-tokens")
+                        .help("Print the tree's tokens as a JSON array instead of the tree")
+                        .conflicts_with("pretty-print"),
+                )
+                .arg(
+                    Arg::with_name("omit-ranges")
+                        .long("omit-ranges")
+                        .help("Print the tree without the range of each node")
+                        .conflicts_with_all(&["extract-nodes", "count", "json-tokens"]),
+                )
+                .arg(
+                    Arg::with_name("show-missing")
+                        .long("show-missing")
+                        .help("Label MISSING nodes, and print missing anonymous nodes, as in corpus tests"),
+                )
+                .arg(
+                    Arg::with_name("pretty-print")
+                        .long("pretty-print")
+                        .help("Print each subtree that fits within the line width on a single line")
+                        .conflicts_with_all(&["select-fields", "select-depth"]),
+                )
+                .arg(
+                    Arg::with_name("line-width")
+                        .long("line-width")
+                        .value_name("width")
+                        .help("The line width for --pretty-print")
+                        .takes_value(true)
+                        .requires("pretty-print"),
+                )
+                .arg(
+                    Arg::with_name("compare")
+                        .long("compare")
+                        .value_names(&["old-path", "new-path"])
+                        .help("Print the text and the parts of the tree that change from one file to another")
+                        .takes_value(true)
+                        .number_of_values(2)
+                        .conflicts_with("path"),
+                )
                 .arg(
                     Arg::with_name("edits")
                         .long("edit")
@@ -86,7 +357,21 @@ fn run() -> error::Result<()> {
                         .required(true),
                 )
                 .arg(Arg::with_name("scope").long("scope").takes_value(true))
-                .arg(Arg::with_name("captures").long("captures").short("c")),
+                .arg(Arg::with_name("captures").long("captures").short("c"))
+                .arg(
+                    Arg::with_name("stats")
+                        .long("stats")
+                        .help("Print the number of matches and visited nodes, and the time taken"),
+                )
+                .arg(
+                    Arg::with_name("output-format")
+                        .long("output-format")
+                        .value_name("format")
+                        .help("The format in which to print the results")
+                        .takes_value(true)
+                        .possible_values(&["default", "lsp"])
+                        .default_value("default"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("tags")
@@ -122,7 +407,38 @@ fn run() -> error::Result<()> {
                         .takes_value(true),
                 )
                 .arg(Arg::with_name("debug").long("debug").short("d"))
-                .arg(Arg::with_name("debug-graph").long("debug-graph").short("D")),
+                .arg(Arg::with_name("debug-graph").long("debug-graph").short("D"))
+                .arg(
+                    Arg::with_name("show-tree")
+                        .long("show-tree")
+                        .help("Print the full parse tree for each failing test"),
+                )
+                .arg(
+                    Arg::with_name("error-on-empty-corpus")
+                        .long("error-on-empty-corpus")
+                        .help("Fail if the grammar's test corpus is missing or has no tests"),
+                )
+                .arg(
+                    Arg::with_name("no-recovery")
+                        .long("no-recovery")
+                        .help("Fail tests whose parse trees contain ERROR or MISSING nodes"),
+                )
+                .arg(
+                    Arg::with_name("diff-format")
+                        .long("diff-format")
+                        .value_name("format")
+                        .help("The format in which to show the differences in failing tests")
+                        .takes_value(true)
+                        .possible_values(&["default", "unified", "side-by-side"])
+                        .default_value("default"),
+                )
+                .arg(
+                    Arg::with_name("memory-limit")
+                        .long("memory-limit")
+                        .value_name("MB")
+                        .help("Fail the first test whose parse raises peak memory use this far above the starting peak")
+                        .takes_value(true),
+                ),
         )
         .subcommand(
             SubCommand::with_name("highlight")
@@ -134,9 +450,36 @@ fn run() -> error::Result<()> {
                         .required(true),
                 )
                 .arg(Arg::with_name("scope").long("scope").takes_value(true))
+                .arg(
+                    Arg::with_name("pattern-scope")
+                        .long("pattern-scope")
+                        .value_name("name")
+                        .takes_value(true)
+                        .help("Only apply the highlight patterns that follow a `; scope: <name>` comment"),
+                )
                 .arg(Arg::with_name("html").long("html").short("h"))
                 .arg(Arg::with_name("time").long("time").short("t"))
-                .arg(Arg::with_name("q").short("q")),
+                .arg(Arg::with_name("q").short("q"))
+                .arg(
+                    Arg::with_name("inject-language")
+                        .long("inject-language")
+                        .value_name("name=start_byte:end_byte")
+                        .help("Highlight the given byte range using the given language")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
+                .arg(
+                    Arg::with_name("check-injections")
+                        .long("check-injections")
+                        .help("List the injected languages that are not installed"),
+                )
+                .arg(
+                    Arg::with_name("show-tree")
+                        .long("show-tree")
+                        .conflicts_with_all(&["html", "check-injections"])
+                        .help("Print the syntax tree nodes that start on each highlighted line"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("build-wasm")
@@ -146,6 +489,14 @@ fn run() -> error::Result<()> {
                         .long("docker")
                         .help("Run emscripten via docker even if it is installed locally"),
                 )
+                .arg(
+                    Arg::with_name("grammar-dir")
+                        .long("grammar-dir")
+                        .value_name("path")
+                        .takes_value(true)
+                        .conflicts_with("path")
+                        .help("The directory of the grammar to compile"),
+                )
                 .arg(Arg::with_name("path").index(1).multiple(true)),
         )
         .subcommand(
@@ -167,7 +518,76 @@ fn run() -> error::Result<()> {
         let config = config::Config::new(&home_dir);
         config.save(&home_dir)?;
     } else if let Some(matches) = matches.subcommand_matches("generate") {
+        if matches.is_present("emit-node-types-typescript") {
+            generate::generate_typescript_in_directory(&current_dir)?;
+            return Ok(());
+        }
+        if matches.is_present("emit-json-schema") {
+            generate::generate_json_schema_in_directory(&current_dir)?;
+            return Ok(());
+        }
+        if matches.is_present("emit-language-server-info") {
+            let info = generate::language_server_info_in_directory(&current_dir)?;
+            fs::write(
+                current_dir.join("src").join("language-server-info.json"),
+                info.to_json(),
+            )?;
+            return Ok(());
+        }
+        if matches.is_present("emit-haskell-bindings") {
+            generate::generate_haskell_bindings_in_directory(&current_dir)?;
+            return Ok(());
+        }
+        if let Some(binding_language) = matches.value_of("emit-language-bindings") {
+            generate::generate_language_bindings_in_directory(&current_dir, binding_language)?;
+            return Ok(());
+        }
         let grammar_path = matches.value_of("grammar-path");
+        if let Some(name) = matches.value_of("show-references") {
+            print!(
+                "{}",
+                generate::show_references_in_directory(&current_dir, grammar_path, name)?
+            );
+            return Ok(());
+        }
+        if let Some(output_path) = matches.value_of("emit-railroad-diagram") {
+            generate::generate_railroad_diagrams_in_directory(
+                &current_dir,
+                grammar_path,
+                Path::new(output_path),
+            )?;
+            return Ok(());
+        }
+        if let Some(output_path) = matches.value_of("emit-mermaid") {
+            generate::generate_mermaid_diagram_in_directory(
+                &current_dir,
+                grammar_path,
+                Path::new(output_path),
+            )?;
+            return Ok(());
+        }
+        if let Some(output_path) = matches.value_of("emit-c-header") {
+            generate::generate_c_header_in_directory(
+                &current_dir,
+                grammar_path,
+                Path::new(output_path),
+            )?;
+            return Ok(());
+        }
+        if matches.is_present("grammar-info") {
+            print!(
+                "{}",
+                generate::grammar_info_in_directory(&current_dir, grammar_path)?
+            );
+            return Ok(());
+        }
+        if matches.is_present("print-first-sets") {
+            print!(
+                "{}",
+                generate::first_sets_in_directory(&current_dir, grammar_path)?
+            );
+            return Ok(());
+        }
         let report_symbol_name = matches.value_of("report-states-for-rule").or_else(|| {
             if matches.is_present("report-states") {
                 Some("")
@@ -175,33 +595,139 @@ fn run() -> error::Result<()> {
                 None
             }
         });
-        if matches.is_present("log") {
-            logger::init();
+        if let Some(level) = matches.value_of("log-level") {
+            logger::init(level.parse().unwrap());
+        } else if matches.is_present("log") {
+            logger::init(LevelFilter::Debug);
+        }
+        let abi_version = if matches.is_present("prev-abi") {
+            tree_sitter::LANGUAGE_VERSION - 1
+        } else if let Some(version) = matches.value_of("abi-version") {
+            version
+                .parse::<usize>()
+                .map_err(|_| "Invalid value for --abi-version".to_string())?
+        } else {
+            tree_sitter::LANGUAGE_VERSION
+        };
+        let trace_states = if let Some(interval) = matches.value_of("trace-states-interval") {
+            match interval.parse::<usize>() {
+                Ok(interval) if interval > 0 => Some(interval),
+                _ => return Error::err("Invalid value for --trace-states-interval".to_string()),
+            }
+        } else if matches.is_present("trace-states") {
+            Some(1)
+        } else {
+            None
+        };
+        let options = generate::CompileOptions::builder()
+            .abi_version(abi_version)
+            .report_symbol_name(report_symbol_name)
+            .trace_states(trace_states)
+            .optimize(!matches.is_present("no-optimize"))
+            .minimize_grammar(matches.is_present("minimize-grammar"))
+            .validate_externals(matches.is_present("validate-externals"))
+            .infer_word_token(matches.is_present("infer-word-token"))
+            .symbol_map_path(matches.value_of("symbol-map").map(Path::new))
+            .build();
+        generate::generate_parser_in_directory(&current_dir, grammar_path, &options)?;
+        let needs_language = [
+            "check-node-types",
+            "verify-roundtrip",
+            "validate-corpus-coverage",
+        ]
+        .iter()
+        .any(|flag| matches.is_present(flag));
+        if needs_language {
+            let languages = loader.languages_at_path(&current_dir)?;
+            let language = *languages
+                .first()
+                .ok_or_else(|| "No language found".to_string())?;
+            if matches.is_present("check-node-types") {
+                generate::check_node_types_in_directory(&current_dir, language)?;
+            }
+            if matches.is_present("verify-roundtrip") {
+                if let Some(test_corpus_dir) = find_test_corpus_dir(&current_dir) {
+                    test::run_tests_at_path(language, &test_corpus_dir, &Default::default())?;
+                } else {
+                    eprintln!(
+                        "No test corpus found in {:?}, so the round trip was not verified",
+                        current_dir
+                    );
+                }
+            }
+            if let Some(threshold) = matches.value_of("validate-corpus-coverage") {
+                let threshold = match threshold.parse::<f64>() {
+                    Ok(threshold) if (0.0..=100.0).contains(&threshold) => threshold,
+                    _ => {
+                        return Error::err(
+                            "Invalid value for --validate-corpus-coverage".to_string(),
+                        )
+                    }
+                };
+                let test_corpus_dir = find_test_corpus_dir(&current_dir)
+                    .ok_or_else(|| format!("No test corpus found in {:?}", current_dir))?;
+                test::run_tests_at_path(language, &test_corpus_dir, &Default::default())?;
+
+                // A coverage failure uses a different exit status than a test failure, so
+                // that CI scripts can tell them apart.
+                let mut coverage = parse::GrammarCoverage::load(&current_dir)?;
+                test::add_corpus_to_coverage(language, &test_corpus_dir, &mut coverage)?;
+                print!("{}", coverage);
+                if coverage.percentage() < threshold {
+                    eprintln!(
+                        "The corpus covers {:.2}% of the rules, which is below the threshold of {}%",
+                        coverage.percentage(),
+                        threshold
+                    );
+                    exit(3);
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("lint-grammar") {
+        let report =
+            generate::lint_grammar_in_directory(&current_dir, matches.value_of("grammar-path"))?;
+        if matches.value_of("format") == Some("json") {
+            println!("{}", report.to_json());
+        } else {
+            print!("{}", report);
+        }
+        if report.has_errors() {
+            return Error::err(String::new());
         }
-        let prev_abi = matches.is_present("prev-abi");
-        generate::generate_parser_in_directory(
-            &current_dir,
-            grammar_path,
-            !prev_abi,
-            report_symbol_name,
-        )?;
     } else if let Some(matches) = matches.subcommand_matches("test") {
-        let debug = matches.is_present("debug");
-        let debug_graph = matches.is_present("debug-graph");
-        let filter = matches.value_of("filter");
+        let memory_limit = if let Some(limit) = matches.value_of("memory-limit") {
+            match limit.parse::<usize>() {
+                Ok(limit) if limit > 0 => Some(limit * 1024 * 1024),
+                _ => return Error::err("Invalid value for --memory-limit".to_string()),
+            }
+        } else {
+            None
+        };
+        let options = test::TestOptions {
+            debug: matches.is_present("debug"),
+            debug_graph: matches.is_present("debug-graph"),
+            show_tree: matches.is_present("show-tree"),
+            filter: matches.value_of("filter"),
+            error_on_empty_corpus: matches.is_present("error-on-empty-corpus"),
+            no_recovery: matches.is_present("no-recovery"),
+            diff_format: match matches.value_of("diff-format") {
+                Some("unified") => test::DiffFormat::Unified,
+                Some("side-by-side") => test::DiffFormat::SideBySide,
+                _ => test::DiffFormat::Default,
+            },
+            memory_limit,
+        };
         let languages = loader.languages_at_path(&current_dir)?;
         let language = languages
             .first()
             .ok_or_else(|| "No language found".to_string())?;
         let test_dir = current_dir.join("test");
 
-        // Run the corpus tests. Look for them at two paths: `test/corpus` and `corpus`.
-        let mut test_corpus_dir = test_dir.join("corpus");
-        if !test_corpus_dir.is_dir() {
-            test_corpus_dir = current_dir.join("corpus");
-        }
-        if test_corpus_dir.is_dir() {
-            test::run_tests_at_path(*language, &test_corpus_dir, debug, debug_graph, filter)?;
+        // Run the corpus tests.
+        if let Some(test_corpus_dir) = find_test_corpus_dir(&current_dir) {
+            test::run_tests_at_path(*language, &test_corpus_dir, &options)?;
+        } else if options.error_on_empty_corpus {
+            return Error::err(format!("No test corpus found in {:?}", current_dir));
         }
 
         // Check that all of the queries are valid.
@@ -213,6 +739,55 @@ fn run() -> error::Result<()> {
             test_highlight::test_highlights(&loader, &test_highlight_dir)?;
         }
     } else if let Some(matches) = matches.subcommand_matches("parse") {
+        if let Some(mut paths) = matches.values_of("compare") {
+            let old_path = Path::new(paths.next().unwrap());
+            let new_path = Path::new(paths.next().unwrap());
+            loader.find_all_languages(&config.parser_directories)?;
+            let language = select_language(
+                &mut loader,
+                new_path,
+                &current_dir,
+                matches.value_of("scope"),
+            )?;
+            parse::compare_files_at_paths(language, old_path, new_path)?;
+            return Ok(());
+        }
+        if let Some(grammar_dir) = matches.value_of("check-coverage") {
+            let mut coverage = parse::GrammarCoverage::load(Path::new(grammar_dir))?;
+            loader.find_all_languages(&config.parser_directories)?;
+            for path in collect_paths(matches.values_of("path").unwrap())? {
+                let path = Path::new(&path);
+                let language =
+                    select_language(&mut loader, path, &current_dir, matches.value_of("scope"))?;
+                coverage.add_file(language, path)?;
+            }
+            print!("{}", coverage);
+            return Ok(());
+        }
+        if matches.is_present("report-ambiguities") {
+            loader.find_all_languages(&config.parser_directories)?;
+            let mut found_ambiguities = false;
+            for path in collect_paths(matches.values_of("path").unwrap())? {
+                let path = Path::new(&path);
+                let language =
+                    select_language(&mut loader, path, &current_dir, matches.value_of("scope"))?;
+                let source_code = fs::read(path).map_err(Error::wrap(|| {
+                    format!("Error reading source file {:?}", path)
+                }))?;
+                let ambiguities = parse::find_ambiguities(language, &source_code)?;
+                if !ambiguities.is_empty() {
+                    found_ambiguities = true;
+                    println!("{}", path.display());
+                    for ambiguity in ambiguities {
+                        println!("  {}", ambiguity);
+                    }
+                }
+            }
+            if found_ambiguities {
+                return Error::err(String::new());
+            }
+            return Ok(());
+        }
         let debug = matches.is_present("debug");
         let debug_graph = matches.is_present("debug-graph");
         let quiet = matches.is_present("quiet");
@@ -224,32 +799,103 @@ fn run() -> error::Result<()> {
         let timeout = matches
             .value_of("timeout")
             .map_or(0, |t| u64::from_str_radix(t, 10).unwrap());
+        let max_error_recovery_nodes = matches
+            .value_of("max-error-recovery-nodes")
+            .unwrap()
+            .parse::<u32>()
+            .map_err(|_| "Invalid value for --max-error-recovery-nodes".to_string())?;
+        let error_format = match matches.value_of("error-format") {
+            Some("gcc") => parse::ErrorFormat::Gcc,
+            _ => parse::ErrorFormat::Default,
+        };
+        let selected_fields = matches
+            .value_of("select-fields")
+            .map(|fields| fields.split(',').map(str::trim).collect::<Vec<_>>());
+        let max_depth = if let Some(depth) = matches.value_of("select-depth") {
+            match depth.parse::<usize>() {
+                Ok(depth) if depth > 0 => Some(depth),
+                _ => return Error::err("Invalid value for --select-depth".to_string()),
+            }
+        } else {
+            None
+        };
+        let extracted_node_types = matches
+            .value_of("extract-nodes")
+            .map(|types| types.split(',').map(str::trim).collect::<Vec<_>>());
+        let output_format = match matches.value_of("format") {
+            Some("graphml") => parse::OutputFormat::GraphML,
+            _ => parse::OutputFormat::Default,
+        };
+        // `--format sexp` can be combined with `--omit-ranges`, so this conflict can't be
+        // declared on the arguments.
+        if output_format == parse::OutputFormat::GraphML && matches.is_present("omit-ranges") {
+            return Error::err(
+                "The --omit-ranges flag can't be used with GraphML output".to_string(),
+            );
+        }
+        let count = matches.is_present("count");
+        let json_tokens = matches.is_present("json-tokens");
+        let coverage = matches.is_present("coverage");
+        let omit_ranges = matches.is_present("omit-ranges");
+        let show_missing = matches.is_present("show-missing");
+        let pretty_print_width = if matches.is_present("pretty-print") {
+            match matches
+                .value_of("line-width")
+                .unwrap_or("80")
+                .parse::<usize>()
+            {
+                Ok(width) => Some(width),
+                Err(_) => return Error::err("Invalid value for --line-width".to_string()),
+            }
+        } else {
+            None
+        };
         let paths = collect_paths(matches.values_of("path").unwrap())?;
-        let max_path_length = paths.iter().map(|p| p.chars().count()).max().unwrap();
+        let options = parse::ParseOptions {
+            edits: &edits,
+            max_path_length: paths.iter().map(|p| p.chars().count()).max().unwrap(),
+            quiet,
+            print_time: time,
+            timeout,
+            max_error_recovery_nodes,
+            error_format,
+            selected_fields: selected_fields.as_ref().map(Vec::as_slice),
+            max_depth,
+            extracted_node_types: extracted_node_types.as_ref().map(Vec::as_slice),
+            output_format,
+            count,
+            json_tokens,
+            print_coverage: coverage,
+            omit_ranges,
+            show_missing,
+            pretty_print_width,
+            debug,
+            debug_graph,
+            allow_cancellation,
+        };
         let mut has_error = false;
         loader.find_all_languages(&config.parser_directories)?;
         for path in paths {
             let path = Path::new(&path);
             let language =
                 select_language(&mut loader, path, &current_dir, matches.value_of("scope"))?;
-            has_error |= parse::parse_file_at_path(
-                language,
-                path,
-                &edits,
-                max_path_length,
-                quiet,
-                time,
-                timeout,
-                debug,
-                debug_graph,
-                allow_cancellation,
-            )?;
+            has_error |= parse::parse_file_at_path(language, path, &options)?;
         }
         if has_error {
             return Error::err(String::new());
         }
     } else if let Some(matches) = matches.subcommand_matches("query") {
         let ordered_captures = matches.values_of("captures").is_some();
+        let print_stats = matches.is_present("stats");
+        let output_format = match matches.value_of("output-format") {
+            Some("lsp") => query::OutputFormat::Lsp,
+            _ => query::OutputFormat::Default,
+        };
+        if output_format == query::OutputFormat::Lsp && (ordered_captures || print_stats) {
+            return Error::err(
+                "The --captures and --stats flags can't be used with LSP output".to_string(),
+            );
+        }
         let paths = matches
             .values_of("path")
             .unwrap()
@@ -264,18 +910,30 @@ fn run() -> error::Result<()> {
             matches.value_of("scope"),
         )?;
         let query_path = Path::new(matches.value_of("query-path").unwrap());
-        query::query_files_at_paths(language, paths, query_path, ordered_captures)?;
+        query::query_files_at_paths(
+            language,
+            paths,
+            query_path,
+            ordered_captures,
+            print_stats,
+            output_format,
+        )?;
     } else if let Some(matches) = matches.subcommand_matches("tags") {
         loader.find_all_languages(&config.parser_directories)?;
         let paths = collect_paths(matches.values_of("inputs").unwrap())?;
         tags::generate_tags(&loader, matches.value_of("scope"), &paths)?;
     } else if let Some(matches) = matches.subcommand_matches("highlight") {
         loader.configure_highlights(&config.theme.highlight_names);
+        if let Some(scope) = matches.value_of("pattern-scope") {
+            loader.configure_highlight_pattern_scope(scope);
+        }
         loader.find_all_languages(&config.parser_directories)?;
 
         let time = matches.is_present("time");
         let paths = collect_paths(matches.values_of("path").unwrap())?;
-        let html_mode = matches.is_present("html");
+        let check_injections = matches.is_present("check-injections");
+        let html_mode = matches.is_present("html") && !check_injections;
+        let show_tree = matches.is_present("show-tree");
         if html_mode {
             println!("{}", highlight::HTML_HEADER);
         }
@@ -288,6 +946,28 @@ fn run() -> error::Result<()> {
             }
         }
 
+        let mut injections = Vec::new();
+        for flag in matches.values_of("inject-language").into_iter().flatten() {
+            let error = || {
+                Error::from(format!(
+                    "Invalid injection '{}'. Injections must match the pattern '<LANGUAGE>=<START_BYTE>:<END_BYTE>'",
+                    flag
+                ))
+            };
+            let mut parts = flag.splitn(2, '=');
+            let language_name = parts.next().ok_or_else(error)?;
+            let mut range_parts = parts.next().ok_or_else(error)?.splitn(2, ':');
+            let start_byte = range_parts.next().ok_or_else(error)?;
+            let start_byte = start_byte.parse::<usize>().map_err(|_| error())?;
+            let end_byte = range_parts.next().ok_or_else(error)?;
+            let end_byte = end_byte.parse::<usize>().map_err(|_| error())?;
+            let injection_config = loader
+                .highlight_config_for_injection_string(language_name)
+                .ok_or_else(|| format!("Unknown injected language '{}'", language_name))?;
+            injections.push((start_byte..end_byte, injection_config));
+        }
+
+        let mut has_unresolved_injection = false;
         for path in paths {
             let path = Path::new(&path);
             let (language, language_config) = match lang {
@@ -303,10 +983,44 @@ fn run() -> error::Result<()> {
 
             if let Some(highlight_config) = language_config.highlight_config(language)? {
                 let source = fs::read(path)?;
-                if html_mode {
-                    highlight::html(&loader, &config.theme, &source, highlight_config, time)?;
+                if check_injections {
+                    let unresolved = highlight::unresolved_injection_languages(
+                        &loader,
+                        &source,
+                        highlight_config,
+                    )?;
+                    for name in unresolved {
+                        println!("{}: unknown injected language '{}'", path.display(), name);
+                        has_unresolved_injection = true;
+                    }
+                } else if html_mode {
+                    highlight::html(
+                        &loader,
+                        &config.theme,
+                        &source,
+                        highlight_config,
+                        &injections,
+                        time,
+                    )?;
+                } else if show_tree {
+                    highlight::ansi_with_tree(
+                        &loader,
+                        &config.theme,
+                        &source,
+                        language,
+                        highlight_config,
+                        &injections,
+                        time,
+                    )?;
                 } else {
-                    highlight::ansi(&loader, &config.theme, &source, highlight_config, time)?;
+                    highlight::ansi(
+                        &loader,
+                        &config.theme,
+                        &source,
+                        highlight_config,
+                        &injections,
+                        time,
+                    )?;
                 }
             } else {
                 eprintln!("No syntax highlighting config found for path {:?}", path);
@@ -316,8 +1030,16 @@ fn run() -> error::Result<()> {
         if html_mode {
             println!("{}", highlight::HTML_FOOTER);
         }
+        if has_unresolved_injection {
+            return Error::err(String::new());
+        }
     } else if let Some(matches) = matches.subcommand_matches("build-wasm") {
-        let grammar_path = current_dir.join(matches.value_of("path").unwrap_or(""));
+        let grammar_path = current_dir.join(
+            matches
+                .value_of("grammar-dir")
+                .or(matches.value_of("path"))
+                .unwrap_or(""),
+        );
         wasm::compile_language_to_wasm(&grammar_path, matches.is_present("docker"))?;
     } else if let Some(matches) = matches.subcommand_matches("web-ui") {
         let open_in_browser = !matches.is_present("quiet");
@@ -347,6 +1069,20 @@ fn run() -> error::Result<()> {
     Ok(())
 }
 
+// Look for a grammar's corpus tests at two paths: `test/corpus` and `corpus`.
+fn find_test_corpus_dir(grammar_dir: &Path) -> Option<PathBuf> {
+    let test_corpus_dir = grammar_dir.join("test").join("corpus");
+    if test_corpus_dir.is_dir() {
+        return Some(test_corpus_dir);
+    }
+    let test_corpus_dir = grammar_dir.join("corpus");
+    if test_corpus_dir.is_dir() {
+        Some(test_corpus_dir)
+    } else {
+        None
+    }
+}
+
 fn collect_paths<'a>(paths: impl Iterator<Item = &'a str>) -> error::Result<Vec<String>> {
     let mut result = Vec::new();
 
@@ -1,11 +1,18 @@
 use super::error::{Error, Result};
 use super::util;
+use ansi_term::Colour;
+use serde_derive::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
-use std::{fs, thread, usize};
-use tree_sitter::{InputEdit, Language, LogType, Parser, Point, Tree};
+use std::{fmt, fs, thread, usize};
+use tree_sitter::{
+    InputEdit, Language, LogType, Node, Parser, Point, SexpOptions, Tree, TreeCursor,
+};
 
 #[derive(Debug)]
 pub struct Edit {
@@ -14,18 +21,58 @@ pub struct Edit {
     pub inserted_text: Vec<u8>,
 }
 
-pub fn parse_file_at_path(
-    language: Language,
-    path: &Path,
-    edits: &Vec<&str>,
-    max_path_length: usize,
-    quiet: bool,
-    print_time: bool,
-    timeout: u64,
-    debug: bool,
-    debug_graph: bool,
-    allow_cancellation: bool,
-) -> Result<bool> {
+/// The format in which syntax errors are reported after parsing a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Report the first error next to the file's path and parse time.
+    Default,
+    /// Report every error on its own line as `<path>:<line>:<column>: error: <message>`,
+    /// like GCC, so that editors and build tools can locate it.
+    Gcc,
+}
+
+/// The format in which `tree-sitter parse` prints each file's syntax tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Print the tree as an indented S-expression.
+    Default,
+    /// Print the tree as a GraphML document, with a graph node for each syntax node
+    /// and an edge from each syntax node to each of its children.
+    GraphML,
+}
+
+/// The options that control how `tree-sitter parse` parses a file and what it prints.
+#[derive(Clone, Copy, Debug)]
+pub struct ParseOptions<'a> {
+    /// Edits to apply to the source code, reparsing incrementally after each one, in
+    /// the format of the `--edit` flag.
+    pub edits: &'a [&'a str],
+    /// The width to which file paths are padded when printing times and coverage.
+    pub max_path_length: usize,
+    pub quiet: bool,
+    pub print_time: bool,
+    pub timeout: u64,
+    pub max_error_recovery_nodes: u32,
+    pub error_format: ErrorFormat,
+    /// Only print the children assigned to these fields.
+    pub selected_fields: Option<&'a [&'a str]>,
+    pub max_depth: Option<usize>,
+    /// Print the text of the nodes of these types instead of the tree.
+    pub extracted_node_types: Option<&'a [&'a str]>,
+    pub output_format: OutputFormat,
+    pub count: bool,
+    pub json_tokens: bool,
+    pub print_coverage: bool,
+    pub omit_ranges: bool,
+    pub show_missing: bool,
+    /// If set, pretty-print the tree with lines of at most this many characters.
+    pub pretty_print_width: Option<usize>,
+    pub debug: bool,
+    pub debug_graph: bool,
+    pub allow_cancellation: bool,
+}
+
+pub fn parse_file_at_path(language: Language, path: &Path, options: &ParseOptions) -> Result<bool> {
     let mut _log_session = None;
     let mut parser = Parser::new();
     parser.set_language(language).map_err(|e| e.to_string())?;
@@ -35,7 +82,7 @@ pub fn parse_file_at_path(
 
     // If the `--cancel` flag was passed, then cancel the parse
     // when the user types a newline.
-    if allow_cancellation {
+    if options.allow_cancellation {
         let flag = Box::new(AtomicUsize::new(0));
         unsafe { parser.set_cancellation_flag(Some(&flag)) };
         thread::spawn(move || {
@@ -47,14 +94,17 @@ pub fn parse_file_at_path(
     }
 
     // Set a timeout based on the `--time` flag.
-    parser.set_timeout_micros(timeout);
+    parser.set_timeout_micros(options.timeout);
+
+    // Limit the number of MISSING nodes based on the `--max-error-recovery-nodes` flag.
+    parser.set_max_error_recovery_nodes(options.max_error_recovery_nodes);
 
     // Render an HTML graph if `--debug-graph` was passed
-    if debug_graph {
+    if options.debug_graph {
         _log_session = Some(util::log_graphs(&mut parser, "log.html")?);
     }
     // Log to stderr if `--debug` was passed
-    else if debug {
+    else if options.debug {
         parser.set_logger(Some(Box::new(|log_type, message| {
             if log_type == LogType::Lex {
                 io::stderr().write(b"  ").unwrap();
@@ -70,81 +120,89 @@ pub fn parse_file_at_path(
     let mut stdout = stdout.lock();
 
     if let Some(mut tree) = tree {
-        if debug_graph && !edits.is_empty() {
+        if options.debug_graph && !options.edits.is_empty() {
             println!("BEFORE:\n{}", String::from_utf8_lossy(&source_code));
         }
 
-        for (i, edit) in edits.iter().enumerate() {
+        for (i, edit) in options.edits.iter().enumerate() {
             let edit = parse_edit_flag(&source_code, edit)?;
             perform_edit(&mut tree, &mut source_code, &edit);
             tree = parser.parse(&source_code, Some(&tree)).unwrap();
 
-            if debug_graph {
+            if options.debug_graph {
                 println!("AFTER {}:\n{}", i, String::from_utf8_lossy(&source_code));
             }
         }
 
         let duration = time.elapsed();
         let duration_ms = duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1000000;
-        let mut cursor = tree.walk();
+        if !options.quiet {
+            // The command line declares these output modes as mutually exclusive, so
+            // at most one of them is set.
+            if options.count {
+                write_node_counts(&mut stdout, &tree)?;
+            } else if options.json_tokens {
+                write_json_tokens(&mut stdout, &tree, &source_code, options.show_missing)?;
+            } else if let Some(node_types) = options.extracted_node_types {
+                write_extracted_nodes(
+                    &mut stdout,
+                    &tree,
+                    &source_code,
+                    node_types,
+                    options.show_missing,
+                )?;
+            } else if options.output_format == OutputFormat::GraphML {
+                write_graphml(&mut stdout, &tree, &source_code, options.show_missing)?;
+            } else if let Some(line_width) = options.pretty_print_width {
+                writeln!(
+                    &mut stdout,
+                    "{}",
+                    pretty_print_sexp(
+                        tree.root_node(),
+                        !options.omit_ranges,
+                        options.show_missing,
+                        line_width
+                    )
+                )?;
+            } else {
+                write_tree(
+                    &mut stdout,
+                    &tree,
+                    false,
+                    !options.omit_ranges,
+                    options.show_missing,
+                    options.selected_fields,
+                    options.max_depth,
+                )?;
+            }
+        }
 
-        if !quiet {
-            let mut needs_newline = false;
-            let mut indent_level = 0;
-            let mut did_visit_children = false;
-            loop {
-                let node = cursor.node();
-                let is_named = node.is_named();
-                if did_visit_children {
-                    if is_named {
-                        stdout.write(b")")?;
-                        needs_newline = true;
-                    }
-                    if cursor.goto_next_sibling() {
-                        did_visit_children = false;
-                    } else if cursor.goto_parent() {
-                        did_visit_children = true;
-                        indent_level -= 1;
-                    } else {
-                        break;
-                    }
-                } else {
-                    if is_named {
-                        if needs_newline {
-                            stdout.write(b"\n")?;
-                        }
-                        for _ in 0..indent_level {
-                            stdout.write(b"  ")?;
-                        }
-                        let start = node.start_position();
-                        let end = node.end_position();
-                        if let Some(field_name) = cursor.field_name() {
-                            write!(&mut stdout, "{}: ", field_name)?;
-                        }
-                        write!(
-                            &mut stdout,
-                            "({} [{}, {}] - [{}, {}]",
-                            node.kind(),
-                            start.row,
-                            start.column,
-                            end.row,
-                            end.column
-                        )?;
-                        needs_newline = true;
-                    }
-                    if cursor.goto_first_child() {
-                        did_visit_children = false;
-                        indent_level += 1;
-                    } else {
-                        did_visit_children = true;
-                    }
-                }
+        if options.print_coverage {
+            writeln!(
+                &mut stdout,
+                "{:width$}\t{:.2}% coverage",
+                path.to_str().unwrap(),
+                100.0 * tree.root_node().coverage(&source_code),
+                width = options.max_path_length
+            )?;
+        }
+
+        if options.error_format == ErrorFormat::Gcc {
+            if options.print_time {
+                writeln!(
+                    &mut stdout,
+                    "{:width$}\t{} ms",
+                    path.to_str().unwrap(),
+                    duration_ms,
+                    width = options.max_path_length
+                )?;
             }
-            cursor.reset(tree.root_node());
-            println!("");
+            write_gcc_errors(&mut stdout, path, tree.root_node())?;
+            return Ok(tree.root_node().has_error());
         }
 
         let mut first_error = None;
+        let mut cursor = tree.walk();
         loop {
             let node = cursor.node();
             if node.has_error() {
@@ -161,13 +219,13 @@ pub fn parse_file_at_path(
             }
         }
 
-        if first_error.is_some() || print_time {
+        if first_error.is_some() || options.print_time {
             write!(
                 &mut stdout,
                 "{:width$}\t{} ms",
                 path.to_str().unwrap(),
                 duration_ms,
-                width = max_path_length
+                width = options.max_path_length
             )?;
             if let Some(node) = first_error {
                 let start = node.start_position();
@@ -196,7 +254,7 @@ pub fn parse_file_at_path(
         }
 
         return Ok(first_error.is_some());
-    } else if print_time {
+    } else if options.print_time {
         let duration = time.elapsed();
         let duration_ms = duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1000000;
         writeln!(
@@ -204,13 +262,919 @@ pub fn parse_file_at_path(
             "{:width$}\t{} ms (timed out)",
             path.to_str().unwrap(),
             duration_ms,
-            width = max_path_length
+            width = options.max_path_length
         )?;
     }
 
     Ok(false)
 }
 
+/// Parse two versions of a file, the second one incrementally, and print the parts
+/// of the trees that changed between them, as described in [write_comparison].
+pub fn compare_files_at_paths(language: Language, old_path: &Path, new_path: &Path) -> Result<()> {
+    let read = |path: &Path| {
+        fs::read(path).map_err(Error::wrap(|| {
+            format!("Error reading source file {:?}", path)
+        }))
+    };
+    let old_source = read(old_path)?;
+    let new_source = read(new_path)?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    write_comparison(
+        &mut stdout,
+        language,
+        (&old_path.to_string_lossy(), &old_source),
+        (&new_path.to_string_lossy(), &new_source),
+    )
+}
+
+/// Parse two versions of a source file, the second one incrementally, and write
+/// the differences between them. Each version is given as a name and its source
+/// code. If the texts differ, the edited text is written first, from each version.
+/// Then for each range whose syntax changed, the named nodes in the range are
+/// written from each version, labeled with its name.
+pub fn write_comparison(
+    out: &mut impl Write,
+    language: Language,
+    (old_name, old_source): (&str, &[u8]),
+    (new_name, new_source): (&str, &[u8]),
+) -> Result<()> {
+    if old_source == new_source {
+        writeln!(out, "{} -> {}: no changes", old_name, new_name)?;
+        return Ok(());
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|e| e.to_string())?;
+    let old_tree = parser.parse(old_source, None).unwrap();
+    let edit = edit_between(old_source, new_source);
+    let mut edited_tree = old_tree.clone();
+    edited_tree.edit(&edit);
+    let new_tree = parser.parse(new_source, Some(&edited_tree)).unwrap();
+
+    // An edit can change the text without changing the syntax, for example by
+    // renaming an identifier, so the text is compared separately from the trees.
+    writeln!(out, "text")?;
+    let texts = [
+        (
+            old_name,
+            old_source,
+            edit.old_end_byte,
+            edit.old_end_position,
+        ),
+        (
+            new_name,
+            new_source,
+            edit.new_end_byte,
+            edit.new_end_position,
+        ),
+    ];
+    for (name, source, end_byte, end_position) in texts.iter() {
+        writeln!(
+            out,
+            "  {}: [{}, {}] - [{}, {}] {:?}",
+            name,
+            edit.start_position.row,
+            edit.start_position.column,
+            end_position.row,
+            end_position.column,
+            String::from_utf8_lossy(&source[edit.start_byte..*end_byte])
+        )?;
+    }
+
+    // The changed ranges are positioned in the new file. Any part of a range that
+    // overlaps the edit covers the whole deleted text in the old file.
+    let old_offset = |offset: usize, is_end: bool| {
+        if offset <= edit.start_byte {
+            offset
+        } else if offset >= edit.new_end_byte {
+            offset - edit.new_end_byte + edit.old_end_byte
+        } else if is_end {
+            edit.old_end_byte
+        } else {
+            edit.start_byte
+        }
+    };
+    for range in edited_tree.changed_ranges(&new_tree) {
+        writeln!(
+            out,
+            "changed [{}, {}] - [{}, {}]",
+            range.start_point.row,
+            range.start_point.column,
+            range.end_point.row,
+            range.end_point.column
+        )?;
+        let old_start = old_offset(range.start_byte, false);
+        let old_end = old_offset(range.end_byte, true);
+        let versions = [
+            (old_name, &old_tree, old_source, old_start, old_end),
+            (
+                new_name,
+                &new_tree,
+                new_source,
+                range.start_byte,
+                range.end_byte,
+            ),
+        ];
+        for (name, tree, source, start, end) in versions.iter() {
+            // Print the named children of the smallest node that contains the range,
+            // skipping those outside of the range, or that node itself if none of
+            // its named children are in the range.
+            let parent = tree
+                .root_node()
+                .named_descendant_for_byte_range(*start, *end)
+                .unwrap();
+            let mut nodes = parent
+                .children_in_range(*start, *end)
+                .filter(|node| node.is_named())
+                .collect::<Vec<_>>();
+            if nodes.is_empty() || parent.byte_range() == (*start..*end) {
+                nodes = vec![parent];
+            }
+            let options = SexpOptions {
+                include_ranges: true,
+                source_code: Some(source),
+                indentation: 2,
+                ..SexpOptions::default()
+            };
+            writeln!(out, "  {}:", name)?;
+            for node in nodes {
+                for line in node.format_sexp(&options).lines() {
+                    writeln!(out, "    {}", line)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Describe the change from one text to another as a single edit, which replaces
+// everything between their common prefix and their common suffix.
+fn edit_between(old: &[u8], new: &[u8]) -> InputEdit {
+    let prefix_length = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix_length = old[prefix_length..]
+        .iter()
+        .rev()
+        .zip(new[prefix_length..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let start_byte = prefix_length;
+    let old_end_byte = old.len() - suffix_length;
+    let new_end_byte = new.len() - suffix_length;
+    InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: position_for_offset(old, start_byte),
+        old_end_position: position_for_offset(old, old_end_byte),
+        new_end_position: position_for_offset(new, new_end_byte),
+    }
+}
+
+/// Write a syntax tree as an indented list of its named nodes, along with their
+/// fields and ranges. If `highlight_errors` is true, then `ERROR` and `MISSING` nodes
+/// are labeled and printed in red. If `show_missing` is true, then `MISSING` nodes
+/// are labeled, and missing anonymous nodes are written too, as in corpus tests. If
+/// `include_ranges` is false, then only the structure of the tree is printed. If
+/// `selected_fields` is given, then children in any other field are omitted, along
+/// with their descendants. If `max_depth` is given, then only that many levels of
+/// the tree are printed, and the children of the deepest printed nodes are replaced
+/// with `...`.
+pub fn write_tree(
+    out: &mut impl Write,
+    tree: &Tree,
+    highlight_errors: bool,
+    include_ranges: bool,
+    show_missing: bool,
+    selected_fields: Option<&[&str]>,
+    max_depth: Option<usize>,
+) -> Result<()> {
+    let mut cursor = tree.walk();
+    let mut needs_newline = false;
+    let mut indent_level = 0;
+    let mut did_visit_children = false;
+    loop {
+        let node = cursor.node();
+        let is_visible = node.is_named() || (show_missing && node.is_missing());
+        if did_visit_children {
+            if is_visible {
+                out.write(b")")?;
+                needs_newline = true;
+            }
+            if cursor.goto_next_sibling() {
+                did_visit_children = false;
+            } else if cursor.goto_parent() {
+                did_visit_children = true;
+                indent_level -= 1;
+            } else {
+                break;
+            }
+        } else {
+            if let (Some(fields), Some(field_name)) = (selected_fields, cursor.field_name()) {
+                if !fields.contains(&field_name) {
+                    if cursor.goto_next_sibling() {
+                        did_visit_children = false;
+                    } else if cursor.goto_parent() {
+                        did_visit_children = true;
+                        indent_level -= 1;
+                    } else {
+                        break;
+                    }
+                    continue;
+                }
+            }
+            if is_visible {
+                if needs_newline {
+                    out.write(b"\n")?;
+                }
+                for _ in 0..indent_level {
+                    out.write(b"  ")?;
+                }
+                if let Some(field_name) = cursor.field_name() {
+                    write!(out, "{}: ", field_name)?;
+                }
+                if node.is_missing() && (highlight_errors || show_missing) {
+                    let kind = if node.is_named() {
+                        format!("MISSING {}", node.kind())
+                    } else {
+                        format!("MISSING \"{}\"", node.kind())
+                    };
+                    if highlight_errors {
+                        write!(out, "({}", Colour::Red.paint(kind))?;
+                    } else {
+                        write!(out, "({}", kind)?;
+                    }
+                } else if highlight_errors && node.is_error() {
+                    write!(out, "({}", Colour::Red.paint(node.kind()))?;
+                } else {
+                    write!(out, "({}", node.kind())?;
+                }
+                if include_ranges {
+                    let start = node.start_position();
+                    let end = node.end_position();
+                    write!(
+                        out,
+                        " [{}, {}] - [{}, {}]",
+                        start.row, start.column, end.row, end.column
+                    )?;
+                }
+                needs_newline = true;
+            }
+            if max_depth == Some(indent_level + 1) && node.named_child_count() > 0 {
+                out.write_all(b" ...")?;
+                did_visit_children = true;
+            } else if cursor.goto_first_child() {
+                did_visit_children = false;
+                indent_level += 1;
+            } else {
+                did_visit_children = true;
+            }
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Write every node in a syntax tree whose type is one of the given types, one
+/// line per node with its type, its range, and its text. If `show_missing` is true,
+/// then the types of `MISSING` nodes are labeled.
+pub fn write_extracted_nodes(
+    out: &mut impl Write,
+    tree: &Tree,
+    source_code: &[u8],
+    node_types: &[&str],
+    show_missing: bool,
+) -> Result<()> {
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if node_types.contains(&node.kind()) {
+            let start = node.start_position();
+            let end = node.end_position();
+            let missing_label = if show_missing && node.is_missing() {
+                "MISSING "
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "{}{} [{}, {}] - [{}, {}] {:?}",
+                missing_label,
+                node.kind(),
+                start.row,
+                start.column,
+                end.row,
+                end.column,
+                String::from_utf8_lossy(&source_code[node.byte_range()])
+            )?;
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return Ok(());
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonToken<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    text: String,
+    start: usize,
+    end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    missing: Option<bool>,
+}
+
+/// Write the leaf nodes of a syntax tree as a JSON array of objects with each
+/// token's type, its text, and its start and end byte offsets. If `show_missing` is
+/// true, then each token also says whether it is `MISSING`.
+pub fn write_json_tokens(
+    out: &mut impl Write,
+    tree: &Tree,
+    source_code: &[u8],
+    show_missing: bool,
+) -> Result<()> {
+    let mut tokens = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if node.child_count() == 0 {
+            tokens.push(JsonToken {
+                kind: node.kind(),
+                text: String::from_utf8_lossy(&source_code[node.byte_range()]).to_string(),
+                start: node.start_byte(),
+                end: node.end_byte(),
+                missing: Some(node.is_missing()).filter(|_| show_missing),
+            });
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                writeln!(out, "{}", serde_json::to_string(&tokens)?)?;
+                return Ok(());
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+/// Format a node as an S-expression that is indented by two spaces per level. Each
+/// subtree that fits within the given line width, including its indentation, is
+/// written on a single line. Otherwise, each of its children is written on its own
+/// line. If `show_missing` is true, then `MISSING` nodes are labeled, and missing
+/// anonymous nodes are written too.
+pub fn pretty_print_sexp(
+    node: Node,
+    include_ranges: bool,
+    show_missing: bool,
+    line_width: usize,
+) -> String {
+    let mut result = String::new();
+    let options = SexpOptions {
+        include_ranges,
+        label_missing_nodes: show_missing,
+        ..SexpOptions::default()
+    };
+    let mut subtree_sizes = Vec::new();
+    measure_sexp(&mut node.walk(), &options, &mut subtree_sizes);
+    write_pretty_sexp(
+        &mut result,
+        &mut node.walk(),
+        &options,
+        &subtree_sizes,
+        0,
+        line_width,
+        0,
+    );
+    result
+}
+
+// Find the width of each node's S-expression when it is written on a single line,
+// working from the bottom of the tree up. The widths are stored in the order that
+// the nodes are visited, along with the number of nodes in each subtree, so that
+// the subtrees which are written on a single line can be skipped. Returns the width
+// that the node adds to its parent's S-expression.
+fn measure_sexp(
+    cursor: &mut TreeCursor,
+    options: &SexpOptions,
+    subtree_sizes: &mut Vec<(usize, usize)>,
+) -> usize {
+    let node = cursor.node();
+    let field_name = cursor.field_name();
+    let index = subtree_sizes.len();
+    subtree_sizes.push((0, 0));
+    let mut children_width = 0;
+    if cursor.goto_first_child() {
+        loop {
+            children_width += measure_sexp(cursor, options, subtree_sizes);
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+        cursor.goto_parent();
+    }
+
+    // A leaf is formatted directly, because the library's printer also describes
+    // the unexpected characters within `ERROR` leaves.
+    let is_delimited = node.is_named() || (node.is_missing() && options.label_missing_nodes);
+    let width = if node.child_count() == 0 {
+        node.format_sexp(options).chars().count()
+    } else {
+        sexp_header(node, options).chars().count() + children_width + is_delimited as usize
+    };
+    subtree_sizes[index] = (width, subtree_sizes.len() - index);
+    if is_delimited {
+        let prefix_width = field_name.map_or(0, |name| name.chars().count() + 2);
+        1 + prefix_width + width
+    } else {
+        children_width
+    }
+}
+
+// The start of a node's S-expression, before any of its children.
+fn sexp_header(node: Node, options: &SexpOptions) -> String {
+    let mut result = if node.is_missing() && options.label_missing_nodes {
+        String::from("(MISSING ")
+    } else if node.is_named() {
+        String::from("(")
+    } else {
+        String::new()
+    };
+    if node.is_named() {
+        result.push_str(node.kind());
+    } else {
+        result.push_str(&format!("{:?}", node.kind()));
+    }
+    if options.include_ranges {
+        let start = node.start_position();
+        let end = node.end_position();
+        result.push_str(&format!(
+            " [{}, {}] - [{}, {}]",
+            start.row, start.column, end.row, end.column
+        ));
+    }
+    result
+}
+
+fn write_pretty_sexp(
+    out: &mut String,
+    cursor: &mut TreeCursor,
+    options: &SexpOptions,
+    subtree_sizes: &[(usize, usize)],
+    index: usize,
+    line_width: usize,
+    indent_level: usize,
+) {
+    let node = cursor.node();
+    let indentation = indent_level * 2;
+    let prefix = match cursor.field_name() {
+        Some(field_name) if indent_level > 0 => format!("{}: ", field_name),
+        _ => String::new(),
+    };
+    out.push_str(&" ".repeat(indentation));
+    out.push_str(&prefix);
+    if node.named_child_count() == 0
+        || indentation + prefix.chars().count() + subtree_sizes[index].0 <= line_width
+    {
+        out.push_str(&node.format_sexp(options));
+        return;
+    }
+
+    out.push_str(&sexp_header(node, options));
+    if cursor.goto_first_child() {
+        let mut child_index = index + 1;
+        loop {
+            let child = cursor.node();
+            if child.is_named() || (child.is_missing() && options.label_missing_nodes) {
+                out.push('\n');
+                write_pretty_sexp(
+                    out,
+                    cursor,
+                    options,
+                    subtree_sizes,
+                    child_index,
+                    line_width,
+                    indent_level + 1,
+                );
+            }
+            child_index += subtree_sizes[child_index].1;
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+        cursor.goto_parent();
+    }
+    out.push(')');
+}
+
+/// Write a syntax tree as a GraphML document. Each syntax node becomes a graph node
+/// with its type and its start and end byte offsets as attributes, and each
+/// parent-child relationship becomes a directed edge. Leaf nodes also have their
+/// text as an attribute. The text of any other node can be found from its offsets.
+/// If `show_missing` is true, then each graph node also has an attribute that says
+/// whether it is `MISSING`.
+pub fn write_graphml(
+    out: &mut impl Write,
+    tree: &Tree,
+    source_code: &[u8],
+    show_missing: bool,
+) -> Result<()> {
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    let mut keys = vec![
+        ("type", "string"),
+        ("start_byte", "long"),
+        ("end_byte", "long"),
+        ("text", "string"),
+    ];
+    if show_missing {
+        keys.push(("missing", "boolean"));
+    }
+    for (name, kind) in &keys {
+        writeln!(
+            out,
+            "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>",
+            name, kind
+        )?;
+    }
+    writeln!(out, "  <graph id=\"tree\" edgedefault=\"directed\">")?;
+
+    // Graph nodes are numbered in the order that the tree is traversed. The stack
+    // holds the numbers of the current node's ancestors.
+    let mut cursor = tree.walk();
+    let mut node_count = 0;
+    let mut ancestor_ids = Vec::new();
+    loop {
+        let node = cursor.node();
+        let id = node_count;
+        node_count += 1;
+        writeln!(out, "    <node id=\"n{}\">", id)?;
+        writeln!(
+            out,
+            "      <data key=\"type\">{}</data>",
+            escape_xml(node.kind())
+        )?;
+        writeln!(
+            out,
+            "      <data key=\"start_byte\">{}</data>",
+            node.start_byte()
+        )?;
+        writeln!(
+            out,
+            "      <data key=\"end_byte\">{}</data>",
+            node.end_byte()
+        )?;
+        if node.child_count() == 0 {
+            writeln!(
+                out,
+                "      <data key=\"text\">{}</data>",
+                escape_xml(&String::from_utf8_lossy(&source_code[node.byte_range()]))
+            )?;
+        }
+        if show_missing {
+            writeln!(
+                out,
+                "      <data key=\"missing\">{}</data>",
+                node.is_missing()
+            )?;
+        }
+        writeln!(out, "    </node>")?;
+        if let Some(parent_id) = ancestor_ids.last() {
+            writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"/>",
+                parent_id, id
+            )?;
+        }
+
+        if cursor.goto_first_child() {
+            ancestor_ids.push(id);
+            continue;
+        }
+        if cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                writeln!(out, "  </graph>")?;
+                writeln!(out, "</graphml>")?;
+                return Ok(());
+            }
+            ancestor_ids.pop();
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+// Escape text for use in an XML document. Control characters aren't allowed in
+// XML, even as character references, so they are replaced.
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result += "&amp;",
+            '<' => result += "&lt;",
+            '>' => result += "&gt;",
+            '"' => result += "&quot;",
+            '\t' | '\n' | '\r' => result.push(c),
+            '\u{0}'..='\u{1F}' => result.push('\u{FFFD}'),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// The number of times that each of a grammar's visible rules appears as a node in
+/// a set of syntax trees, for measuring how much of the grammar some source files
+/// exercise. Hidden rules, whose names start with an underscore, never appear in
+/// syntax trees, so they aren't counted.
+#[derive(Debug, Default)]
+pub struct GrammarCoverage {
+    rule_names: Vec<String>,
+    counts: HashMap<String, usize>,
+}
+
+impl GrammarCoverage {
+    /// Start measuring the coverage of the given rules.
+    pub fn new(rule_names: impl IntoIterator<Item = String>) -> Self {
+        GrammarCoverage {
+            rule_names: rule_names
+                .into_iter()
+                .filter(|name| !name.starts_with('_'))
+                .collect(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Start measuring the coverage of the rules in the `src/grammar.json` file of the
+    /// grammar repository at the given path.
+    pub fn load(grammar_dir: &Path) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct GrammarJSON {
+            rules: serde_json::Map<String, serde_json::Value>,
+        }
+
+        let grammar_json_path = grammar_dir.join("src").join("grammar.json");
+        let grammar_json = fs::read_to_string(&grammar_json_path).map_err(Error::wrap(|| {
+            format!(
+                "Failed to read {:?}. Run `tree-sitter generate` first",
+                grammar_json_path
+            )
+        }))?;
+        let grammar = serde_json::from_str::<GrammarJSON>(&grammar_json)?;
+        Ok(Self::new(grammar.rules.into_iter().map(|(name, _)| name)))
+    }
+
+    /// Parse the file at the given path, and count the nodes in its syntax tree.
+    pub fn add_file(&mut self, language: Language, path: &Path) -> Result<()> {
+        let mut parser = Parser::new();
+        parser.set_language(language).map_err(|e| e.to_string())?;
+        let source_code = fs::read(path).map_err(Error::wrap(|| {
+            format!("Error reading source file {:?}", path)
+        }))?;
+        let tree = parser
+            .parse(&source_code, None)
+            .ok_or_else(|| format!("Failed to parse {:?}", path))?;
+        self.add_tree(&tree);
+        Ok(())
+    }
+
+    /// Count the named nodes in the given syntax tree.
+    pub fn add_tree(&mut self, tree: &Tree) {
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_named() {
+                *self.counts.entry(node.kind().to_string()).or_insert(0) += 1;
+            }
+            if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                continue;
+            }
+            loop {
+                if !cursor.goto_parent() {
+                    return;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Get the names of the rules that haven't appeared in any of the syntax trees,
+    /// in the order that they are defined.
+    pub fn unused_rule_names(&self) -> Vec<&str> {
+        self.rule_names
+            .iter()
+            .filter(|name| !self.counts.contains_key(name.as_str()))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Get the percentage of the rules that have appeared in the syntax trees. This
+    /// is 100 if there are no rules.
+    pub fn percentage(&self) -> f64 {
+        if self.rule_names.is_empty() {
+            100.0
+        } else {
+            let used_rule_count = self.rule_names.len() - self.unused_rule_names().len();
+            100.0 * used_rule_count as f64 / self.rule_names.len() as f64
+        }
+    }
+}
+
+impl fmt::Display for GrammarCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in &self.rule_names {
+            writeln!(
+                f,
+                "{}: {}",
+                name,
+                self.counts.get(name).cloned().unwrap_or(0)
+            )?;
+        }
+        let unused_rule_names = self.unused_rule_names();
+        let used_rule_count = self.rule_names.len() - unused_rule_names.len();
+        writeln!(
+            f,
+            "{} of {} rules ({:.2}%) appear in the syntax trees",
+            used_rule_count,
+            self.rule_names.len(),
+            self.percentage()
+        )?;
+        if !unused_rule_names.is_empty() {
+            writeln!(
+                f,
+                "Rules that never appear: {}",
+                unused_rule_names.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A kind of node that the parser found more than one valid way to parse, while
+/// parsing some source code, and how it chose between the alternatives.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ambiguity {
+    pub kind: String,
+    /// Whether the alternatives were distinguished by their dynamic precedence. If
+    /// not, the parser chose the alternative whose children start earliest.
+    pub resolved_by_precedence: bool,
+    /// The number of times that the parser chose between alternatives for this kind
+    /// of node in this way.
+    pub count: usize,
+}
+
+/// Parse the given source code, and find the ambiguities that the grammar's conflicts
+/// allowed: the places where several of the parser's GLR stack versions completed
+/// error-free nodes of the same kind for the same text, and the parser kept only one
+/// of them. Alternatives that fail before they complete such a node aren't found.
+pub fn find_ambiguities(language: Language, source_code: &[u8]) -> Result<Vec<Ambiguity>> {
+    let choices = Rc::new(RefCell::new(Vec::<(String, bool)>::new()));
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|e| e.to_string())?;
+    let logged_choices = choices.clone();
+    parser.set_logger(Some(Box::new(move |log_type, message| {
+        if log_type == LogType::Parse {
+            if let Some(choice) = parse_ambiguity_message(message) {
+                logged_choices.borrow_mut().push(choice);
+            }
+        }
+    })));
+    parser
+        .parse(source_code, None)
+        .ok_or_else(|| "Failed to parse".to_string())?;
+    parser.set_logger(None);
+
+    let mut result = Vec::<Ambiguity>::new();
+    for (kind, resolved_by_precedence) in choices.borrow_mut().drain(..) {
+        let existing = result.iter_mut().find(|ambiguity| {
+            ambiguity.kind == kind && ambiguity.resolved_by_precedence == resolved_by_precedence
+        });
+        match existing {
+            Some(ambiguity) => ambiguity.count += 1,
+            None => result.push(Ambiguity {
+                kind,
+                resolved_by_precedence,
+                count: 1,
+            }),
+        }
+    }
+    Ok(result)
+}
+
+// Get the node kind from a log message in which the parser chose between two
+// error-free alternatives, and whether it chose by dynamic precedence.
+fn parse_ambiguity_message(message: &str) -> Option<(String, bool)> {
+    let (rest, resolved_by_precedence, separator) =
+        if let Some(rest) = message.strip_prefix("select_higher_precedence symbol:") {
+            (rest, true, ", prec:")
+        } else if let Some(rest) = message.strip_prefix("select_earlier symbol:") {
+            (rest, false, ", over_symbol:")
+        } else {
+            return None;
+        };
+    let end = rest.find(separator)?;
+    Some((rest[0..end].to_string(), resolved_by_precedence))
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} {}",
+            self.kind,
+            self.count,
+            if self.count == 1 { "choice" } else { "choices" },
+            if self.resolved_by_precedence {
+                "made by dynamic precedence"
+            } else {
+                "between equally valid parses"
+            }
+        )
+    }
+}
+
+/// Write the number of nodes of each type in a syntax tree, one `type: count` line
+/// per type, with the most common types first. Anonymous node types are quoted.
+pub fn write_node_counts(out: &mut impl Write, tree: &Tree) -> Result<()> {
+    let mut counts = HashMap::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        *counts.entry((node.kind(), node.is_named())).or_insert(0) += 1;
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                let mut counts = counts.into_iter().collect::<Vec<_>>();
+                counts.sort_unstable_by(|(a, a_count), (b, b_count)| {
+                    b_count.cmp(a_count).then(a.cmp(b))
+                });
+                for ((kind, is_named), count) in counts {
+                    if is_named {
+                        writeln!(out, "{}: {}", kind, count)?;
+                    } else {
+                        writeln!(out, "\"{}\": {}", kind.replace("\n", "\\n"), count)?;
+                    }
+                }
+                return Ok(());
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+fn write_gcc_errors(stdout: &mut impl Write, path: &Path, node: Node) -> Result<()> {
+    if node.is_error() || node.is_missing() {
+        let start = node.start_position();
+        write!(
+            stdout,
+            "{}:{}:{}: error: ",
+            path.to_str().unwrap(),
+            start.row + 1,
+            start.column + 1
+        )?;
+        if node.is_missing() {
+            if node.is_named() {
+                writeln!(stdout, "missing {}", node.kind())?;
+            } else {
+                writeln!(stdout, "missing \"{}\"", node.kind().replace("\n", "\\n"))?;
+            }
+        } else {
+            writeln!(stdout, "unexpected token")?;
+        }
+    } else if node.has_error() {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            write_gcc_errors(stdout, path, child)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn perform_edit(tree: &mut Tree, input: &mut Vec<u8>, edit: &Edit) -> InputEdit {
     let start_byte = edit.position;
     let old_end_byte = edit.position + edit.deleted_length;
@@ -286,7 +1250,7 @@ fn offset_for_position(input: &Vec<u8>, position: Point) -> usize {
     return input.len();
 }
 
-fn position_for_offset(input: &Vec<u8>, offset: usize) -> Point {
+fn position_for_offset(input: &[u8], offset: usize) -> Point {
     let mut result = Point { row: 0, column: 0 };
     for c in &input[0..offset] {
         if *c as char == '\n' {
@@ -298,3 +1262,27 @@ fn position_for_offset(input: &Vec<u8>, offset: usize) -> Point {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_between() {
+        let edit = edit_between(b"ab\ncd\nef", b"ab\ncxyd\nef");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (4, 4, 6)
+        );
+        assert_eq!(edit.start_position, Point::new(1, 1));
+        assert_eq!(edit.old_end_position, Point::new(1, 1));
+        assert_eq!(edit.new_end_position, Point::new(1, 3));
+
+        // The common suffix can't overlap the common prefix.
+        let edit = edit_between(b"aaa", b"aaaa");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (3, 3, 4)
+        );
+    }
+}
@@ -1,14 +1,58 @@
 use super::error::{Error, Result};
+use serde_derive::Serialize;
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
-use tree_sitter::{Language, Node, Parser, Query, QueryCursor};
+use std::time::{Duration, Instant};
+use tree_sitter::{Language, Node, Parser, Point, Query, QueryCursor, Tree};
+
+/// The format in which query results are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Print each match or capture on its own line, below the file's path.
+    Default,
+    /// Print a JSON array of Language Server Protocol `Diagnostic` objects for each
+    /// file, one array per line. Diagnostics are created from the captures named
+    /// `diagnostic.error`, `diagnostic.warning`, `diagnostic.information` and
+    /// `diagnostic.hint`, and their message is taken from the pattern's `message`
+    /// property.
+    Lsp,
+}
+
+const LSP_DIAGNOSTIC_SEVERITIES: [(&str, u8); 4] = [
+    ("diagnostic.error", 1),
+    ("diagnostic.warning", 2),
+    ("diagnostic.information", 3),
+    ("diagnostic.hint", 4),
+];
+
+#[derive(Serialize)]
+pub struct LspPosition {
+    line: usize,
+    character: usize,
+}
+
+#[derive(Serialize)]
+pub struct LspRange {
+    start: LspPosition,
+    end: LspPosition,
+}
+
+#[derive(Serialize)]
+pub struct LspDiagnostic {
+    range: LspRange,
+    severity: u8,
+    message: String,
+    source: &'static str,
+}
 
 pub fn query_files_at_paths(
     language: Language,
     paths: Vec<&Path>,
     query_path: &Path,
     ordered_captures: bool,
+    print_stats: bool,
+    output_format: OutputFormat,
 ) -> Result<()> {
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
@@ -24,15 +68,26 @@ pub fn query_files_at_paths(
     let mut parser = Parser::new();
     parser.set_language(language).map_err(|e| e.to_string())?;
 
-    for path in paths {
-        writeln!(&mut stdout, "{}", path.to_str().unwrap())?;
+    let mut match_counts = vec![0; query.pattern_count()];
+    let mut visited_node_count = 0;
+    let mut duration = Duration::default();
 
+    for path in paths {
         let source_code = fs::read(path).map_err(Error::wrap(|| {
             format!("Error reading source file {:?}", path)
         }))?;
         let text_callback = |n: Node| &source_code[n.byte_range()];
         let tree = parser.parse(&source_code, None).unwrap();
 
+        if output_format == OutputFormat::Lsp {
+            let diagnostics = lsp_diagnostics(&query, &mut query_cursor, &tree, &source_code);
+            writeln!(&mut stdout, "{}", serde_json::to_string(&diagnostics)?)?;
+            continue;
+        }
+
+        writeln!(&mut stdout, "{}", path.to_str().unwrap())?;
+
+        let start_time = Instant::now();
         if ordered_captures {
             for (mat, capture_index) in
                 query_cursor.captures(&query, tree.root_node(), text_callback)
@@ -46,10 +101,14 @@ pub fn query_files_at_paths(
                     capture.node.start_position().row,
                     capture.node.utf8_text(&source_code).unwrap_or("")
                 )?;
+                if capture_index == 0 {
+                    match_counts[mat.pattern_index] += 1;
+                }
             }
         } else {
             for m in query_cursor.matches(&query, tree.root_node(), text_callback) {
                 writeln!(&mut stdout, "  pattern: {}", m.pattern_index)?;
+                match_counts[m.pattern_index] += 1;
                 for capture in m.captures {
                     let start = capture.node.start_position();
                     let end = capture.node.end_position();
@@ -73,7 +132,88 @@ pub fn query_files_at_paths(
                 }
             }
         }
+        duration += start_time.elapsed();
+        visited_node_count += query_cursor.visited_node_count();
+    }
+
+    if print_stats {
+        writeln!(&mut stdout, "stats:")?;
+        writeln!(
+            &mut stdout,
+            "  matches: {}",
+            match_counts.iter().sum::<usize>()
+        )?;
+        writeln!(&mut stdout, "  visited nodes: {}", visited_node_count)?;
+        writeln!(
+            &mut stdout,
+            "  time: {:.3} ms",
+            duration.as_secs_f64() * 1000.0
+        )?;
+        if match_counts.len() > 1 {
+            for (pattern_index, count) in match_counts.iter().enumerate() {
+                writeln!(
+                    &mut stdout,
+                    "  pattern: {}, matches: {}",
+                    pattern_index, count
+                )?;
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Create an LSP `Diagnostic` for each capture of a match of the query in the tree
+/// whose capture name corresponds to a diagnostic severity.
+pub fn lsp_diagnostics(
+    query: &Query,
+    query_cursor: &mut QueryCursor,
+    tree: &Tree,
+    source_code: &[u8],
+) -> Vec<LspDiagnostic> {
+    let severities = query
+        .capture_names()
+        .iter()
+        .map(|name| {
+            LSP_DIAGNOSTIC_SEVERITIES
+                .iter()
+                .find(|(capture_name, _)| capture_name == name)
+                .map(|(_, severity)| *severity)
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+    let text_callback = |n: Node| &source_code[n.byte_range()];
+    for m in query_cursor.matches(query, tree.root_node(), text_callback) {
+        for capture in m.captures {
+            if let Some(severity) = severities[capture.index as usize] {
+                let node = capture.node;
+                let message = query
+                    .property_settings(m.pattern_index)
+                    .iter()
+                    .find(|property| &*property.key == "message")
+                    .and_then(|property| property.value.as_ref())
+                    .map_or_else(|| node.kind().to_string(), |value| value.to_string());
+                result.push(LspDiagnostic {
+                    range: LspRange {
+                        start: lsp_position(source_code, node.start_byte(), node.start_position()),
+                        end: lsp_position(source_code, node.end_byte(), node.end_position()),
+                    },
+                    severity,
+                    message,
+                    source: "tree-sitter",
+                });
+            }
+        }
+    }
+    result
+}
+
+// LSP positions measure columns in UTF16 code units rather than in bytes.
+fn lsp_position(source_code: &[u8], byte: usize, point: Point) -> LspPosition {
+    let line_prefix = &source_code[(byte - point.column)..byte];
+    LspPosition {
+        line: point.row,
+        character: String::from_utf8_lossy(line_prefix).encode_utf16().count(),
+    }
+}
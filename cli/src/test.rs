@@ -1,11 +1,12 @@
 use super::error::{Error, Result};
-use super::util;
+use super::{parse, util};
 use ansi_term::Colour;
 use difference::{Changeset, Difference};
 use lazy_static::lazy_static;
 use regex::bytes::{Regex as ByteRegex, RegexBuilder as ByteRegexBuilder};
 use regex::Regex;
 use std::char;
+use std::env;
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
@@ -25,6 +26,62 @@ lazy_static! {
     static ref SEXP_FIELD_REGEX: Regex = Regex::new(r" \w+: \(").unwrap();
 }
 
+// The number of unchanged lines shown around each change in a unified diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+const BYTES_PER_MEGABYTE: usize = 1024 * 1024;
+
+// The terminal width assumed for side-by-side diffs when `COLUMNS` is not set.
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// The format in which the differences between a failing test's expected and
+/// actual S-expressions are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Print the S-expression on one line, highlighting the words that differ.
+    Default,
+    /// Print a unified diff, like `diff -u`, with one node per line.
+    Unified,
+    /// Print the expected and actual S-expressions in two columns that fit the width of
+    /// the terminal, with one node per line.
+    SideBySide,
+}
+
+impl Default for DiffFormat {
+    fn default() -> Self {
+        DiffFormat::Default
+    }
+}
+
+/// The options that control how the tests in a corpus are run and reported.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestOptions<'a> {
+    pub debug: bool,
+    pub debug_graph: bool,
+    /// Whether to print the full parse tree for each failing test.
+    pub show_tree: bool,
+    /// Only run the tests whose names contain this string.
+    pub filter: Option<&'a str>,
+    /// Whether to fail if the corpus contains no tests.
+    pub error_on_empty_corpus: bool,
+    /// Whether to fail tests whose parse trees contain ERROR or MISSING nodes, even
+    /// if their expected S-expressions contain them too.
+    pub no_recovery: bool,
+    pub diff_format: DiffFormat,
+    /// If set, a test fails when its parse raises the process's peak memory usage more
+    /// than this many bytes above the peak from before the tests started. Because the
+    /// peak never decreases, only the first test that crosses the limit is reported.
+    pub memory_limit: Option<usize>,
+}
+
+// One line of a line-by-line diff between an expected and an actual S-expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Expected(&'a str),
+    Actual(&'a str),
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum TestEntry {
     Group {
@@ -39,6 +96,30 @@ pub enum TestEntry {
     },
 }
 
+/// The outcome of running a single example from a test corpus.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CorpusTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub expected_sexp: String,
+    pub actual_sexp: String,
+}
+
+/// The outcome of parsing a single source string with [InlineTester::test_against_strings].
+#[derive(Debug, PartialEq, Eq)]
+pub struct TestResult {
+    pub source: String,
+    pub passed: bool,
+    pub expected_sexp: String,
+    pub actual_sexp: String,
+}
+
+/// A parser for a compiled grammar, for running small tests that are written directly
+/// in Rust code instead of in a test corpus.
+pub struct InlineTester {
+    parser: Parser,
+}
+
 impl Default for TestEntry {
     fn default() -> Self {
         TestEntry::Group {
@@ -48,21 +129,28 @@ impl Default for TestEntry {
     }
 }
 
-pub fn run_tests_at_path(
-    language: Language,
-    path: &Path,
-    debug: bool,
-    debug_graph: bool,
-    filter: Option<&str>,
-) -> Result<()> {
+impl TestEntry {
+    /// The number of examples in this entry, including those in nested groups.
+    pub fn example_count(&self) -> usize {
+        match self {
+            TestEntry::Group { children, .. } => children.iter().map(Self::example_count).sum(),
+            TestEntry::Example { .. } => 1,
+        }
+    }
+}
+
+pub fn run_tests_at_path(language: Language, path: &Path, options: &TestOptions) -> Result<()> {
     let test_entry = parse_tests(path)?;
+    if options.error_on_empty_corpus && test_entry.example_count() == 0 {
+        return Error::err(format!("No tests found in the corpus at {:?}", path));
+    }
     let mut _log_session = None;
     let mut parser = Parser::new();
     parser.set_language(language).map_err(|e| e.to_string())?;
 
-    if debug_graph {
+    if options.debug_graph {
         _log_session = Some(util::log_graphs(&mut parser, "log.html")?);
-    } else if debug {
+    } else if options.debug {
         parser.set_logger(Some(Box::new(|log_type, message| {
             if log_type == LogType::Lex {
                 io::stderr().write(b"  ").unwrap();
@@ -72,9 +160,46 @@ pub fn run_tests_at_path(
     }
 
     let mut failures = Vec::new();
+    let mut memory_failure = None;
+    let mut recovery_failures = Vec::new();
+    let run_options = TestOptions {
+        memory_limit: options
+            .memory_limit
+            .and_then(|limit| Some(util::peak_memory_usage()? + limit)),
+        ..*options
+    };
     if let TestEntry::Group { children, .. } = test_entry {
         for child in children {
-            run_tests(&mut parser, child, filter, 0, &mut failures)?;
+            run_tests(
+                &mut parser,
+                child,
+                &run_options,
+                0,
+                &mut failures,
+                &mut memory_failure,
+                &mut recovery_failures,
+            )?;
+        }
+    }
+
+    if let (Some(memory_limit), Some(name)) = (options.memory_limit, &memory_failure) {
+        println!(
+            "\n{}",
+            Colour::Red.paint(format!(
+                "Test '{}' exceeded the memory limit of {} MB",
+                name,
+                memory_limit / BYTES_PER_MEGABYTE
+            ))
+        );
+    }
+
+    if !recovery_failures.is_empty() {
+        println!();
+        for name in &recovery_failures {
+            println!(
+                "{}",
+                Colour::Red.paint(format!("Test '{}' contains ERROR or MISSING nodes", name))
+            );
         }
     }
 
@@ -88,16 +213,94 @@ pub fn run_tests_at_path(
         }
 
         print_diff_key();
-        for (i, (name, actual, expected)) in failures.iter().enumerate() {
+        for (i, (name, actual, expected, tree)) in failures.iter().enumerate() {
             println!("\n  {}. {}:", i + 1, name);
-            print_diff(actual, expected);
+            match options.diff_format {
+                DiffFormat::Default => print_diff(actual, expected),
+                DiffFormat::Unified => print_unified_diff(actual, expected),
+                DiffFormat::SideBySide => print_side_by_side_diff(actual, expected),
+            }
+            if let Some(tree) = tree {
+                println!("\n    parse tree:");
+                for line in tree.lines() {
+                    println!("    {}", line);
+                }
+            }
         }
         Error::err(String::new())
+    } else if memory_failure.is_some() || !recovery_failures.is_empty() {
+        Error::err(String::new())
     } else {
         Ok(())
     }
 }
 
+/// Run all of the corpus tests at the given path, returning the outcome of each
+/// example rather than printing a report.
+///
+/// Examples in nested groups are named by joining the group names and the example
+/// name with `" - "`.
+pub fn run_corpus_tests(parser: &mut Parser, corpus_path: &Path) -> Result<Vec<CorpusTestResult>> {
+    let mut results = Vec::new();
+    if let TestEntry::Group { children, .. } = parse_tests(corpus_path)? {
+        for child in children {
+            collect_corpus_test_results(parser, child, "", &mut results)?;
+        }
+    }
+    Ok(results)
+}
+
+impl InlineTester {
+    pub fn new(language: Language) -> Result<Self> {
+        let mut parser = Parser::new();
+        parser.set_language(language).map_err(|e| e.to_string())?;
+        Ok(InlineTester { parser })
+    }
+
+    /// Parse each `(source, expected_sexp)` pair and compare the resulting tree with
+    /// the expected S-expression in the same way as corpus tests: whitespace in the
+    /// expected S-expression is normalized, and fields are only compared if the
+    /// expected S-expression contains any.
+    pub fn test_against_strings(&mut self, examples: &[(&str, &str)]) -> Result<Vec<TestResult>> {
+        let parser = &mut self.parser;
+        examples
+            .iter()
+            .map(|(source, expected)| {
+                let expected = normalize_sexp(expected);
+                let has_fields = SEXP_FIELD_REGEX.is_match(&expected);
+                let actual = parse_to_sexp(parser, source.as_bytes(), has_fields)?;
+                Ok(TestResult {
+                    source: source.to_string(),
+                    passed: actual == expected,
+                    expected_sexp: expected,
+                    actual_sexp: actual,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse the input of each example in the test corpus at the given path, and count
+/// the nodes in the resulting syntax trees.
+pub fn add_corpus_to_coverage(
+    language: Language,
+    corpus_path: &Path,
+    coverage: &mut parse::GrammarCoverage,
+) -> Result<()> {
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|e| e.to_string())?;
+    let mut entries = vec![parse_tests(corpus_path)?];
+    while let Some(entry) = entries.pop() {
+        match entry {
+            TestEntry::Group { children, .. } => entries.extend(children),
+            TestEntry::Example { input, .. } => {
+                coverage.add_tree(&parser.parse(&input, None).unwrap());
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn check_queries_at_path(language: Language, path: &Path) -> Result<()> {
     if path.exists() {
         for entry in fs::read_dir(path)? {
@@ -143,12 +346,235 @@ pub fn print_diff(actual: &String, expected: &String) {
     println!("");
 }
 
+fn print_unified_diff(actual: &str, expected: &str) {
+    for line in unified_diff(actual, expected) {
+        if line.starts_with('-') {
+            println!("    {}", Colour::Green.paint(line));
+        } else if line.starts_with('+') {
+            println!("    {}", Colour::Red.paint(line));
+        } else {
+            println!("    {}", line);
+        }
+    }
+}
+
+fn print_side_by_side_diff(actual: &str, expected: &str) {
+    let width = env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+    // Leave room for the indentation and for the marker between the columns.
+    let column_width = width.saturating_sub(7) / 2;
+    println!("    {}   actual", fit_to_width("expected", column_width));
+    for (left, marker, right) in side_by_side_diff(actual, expected) {
+        let left = fit_to_width(&left, column_width);
+        let right = fit_to_width(&right, column_width).trim_end().to_string();
+        if marker == ' ' {
+            println!("    {} {} {}", left, marker, right);
+        } else {
+            println!(
+                "    {} {} {}",
+                Colour::Green.paint(left),
+                marker,
+                Colour::Red.paint(right)
+            );
+        }
+    }
+}
+
+// Produce the lines of a unified diff between the expected and the actual
+// S-expression, with a header and a `@@` line at the start of each hunk.
+fn unified_diff(actual: &str, expected: &str) -> Vec<String> {
+    let actual_lines = sexp_lines(actual);
+    let expected_lines = sexp_lines(expected);
+    let lines = diff_lines(&actual_lines, &expected_lines);
+    let is_change = |line: &DiffLine| !matches!(line, DiffLine::Same(_));
+
+    let mut result = vec!["--- expected".to_string(), "+++ actual".to_string()];
+    let mut expected_line_number = 0;
+    let mut actual_line_number = 0;
+    let mut index = 0;
+    while let Some(first_change) = lines[index..].iter().position(is_change) {
+        // Extend the hunk until the next change is too far away to share context with it.
+        let first_change = index + first_change;
+        let mut end = first_change;
+        loop {
+            while end < lines.len() && is_change(&lines[end]) {
+                end += 1;
+            }
+            match lines[end..].iter().position(is_change) {
+                Some(distance) if distance <= 2 * DIFF_CONTEXT_LINES => end += distance,
+                _ => break,
+            }
+        }
+        let start = first_change.saturating_sub(DIFF_CONTEXT_LINES).max(index);
+        let end = (end + DIFF_CONTEXT_LINES).min(lines.len());
+
+        // The lines between the previous hunk and this one are unchanged.
+        expected_line_number += start - index;
+        actual_line_number += start - index;
+        let hunk = &lines[start..end];
+        let expected_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Actual(_)))
+            .count();
+        let actual_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Expected(_)))
+            .count();
+        result.push(format!(
+            "@@ -{} +{} @@",
+            hunk_range(expected_line_number, expected_count),
+            hunk_range(actual_line_number, actual_count)
+        ));
+        for line in hunk {
+            match line {
+                DiffLine::Same(text) => result.push(format!(" {}", text)),
+                DiffLine::Expected(text) => result.push(format!("-{}", text)),
+                DiffLine::Actual(text) => result.push(format!("+{}", text)),
+            }
+        }
+        expected_line_number += expected_count;
+        actual_line_number += actual_count;
+        index = end;
+    }
+    result
+}
+
+// Format the line range of a hunk, given the number of lines that precede it.
+fn hunk_range(preceding_line_count: usize, line_count: usize) -> String {
+    if line_count == 0 {
+        format!("{},0", preceding_line_count)
+    } else {
+        format!("{},{}", preceding_line_count + 1, line_count)
+    }
+}
+
+// Pair up the lines of the expected and the actual S-expression, along with a
+// marker: ` ` for identical lines, `|` for changed lines, `<` for lines that only
+// appear in the expected S-expression, and `>` for lines that only appear in the
+// actual S-expression.
+fn side_by_side_diff(actual: &str, expected: &str) -> Vec<(String, char, String)> {
+    let actual_lines = sexp_lines(actual);
+    let expected_lines = sexp_lines(expected);
+    let mut result = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut lines = diff_lines(&actual_lines, &expected_lines).into_iter();
+    loop {
+        let line = lines.next();
+        if let Some(DiffLine::Expected(text)) = line {
+            removed.push(text);
+            continue;
+        }
+        if let Some(DiffLine::Actual(text)) = line {
+            added.push(text);
+            continue;
+        }
+
+        for i in 0..removed.len().max(added.len()) {
+            match (removed.get(i), added.get(i)) {
+                (Some(left), Some(right)) => {
+                    result.push((left.to_string(), '|', right.to_string()))
+                }
+                (Some(left), None) => result.push((left.to_string(), '<', String::new())),
+                (None, Some(right)) => result.push((String::new(), '>', right.to_string())),
+                (None, None) => {}
+            }
+        }
+        removed.clear();
+        added.clear();
+
+        match line {
+            Some(DiffLine::Same(text)) => result.push((text.to_string(), ' ', text.to_string())),
+            _ => break,
+        }
+    }
+    result
+}
+
+fn diff_lines<'a>(actual_lines: &'a [String], expected_lines: &'a [String]) -> Vec<DiffLine<'a>> {
+    let changeset = Changeset::new(&expected_lines.join("\n"), &actual_lines.join("\n"), "\n");
+    let mut expected_lines = expected_lines.iter();
+    let mut actual_lines = actual_lines.iter();
+    let mut result = Vec::new();
+    for diff in &changeset.diffs {
+        match diff {
+            Difference::Same(part) => {
+                for _ in part.split('\n') {
+                    actual_lines.next();
+                    result.push(DiffLine::Same(expected_lines.next().unwrap()));
+                }
+            }
+            Difference::Rem(part) => {
+                for _ in part.split('\n') {
+                    result.push(DiffLine::Expected(expected_lines.next().unwrap()));
+                }
+            }
+            Difference::Add(part) => {
+                for _ in part.split('\n') {
+                    result.push(DiffLine::Actual(actual_lines.next().unwrap()));
+                }
+            }
+        }
+    }
+    result
+}
+
+// Split a normalized S-expression into lines, with one node per line, indented
+// according to its depth. Field names stay on the same line as their node.
+fn sexp_lines(sexp: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut field_name = None;
+    for token in sexp.split_whitespace() {
+        if token.ends_with(':') {
+            field_name = Some(token);
+            continue;
+        }
+        if token.starts_with('(') || result.is_empty() {
+            let mut line = "  ".repeat(depth);
+            if let Some(field_name) = field_name.take() {
+                line += field_name;
+                line.push(' ');
+            }
+            line += token;
+            result.push(line);
+        } else {
+            let line = result.last_mut().unwrap();
+            line.push(' ');
+            line.push_str(token);
+        }
+        depth += token.matches('(').count();
+        depth = depth.saturating_sub(token.matches(')').count());
+    }
+    result
+}
+
+fn fit_to_width(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length > width {
+        let mut result = text
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        if width > 0 {
+            result.push('…');
+        }
+        result
+    } else {
+        format!("{:width$}", text, width = width)
+    }
+}
+
 fn run_tests(
     parser: &mut Parser,
     test_entry: TestEntry,
-    filter: Option<&str>,
+    options: &TestOptions,
     mut indent_level: i32,
-    failures: &mut Vec<(String, String, String)>,
+    failures: &mut Vec<(String, String, String, Option<String>)>,
+    memory_failure: &mut Option<String>,
+    recovery_failures: &mut Vec<String>,
 ) -> Result<()> {
     match test_entry {
         TestEntry::Example {
@@ -157,12 +583,19 @@ fn run_tests(
             output,
             has_fields,
         } => {
-            if let Some(filter) = filter {
+            if let Some(filter) = options.filter {
                 if !name.contains(filter) {
                     return Ok(());
                 }
             }
+            let peak_memory_before = util::peak_memory_usage();
             let tree = parser.parse(&input, None).unwrap();
+            let exceeded_memory_limit = match (options.memory_limit, peak_memory_before) {
+                (Some(limit), Some(before)) if before <= limit => {
+                    matches!(util::peak_memory_usage(), Some(after) if after > limit)
+                }
+                _ => false,
+            };
             let mut actual = tree.root_node().to_sexp();
             if !has_fields {
                 actual = strip_sexp_fields(actual);
@@ -170,11 +603,24 @@ fn run_tests(
             for _ in 0..indent_level {
                 print!("  ");
             }
-            if actual == output {
+            if exceeded_memory_limit {
+                println!("✗ {}", Colour::Red.paint(&name));
+                *memory_failure = Some(name);
+            } else if actual == output && options.no_recovery && tree.root_node().has_error() {
+                println!("✗ {}", Colour::Red.paint(&name));
+                recovery_failures.push(name);
+            } else if actual == output {
                 println!("✓ {}", Colour::Green.paint(&name));
             } else {
                 println!("✗ {}", Colour::Red.paint(&name));
-                failures.push((name, actual, output));
+                let tree_text = if options.show_tree {
+                    let mut tree_text = Vec::new();
+                    parse::write_tree(&mut tree_text, &tree, true, true, false, None, None)?;
+                    Some(String::from_utf8_lossy(&tree_text).to_string())
+                } else {
+                    None
+                };
+                failures.push((name, actual, output, tree_text));
             }
         }
         TestEntry::Group { name, children } => {
@@ -184,7 +630,55 @@ fn run_tests(
             println!("{}:", name);
             indent_level += 1;
             for child in children {
-                run_tests(parser, child, filter, indent_level, failures)?;
+                run_tests(
+                    parser,
+                    child,
+                    options,
+                    indent_level,
+                    failures,
+                    memory_failure,
+                    recovery_failures,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn collect_corpus_test_results(
+    parser: &mut Parser,
+    test_entry: TestEntry,
+    prefix: &str,
+    results: &mut Vec<CorpusTestResult>,
+) -> Result<()> {
+    match test_entry {
+        TestEntry::Example {
+            mut name,
+            input,
+            output,
+            has_fields,
+        } => {
+            if !prefix.is_empty() {
+                name.insert_str(0, " - ");
+                name.insert_str(0, prefix);
+            }
+            let actual = parse_to_sexp(parser, &input, has_fields).map_err(Error::wrap(|| {
+                format!("Failed to run corpus test {:?}", name)
+            }))?;
+            results.push(CorpusTestResult {
+                name,
+                passed: actual == output,
+                expected_sexp: output,
+                actual_sexp: actual,
+            });
+        }
+        TestEntry::Group { mut name, children } => {
+            if !prefix.is_empty() {
+                name.insert_str(0, " - ");
+                name.insert_str(0, prefix);
+            }
+            for child in children {
+                collect_corpus_test_results(parser, child, &name, results)?;
             }
         }
     }
@@ -217,6 +711,24 @@ pub fn strip_sexp_fields(sexp: String) -> String {
     SEXP_FIELD_REGEX.replace_all(&sexp, " (").to_string()
 }
 
+fn parse_to_sexp(parser: &mut Parser, input: &[u8], has_fields: bool) -> Result<String> {
+    let tree = parser
+        .parse(input, None)
+        .ok_or_else(|| Error::new("Parsing timed out or was cancelled".to_string()))?;
+    let sexp = tree.root_node().to_sexp();
+    Ok(if has_fields {
+        sexp
+    } else {
+        strip_sexp_fields(sexp)
+    })
+}
+
+fn normalize_sexp(sexp: &str) -> String {
+    WHITESPACE_REGEX
+        .replace_all(sexp.trim(), " ")
+        .replace(" )", ")")
+}
+
 fn parse_test_content(name: String, content: String) -> TestEntry {
     let mut children = Vec::new();
     let bytes = content.as_bytes();
@@ -247,8 +759,7 @@ fn parse_test_content(name: String, content: String) -> TestEntry {
                     }
 
                     // Normalize the whitespace in the expected output.
-                    let output = WHITESPACE_REGEX.replace_all(output.trim(), " ").to_string();
-                    let output = output.replace(" )", ")");
+                    let output = normalize_sexp(output);
 
                     // Identify if the expected output has fields indicated. If not, then
                     // fields will not be checked.
@@ -324,6 +835,30 @@ d
         );
     }
 
+    #[test]
+    fn test_example_count() {
+        let entry = TestEntry::Group {
+            name: "corpus".to_string(),
+            children: vec![
+                parse_test_content(
+                    "the-filename".to_string(),
+                    "===\nThe first test\n===\na\n---\n(a)\n===\nThe second test\n===\nb\n---\n(b)"
+                        .to_string(),
+                ),
+                TestEntry::Group {
+                    name: "empty".to_string(),
+                    children: Vec::new(),
+                },
+            ],
+        };
+        assert_eq!(entry.example_count(), 2);
+        assert_eq!(TestEntry::default().example_count(), 0);
+        assert_eq!(
+            parse_test_content("blank".to_string(), "\n".to_string()).example_count(),
+            0
+        );
+    }
+
     #[test]
     fn test_parse_test_content_with_dashes_in_source_code() {
         let entry = parse_test_content(
@@ -375,4 +910,67 @@ abc
             }
         );
     }
+
+    #[test]
+    fn test_sexp_lines() {
+        assert_eq!(
+            sexp_lines(
+                "(program (call function: (identifier) arguments: (arguments (MISSING \")\"))))"
+            ),
+            vec![
+                "(program",
+                "  (call",
+                "    function: (identifier)",
+                "    arguments: (arguments",
+                "      (MISSING \")\"))))",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let expected = "(a (b) (c) (d) (e) (f) (g) (h) (i) (j) (k) (l))";
+        let actual = "(a (b) (x) (d) (e) (f) (g) (h) (i) (j) (k))";
+        assert_eq!(
+            unified_diff(actual, expected),
+            vec![
+                "--- expected",
+                "+++ actual",
+                "@@ -1,6 +1,6 @@",
+                " (a",
+                "   (b)",
+                "-  (c)",
+                "+  (x)",
+                "   (d)",
+                "   (e)",
+                "   (f)",
+                "@@ -8,5 +8,4 @@",
+                "   (h)",
+                "   (i)",
+                "   (j)",
+                "-  (k)",
+                "-  (l))",
+                "+  (k))",
+            ]
+        );
+        assert_eq!(
+            unified_diff(expected, expected),
+            vec!["--- expected", "+++ actual"]
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_diff() {
+        assert_eq!(
+            side_by_side_diff("(a (b) (x) (y))", "(a (b) (c))"),
+            vec![
+                ("(a".to_string(), ' ', "(a".to_string()),
+                ("  (b)".to_string(), ' ', "  (b)".to_string()),
+                ("  (c))".to_string(), '|', "  (x)".to_string()),
+                ("".to_string(), '>', "  (y))".to_string()),
+            ]
+        );
+        assert_eq!(fit_to_width("abc", 5), "abc  ");
+        assert_eq!(fit_to_width("abcdef", 5), "abcd…");
+    }
 }
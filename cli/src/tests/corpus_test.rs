@@ -4,8 +4,12 @@ use super::helpers::fixtures::{fixtures_dir, get_language, get_test_language};
 use super::helpers::random::Rand;
 use super::helpers::scope_sequence::ScopeSequence;
 use crate::generate;
-use crate::parse::perform_edit;
-use crate::test::{parse_tests, print_diff, print_diff_key, strip_sexp_fields, TestEntry};
+use crate::parse::{perform_edit, GrammarCoverage};
+use crate::test::{
+    add_corpus_to_coverage, parse_tests, print_diff, print_diff_key, run_corpus_tests,
+    run_tests_at_path, strip_sexp_fields, CorpusTestResult, InlineTester, TestEntry, TestOptions,
+    TestResult,
+};
 use crate::util;
 use lazy_static::lazy_static;
 use std::{env, fs, time, usize};
@@ -289,6 +293,173 @@ fn test_feature_corpus_files() {
     }
 }
 
+#[test]
+fn test_corpus_test_results() {
+    let test_path = fixtures_dir()
+        .join("test_grammars")
+        .join("associativity_left");
+    let grammar_json = fs::read_to_string(test_path.join("grammar.json")).unwrap();
+    let c_code = generate::generate_parser_for_grammar(&grammar_json)
+        .unwrap()
+        .1;
+    let language = get_test_language("associativity_left", &c_code, Some(&test_path));
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+
+    let corpus_dir = tempfile::tempdir().unwrap();
+    fs::copy(
+        test_path.join("corpus.txt"),
+        corpus_dir.path().join("passing.txt"),
+    )
+    .unwrap();
+    fs::write(
+        corpus_dir.path().join("failing.txt"),
+        "===\nsingle identifier\n===\nx\n---\n(expression (math_operation))\n",
+    )
+    .unwrap();
+
+    let chained_sexp = "(expression (math_operation \
+        (expression (math_operation (expression (identifier)) (expression (identifier)))) \
+        (expression (identifier))))";
+    let mut results = run_corpus_tests(&mut parser, corpus_dir.path()).unwrap();
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        results,
+        vec![
+            CorpusTestResult {
+                name: "failing - single identifier".to_string(),
+                passed: false,
+                expected_sexp: "(expression (math_operation))".to_string(),
+                actual_sexp: "(expression (identifier))".to_string(),
+            },
+            CorpusTestResult {
+                name: "passing - chained operations".to_string(),
+                passed: true,
+                expected_sexp: chained_sexp.to_string(),
+                actual_sexp: chained_sexp.to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_run_tests_with_no_recovery() {
+    let test_path = fixtures_dir()
+        .join("test_grammars")
+        .join("associativity_left");
+    let grammar_json = fs::read_to_string(test_path.join("grammar.json")).unwrap();
+    let c_code = generate::generate_parser_for_grammar(&grammar_json)
+        .unwrap()
+        .1;
+    let language = get_test_language("associativity_left", &c_code, Some(&test_path));
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+
+    // A test whose expected output matches a parse that recovered from an error.
+    let tree = parser.parse("x + + y", None).unwrap();
+    assert!(tree.root_node().has_error());
+    let corpus_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        corpus_dir.path().join("recovered.txt"),
+        format!(
+            "===\nrecovered error\n===\nx + + y\n---\n{}\n",
+            strip_sexp_fields(tree.root_node().to_sexp())
+        ),
+    )
+    .unwrap();
+
+    let options = TestOptions::default();
+    assert!(run_tests_at_path(language, corpus_dir.path(), &options).is_ok());
+    let options = TestOptions {
+        no_recovery: true,
+        ..TestOptions::default()
+    };
+    assert!(run_tests_at_path(language, corpus_dir.path(), &options).is_err());
+}
+
+#[test]
+fn test_corpus_coverage() {
+    let test_path = fixtures_dir()
+        .join("test_grammars")
+        .join("associativity_left");
+    let grammar_json = fs::read_to_string(test_path.join("grammar.json")).unwrap();
+    let c_code = generate::generate_parser_for_grammar(&grammar_json)
+        .unwrap()
+        .1;
+    let language = get_test_language("associativity_left", &c_code, Some(&test_path));
+    let rule_names = ["expression", "math_operation", "identifier"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    let corpus_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        corpus_dir.path().join("identifiers.txt"),
+        "===\nsingle identifier\n===\nx\n---\n(expression (identifier))\n",
+    )
+    .unwrap();
+    let mut coverage = GrammarCoverage::new(rule_names.clone());
+    add_corpus_to_coverage(language, corpus_dir.path(), &mut coverage).unwrap();
+    assert_eq!(coverage.unused_rule_names(), &["math_operation"]);
+    assert_eq!(format!("{:.2}", coverage.percentage()), "66.67");
+
+    fs::copy(
+        test_path.join("corpus.txt"),
+        corpus_dir.path().join("operations.txt"),
+    )
+    .unwrap();
+    let mut coverage = GrammarCoverage::new(rule_names);
+    add_corpus_to_coverage(language, corpus_dir.path(), &mut coverage).unwrap();
+    assert!(coverage.unused_rule_names().is_empty());
+    assert_eq!(coverage.percentage(), 100.0);
+}
+
+#[test]
+fn test_grammar_test_against_strings() {
+    let test_path = fixtures_dir()
+        .join("test_grammars")
+        .join("associativity_left");
+    let grammar_json = fs::read_to_string(test_path.join("grammar.json")).unwrap();
+    let c_code = generate::generate_parser_for_grammar(&grammar_json)
+        .unwrap()
+        .1;
+    let language = get_test_language("associativity_left", &c_code, Some(&test_path));
+    let mut tester = InlineTester::new(language).unwrap();
+    let sum_sexp =
+        "(expression (math_operation (expression (identifier)) (expression (identifier))))";
+
+    let results = tester
+        .test_against_strings(&[
+            (
+                "a+b",
+                "
+            (expression (math_operation
+              (expression (identifier))
+              (expression (identifier))))
+            ",
+            ),
+            ("x", "(expression (math_operation))"),
+        ])
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            TestResult {
+                source: "a+b".to_string(),
+                passed: true,
+                expected_sexp: sum_sexp.to_string(),
+                actual_sexp: sum_sexp.to_string(),
+            },
+            TestResult {
+                source: "x".to_string(),
+                passed: false,
+                expected_sexp: "(expression (math_operation))".to_string(),
+                actual_sexp: "(expression (identifier))".to_string(),
+            },
+        ]
+    );
+}
+
 fn check_consistent_sizes(tree: &Tree, input: &Vec<u8>) {
     fn check(node: Node, line_offsets: &Vec<usize>) {
         let start_byte = node.start_byte();
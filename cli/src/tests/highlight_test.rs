@@ -1,8 +1,13 @@
-use super::helpers::fixtures::{get_highlight_config, get_language, get_language_queries_path};
+use super::helpers::fixtures::{
+    get_highlight_config, get_language, get_language_queries_path, get_test_language,
+};
+use crate::generate::generate_parser_for_grammar;
+use crate::highlight::unresolved_injection_languages;
+use crate::loader::Loader;
 use lazy_static::lazy_static;
 use std::ffi::CString;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::{fs, ptr, slice, str};
+use std::{fs, ops, ptr, slice, str};
 use tree_sitter_highlight::{
     c, Error, Highlight, HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer,
 };
@@ -292,6 +297,68 @@ fn test_highlighting_with_local_variable_tracking() {
     );
 }
 
+#[test]
+fn test_highlighting_with_local_variable_predicates() {
+    let (language_name, language_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_local_variable_predicates",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "definition"},
+                            {"type": "SYMBOL", "name": "word"}
+                        ]
+                    }
+                },
+                "definition": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "let"},
+                        {"type": "SYMBOL", "name": "word"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&language_name, &language_code, None);
+
+    let source = "let a b a";
+    let highlights = |query: &str| {
+        let mut config = HighlightConfiguration::new(
+            language,
+            query,
+            "",
+            "(definition (word) @local.definition) (word) @local.reference",
+        )
+        .unwrap();
+        config.configure(&HIGHLIGHT_NAMES);
+        to_token_vector(source, &config).unwrap()[0]
+            .iter()
+            .filter(|(text, _)| *text != " ")
+            .map(|(_, highlights)| highlights.first().cloned())
+            .collect::<Vec<_>>()
+    };
+
+    // The definition of `a` is excluded from the first pattern. References to `a`
+    // take the highlight of its definition.
+    assert_eq!(
+        highlights("((word) @function (#is-not? local.definition)) (word) @variable"),
+        &[None, Some("variable"), Some("function"), Some("variable")]
+    );
+
+    // Only the definition of `a` matches the first pattern.
+    assert_eq!(
+        highlights("((word) @constant (#is? local.definition)) (word) @function"),
+        &[None, Some("constant"), Some("function"), Some("constant")]
+    );
+}
+
 #[test]
 fn test_highlighting_empty_lines() {
     let source = vec![
@@ -479,6 +546,165 @@ fn test_highlighting_cancellation() {
     panic!("Expected an error while iterating highlighter");
 }
 
+#[test]
+fn test_highlighting_with_forced_injections() {
+    let (words_name, words_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_highlight_words",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                "word": {"type": "PATTERN", "value": "[a-z0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let (numbers_name, numbers_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_highlight_numbers",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "number"}},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut words_config = HighlightConfiguration::new(
+        get_test_language(&words_name, &words_code, None),
+        "(word) @variable",
+        "((word) @injection.content (#match? @injection.content \"^[0-9]+$\") (#set! injection.language \"numbers\"))",
+        "",
+    )
+    .unwrap();
+    words_config.configure(&HIGHLIGHT_NAMES);
+    let mut numbers_config = HighlightConfiguration::new(
+        get_test_language(&numbers_name, &numbers_code, None),
+        "(number) @constant",
+        "",
+        "",
+    )
+    .unwrap();
+    numbers_config.configure(&HIGHLIGHT_NAMES);
+    let mut numbers_as_strings_config = HighlightConfiguration::new(
+        get_test_language(&numbers_name, &numbers_code, None),
+        "(number) @string",
+        "",
+        "",
+    )
+    .unwrap();
+    numbers_as_strings_config.configure(&HIGHLIGHT_NAMES);
+    let injection_callback = |name: &str| match name {
+        "numbers" => Some(&numbers_as_strings_config),
+        _ => None,
+    };
+
+    // The words language's own injections are ignored within the forced range.
+    let source = "12 34 56 78";
+    assert_eq!(
+        &to_token_vector_with_injections(
+            source,
+            &words_config,
+            &[(3..8, &numbers_config)],
+            injection_callback
+        )
+        .unwrap(),
+        &[vec![
+            ("12", vec!["string"]),
+            (" ", vec![]),
+            ("34", vec!["constant"]),
+            (" ", vec![]),
+            ("56", vec!["constant"]),
+            (" ", vec![]),
+            ("78", vec!["string"]),
+        ]]
+    );
+
+    assert_eq!(
+        to_token_vector_with_injections(
+            source,
+            &words_config,
+            &[(3..100, &numbers_config)],
+            injection_callback
+        )
+        .unwrap_err(),
+        Error::InvalidRange
+    );
+}
+
+#[test]
+fn test_highlighting_with_highlight_names_set_on_highlighter() {
+    let (language_name, language_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_highlight_names",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut config = HighlightConfiguration::new(
+        get_test_language(&language_name, &language_code, None),
+        "(word) @variable.parameter (number) @constant.numeric",
+        "",
+        "",
+    )
+    .unwrap();
+    config.configure(&HIGHLIGHT_NAMES);
+
+    let source = "a 1";
+    let mut highlighter = Highlighter::new();
+    let highlights = |highlighter: &mut Highlighter| {
+        highlighter
+            .highlight(&config, source.as_bytes(), None, |_| None)
+            .unwrap()
+            .filter_map(|event| match event.unwrap() {
+                HighlightEvent::HighlightStart(highlight) => Some(highlight),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // By default, the names passed to the configuration are used.
+    let variable_parameter_index = HIGHLIGHT_NAMES
+        .iter()
+        .position(|name| name == "variable.parameter")
+        .unwrap();
+    let constant_index = HIGHLIGHT_NAMES
+        .iter()
+        .position(|name| name == "constant")
+        .unwrap();
+    assert_eq!(
+        highlights(&mut highlighter),
+        &[
+            Highlight(variable_parameter_index),
+            Highlight(constant_index)
+        ]
+    );
+
+    // Names set on the highlighter replace those of the configuration.
+    highlighter.set_highlight_names(&["constant.numeric", "variable"]);
+    assert_eq!(highlights(&mut highlighter), &[Highlight(1), Highlight(0)]);
+
+    // Captures that match none of the names are not highlighted.
+    highlighter.set_highlight_names(&["variable"]);
+    assert_eq!(highlights(&mut highlighter), &[Highlight(0)]);
+}
+
 #[test]
 fn test_highlighting_via_c_api() {
     let highlights = vec![
@@ -586,6 +812,51 @@ fn test_highlighting_via_c_api() {
     c::ts_highlight_buffer_delete(buffer);
 }
 
+#[test]
+fn test_unresolved_injection_languages() {
+    let (language_name, language_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_unresolved_injection_languages",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "block"}},
+                "block": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "name"},
+                        {"type": "STRING", "value": "{"},
+                        {"type": "SYMBOL", "name": "code"},
+                        {"type": "STRING", "value": "}"}
+                    ]
+                },
+                "name": {"type": "PATTERN", "value": "[a-z]+"},
+                "code": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&language_name, &language_code, None);
+    let config = HighlightConfiguration::new(
+        language,
+        "(name) @keyword",
+        "(block (name) @injection.language (code) @injection.content)",
+        "",
+    )
+    .unwrap();
+
+    // The loader doesn't know about any languages, so each distinct language name
+    // is reported once, in the order that it first appears.
+    let scratch_dir = tempfile::tempdir().unwrap();
+    let loader = Loader::new(scratch_dir.path().to_owned());
+    assert_eq!(
+        unresolved_injection_languages(&loader, b"js{1} py{2} js{3}", &config).unwrap(),
+        &["js", "py"]
+    );
+    assert!(unresolved_injection_languages(&loader, b"", &config)
+        .unwrap()
+        .is_empty());
+}
+
 #[test]
 fn test_decode_utf8_lossy() {
     use tree_sitter_highlight::util::LossyUtf8;
@@ -646,17 +917,32 @@ fn to_html<'a>(
 fn to_token_vector<'a>(
     src: &'a str,
     language_config: &'a HighlightConfiguration,
+) -> Result<Vec<Vec<(&'a str, Vec<&'static str>)>>, Error> {
+    to_token_vector_with_injections(
+        src,
+        language_config,
+        &[],
+        &test_language_for_injection_string,
+    )
+}
+
+fn to_token_vector_with_injections<'a>(
+    src: &'a str,
+    language_config: &'a HighlightConfiguration,
+    injections: &[(ops::Range<usize>, &'a HighlightConfiguration)],
+    injection_callback: impl Fn(&str) -> Option<&'a HighlightConfiguration> + 'a,
 ) -> Result<Vec<Vec<(&'a str, Vec<&'static str>)>>, Error> {
     let src = src.as_bytes();
     let mut highlighter = Highlighter::new();
     let mut lines = Vec::new();
     let mut highlights = Vec::new();
     let mut line = Vec::new();
-    let events = highlighter.highlight(
+    let events = highlighter.highlight_with_injections(
         language_config,
         src,
         None,
-        &test_language_for_injection_string,
+        injections,
+        move |string| injection_callback(string),
     )?;
     for event in events {
         match event? {
@@ -0,0 +1,1024 @@
+use super::helpers::fixtures::{get_test_language, test_loader};
+use crate::generate::{
+    check_node_types_in_directory, generate_parser_for_grammar, generate_parser_in_directory,
+    CompileOptions,
+};
+use crate::loader::IncompatibleAbi;
+use std::fs;
+use tree_sitter::{ParseAction, Parser, SymbolMetadata, TSLanguage};
+
+#[test]
+fn test_language_name() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_name",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.name(), Some("test_language_with_name"));
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("a", None).unwrap();
+    assert_eq!(tree.language().name(), Some("test_language_with_name"));
+}
+
+#[test]
+fn test_language_grammar_version() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_grammar_version",
+            "version": "1.2.3-beta.1",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.grammar_version(), Some("1.2.3-beta.1"));
+
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_without_grammar_version",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.grammar_version(), None);
+
+    let error = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_invalid_grammar_version",
+            "version": "one",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error.message().starts_with("Invalid grammar version 'one'"));
+}
+
+#[test]
+fn test_language_parse_state_count() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_parse_state_count",
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "a"},
+                        {"type": "STRING", "value": "b"}
+                    ]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.parse_state_count(), 5);
+    assert!(parser_code.contains(&format!(
+        "#define STATE_COUNT {}\n",
+        language.parse_state_count()
+    )));
+}
+
+#[test]
+fn test_language_fields_for_node_type() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_fields_for_node_type",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "call"}
+                },
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "function",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "REPEAT",
+                            "content": {
+                                "type": "FIELD",
+                                "name": "argument",
+                                "content": {"type": "SYMBOL", "name": "word"}
+                            }
+                        },
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let call = language
+        .node_kind_id_for_type_checked("call", true)
+        .unwrap();
+    let argument = language.field_id_for_name("argument").unwrap();
+    let function = language.field_id_for_name("function").unwrap();
+    assert_eq!(
+        language.fields_for_node_type(call),
+        &[(argument, true), (function, false)]
+    );
+
+    let program = language
+        .node_kind_id_for_type_checked("program", true)
+        .unwrap();
+    let word = language
+        .node_kind_id_for_type_checked("word", true)
+        .unwrap();
+    assert!(language.fields_for_node_type(program).is_empty());
+    assert!(language.fields_for_node_type(word).is_empty());
+    assert!(language.fields_for_node_type(u16::MAX).is_empty());
+}
+
+#[test]
+fn test_language_field_for_child_at_index() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_field_for_child_at_index",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "call"},
+                            {"type": "SYMBOL", "name": "pair"},
+                            {"type": "SYMBOL", "name": "tagged"},
+                            {"type": "SYMBOL", "name": "group"}
+                        ]
+                    }
+                },
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "function",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "CHOICE",
+                            "members": [
+                                {
+                                    "type": "FIELD",
+                                    "name": "argument",
+                                    "content": {"type": "SYMBOL", "name": "word"}
+                                },
+                                {"type": "BLANK"}
+                            ]
+                        },
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "pair": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "{"},
+                        {
+                            "type": "CHOICE",
+                            "members": [
+                                {
+                                    "type": "FIELD",
+                                    "name": "key",
+                                    "content": {"type": "SYMBOL", "name": "word"}
+                                },
+                                {
+                                    "type": "FIELD",
+                                    "name": "value",
+                                    "content": {"type": "SYMBOL", "name": "number"}
+                                }
+                            ]
+                        },
+                        {"type": "STRING", "value": "}"}
+                    ]
+                },
+                "tagged": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "tag",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "SYMBOL", "name": "_colon"},
+                        {
+                            "type": "FIELD",
+                            "name": "value",
+                            "content": {"type": "SYMBOL", "name": "number"}
+                        }
+                    ]
+                },
+                "group": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "open",
+                            "content": {"type": "STRING", "value": "<"}
+                        },
+                        {"type": "SYMBOL", "name": "_words"},
+                        {
+                            "type": "FIELD",
+                            "name": "close",
+                            "content": {"type": "STRING", "value": ">"}
+                        }
+                    ]
+                },
+                "_words": {
+                    "type": "REPEAT1",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "_colon": {"type": "STRING", "value": ":"},
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "\\d+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let call = language
+        .node_kind_id_for_type_checked("call", true)
+        .unwrap();
+    let function = language.field_id_for_name("function").unwrap();
+    let argument = language.field_id_for_name("argument").unwrap();
+    assert_eq!(language.field_for_child_at_index(call, 0), Some(function));
+    assert_eq!(language.field_for_child_at_index(call, 1), None);
+    assert_eq!(language.field_for_child_at_index(call, 2), Some(argument));
+    assert_eq!(language.field_for_child_at_index(call, 3), None);
+
+    // The two productions of `pair` assign different fields to the same child.
+    let pair = language
+        .node_kind_id_for_type_checked("pair", true)
+        .unwrap();
+    assert_eq!(language.field_for_child_at_index(pair, 1), None);
+
+    let program = language
+        .node_kind_id_for_type_checked("program", true)
+        .unwrap();
+    assert_eq!(language.field_for_child_at_index(program, 0), None);
+
+    // Hidden tokens are not counted, like in `Node::child`.
+    let tagged = language
+        .node_kind_id_for_type_checked("tagged", true)
+        .unwrap();
+    let value = language.field_id_for_name("value").unwrap();
+    assert_eq!(language.field_for_child_at_index(tagged, 1), Some(value));
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("a:1", None).unwrap();
+    let tagged_node = tree.root_node().child(0).unwrap();
+    assert_eq!(tagged_node.kind(), "tagged");
+    assert_eq!(
+        tagged_node.child_by_field_name("value"),
+        tagged_node.child(1)
+    );
+
+    // The positions after a hidden non-terminal depend on how many children it has.
+    let group = language
+        .node_kind_id_for_type_checked("group", true)
+        .unwrap();
+    let open = language.field_id_for_name("open").unwrap();
+    assert_eq!(language.field_for_child_at_index(group, 0), Some(open));
+    assert_eq!(language.field_for_child_at_index(group, 1), None);
+    assert_eq!(language.field_for_child_at_index(group, 2), None);
+}
+
+#[test]
+fn test_language_checked_ids() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_checked_ids",
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "operator",
+                            "content": {"type": "STRING", "value": "+"}
+                        },
+                        {"type": "SYMBOL", "name": "word"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("+a", None).unwrap();
+    let program_node = tree.root_node();
+    let operator_node = program_node.child(0).unwrap();
+    let word_node = program_node.child(1).unwrap();
+
+    assert_eq!(
+        language.node_kind_id_for_type_checked("program", true),
+        Some(program_node.kind_id())
+    );
+    assert_eq!(
+        language.node_kind_id_for_type_checked("word", true),
+        Some(word_node.kind_id())
+    );
+    assert_eq!(
+        language.node_kind_id_for_type_checked("+", false),
+        Some(operator_node.kind_id())
+    );
+    assert_eq!(language.node_kind_id_for_type_checked("+", true), None);
+    assert_eq!(language.node_kind_id_for_type_checked("word", false), None);
+    assert_eq!(
+        language.node_kind_id_for_type_checked("missing", true),
+        None
+    );
+
+    let operator_field_id = language.field_id_for_name("operator").unwrap();
+    assert_eq!(
+        language.field_name_for_id(operator_field_id),
+        Some("operator")
+    );
+    assert_eq!(language.field_id_for_name("missing"), None);
+}
+
+#[test]
+fn test_language_symbol_metadata() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_symbol_metadata",
+            "supertypes": ["_expression"],
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "_prefix"},
+                        {"type": "SYMBOL", "name": "_expression"}
+                    ]
+                },
+                "_prefix": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "-"},
+                        {"type": "STRING", "value": ">"}
+                    ]
+                },
+                "_expression": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "SYMBOL", "name": "number"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let metadata_for_kind = |kind: &str| {
+        let id = (0..language.node_kind_count() as u16)
+            .find(|id| language.node_kind_for_id(*id) == Some(kind))
+            .unwrap();
+        language.symbol_metadata(id)
+    };
+
+    assert_eq!(
+        metadata_for_kind("program"),
+        SymbolMetadata {
+            visible: true,
+            named: true,
+            supertype: false
+        }
+    );
+    assert_eq!(
+        metadata_for_kind("-"),
+        SymbolMetadata {
+            visible: true,
+            named: false,
+            supertype: false
+        }
+    );
+    assert_eq!(
+        metadata_for_kind("_expression"),
+        SymbolMetadata {
+            visible: false,
+            named: true,
+            supertype: true
+        }
+    );
+    assert_eq!(
+        metadata_for_kind("_prefix"),
+        SymbolMetadata {
+            visible: false,
+            named: true,
+            supertype: false
+        }
+    );
+
+    // The named kinds are the end token, the three rules, and the two patterns.
+    assert_eq!(language.symbol_count_named(), 6);
+    assert_eq!(language.symbol_count_anonymous(), 2);
+    assert_eq!(language.node_kind_count(), 8);
+}
+
+#[test]
+fn test_language_parse_table() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_parse_table",
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "a"},
+                        {"type": "STRING", "value": "b"}
+                    ]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let a = language.node_kind_id_for_type_checked("a", false).unwrap();
+    let b = language.node_kind_id_for_type_checked("b", false).unwrap();
+    let program = language
+        .node_kind_id_for_type_checked("program", true)
+        .unwrap();
+
+    // State 1 is the parser's start state.
+    let after_a = match language.parse_table(1, a) {
+        ParseAction::Shift(state) => state,
+        action => panic!("Unexpected action {:?}", action),
+    };
+    assert_eq!(language.parse_table(1, b), ParseAction::Error);
+    let after_b = match language.parse_table(after_a, b) {
+        ParseAction::Shift(state) => state,
+        action => panic!("Unexpected action {:?}", action),
+    };
+    assert_eq!(
+        language.parse_table(after_b, 0),
+        ParseAction::Reduce {
+            symbol: program,
+            count: 2
+        }
+    );
+    let after_program = match language.parse_table(1, program) {
+        ParseAction::Shift(state) => state,
+        action => panic!("Unexpected action {:?}", action),
+    };
+    assert_eq!(language.parse_table(after_program, 0), ParseAction::Accept);
+
+    // Out-of-range states and symbols have no actions.
+    let state_count = language.parse_state_count() as u16;
+    assert_eq!(language.parse_table(state_count, a), ParseAction::Error);
+    assert_eq!(language.parse_table(1, u16::MAX), ParseAction::Error);
+}
+
+#[test]
+fn test_language_lookahead_iterator() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_with_lookahead_iterator",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    // State 1 is the parser's start state.
+    let mut iterator = language.lookahead_iterator(1).unwrap();
+    assert_eq!(
+        iterator
+            .with_names()
+            .map(|(_, name, is_named)| (name, is_named))
+            .collect::<Vec<_>>(),
+        &[
+            ("end", true),
+            ("word", true),
+            ("number", true),
+            ("program", true),
+            ("program_repeat1", false),
+        ]
+    );
+    assert_eq!(iterator.next(), None);
+    assert_eq!(iterator.current_symbol_name(), "");
+
+    // The iterator can be moved back to the start of a state and reused.
+    assert!(iterator.reset_to(1));
+    assert_eq!(iterator.next(), Some(0));
+    assert_eq!(iterator.current_symbol_name(), "end");
+    assert_eq!(
+        iterator.next(),
+        language.node_kind_id_for_type_checked("word", true)
+    );
+    assert_eq!(iterator.current_symbol_name(), "word");
+    assert_eq!(iterator.language(), language);
+    assert_eq!(iterator.count(), 3);
+
+    // States outside of the parse table are rejected.
+    let state_count = language.parse_state_count() as u16;
+    assert!(language.lookahead_iterator(state_count).is_none());
+    let mut iterator = language.lookahead_iterator(state_count - 1).unwrap();
+    assert!(!iterator.reset_to(state_count));
+    assert!(iterator.reset_to(0));
+}
+
+#[test]
+fn test_language_generated_with_older_abi_version() {
+    let grammar_dir = tempfile::tempdir().unwrap();
+    let grammar_path = grammar_dir.path().join("grammar.json");
+    fs::write(
+        &grammar_path,
+        r#"{
+            "name": "test_language_with_older_abi",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let repo_path = grammar_dir.path().to_owned();
+    let options = CompileOptions::builder().abi_version(11).build();
+    generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).unwrap();
+
+    let header = fs::read_to_string(repo_path.join("src/tree_sitter/parser.h")).unwrap();
+    assert!(header.contains("const TSSymbol *public_symbol_map;"));
+    assert!(!header.contains("const char *name;"));
+    assert!(!header.contains("TSNodeTypeField"));
+
+    let parser_code = fs::read_to_string(repo_path.join("src/parser.c")).unwrap();
+    assert!(parser_code.contains("#define LANGUAGE_VERSION 11\n"));
+
+    let language = get_test_language("test_language_with_older_abi", &parser_code, None);
+    assert_eq!(language.abi_version(), 11);
+    assert_eq!(language.name(), None);
+    assert_eq!(language.parse_state_count(), 0);
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("a", None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program)");
+
+    let options = CompileOptions::builder().abi_version(9).build();
+    assert!(generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).is_err());
+    let options = CompileOptions::builder()
+        .abi_version(tree_sitter::LANGUAGE_VERSION + 1)
+        .build();
+    assert!(generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).is_err());
+}
+
+#[test]
+fn test_language_check_node_types() {
+    let grammar_dir = tempfile::tempdir().unwrap();
+    let grammar_path = grammar_dir.path().join("grammar.json");
+    fs::write(
+        &grammar_path,
+        r#"{
+            "name": "test_language_check_node_types",
+            "supertypes": ["_expression"],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_expression"}},
+                "_expression": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "call"},
+                        {"type": "SYMBOL", "name": "identifier"}
+                    ]
+                },
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "FIELD", "name": "function", "content": {"type": "SYMBOL", "name": "identifier"}},
+                        {"type": "STRING", "value": "()"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let repo_path = grammar_dir.path().to_owned();
+    generate_parser_in_directory(&repo_path, grammar_path.to_str(), &Default::default()).unwrap();
+    let src_path = repo_path.join("src");
+    fs::copy(&grammar_path, src_path.join("grammar.json")).unwrap();
+    let language = test_loader()
+        .load_language_at_path(&src_path, &src_path)
+        .unwrap();
+    check_node_types_in_directory(&repo_path, language).unwrap();
+
+    let node_types_path = src_path.join("node-types.json");
+    let node_types_json = fs::read_to_string(&node_types_path).unwrap();
+    let mut node_types: serde_json::Value = serde_json::from_str(&node_types_json).unwrap();
+    node_types.as_array_mut().unwrap().push(serde_json::json!({
+        "type": "block",
+        "named": true,
+        "fields": {
+            "body": {
+                "multiple": false,
+                "required": true,
+                "types": [{"type": "call", "named": true}, {"type": "{", "named": false}]
+            }
+        }
+    }));
+    fs::write(&node_types_path, node_types.to_string()).unwrap();
+    let message = check_node_types_in_directory(&repo_path, language)
+        .unwrap_err()
+        .message();
+    assert_eq!(
+        message.lines().skip(1).collect::<Vec<_>>(),
+        &[
+            "  `block` is not a node type in the language",
+            "  The field `body` of `block` is not a field in the language",
+            "  `block` refers to the node type `\"{\"`, which the language doesn't have",
+        ]
+    );
+}
+
+#[test]
+fn test_language_check_version_compatibility() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_version_compatibility",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let version = language.abi_version();
+
+    assert!(language.check_version_compatibility(version, version));
+    assert!(language.check_version_compatibility(version - 1, version + 1));
+    assert!(!language.check_version_compatibility(version + 1, version + 2));
+    assert!(!language.check_version_compatibility(version - 2, version - 1));
+    assert!(language.compatible_with_version(version, version + 1));
+    assert!(!language.compatible_with_version(version + 1, version + 1));
+
+    // The loader rejects languages with an unsupported ABI version.
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_from_the_future",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let future_parser_code = parser_code.replace(
+        &format!("#define LANGUAGE_VERSION {}\n", version),
+        &format!("#define LANGUAGE_VERSION {}\n", version + 1),
+    );
+    assert_ne!(future_parser_code, parser_code);
+    let scratch_dir = tempfile::tempdir().unwrap();
+    let parser_path = scratch_dir.path().join("parser.c");
+    fs::write(&parser_path, future_parser_code).unwrap();
+    let header_dir = scratch_dir.path().join("tree_sitter");
+    fs::create_dir(&header_dir).unwrap();
+    fs::write(header_dir.join("parser.h"), tree_sitter::PARSER_HEADER).unwrap();
+    let error = test_loader()
+        .load_language_from_sources(&parser_name, scratch_dir.path(), &parser_path, &None)
+        .unwrap_err();
+    assert_eq!(
+        error.incompatible_abi(),
+        Some(&IncompatibleAbi {
+            language_name: "test_language_from_the_future".to_string(),
+            version: version as usize + 1,
+        })
+    );
+}
+
+#[test]
+fn test_language_from_c_source() {
+    let (_, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_from_c_source",
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            },
+            "extras": [{"type": "PATTERN", "value": "\\s"}]
+        }"#,
+    )
+    .unwrap();
+    let language = test_loader()
+        .load_language_from_c_source(&parser_code)
+        .unwrap();
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("one two", None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program (word) (word))");
+
+    assert_eq!(
+        test_loader()
+            .load_language_from_c_source("int main() { return 0; }")
+            .unwrap_err()
+            .message(),
+        "The source does not define a language function"
+    );
+}
+
+#[test]
+fn test_language_as_raw() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_as_raw",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let raw_language = language.as_raw();
+    assert!(!raw_language.is_null());
+    assert_eq!(raw_language, language.as_raw());
+
+    // The pointer can be passed directly to the C API.
+    extern "C" {
+        fn ts_language_version(language: *const TSLanguage) -> u32;
+    }
+    assert_eq!(
+        unsafe { ts_language_version(raw_language) },
+        language.abi_version()
+    );
+}
+
+#[test]
+fn test_parser_language() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_for_parser",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let mut parser = Parser::new();
+    assert_eq!(parser.language(), None);
+
+    parser.set_language(language).unwrap();
+    assert_eq!(parser.language(), Some(language));
+
+    // Retrieving the language does not affect the parser's state.
+    let tree = parser.parse("a", None).unwrap();
+    assert_eq!(parser.language(), Some(language));
+    let tree = parser.parse("a", Some(&tree)).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program)");
+
+    parser.reset();
+    assert_eq!(parser.language(), Some(language));
+}
+
+#[test]
+fn test_language_generated_into_output_directory() {
+    let grammar_dir = tempfile::tempdir().unwrap();
+    let grammar_path = grammar_dir.path().join("grammar.json");
+    fs::write(
+        &grammar_path,
+        r#"{
+            "name": "test_language_with_output_dir",
+            "rules": {
+                "program": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let repo_path = grammar_dir.path().to_owned();
+    let output_dir = repo_path.join("generated");
+    let options = CompileOptions::builder().output_dir(&output_dir).build();
+    generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).unwrap();
+
+    assert!(output_dir.join("parser.c").is_file());
+    assert!(output_dir.join("node-types.json").is_file());
+    assert!(output_dir.join("tree_sitter").join("parser.h").is_file());
+    assert!(!repo_path.join("src").join("parser.c").exists());
+}
+
+#[test]
+fn test_language_symbol_map() {
+    let grammar_dir = tempfile::tempdir().unwrap();
+    let grammar_path = grammar_dir.path().join("grammar.json");
+    fs::write(
+        &grammar_path,
+        r#"{
+            "name": "test_language_symbol_map",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "_item"}
+                },
+                "_item": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "word"},
+                        {
+                            "type": "SEQ",
+                            "members": [
+                                {"type": "STRING", "value": "$"},
+                                {
+                                    "type": "ALIAS",
+                                    "value": "tag",
+                                    "named": true,
+                                    "content": {"type": "SYMBOL", "name": "word"}
+                                }
+                            ]
+                        }
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let repo_path = grammar_dir.path().to_owned();
+    let symbol_map_path = repo_path.join("symbol_map.json");
+    let options = CompileOptions::builder()
+        .symbol_map_path(Some(&symbol_map_path))
+        .build();
+    generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).unwrap();
+    let parser_code = fs::read_to_string(repo_path.join("src/parser.c")).unwrap();
+    let language = get_test_language("test_language_symbol_map", &parser_code, None);
+
+    // Every symbol in the language is listed, along with its name and whether it's
+    // named, including the symbol for the alias.
+    let symbol_map: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&symbol_map_path).unwrap()).unwrap();
+    let symbol_map = symbol_map.as_object().unwrap();
+    assert_eq!(symbol_map.len(), language.node_kind_count());
+    for id in 0..language.node_kind_count() as u16 {
+        let entry = &symbol_map[&id.to_string()];
+        assert_eq!(entry["name"], language.node_kind_for_id(id).unwrap());
+        assert_eq!(entry["named"], language.node_kind_is_named(id));
+    }
+    assert!(symbol_map
+        .values()
+        .any(|entry| entry["name"] == "tag" && entry["named"] == true));
+}
+
+#[test]
+fn test_language_generated_without_optimizations() {
+    let grammar_json = r#"{
+        "name": "NAME",
+        "extras": [{"type": "PATTERN", "value": "\\s"}],
+        "rules": {
+            "program": {
+                "type": "REPEAT",
+                "content": {"type": "SYMBOL", "name": "_expression"}
+            },
+            "_expression": {
+                "type": "CHOICE",
+                "members": [
+                    {"type": "SYMBOL", "name": "sum"},
+                    {"type": "SYMBOL", "name": "product"},
+                    {"type": "SYMBOL", "name": "number"}
+                ]
+            },
+            "sum": {
+                "type": "PREC_LEFT",
+                "value": 1,
+                "content": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "_expression"},
+                        {"type": "STRING", "value": "+"},
+                        {"type": "SYMBOL", "name": "_expression"}
+                    ]
+                }
+            },
+            "product": {
+                "type": "PREC_LEFT",
+                "value": 2,
+                "content": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "_expression"},
+                        {"type": "STRING", "value": "*"},
+                        {"type": "SYMBOL", "name": "_expression"}
+                    ]
+                }
+            },
+            "number": {"type": "PATTERN", "value": "\\d+"}
+        }
+    }"#;
+
+    let mut state_counts = Vec::new();
+    let mut trees = Vec::new();
+    for (name, optimize) in &[
+        ("test_language_with_optimizations", true),
+        ("test_language_without_optimizations", false),
+    ] {
+        let grammar_dir = tempfile::tempdir().unwrap();
+        let grammar_path = grammar_dir.path().join("grammar.json");
+        fs::write(&grammar_path, grammar_json.replace("NAME", name)).unwrap();
+
+        let repo_path = grammar_dir.path().to_owned();
+        let options = CompileOptions::builder().optimize(*optimize).build();
+        generate_parser_in_directory(&repo_path, grammar_path.to_str(), &options).unwrap();
+        let parser_code = fs::read_to_string(repo_path.join("src/parser.c")).unwrap();
+        state_counts.push(
+            parser_code
+                .lines()
+                .find_map(|line| line.strip_prefix("#define STATE_COUNT "))
+                .unwrap()
+                .parse::<usize>()
+                .unwrap(),
+        );
+
+        let language = get_test_language(name, &parser_code, None);
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse("1 + 2 * 3\n4 * 5 + 6", None).unwrap();
+        trees.push(tree.root_node().to_sexp());
+    }
+
+    // The unoptimized parser has more states, but produces the same trees.
+    assert!(state_counts[1] > state_counts[0]);
+    assert_eq!(
+        trees[0],
+        concat!(
+            "(program ",
+            "(sum (number) (product (number) (number))) ",
+            "(sum (product (number) (number)) (number)))"
+        )
+    );
+    assert_eq!(trees[1], trees[0]);
+}
+
+#[test]
+fn test_language_max_alias_sequence_length() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_max_alias_sequence_length",
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "ALIAS",
+                            "content": {"type": "SYMBOL", "name": "word"},
+                            "named": true,
+                            "value": "name"
+                        },
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.max_alias_sequence_length(), 4);
+}
@@ -1,7 +1,9 @@
 mod corpus_test;
 mod helpers;
 mod highlight_test;
+mod language_test;
 mod node_test;
+mod parse_test;
 mod parser_test;
 mod query_test;
 mod tags_test;
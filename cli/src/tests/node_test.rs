@@ -3,8 +3,11 @@ use super::helpers::fixtures::{fixtures_dir, get_language, get_test_language};
 use super::helpers::random::Rand;
 use crate::generate::generate_parser_for_grammar;
 use crate::parse::perform_edit;
+use serde_json::json;
 use std::fs;
-use tree_sitter::{Node, Parser, Point, Tree};
+use tree_sitter::{
+    DetachedNode, InputEdit, Node, Parser, Point, SexpOptions, Tree, SERIALIZATION_VERSION,
+};
 
 const JSON_EXAMPLE: &'static str = r#"
 
@@ -199,6 +202,44 @@ fn test_node_children() {
     );
 }
 
+#[test]
+fn test_node_children_in_range() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_children_in_range",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = "a bb ccc dddd";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+    let words_in_range = |start_byte, end_byte| {
+        root_node
+            .children_in_range(start_byte, end_byte)
+            .map(|node| &source[node.byte_range()])
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(words_in_range(0, source.len()), &["a", "bb", "ccc", "dddd"]);
+    assert_eq!(words_in_range(3, 6), &["bb", "ccc"]);
+    assert_eq!(words_in_range(4, 5), &[] as &[&str]);
+    assert_eq!(words_in_range(7, 100), &["ccc", "dddd"]);
+    assert_eq!(words_in_range(13, 100), &[] as &[&str]);
+}
+
 #[test]
 fn test_node_children_by_field_name() {
     let mut parser = Parser::new();
@@ -227,6 +268,477 @@ fn test_node_children_by_field_name() {
     );
 }
 
+#[test]
+fn test_node_iter_fields() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_iter_fields",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "call"}},
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "function",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "REPEAT",
+                            "content": {
+                                "type": "FIELD",
+                                "name": "argument",
+                                "content": {"type": "SYMBOL", "name": "word"}
+                            }
+                        },
+                        {"type": "STRING", "value": ")"},
+                        {
+                            "type": "CHOICE",
+                            "members": [
+                                {
+                                    "type": "FIELD",
+                                    "name": "suffix",
+                                    "content": {"type": "STRING", "value": "!"}
+                                },
+                                {"type": "BLANK"}
+                            ]
+                        }
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = "f(a b)! g()";
+    let tree = parser.parse(source, None).unwrap();
+    let fields = |node: Node| {
+        let mut cursor = node.walk();
+        node.iter_fields(&mut cursor)
+            .map(|(name, node)| (name, &source[node.byte_range()]))
+            .collect::<Vec<_>>()
+    };
+    let root_node = tree.root_node();
+    assert_eq!(
+        fields(root_node.child(0).unwrap()),
+        &[
+            ("function", "f"),
+            ("argument", "a"),
+            ("argument", "b"),
+            ("suffix", "!")
+        ]
+    );
+    assert_eq!(fields(root_node.child(1).unwrap()), &[("function", "g")]);
+    assert_eq!(fields(root_node), &[]);
+    assert_eq!(fields(root_node.child(1).unwrap().child(0).unwrap()), &[]);
+
+    let mut cursor = tree.walk();
+    let children = root_node
+        .child(0)
+        .unwrap()
+        .named_children_with_fields(&mut cursor)
+        .map(|(name, node)| (name, node.kind()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        children,
+        &[
+            (Some("function"), "word"),
+            (Some("argument"), "word"),
+            (Some("argument"), "word"),
+        ]
+    );
+    let children = root_node
+        .named_children_with_fields(&mut cursor)
+        .map(|(name, node)| (name, node.kind()))
+        .collect::<Vec<_>>();
+    assert_eq!(children, &[(None, "call"), (None, "call")]);
+}
+
+#[test]
+fn test_node_closest_ancestor_matching() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_closest_ancestor_matching",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_item"}},
+                "_item": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "class"},
+                        {"type": "SYMBOL", "name": "function"},
+                        {"type": "SYMBOL", "name": "identifier"}
+                    ]
+                },
+                "class": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "class"},
+                        {"type": "SYMBOL", "name": "class_body"}
+                    ]
+                },
+                "class_body": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "{"},
+                        {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_item"}},
+                        {"type": "STRING", "value": "}"}
+                    ]
+                },
+                "function": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "fn"},
+                        {"type": "STRING", "value": "("},
+                        {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_item"}},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let source = "class { fn ( fn ( x ) ) }";
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let x_index = source.find('x').unwrap();
+    let x_node = tree
+        .root_node()
+        .named_descendant_for_byte_range(x_index, x_index + 1)
+        .unwrap();
+    assert_eq!(x_node.kind(), "identifier");
+
+    let function_or_class_body = x_node
+        .closest_ancestor_matching(|node| node.kind() == "function" || node.kind() == "class_body")
+        .unwrap();
+    assert_eq!(function_or_class_body.kind(), "function");
+    assert_eq!(
+        function_or_class_body.start_byte(),
+        source.rfind("fn").unwrap()
+    );
+
+    let class_body = x_node
+        .closest_ancestor_matching(|node| node.kind() == "class_body")
+        .unwrap();
+    assert_eq!(class_body.parent().unwrap().kind(), "class");
+
+    // The node itself is not considered.
+    assert_eq!(
+        x_node.closest_ancestor_matching(|node| node.kind() == "identifier"),
+        None
+    );
+    assert_eq!(tree.root_node().closest_ancestor_matching(|_| true), None);
+}
+
+#[test]
+fn test_node_contains_byte_and_point() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_contains_byte_and_point",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = "abc\n  def\n";
+    let tree = parser.parse(source, None).unwrap();
+    let second_word = tree.root_node().named_child(1).unwrap();
+    assert_eq!(second_word.byte_range(), 6..9);
+
+    assert!(!second_word.contains_byte(5));
+    assert!(second_word.contains_byte(6));
+    assert!(second_word.contains_byte(8));
+    assert!(!second_word.contains_byte(9));
+
+    assert!(!second_word.contains_point(Point::new(0, 2)));
+    assert!(!second_word.contains_point(Point::new(1, 1)));
+    assert!(second_word.contains_point(Point::new(1, 2)));
+    assert!(second_word.contains_point(Point::new(1, 4)));
+    assert!(!second_word.contains_point(Point::new(1, 5)));
+    assert!(!second_word.contains_point(Point::new(2, 0)));
+}
+
+#[test]
+fn test_node_contains_and_overlaps() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_contains_and_overlaps",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "pair"}
+                },
+                "pair": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "SYMBOL", "name": "word"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let tree = parser.parse("ab cd ef gh", None).unwrap();
+    let root_node = tree.root_node();
+    let first_pair = root_node.named_child(0).unwrap();
+    let second_pair = root_node.named_child(1).unwrap();
+    let first_word = first_pair.named_child(0).unwrap();
+    let third_word = second_pair.named_child(0).unwrap();
+
+    assert!(root_node.contains(first_pair));
+    assert!(first_pair.contains(first_word));
+    assert!(first_pair.contains(first_pair));
+    assert!(!first_word.contains(first_pair));
+    assert!(!first_pair.contains(third_word));
+
+    assert!(first_pair.overlaps(first_word));
+    assert!(first_word.overlaps(first_pair));
+    assert!(root_node.overlaps(third_word));
+    assert!(!first_pair.overlaps(second_pair));
+    assert!(!first_word.overlaps(third_word));
+}
+
+#[test]
+fn test_node_coverage() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_coverage",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = "abc  12\n";
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().coverage(source.as_bytes()), 1.0);
+
+    // Six of the nine non-whitespace bytes are outside of the `ERROR` node.
+    let source = "abc 12 ??? x";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    assert!(root.has_error());
+    assert_eq!(root.coverage(source.as_bytes()), 6.0 / 9.0);
+    assert_eq!(
+        root.named_child(0).unwrap().coverage(source.as_bytes()),
+        1.0
+    );
+
+    let source = "  ";
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().coverage(source.as_bytes()), 1.0);
+}
+
+#[test]
+fn test_node_has_field_in_production() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_has_field_in_production",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "assignment"}
+                },
+                "assignment": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "name",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {
+                            "type": "CHOICE",
+                            "members": [
+                                {
+                                    "type": "SEQ",
+                                    "members": [
+                                        {"type": "STRING", "value": "="},
+                                        {
+                                            "type": "FIELD",
+                                            "name": "value",
+                                            "content": {"type": "SYMBOL", "name": "number"}
+                                        }
+                                    ]
+                                },
+                                {"type": "BLANK"}
+                            ]
+                        }
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let tree = parser.parse("a = 1 b", None).unwrap();
+    let root = tree.root_node();
+    let first = root.named_child(0).unwrap();
+    let second = root.named_child(1).unwrap();
+    assert!(first.has_field_in_production("name"));
+    assert!(first.has_field_in_production("value"));
+    assert!(second.has_field_in_production("name"));
+    assert!(!second.has_field_in_production("value"));
+    assert!(!first.has_field_in_production("condition"));
+    assert!(!root.has_field_in_production("name"));
+    assert!(!first
+        .child_by_field_name("name")
+        .unwrap()
+        .has_field_in_production("name"));
+
+    // The grammar declares the `value` field for every assignment, even though the
+    // second one was parsed without it.
+    assert!(second.has_field("value"));
+    assert!(second.has_field("name"));
+    assert!(!second.has_field("condition"));
+    assert!(!root.has_field("name"));
+}
+
+#[test]
+fn test_node_to_json() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_to_json",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "assignment"}
+                },
+                "assignment": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "name",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "="},
+                        {
+                            "type": "FIELD",
+                            "name": "value",
+                            "content": {"type": "SYMBOL", "name": "number"}
+                        }
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = "ab = 1";
+    let tree = parser.parse(source, None).unwrap();
+    let assignment = tree.root_node().child(0).unwrap();
+    assert_eq!(
+        assignment.to_json(source.as_bytes()),
+        json!({
+            "type": "assignment",
+            "named": true,
+            "startByte": 0,
+            "endByte": 6,
+            "startPoint": {"row": 0, "column": 0},
+            "endPoint": {"row": 0, "column": 6},
+            "fields": {"name": [0], "value": [2]},
+            "children": [
+                {
+                    "type": "word",
+                    "named": true,
+                    "startByte": 0,
+                    "endByte": 2,
+                    "startPoint": {"row": 0, "column": 0},
+                    "endPoint": {"row": 0, "column": 2},
+                    "text": "ab",
+                    "fields": {},
+                    "children": []
+                },
+                {
+                    "type": "=",
+                    "named": false,
+                    "startByte": 3,
+                    "endByte": 4,
+                    "startPoint": {"row": 0, "column": 3},
+                    "endPoint": {"row": 0, "column": 4},
+                    "text": "=",
+                    "fields": {},
+                    "children": []
+                },
+                {
+                    "type": "number",
+                    "named": true,
+                    "startByte": 5,
+                    "endByte": 6,
+                    "startPoint": {"row": 0, "column": 5},
+                    "endPoint": {"row": 0, "column": 6},
+                    "text": "1",
+                    "fields": {},
+                    "children": []
+                }
+            ]
+        })
+    );
+}
+
 #[test]
 fn test_node_parent_of_child_by_field_name() {
     let mut parser = Parser::new();
@@ -314,32 +826,141 @@ fn test_node_named_child() {
 }
 
 #[test]
-fn test_node_named_child_with_aliases_and_extras() {
-    let (parser_name, parser_code) =
-        generate_parser_for_grammar(GRAMMAR_WITH_ALIASES_AND_EXTRAS).unwrap();
+fn test_node_sibling_count() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    let array_node = root_node.child(0).unwrap();
+    let number_node = array_node.named_child(0).unwrap();
+    let comma_node = array_node.child(2).unwrap();
+    let object_node = array_node.named_child(2).unwrap();
+    let pair_node = object_node.named_child(0).unwrap();
+    assert_eq!(comma_node.kind(), ",");
+    assert_eq!(pair_node.kind(), "pair");
 
-    let mut parser = Parser::new();
-    parser
-        .set_language(get_test_language(&parser_name, &parser_code, None))
-        .unwrap();
+    assert_eq!(root_node.sibling_count(), 0);
+    assert_eq!(root_node.named_sibling_count(), 0);
+    assert_eq!(array_node.sibling_count(), 1);
+    assert_eq!(array_node.named_sibling_count(), 1);
 
-    let tree = parser.parse("b ... b ... c", None).unwrap();
-    let root = tree.root_node();
-    assert_eq!(root.to_sexp(), "(a (b) (comment) (B) (comment) (C))");
-    assert_eq!(root.named_child_count(), 5);
-    assert_eq!(root.named_child(0).unwrap().kind(), "b");
-    assert_eq!(root.named_child(1).unwrap().kind(), "comment");
-    assert_eq!(root.named_child(2).unwrap().kind(), "B");
-    assert_eq!(root.named_child(3).unwrap().kind(), "comment");
-    assert_eq!(root.named_child(4).unwrap().kind(), "C");
+    // Anonymous siblings are only included in `sibling_count`.
+    assert_eq!(number_node.sibling_count(), 7);
+    assert_eq!(number_node.named_sibling_count(), 3);
+    assert_eq!(comma_node.sibling_count(), 7);
+    assert_eq!(comma_node.named_sibling_count(), 3);
+
+    assert_eq!(pair_node.sibling_count(), 3);
+    assert_eq!(pair_node.named_sibling_count(), 1);
 }
 
 #[test]
-fn test_node_descendant_for_range() {
-    let tree = parse_json_example();
-    let array_node = tree.root_node().child(0).unwrap();
+fn test_node_sibling_of_type() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_for_sibling_of_type",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "statement"}
+                },
+                "statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "REPEAT1",
+                            "content": {
+                                "type": "CHOICE",
+                                "members": [
+                                    {"type": "SYMBOL", "name": "identifier"},
+                                    {"type": "SYMBOL", "name": "number"}
+                                ]
+                            }
+                        },
+                        {"type": "STRING", "value": ";"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
 
-    // Leaf node exactly matches the given bounds - byte query
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("a 1 b 2 c;\nd e", None).unwrap();
+    let root_node = tree.root_node();
+    assert_eq!(
+        root_node.to_sexp(),
+        concat!(
+            "(program ",
+            "(statement (identifier) (number) (identifier) (number) (identifier)) ",
+            "(statement (identifier) (identifier) (MISSING \";\")))",
+        )
+    );
+
+    let statement1 = root_node.named_child(0).unwrap();
+    let statement2 = root_node.named_child(1).unwrap();
+    let a = statement1.child(0).unwrap();
+    let b = statement1.child(2).unwrap();
+    let c = statement1.child(4).unwrap();
+    let d = statement2.child(0).unwrap();
+
+    assert_eq!(a.next_sibling_of_type("identifier"), Some(b));
+    assert_eq!(b.next_sibling_of_type("identifier"), Some(c));
+    assert_eq!(c.next_sibling_of_type("identifier"), None);
+    assert_eq!(a.next_sibling_of_type("number").unwrap().start_byte(), 2);
+    assert_eq!(a.next_sibling_of_type(";").unwrap().start_byte(), 9);
+    assert_eq!(c.prev_sibling_of_type("identifier"), Some(b));
+    assert_eq!(c.prev_sibling_of_type("number").unwrap().start_byte(), 6);
+    assert_eq!(a.prev_sibling_of_type("identifier"), None);
+    assert_eq!(
+        statement2.prev_sibling_of_type("statement"),
+        Some(statement1)
+    );
+    assert_eq!(
+        statement1.next_sibling_of_type("statement"),
+        Some(statement2)
+    );
+
+    // Missing nodes are skipped.
+    assert!(statement2.child(2).unwrap().is_missing());
+    assert_eq!(d.next_sibling_of_type(";"), None);
+}
+
+#[test]
+fn test_node_named_child_with_aliases_and_extras() {
+    let (parser_name, parser_code) =
+        generate_parser_for_grammar(GRAMMAR_WITH_ALIASES_AND_EXTRAS).unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let tree = parser.parse("b ... b ... c", None).unwrap();
+    let root = tree.root_node();
+    assert_eq!(root.to_sexp(), "(a (b) (comment) (B) (comment) (C))");
+    assert_eq!(root.named_child_count(), 5);
+    assert_eq!(root.named_child(0).unwrap().kind(), "b");
+    assert_eq!(root.named_child(1).unwrap().kind(), "comment");
+    assert_eq!(root.named_child(2).unwrap().kind(), "B");
+    assert_eq!(root.named_child(3).unwrap().kind(), "comment");
+    assert_eq!(root.named_child(4).unwrap().kind(), "C");
+}
+
+#[test]
+fn test_node_descendant_for_range() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+
+    // Leaf node exactly matches the given bounds - byte query
     let colon_index = JSON_EXAMPLE.find(":").unwrap();
     let colon_node = array_node
         .descendant_for_byte_range(colon_index, colon_index + 1)
@@ -474,109 +1095,589 @@ fn test_node_edit() {
             );
         }
 
-        tree = tree2;
-    }
+        tree = tree2;
+    }
+}
+
+#[test]
+fn test_node_is_extra() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let tree = parser.parse("foo(/* hi */);", None).unwrap();
+
+    let root_node = tree.root_node();
+    let comment_node = root_node.descendant_for_byte_range(7, 7).unwrap();
+
+    assert_eq!(root_node.kind(), "program");
+    assert_eq!(comment_node.kind(), "comment");
+    assert!(!root_node.is_extra());
+    assert!(comment_node.is_extra());
+}
+
+#[test]
+fn test_node_is_leaf() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_is_leaf",
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "call"}},
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "STRING", "value": "("},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let tree = parser.parse("f()", None).unwrap();
+
+    let root_node = tree.root_node();
+    let call_node = root_node.child(0).unwrap();
+    assert!(!root_node.is_leaf());
+    assert!(!call_node.is_leaf());
+    assert!(call_node.child(0).unwrap().is_leaf());
+    assert!(call_node.child(1).unwrap().is_leaf());
+}
+
+#[test]
+fn test_node_child_count_filtered() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_child_count_filtered",
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "call"}},
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "STRING", "value": "("},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let tree = parser.parse("f()", None).unwrap();
+
+    let call_node = tree.root_node().child(0).unwrap();
+    assert_eq!(call_node.child_count_filtered(|child| child.is_leaf()), 3);
+    assert_eq!(call_node.child_count_filtered(|child| child.is_named()), 1);
+    assert_eq!(
+        call_node.child_count_filtered(|child| child.kind() == ")"),
+        1
+    );
+    assert_eq!(
+        call_node.child(0).unwrap().child_count_filtered(|_| true),
+        0
+    );
+}
+
+#[test]
+fn test_node_all_errors() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_all_errors",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "call"}},
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "STRING", "value": "("},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let tree = parser.parse("f() g()", None).unwrap();
+    assert!(!tree.root_node().has_errors());
+    assert!(tree.root_node().all_errors().is_empty());
+
+    let tree = parser.parse("f() g( h() ) )", None).unwrap();
+    let root_node = tree.root_node();
+    assert!(root_node.has_errors());
+    let errors = root_node.all_errors();
+    assert_eq!(
+        errors
+            .iter()
+            .map(|node| (node.kind(), node.is_missing(), node.start_byte()))
+            .collect::<Vec<_>>(),
+        &[(")", true, 6), ("ERROR", false, 11)]
+    );
+}
+
+#[test]
+fn test_node_sexp() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let tree = parser.parse("if (a) b", None).unwrap();
+    let root_node = tree.root_node();
+    let if_node = root_node.descendant_for_byte_range(0, 0).unwrap();
+    let paren_node = root_node.descendant_for_byte_range(3, 3).unwrap();
+    let identifier_node = root_node.descendant_for_byte_range(4, 4).unwrap();
+    assert_eq!(if_node.kind(), "if");
+    assert_eq!(if_node.to_sexp(), "(\"if\")");
+    assert_eq!(paren_node.kind(), "(");
+    assert_eq!(paren_node.to_sexp(), "(\"(\")");
+    assert_eq!(identifier_node.kind(), "identifier");
+    assert_eq!(identifier_node.to_sexp(), "(identifier)");
+}
+
+#[test]
+fn test_node_field_names() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_with_fields",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "rule_a": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "field_1",
+                            "content": {"type": "STRING", "value": "child-0"}
+                        },
+                        {
+                            "type": "CHOICE",
+                            "members": [
+                                {"type": "STRING", "value": "child-1"},
+                                {"type": "BLANK"},
+
+                                // This isn't used in the test, but prevents `_hidden_rule1`
+                                // from being eliminated as a unit reduction.
+                                {
+                                    "type": "ALIAS",
+                                    "value": "x",
+                                    "named": true,
+                                    "content": {
+                                        "type": "SYMBOL",
+                                        "name": "_hidden_rule1"
+                                    }
+                                }
+                            ]
+                        },
+                        {
+                            "type": "FIELD",
+                            "name": "field_2",
+                            "content": {"type": "SYMBOL", "name": "_hidden_rule1"}
+                        },
+                        {"type": "SYMBOL", "name": "_hidden_rule2"}
+                    ]
+                },
+
+                // Fields pointing to hidden nodes with a single child resolve to the child.
+                "_hidden_rule1": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "STRING", "value": "child-2"},
+                        {"type": "STRING", "value": "child-2.5"}
+                    ]
+                },
+
+                // Fields within hidden nodes can be referenced through the parent node.
+                "_hidden_rule2": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "child-3"},
+                        {
+                            "type": "FIELD",
+                            "name": "field_3",
+                            "content": {"type": "STRING", "value": "child-4"}
+                        }
+                    ]
+                }
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser
+        .parse("child-0 child-1 child-2 child-3 child-4", None)
+        .unwrap();
+    let root_node = tree.root_node();
+
+    assert_eq!(root_node.child_by_field_name("field_1"), root_node.child(0));
+    assert_eq!(root_node.child_by_field_name("field_2"), root_node.child(2));
+    assert_eq!(root_node.child_by_field_name("field_3"), root_node.child(4));
+    assert_eq!(
+        root_node.child(0).unwrap().child_by_field_name("field_1"),
+        None
+    );
+    assert_eq!(root_node.child_by_field_name("not_a_real_field"), None);
+
+    let mut cursor = root_node.walk();
+    assert_eq!(cursor.field_name(), None);
+    cursor.goto_first_child();
+    assert_eq!(cursor.node().kind(), "child-0");
+    assert_eq!(cursor.field_name(), Some("field_1"));
+    cursor.goto_next_sibling();
+    assert_eq!(cursor.node().kind(), "child-1");
+    assert_eq!(cursor.field_name(), None);
+    cursor.goto_next_sibling();
+    assert_eq!(cursor.node().kind(), "child-2");
+    assert_eq!(cursor.field_name(), Some("field_2"));
+    cursor.goto_next_sibling();
+    assert_eq!(cursor.node().kind(), "child-3");
+    assert_eq!(cursor.field_name(), None);
+    cursor.goto_next_sibling();
+    assert_eq!(cursor.node().kind(), "child-4");
+    assert_eq!(cursor.field_name(), Some("field_3"));
+}
+
+#[test]
+fn test_node_path_from_root() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_with_nested_fields",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "FIELD",
+                    "name": "body",
+                    "content": {"type": "SYMBOL", "name": "block"}
+                },
+                "block": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "{"},
+                        {"type": "SYMBOL", "name": "_statement"},
+                        {"type": "STRING", "value": "}"}
+                    ]
+                },
+
+                // Fields within hidden nodes are reported for the visible children.
+                "_statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {
+                            "type": "FIELD",
+                            "name": "value",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        }
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("{ a b }", None).unwrap();
+    let root_node = tree.root_node();
+    let block_node = root_node.child(0).unwrap();
+    let a_node = block_node.child(1).unwrap();
+    let b_node = block_node.child(2).unwrap();
+
+    assert_eq!(root_node.path_from_root(), vec![(root_node, None)]);
+    assert_eq!(root_node.parent_field_name(), None);
+    assert_eq!(a_node.parent_field_name(), None);
+    assert_eq!(
+        b_node.path_from_root(),
+        vec![
+            (root_node, None),
+            (block_node, Some("body")),
+            (b_node, Some("value")),
+        ]
+    );
+
+    assert_eq!(root_node.depth_from_root(), 0);
+    assert_eq!(block_node.depth_from_root(), 1);
+    assert_eq!(b_node.depth_from_root(), 2);
+
+    assert_eq!(
+        root_node.path_to(b_node),
+        Some(vec![root_node, block_node, b_node])
+    );
+    assert_eq!(block_node.path_to(a_node), Some(vec![block_node, a_node]));
+    assert_eq!(a_node.path_to(a_node), Some(vec![a_node]));
+    assert_eq!(a_node.path_to(b_node), None);
+    assert_eq!(b_node.path_to(root_node), None);
+}
+
+#[test]
+fn test_node_is_named_or_field_named() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_with_operator_fields",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "("},
+                        {"type": "SYMBOL", "name": "binary_expression"},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "binary_expression": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "left",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {
+                            "type": "FIELD",
+                            "name": "operator",
+                            "content": {
+                                "type": "CHOICE",
+                                "members": [
+                                    {"type": "STRING", "value": "+"},
+                                    {"type": "STRING", "value": "-"}
+                                ]
+                            }
+                        },
+                        {"type": "SYMBOL", "name": "identifier"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("(a + b)", None).unwrap();
+    let root_node = tree.root_node();
+    let binary_node = root_node.named_child(0).unwrap();
+    assert!(root_node.is_named_or_field_named());
+    assert!(!root_node.child(0).unwrap().is_named_or_field_named());
+    assert!(!root_node.child(2).unwrap().is_named_or_field_named());
+
+    let mut cursor = tree.walk();
+    let kinds = binary_node
+        .children(&mut cursor)
+        .filter(Node::is_named_or_field_named)
+        .map(|node| node.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, &["identifier", "+", "identifier"]);
+}
+
+#[test]
+fn test_node_matches_pattern() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_for_node_patterns",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "call"}
+                },
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "function",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "REPEAT",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("f(a) g()", None).unwrap();
+    let root_node = tree.root_node();
+    let call_with_args = root_node.named_child(0).unwrap();
+    let call_without_args = root_node.named_child(1).unwrap();
+
+    assert!(call_with_args.matches_pattern("(call)", &language));
+    assert!(call_with_args.matches_pattern("(call (identifier) (identifier))", &language));
+    assert!(call_with_args.matches_pattern("(call function: (identifier) @f)", &language));
+    assert!(!call_without_args.matches_pattern("(call (identifier) (identifier))", &language));
+
+    // Only the node itself is matched, not its descendants.
+    assert!(!root_node.matches_pattern("(call)", &language));
+    assert!(root_node.matches_pattern("(program (call) (call))", &language));
+
+    // A trailing comment in the pattern doesn't hide the node itself.
+    assert!(call_with_args.matches_pattern("(call) ; a call", &language));
+    assert!(!root_node.matches_pattern("(call) ; a call", &language));
+
+    // Invalid patterns and patterns with text predicates never match.
+    assert!(!call_with_args.matches_pattern("(call", &language));
+    assert!(!call_with_args.matches_pattern("(not_a_node)", &language));
+    assert!(!call_with_args.matches_pattern(
+        "(call function: (identifier) @f (#eq? @f \"f\"))",
+        &language
+    ));
 }
 
 #[test]
-fn test_node_is_extra() {
+fn test_node_text_length() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_node_text_length",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "word": {"type": "PATTERN", "value": "[^\\s]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
     let mut parser = Parser::new();
-    parser.set_language(get_language("javascript")).unwrap();
-    let tree = parser.parse("foo(/* hi */);", None).unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
 
+    let source = "word na\u{ef}ve \u{1f600}\u{1f600}".as_bytes();
+    let tree = parser.parse(source, None).unwrap();
     let root_node = tree.root_node();
-    let comment_node = root_node.descendant_for_byte_range(7, 7).unwrap();
-
-    assert_eq!(root_node.kind(), "program");
-    assert_eq!(comment_node.kind(), "comment");
-    assert!(!root_node.is_extra());
-    assert!(comment_node.is_extra());
+    let word_lengths = |source| {
+        (0..root_node.named_child_count())
+            .map(|i| root_node.named_child(i).unwrap().text_length(source))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(word_lengths(source), &[4, 5, 2]);
+    assert_eq!(root_node.text_length(source), 13);
+    assert_eq!(root_node.byte_range().len(), 20);
+
+    // Invalid UTF8 sequences count as one code point each.
+    let latin1_source = b"caf\xe9";
+    let tree = parser.parse(&latin1_source[..], None).unwrap();
+    assert_eq!(tree.root_node().text_length(latin1_source), 4);
 }
 
 #[test]
-fn test_node_sexp() {
+fn test_node_byte_gap_to() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_node_byte_gap_to",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
     let mut parser = Parser::new();
-    parser.set_language(get_language("javascript")).unwrap();
-    let tree = parser.parse("if (a) b", None).unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("one  two three", None).unwrap();
     let root_node = tree.root_node();
-    let if_node = root_node.descendant_for_byte_range(0, 0).unwrap();
-    let paren_node = root_node.descendant_for_byte_range(3, 3).unwrap();
-    let identifier_node = root_node.descendant_for_byte_range(4, 4).unwrap();
-    assert_eq!(if_node.kind(), "if");
-    assert_eq!(if_node.to_sexp(), "(\"if\")");
-    assert_eq!(paren_node.kind(), "(");
-    assert_eq!(paren_node.to_sexp(), "(\"(\")");
-    assert_eq!(identifier_node.kind(), "identifier");
-    assert_eq!(identifier_node.to_sexp(), "(identifier)");
+    let one = root_node.named_child(0).unwrap();
+    let two = root_node.named_child(1).unwrap();
+    let three = root_node.named_child(2).unwrap();
+    assert_eq!(one.byte_gap_to(two), Some(2));
+    assert!(one.has_gap_to(two));
+    assert_eq!(two.byte_gap_to(three), Some(1));
+    assert_eq!(one.byte_gap_to(three), Some(6));
+
+    // Nodes that overlap or are out of order have no gap.
+    assert_eq!(two.byte_gap_to(one), None);
+    assert!(!two.has_gap_to(one));
+    assert_eq!(root_node.byte_gap_to(two), None);
+    assert_eq!(one.byte_gap_to(one), None);
 }
 
 #[test]
-fn test_node_field_names() {
+fn test_node_text_matches_regex() {
     let (parser_name, parser_code) = generate_parser_for_grammar(
         r#"
         {
-            "name": "test_grammar_with_fields",
+            "name": "test_grammar_for_node_text",
             "extras": [
                 {"type": "PATTERN", "value": "\\s+"}
             ],
             "rules": {
-                "rule_a": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "block"}
+                },
+                "block": {
                     "type": "SEQ",
                     "members": [
+                        {"type": "STRING", "value": "{"},
                         {
-                            "type": "FIELD",
-                            "name": "field_1",
-                            "content": {"type": "STRING", "value": "child-0"}
-                        },
-                        {
-                            "type": "CHOICE",
-                            "members": [
-                                {"type": "STRING", "value": "child-1"},
-                                {"type": "BLANK"},
-
-                                // This isn't used in the test, but prevents `_hidden_rule1`
-                                // from being eliminated as a unit reduction.
-                                {
-                                    "type": "ALIAS",
-                                    "value": "x",
-                                    "named": true,
-                                    "content": {
-                                        "type": "SYMBOL",
-                                        "name": "_hidden_rule1"
-                                    }
-                                }
-                            ]
-                        },
-                        {
-                            "type": "FIELD",
-                            "name": "field_2",
-                            "content": {"type": "SYMBOL", "name": "_hidden_rule1"}
+                            "type": "REPEAT",
+                            "content": {"type": "SYMBOL", "name": "word"}
                         },
-                        {"type": "SYMBOL", "name": "_hidden_rule2"}
-                    ]
-                },
-
-                // Fields pointing to hidden nodes with a single child resolve to the child.
-                "_hidden_rule1": {
-                    "type": "CHOICE",
-                    "members": [
-                        {"type": "STRING", "value": "child-2"},
-                        {"type": "STRING", "value": "child-2.5"}
+                        {"type": "STRING", "value": "}"}
                     ]
                 },
-
-                // Fields within hidden nodes can be referenced through the parent node.
-                "_hidden_rule2": {
-                    "type": "SEQ",
-                    "members": [
-                        {"type": "STRING", "value": "child-3"},
-                        {
-                            "type": "FIELD",
-                            "name": "field_3",
-                            "content": {"type": "STRING", "value": "child-4"}
-                        }
-                    ]
-                }
+                "word": {"type": "PATTERN", "value": "[^\\s{}]+"}
             }
         }
     "#,
@@ -587,37 +1688,20 @@ fn test_node_field_names() {
     let language = get_test_language(&parser_name, &parser_code, None);
     parser.set_language(language).unwrap();
 
-    let tree = parser
-        .parse("child-0 child-1 child-2 child-3 child-4", None)
-        .unwrap();
-    let root_node = tree.root_node();
-
-    assert_eq!(root_node.child_by_field_name("field_1"), root_node.child(0));
-    assert_eq!(root_node.child_by_field_name("field_2"), root_node.child(2));
-    assert_eq!(root_node.child_by_field_name("field_3"), root_node.child(4));
-    assert_eq!(
-        root_node.child(0).unwrap().child_by_field_name("field_1"),
-        None
-    );
-    assert_eq!(root_node.child_by_field_name("not_a_real_field"), None);
-
-    let mut cursor = root_node.walk();
-    assert_eq!(cursor.field_name(), None);
-    cursor.goto_first_child();
-    assert_eq!(cursor.node().kind(), "child-0");
-    assert_eq!(cursor.field_name(), Some("field_1"));
-    cursor.goto_next_sibling();
-    assert_eq!(cursor.node().kind(), "child-1");
-    assert_eq!(cursor.field_name(), None);
-    cursor.goto_next_sibling();
-    assert_eq!(cursor.node().kind(), "child-2");
-    assert_eq!(cursor.field_name(), Some("field_2"));
-    cursor.goto_next_sibling();
-    assert_eq!(cursor.node().kind(), "child-3");
-    assert_eq!(cursor.field_name(), None);
-    cursor.goto_next_sibling();
-    assert_eq!(cursor.node().kind(), "child-4");
-    assert_eq!(cursor.field_name(), Some("field_3"));
+    let source = b"{ one\n  two }\n{ caf\xe9 }";
+    let tree = parser.parse(&source[..], None).unwrap();
+    let multi_line_block = tree.root_node().named_child(0).unwrap();
+    let latin1_block = tree.root_node().named_child(1).unwrap();
+    let latin1_word = latin1_block.named_child(0).unwrap();
+    assert!(latin1_word.utf8_text(source).is_err());
+
+    let regex = |pattern| regex::bytes::Regex::new(pattern).unwrap();
+    assert!(multi_line_block.text_matches_regex(&regex("one\\s+two"), source));
+    assert!(!multi_line_block.text_matches_regex(&regex("one.+two"), source));
+    assert!(!multi_line_block.text_matches_regex(&regex("^  two"), source));
+    assert!(multi_line_block.text_matches_regex(&regex("(?m)^  two"), source));
+    assert!(!multi_line_block.text_matches_regex(&regex("caf"), source));
+    assert!(latin1_word.text_matches_regex(&regex("(?-u)^caf\\xe9$"), source));
 }
 
 #[test]
@@ -757,6 +1841,308 @@ fn test_node_numeric_symbols_respect_simple_aliases() {
     assert_eq!(unary_minus_node.kind_id(), binary_minus_node.kind_id());
 }
 
+#[test]
+fn test_node_equals_and_is_same_position_as() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_for_node_equality",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "list": {
+                    "type": "REPEAT",
+                    "content": {"type": "STRING", "value": "a"}
+                }
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("a a", None).unwrap();
+    let copied_tree = tree.clone();
+    let node = tree.root_node().child(0).unwrap();
+    let copied_node = copied_tree.root_node().child(0).unwrap();
+
+    assert!(node.equals(tree.root_node().child(0).unwrap()));
+    assert!(!node.equals(tree.root_node().child(1).unwrap()));
+
+    // Copies of a tree share their nodes, so only `equals` can tell them apart.
+    assert_eq!(node, copied_node);
+    assert!(!node.equals(copied_node));
+    assert!(node.is_same_position_as(copied_node));
+
+    let mut edited_tree = tree.clone();
+    edited_tree.edit(&InputEdit {
+        start_byte: 2,
+        old_end_byte: 2,
+        new_end_byte: 4,
+        start_position: Point::new(0, 2),
+        old_end_position: Point::new(0, 2),
+        new_end_position: Point::new(0, 4),
+    });
+    let new_tree = parser.parse("a a a", Some(&edited_tree)).unwrap();
+    let new_node = new_tree.root_node().child(0).unwrap();
+    assert!(!new_node.equals(node));
+    assert!(new_node.is_same_position_as(node));
+    assert!(!new_tree
+        .root_node()
+        .child(1)
+        .unwrap()
+        .is_same_position_as(node));
+}
+
+#[test]
+fn test_node_format_sexp() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_for_node_sexp",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "assignment"}
+                },
+                "assignment": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "left",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {"type": "STRING", "value": "="},
+                        {
+                            "type": "FIELD",
+                            "name": "right",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {"type": "STRING", "value": ";"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let source = "a = b;\nc = d";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+    assert_eq!(
+        root_node.format_sexp(&SexpOptions::default()),
+        concat!(
+            "(program ",
+            "(assignment left: (identifier) right: (identifier)) ",
+            "(assignment left: (identifier) right: (identifier) (MISSING \";\")))",
+        )
+    );
+    assert_eq!(
+        root_node.format_sexp(&SexpOptions::default()),
+        root_node.to_sexp()
+    );
+    assert_eq!(
+        root_node.format_sexp(&SexpOptions {
+            include_ranges: true,
+            ..SexpOptions::default()
+        }),
+        concat!(
+            "(program [0, 0] - [1, 5] ",
+            "(assignment [0, 0] - [0, 6] ",
+            "left: (identifier [0, 0] - [0, 1]) ",
+            "right: (identifier [0, 4] - [0, 5])) ",
+            "(assignment [1, 0] - [1, 5] ",
+            "left: (identifier [1, 0] - [1, 1]) ",
+            "right: (identifier [1, 4] - [1, 5]) ",
+            "(MISSING \";\" [1, 5] - [1, 5])))",
+        )
+    );
+
+    let assignment = root_node.named_child(0).unwrap();
+    assert_eq!(
+        assignment.format_sexp(&SexpOptions {
+            include_anonymous_nodes: true,
+            include_field_names: false,
+            ..SexpOptions::default()
+        }),
+        "(assignment (identifier) \"=\" (identifier) \";\")"
+    );
+    assert_eq!(
+        assignment.format_sexp(&SexpOptions {
+            source_code: Some(source.as_bytes()),
+            ..SexpOptions::default()
+        }),
+        "(assignment left: (identifier \"a\") right: (identifier \"b\"))"
+    );
+    assert_eq!(
+        root_node.format_sexp(&SexpOptions {
+            indentation: 2,
+            ..SexpOptions::default()
+        }),
+        concat!(
+            "(program\n",
+            "  (assignment\n",
+            "    left: (identifier)\n",
+            "    right: (identifier))\n",
+            "  (assignment\n",
+            "    left: (identifier)\n",
+            "    right: (identifier)\n",
+            "    (MISSING \";\")))",
+        )
+    );
+
+    let assignment = root_node.named_child(1).unwrap();
+    assert_eq!(
+        assignment.format_sexp(&SexpOptions::default()),
+        assignment.to_sexp()
+    );
+    assert_eq!(
+        assignment.format_sexp(&SexpOptions {
+            label_missing_nodes: false,
+            ..SexpOptions::default()
+        }),
+        "(assignment left: (identifier) right: (identifier))"
+    );
+    assert_eq!(
+        assignment.format_sexp(&SexpOptions {
+            include_anonymous_nodes: true,
+            label_missing_nodes: false,
+            ..SexpOptions::default()
+        }),
+        "(assignment left: (identifier) \"=\" right: (identifier) \";\")"
+    );
+    assert_eq!(
+        assignment.sexp(&SexpOptions::default()),
+        assignment.to_sexp()
+    );
+}
+
+#[test]
+fn test_node_serialize_and_deserialize() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_node_serialization",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "pair"}},
+                "pair": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "key",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "="},
+                        {
+                            "type": "FIELD",
+                            "name": "value",
+                            "content": {"type": "SYMBOL", "name": "number"}
+                        }
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let source = "a = 1\nbc = 23\n=";
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let bytes = tree.root_node().serialize(source.as_bytes());
+    let root = DetachedNode::deserialize(&bytes, &language).unwrap();
+
+    fn assert_same_node(
+        node: Node,
+        detached: &DetachedNode,
+        field_name: Option<&str>,
+        source: &str,
+    ) {
+        assert_eq!(detached.kind, node.kind());
+        assert_eq!(detached.kind_id, node.kind_id());
+        assert_eq!(detached.is_named, node.is_named());
+        assert_eq!(detached.is_missing, node.is_missing());
+        assert_eq!(detached.has_error, node.has_error());
+        assert_eq!(detached.field_name, field_name);
+        assert_eq!(detached.byte_range(), node.byte_range());
+        assert_eq!(detached.start_position(), node.start_position());
+        assert_eq!(detached.end_position(), node.end_position());
+        assert_eq!(detached.children.len(), node.child_count());
+        if node.child_count() == 0 {
+            assert_eq!(
+                detached.text.as_deref(),
+                Some(&source.as_bytes()[node.byte_range()])
+            );
+        }
+
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            for child in &detached.children {
+                assert_same_node(cursor.node(), child, cursor.field_name(), source);
+                cursor.goto_next_sibling();
+            }
+        }
+    }
+    assert_same_node(tree.root_node(), &root, None, source);
+    assert!(root.has_error);
+
+    let pair = DetachedNode::deserialize(
+        &tree
+            .root_node()
+            .named_child(1)
+            .unwrap()
+            .serialize(source.as_bytes()),
+        &language,
+    )
+    .unwrap();
+    assert_eq!(pair.kind, "pair");
+    assert_eq!(pair.start_position(), Point::new(1, 0));
+    assert_eq!(pair.children[0].field_name, Some("key"));
+    assert_eq!(pair.children[0].text.as_deref(), Some(b"bc".as_ref()));
+    assert_eq!(pair.children[2].field_name, Some("value"));
+    assert_eq!(pair.children[2].text.as_deref(), Some(b"23".as_ref()));
+
+    // Bytes that were written with another version of the format are rejected.
+    let mut other_version = bytes.clone();
+    other_version[3] = SERIALIZATION_VERSION + 1;
+    assert_eq!(DetachedNode::deserialize(&other_version, &language), None);
+
+    // Incomplete bytes and trailing bytes are rejected.
+    assert_eq!(
+        DetachedNode::deserialize(&bytes[0..bytes.len() - 1], &language),
+        None
+    );
+    let mut extra_bytes = bytes.clone();
+    extra_bytes.push(0);
+    assert_eq!(DetachedNode::deserialize(&extra_bytes, &language), None);
+
+    // Deeply nested bytes are rejected without overflowing the stack. Each of these
+    // nodes claims to have one child, and the last child is never written.
+    assert!(pair.kind_id < 0x80);
+    let mut nested_bytes = bytes[0..4].to_vec();
+    for _ in 0..1_000_000 {
+        nested_bytes.extend_from_slice(&[pair.kind_id as u8, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+    assert_eq!(DetachedNode::deserialize(&nested_bytes, &language), None);
+}
+
 fn get_all_nodes(tree: &Tree) -> Vec<Node> {
     let mut result = Vec::new();
     let mut visited_children = false;
@@ -0,0 +1,329 @@
+use super::helpers::fixtures::get_test_language;
+use crate::generate::generate_parser_for_grammar;
+use crate::parse::{
+    pretty_print_sexp, write_comparison, write_extracted_nodes, write_graphml, write_json_tokens,
+    write_tree,
+};
+use tree_sitter::{Language, Parser, Tree};
+
+#[test]
+fn test_parse_write_extracted_nodes() {
+    let source = "a = 1;\nbc = 23;";
+    let tree = parse_assignments("test_parse_write_extracted_nodes", source);
+
+    let mut output = Vec::new();
+    write_extracted_nodes(
+        &mut output,
+        &tree,
+        source.as_bytes(),
+        &["identifier", "number"],
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        [
+            "identifier [0, 0] - [0, 1] \"a\"",
+            "number [0, 4] - [0, 5] \"1\"",
+            "identifier [1, 0] - [1, 2] \"bc\"",
+            "number [1, 5] - [1, 7] \"23\"",
+            "",
+        ]
+        .join("\n")
+    );
+
+    let mut output = Vec::new();
+    write_extracted_nodes(&mut output, &tree, source.as_bytes(), &["call"], false).unwrap();
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_parse_write_tree_with_missing_nodes() {
+    let source = "a = 1;\nb = 2";
+    let tree = parse_assignments("test_parse_write_tree_with_missing_nodes", source);
+
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, true, false, None, None).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        [
+            "(program [0, 0] - [1, 5]",
+            "  (assignment [0, 0] - [0, 6]",
+            "    name: (identifier [0, 0] - [0, 1])",
+            "    value: (number [0, 4] - [0, 5]))",
+            "  (assignment [1, 0] - [1, 5]",
+            "    name: (identifier [1, 0] - [1, 1])",
+            "    value: (number [1, 4] - [1, 5])))",
+            "",
+        ]
+        .join("\n")
+    );
+
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, false, true, None, None).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        [
+            "(program",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number))",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number)",
+            "    (MISSING \";\")))",
+            "",
+        ]
+        .join("\n")
+    );
+
+    let root_node = tree.root_node();
+    assert_eq!(
+        pretty_print_sexp(root_node, false, false, 30),
+        [
+            "(program",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number))",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number)))",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        pretty_print_sexp(root_node, false, true, 30),
+        [
+            "(program",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number))",
+            "  (assignment",
+            "    name: (identifier)",
+            "    value: (number)",
+            "    (MISSING \";\")))",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_parse_write_json_tokens() {
+    let source = "a = 1;\nb = 2";
+    let tree = parse_assignments("test_parse_write_json_tokens", source);
+
+    let mut output = Vec::new();
+    write_json_tokens(&mut output, &tree, source.as_bytes(), false).unwrap();
+    let tokens: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        tokens,
+        serde_json::json!([
+            {"type": "identifier", "text": "a", "start": 0, "end": 1},
+            {"type": "=", "text": "=", "start": 2, "end": 3},
+            {"type": "number", "text": "1", "start": 4, "end": 5},
+            {"type": ";", "text": ";", "start": 5, "end": 6},
+            {"type": "identifier", "text": "b", "start": 7, "end": 8},
+            {"type": "=", "text": "=", "start": 9, "end": 10},
+            {"type": "number", "text": "2", "start": 11, "end": 12},
+            {"type": ";", "text": "", "start": 12, "end": 12},
+        ])
+    );
+
+    // With `show_missing`, each token says whether it was inserted during error
+    // recovery.
+    let mut output = Vec::new();
+    write_json_tokens(&mut output, &tree, source.as_bytes(), true).unwrap();
+    let tokens: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+    let missing = tokens
+        .iter()
+        .map(|token| token["missing"].as_bool().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        missing,
+        &[false, false, false, false, false, false, false, true]
+    );
+}
+
+#[test]
+fn test_parse_write_tree_with_max_depth() {
+    let source = "a = 1;\nb = 2;";
+    let tree = parse_assignments("test_parse_write_tree_with_max_depth", source);
+
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, false, false, None, Some(1)).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "(program ...)\n");
+
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, false, false, None, Some(2)).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        ["(program", "  (assignment ...)", "  (assignment ...))", ""].join("\n")
+    );
+
+    // Nodes without named children are printed in full, even at the deepest level.
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, false, false, None, Some(3)).unwrap();
+    let full_tree = String::from_utf8(output).unwrap();
+    let mut output = Vec::new();
+    write_tree(&mut output, &tree, false, false, false, None, None).unwrap();
+    assert_eq!(full_tree, String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn test_parse_write_comparison() {
+    let language = get_assignments_language("test_parse_write_comparison");
+    let compare = |old_source: &str, new_source: &str| {
+        let mut output = Vec::new();
+        write_comparison(
+            &mut output,
+            language,
+            ("old", old_source.as_bytes()),
+            ("new", new_source.as_bytes()),
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    assert_eq!(compare("a = 1;", "a = 1;"), "old -> new: no changes\n");
+
+    // Renaming an identifier only changes the text.
+    assert_eq!(
+        compare("a = 1;\nb = 2;", "a = 1;\nc = 2;"),
+        [
+            "text",
+            "  old: [1, 0] - [1, 1] \"b\"",
+            "  new: [1, 0] - [1, 1] \"c\"",
+            "",
+        ]
+        .join("\n")
+    );
+
+    // Adding an assignment changes the syntax too.
+    let output = compare("a = 1;", "a = 1;\nb = 2;");
+    assert!(output.starts_with(
+        &[
+            "text",
+            "  old: [0, 6] - [0, 6] \"\"",
+            "  new: [0, 6] - [1, 6] \"\\nb = 2;\"",
+            "changed [0, 6] - [1, 6]",
+            "",
+        ]
+        .join("\n")
+    ));
+    assert!(output.ends_with(
+        &[
+            "  new:",
+            "    (assignment [1, 0] - [1, 6]",
+            "      name: (identifier [1, 0] - [1, 1] \"b\")",
+            "      value: (number [1, 4] - [1, 5] \"2\"))",
+            "",
+        ]
+        .join("\n")
+    ));
+}
+
+#[test]
+fn test_parse_write_graphml() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_parse_write_graphml",
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "tag"}},
+                "tag": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "<"},
+                        {"type": "SYMBOL", "name": "name"},
+                        {"type": "STRING", "value": ">"}
+                    ]
+                },
+                "name": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let source = "<a>";
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut output = Vec::new();
+    write_graphml(&mut output, &tree, source.as_bytes(), false).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let node_types = output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("<data key=\"type\">"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        node_types,
+        &[
+            "program</data>",
+            "tag</data>",
+            "&lt;</data>",
+            "name</data>",
+            "&gt;</data>",
+        ]
+    );
+    let texts = output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("<data key=\"text\">"))
+        .collect::<Vec<_>>();
+    assert_eq!(texts, &["&lt;</data>", "a</data>", "&gt;</data>"]);
+    let edges = output
+        .lines()
+        .filter(|line| line.trim().starts_with("<edge"))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        &[
+            "<edge source=\"n0\" target=\"n1\"/>",
+            "<edge source=\"n1\" target=\"n2\"/>",
+            "<edge source=\"n1\" target=\"n3\"/>",
+            "<edge source=\"n1\" target=\"n4\"/>",
+        ]
+    );
+    assert!(output.trim_end().ends_with("</graphml>"));
+}
+
+fn parse_assignments(name: &str, source: &str) -> Tree {
+    let mut parser = Parser::new();
+    parser.set_language(get_assignments_language(name)).unwrap();
+    parser.parse(source, None).unwrap()
+}
+
+// A grammar for a list of assignments like `a = 1;`, where each assignment has
+// `name` and `value` fields.
+fn get_assignments_language(name: &str) -> Language {
+    let grammar_json = r#"{
+        "name": "NAME",
+        "extras": [{"type": "PATTERN", "value": "\\s"}],
+        "rules": {
+            "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "assignment"}},
+            "assignment": {
+                "type": "SEQ",
+                "members": [
+                    {
+                        "type": "FIELD",
+                        "name": "name",
+                        "content": {"type": "SYMBOL", "name": "identifier"}
+                    },
+                    {"type": "STRING", "value": "="},
+                    {
+                        "type": "FIELD",
+                        "name": "value",
+                        "content": {"type": "SYMBOL", "name": "number"}
+                    },
+                    {"type": "STRING", "value": ";"}
+                ]
+            },
+            "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+            "number": {"type": "PATTERN", "value": "\\d+"}
+        }
+    }"#
+    .replace("NAME", name);
+    let (parser_name, parser_code) = generate_parser_for_grammar(&grammar_json).unwrap();
+    get_test_language(&parser_name, &parser_code, None)
+}
@@ -5,7 +5,7 @@ use crate::generate::generate_parser_for_grammar;
 use crate::parse::{perform_edit, Edit};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{thread, time};
-use tree_sitter::{IncludedRangesError, InputEdit, LogType, Parser, Point, Range};
+use tree_sitter::{IncludedRangesError, InputEdit, LogType, ParseResult, Parser, Point, Range};
 
 #[test]
 fn test_parsing_simple_string() {
@@ -96,6 +96,42 @@ fn test_parsing_with_debug_graph_enabled() {
     }
 }
 
+#[test]
+#[cfg(unix)]
+fn test_parsing_with_debug_graphs_written_to_path() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_parsing_with_debug_graphs_written_to_path",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "PATTERN", "value": "[a-z]+"}
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graphs.dot");
+    std::fs::write(&path, "stale contents").unwrap();
+    parser.print_dot_graphs_to(&path).unwrap();
+    parser.parse("abc def", None).unwrap();
+    parser.stop_printing_dot_graphs();
+
+    let graphs = std::fs::read_to_string(&path).unwrap();
+    assert!(graphs.contains("digraph"));
+    assert!(!graphs.contains("stale contents"));
+
+    let missing_path = dir.path().join("missing").join("graphs.dot");
+    assert!(parser.print_dot_graphs_to(&missing_path).is_err());
+}
+
 #[test]
 fn test_parsing_with_custom_utf8_input() {
     let mut parser = Parser::new();
@@ -178,6 +214,52 @@ fn test_parsing_with_custom_utf16_input() {
     assert_eq!(root.child(0).unwrap().kind(), "function_item");
 }
 
+#[test]
+fn test_parsing_utf16_text_with_non_ascii_characters() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_utf16_words",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                "word": {"type": "PATTERN", "value": "[^\\s]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source_code = "αβγ\n😀 δ";
+    let utf16_source_code = source_code.encode_utf16().collect::<Vec<_>>();
+    let tree = parser.parse_utf16(&utf16_source_code, None).unwrap();
+    let root = tree.root_node();
+    assert_eq!(root.to_sexp(), "(program (word) (word) (word))");
+
+    // Byte offsets and columns count the bytes of the UTF16 encoding, so they are
+    // twice the number of UTF16 code units.
+    let words = [
+        root.child(0).unwrap(),
+        root.child(1).unwrap(),
+        root.child(2).unwrap(),
+    ];
+    assert_eq!(
+        words.iter().map(|w| w.byte_range()).collect::<Vec<_>>(),
+        &[0..6, 8..12, 14..16]
+    );
+    assert_eq!(words[2].start_position(), Point::new(1, 6));
+    assert_eq!(
+        words
+            .iter()
+            .map(|w| String::from_utf16(w.utf16_text(&utf16_source_code)).unwrap())
+            .collect::<Vec<_>>(),
+        &["αβγ", "😀", "δ"]
+    );
+}
+
 #[test]
 fn test_parsing_with_callback_returning_owned_strings() {
     let mut parser = Parser::new();
@@ -610,6 +692,67 @@ fn test_parsing_with_a_timeout_and_a_reset() {
     );
 }
 
+#[test]
+fn test_parsing_bytes_with_a_timeout() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_parsing_bytes_with_a_timeout",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    match parser.parse_bytes_with_timeout(b"a b", 0) {
+        ParseResult::NoLanguage => {}
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    parser.set_timeout_micros(12345);
+
+    // A long document can't be parsed within one millisecond.
+    let long_source = "a ".repeat(1_000_000);
+    match parser.parse_bytes_with_timeout(long_source.as_bytes(), 1) {
+        ParseResult::Timeout => {}
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(parser.timeout_micros(), 12345);
+
+    // The halted parse is not resumed, so the next document is parsed from scratch.
+    match parser.parse_bytes_with_timeout(b"a b", 0) {
+        ParseResult::Complete(tree) => {
+            assert_eq!(tree.root_node().to_sexp(), "(program (word) (word))")
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // A parse that was halted by `parse` isn't resumed either.
+    parser.set_timeout_micros(1000);
+    assert!(parser.parse(&long_source, None).is_none());
+    match parser.parse_bytes_with_timeout(b"a b", 0) {
+        ParseResult::Complete(tree) => {
+            assert_eq!(tree.root_node().to_sexp(), "(program (word) (word))")
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(parser.timeout_micros(), 1000);
+
+    let flag = AtomicUsize::new(1);
+    unsafe { parser.set_cancellation_flag(Some(&flag)) };
+    match parser.parse_bytes_with_timeout(long_source.as_bytes(), 0) {
+        ParseResult::Cancelled => {}
+        result => panic!("Unexpected result {:?}", result),
+    }
+    unsafe { parser.set_cancellation_flag(None) };
+}
+
 #[test]
 fn test_parsing_with_a_timeout_and_implicit_reset() {
     allocations::record(|| {
@@ -643,6 +786,108 @@ fn test_parsing_with_a_timeout_and_implicit_reset() {
     });
 }
 
+#[test]
+fn test_parsing_until_error() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_parsing_until_error",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "SEQ",
+                        "members": [
+                            {"type": "STRING", "value": "("},
+                            {"type": "STRING", "value": "x"},
+                            {"type": "STRING", "value": ")"}
+                        ]
+                    }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let (offset, tree) = parser.parse_until_error(b"(x) (x)").unwrap();
+    assert_eq!(offset, 7);
+    assert!(!tree.root_node().has_error());
+
+    // The error begins at the parenthesis that can't be completed.
+    let (offset, tree) = parser.parse_until_error(b"(x) (x) (y) (x)").unwrap();
+    assert_eq!(offset, 8);
+    assert!(tree.root_node().has_error());
+
+    let (offset, _) = parser.parse_until_error(b"(x) (x").unwrap();
+    assert_eq!(offset, 6);
+}
+
+#[test]
+fn test_parsing_with_thread_local_parsers() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_parsing_with_thread_local_parsers",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "PATTERN", "value": "[a-z]+"}
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    // Each thread reuses its own parser, but the settings from one call are not
+    // kept for the next.
+    let thread_timeouts = (1..=2)
+        .map(|i| {
+            thread::spawn(move || {
+                Parser::with_thread_local(language, |parser| {
+                    parser.set_timeout_micros(i * 1000000);
+                    parser
+                        .set_included_ranges(&[Range {
+                            start_byte: 4,
+                            end_byte: 7,
+                            start_point: Point::new(0, 4),
+                            end_point: Point::new(0, 7),
+                        }])
+                        .unwrap();
+                    parser.set_logger(Some(Box::new(|_, _| {})));
+
+                    // The flag is dropped before the parser is used again.
+                    let flag = AtomicUsize::new(1);
+                    unsafe { parser.set_cancellation_flag(Some(&flag)) };
+                })
+                .unwrap();
+                Parser::with_thread_local(language, |parser| {
+                    assert!(unsafe { parser.cancellation_flag() }.is_none());
+                    assert!(parser.logger().is_none());
+                    let tree = parser.parse("abc def", None).unwrap();
+                    assert_eq!(tree.root_node().to_sexp(), "(program)");
+                    assert_eq!(tree.root_node().start_byte(), 0);
+
+                    // A nested call uses a separate parser.
+                    let inner_timeout = Parser::with_thread_local(language, |inner_parser| {
+                        inner_parser.timeout_micros()
+                    });
+                    (parser.timeout_micros(), inner_timeout.unwrap())
+                })
+                .unwrap()
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(thread_timeouts, &[(0, 0), (0, 0)]);
+}
+
 #[test]
 fn test_parsing_with_timeout_and_no_completion() {
     allocations::record(|| {
@@ -1104,6 +1349,57 @@ fn test_parsing_with_included_ranges_and_missing_tokens() {
     assert_eq!(root.child(3).unwrap().start_byte(), 4);
 }
 
+// Error recovery
+
+#[test]
+fn test_parsing_with_a_limit_on_error_recovery_nodes() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_missing_token_limit",
+            "rules": {
+                "program": {
+                    "type": "REPEAT1",
+                    "content": {
+                        "type": "SEQ",
+                        "members": [
+                            {"type": "SYMBOL", "name": "statement"},
+                            {"type": "STRING", "value": ";"}
+                        ]
+                    }
+                },
+                "statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "a"},
+                        {"type": "STRING", "value": "b"}
+                    ]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    assert_eq!(parser.max_error_recovery_nodes(), u32::MAX);
+
+    let source_code = "a;a;a;";
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp().matches("MISSING").count(), 3);
+
+    parser.set_max_error_recovery_nodes(1);
+    assert_eq!(parser.max_error_recovery_nodes(), 1);
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp().matches("MISSING").count(), 1);
+
+    parser.set_max_error_recovery_nodes(0);
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp().matches("MISSING").count(), 0);
+    assert!(tree.root_node().has_error());
+}
+
 fn simple_range(start: usize, end: usize) -> Range {
     Range {
         start_byte: start,
@@ -1,9 +1,13 @@
 use super::helpers::allocations;
-use super::helpers::fixtures::get_language;
+use super::helpers::fixtures::{get_language, get_test_language};
+use crate::generate::generate_parser_for_grammar;
+use crate::query::lsp_diagnostics;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt::Write;
 use tree_sitter::{
-    Language, Node, Parser, Query, QueryCapture, QueryCursor, QueryError, QueryMatch,
-    QueryPredicate, QueryPredicateArg, QueryProperty,
+    Language, Node, Parser, Query, QueryCapture, QueryCursor, QueryError, QueryErrorKind,
+    QueryMatch, QueryPatternPredicate, QueryPredicate, QueryPredicateArg, QueryProperty,
 };
 
 #[test]
@@ -17,108 +21,126 @@ fn test_query_errors_on_invalid_syntax() {
         // Mismatched parens
         assert_eq!(
             Query::new(language, "(if_statement"),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 13,
+                message: [
                     "(if_statement", //
                     "             ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, "; comment 1\n; comment 2\n  (if_statement))"),
-            Err(QueryError::Syntax(
-                3,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 2,
+                column: 16,
+                message: [
                     "  (if_statement))", //
                     "                ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
 
         // Return an error at the *beginning* of a bare identifier not followed a colon.
         // If there's a colon but no pattern, return an error at the end of the colon.
         assert_eq!(
             Query::new(language, "(if_statement identifier)"),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 14,
+                message: [
                     "(if_statement identifier)", //
                     "              ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, "(if_statement condition:)"),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 24,
+                message: [
                     "(if_statement condition:)", //
                     "                        ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
 
         // Return an error at the beginning of an unterminated string.
         assert_eq!(
             Query::new(language, r#"(identifier) "h "#),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 13,
+                message: [
                     r#"(identifier) "h "#, //
                     r#"             ^"#,
                 ]
                 .join("\n")
-            ))
+            })
         );
 
         assert_eq!(
             Query::new(language, r#"((identifier) ()"#),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 15,
+                message: [
                     "((identifier) ()", //
                     "               ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, r#"((identifier) [])"#),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 15,
+                message: [
                     "((identifier) [])", //
                     "               ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, r#"((identifier) (#a)"#),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 18,
+                message: [
                     "((identifier) (#a)", //
                     "                  ^",
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, r#"((identifier) @x (#eq? @x a"#),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 27,
+                message: [
                     r#"((identifier) @x (#eq? @x a"#,
                     r#"                           ^"#,
                 ]
                 .join("\n")
-            ))
+            })
         );
     });
 }
@@ -130,23 +152,48 @@ fn test_query_errors_on_invalid_symbols() {
 
         assert_eq!(
             Query::new(language, "(clas)"),
-            Err(QueryError::NodeType(1, "clas".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::NodeType,
+                row: 0,
+                column: 1,
+                message: "clas".to_string()
+            })
         );
         assert_eq!(
             Query::new(language, "(if_statement (arrayyyyy))"),
-            Err(QueryError::NodeType(1, "arrayyyyy".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::NodeType,
+                row: 0,
+                column: 15,
+                message: "arrayyyyy".to_string()
+            })
         );
         assert_eq!(
             Query::new(language, "(if_statement condition: (non_existent3))"),
-            Err(QueryError::NodeType(1, "non_existent3".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::NodeType,
+                row: 0,
+                column: 26,
+                message: "non_existent3".to_string()
+            })
         );
         assert_eq!(
             Query::new(language, "(if_statement condit: (identifier))"),
-            Err(QueryError::Field(1, "condit".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::Field,
+                row: 0,
+                column: 14,
+                message: "condit".to_string()
+            })
         );
         assert_eq!(
             Query::new(language, "(if_statement conditioning: (identifier))"),
-            Err(QueryError::Field(1, "conditioning".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::Field,
+                row: 0,
+                column: 14,
+                message: "conditioning".to_string()
+            })
         );
     });
 }
@@ -158,24 +205,35 @@ fn test_query_errors_on_invalid_conditions() {
 
         assert_eq!(
             Query::new(language, "((identifier) @id (@id))"),
-            Err(QueryError::Syntax(
-                1,
-                [
+            Err(QueryError {
+                kind: QueryErrorKind::Syntax,
+                row: 0,
+                column: 19,
+                message: [
                     "((identifier) @id (@id))", //
                     "                   ^"
                 ]
                 .join("\n")
-            ))
+            })
         );
         assert_eq!(
             Query::new(language, "((identifier) @id (#eq? @id))"),
-            Err(QueryError::Predicate(
-                "Wrong number of arguments to #eq? predicate. Expected 2, got 1.".to_string()
-            ))
+            Err(QueryError {
+                kind: QueryErrorKind::Predicate,
+                row: 0,
+                column: 0,
+                message: "Wrong number of arguments to #eq? predicate. Expected 2, got 1."
+                    .to_string()
+            })
         );
         assert_eq!(
             Query::new(language, "((identifier) @id (#eq? @id @ok))"),
-            Err(QueryError::Capture(1, "ok".to_string()))
+            Err(QueryError {
+                kind: QueryErrorKind::Capture,
+                row: 0,
+                column: 29,
+                message: "ok".to_string()
+            })
         );
     });
 }
@@ -1154,6 +1212,36 @@ fn test_query_matches_within_byte_range() {
     });
 }
 
+#[test]
+fn test_query_cursor_visited_node_count() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
+
+        let source = "[a, b, c, d, e, f, g]";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        assert_eq!(cursor.visited_node_count(), 0);
+
+        // The program, the statement, the array, and the array's 15 children.
+        let matches = cursor.matches(&query, tree.root_node(), to_callback(source));
+        assert_eq!(matches.count(), 7);
+        assert_eq!(cursor.visited_node_count(), 18);
+
+        // Nodes outside of the cursor's range are not counted.
+        let matches =
+            cursor
+                .set_byte_range(5, 15)
+                .matches(&query, tree.root_node(), to_callback(source));
+        assert_eq!(matches.count(), 3);
+        assert_eq!(cursor.visited_node_count(), 10);
+    });
+}
+
 #[test]
 fn test_query_matches_different_queries_same_cursor() {
     allocations::record(|| {
@@ -1439,6 +1527,65 @@ fn test_query_captures_with_predicates() {
     });
 }
 
+#[test]
+fn test_query_predicates_for_pattern() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let query = Query::new(
+            language,
+            r#"
+            ((call_expression (identifier) @foo (arguments (identifier) @bar))
+             (#eq? @foo @bar)
+             (#not-eq? @foo "require")
+             (#match? @bar "^[A-Z]")
+             (#set! name something)
+             (#something! @foo omg))
+
+            ((property_identifier) @baz
+             (#is? cool)
+             (#is-not? name something))
+
+            (identifier) @qux"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.predicates_for_pattern(0),
+            &[
+                QueryPatternPredicate::Eq {
+                    capture: 0,
+                    value: QueryPredicateArg::Capture(1),
+                },
+                QueryPatternPredicate::NotEq {
+                    capture: 0,
+                    value: QueryPredicateArg::String("require".to_string().into_boxed_str()),
+                },
+                QueryPatternPredicate::Match {
+                    capture: 1,
+                    regex: "^[A-Z]".to_string().into_boxed_str(),
+                },
+                QueryPatternPredicate::Set(QueryProperty::new("name", Some("something"), None)),
+                QueryPatternPredicate::Other(QueryPredicate {
+                    operator: "something!".to_string().into_boxed_str(),
+                    args: vec![
+                        QueryPredicateArg::Capture(0),
+                        QueryPredicateArg::String("omg".to_string().into_boxed_str()),
+                    ],
+                }),
+            ]
+        );
+        assert_eq!(
+            query.predicates_for_pattern(1),
+            &[
+                QueryPatternPredicate::Is(QueryProperty::new("cool", None, None)),
+                QueryPatternPredicate::IsNot(QueryProperty::new("name", Some("something"), None)),
+            ]
+        );
+        assert_eq!(query.predicates_for_pattern(2), &[]);
+    });
+}
+
 #[test]
 fn test_query_captures_with_quoted_predicate_args() {
     allocations::record(|| {
@@ -1780,6 +1927,87 @@ fn test_query_captures_ordered_by_both_start_and_end_positions() {
     });
 }
 
+#[test]
+fn test_query_capture_ordering_and_display() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_capture_ordering_and_display",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "call"},
+                            {"type": "SYMBOL", "name": "word"}
+                        ]
+                    }
+                },
+                "call": {
+                    "type": "SEQ",
+                    "members": [
+                        {
+                            "type": "FIELD",
+                            "name": "function",
+                            "content": {"type": "SYMBOL", "name": "word"}
+                        },
+                        {"type": "STRING", "value": "("},
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let query = Query::new(
+        language,
+        r#"
+        (word) @variable
+        (call function: (word) @function)
+        (call) @call
+        "#,
+    )
+    .unwrap();
+
+    let source = "a(b)";
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(&source, None).unwrap();
+    let mut cursor = QueryCursor::new();
+
+    let mut captures = cursor
+        .matches(&query, tree.root_node(), to_callback(source))
+        .flat_map(|m| m.captures.iter().cloned())
+        .collect::<Vec<_>>();
+    captures.sort();
+    assert_eq!(
+        captures
+            .iter()
+            .map(|capture| capture.display(&query, source.as_bytes()).to_string())
+            .collect::<Vec<_>>(),
+        &[
+            "@variable: \"a\"",
+            "@function: \"a\"",
+            "@call: \"a(b)\"",
+            "@variable: \"b\"",
+        ]
+    );
+    assert_eq!(captures[0].to_node(), captures[1].node);
+    assert!(captures[0] < captures[1]);
+
+    let unique_captures = captures
+        .iter()
+        .chain(captures.iter())
+        .cloned()
+        .collect::<HashSet<_>>();
+    assert_eq!(unique_captures.len(), captures.len());
+}
+
 #[test]
 fn test_query_captures_with_matches_removed() {
     allocations::record(|| {
@@ -1901,6 +2129,13 @@ fn test_query_start_byte_for_pattern() {
         query.start_byte_for_pattern(7),
         patterns_1.len() + patterns_2.len()
     );
+
+    // Every pattern starts within the query's source, at the beginning of its text.
+    for i in 0..query.pattern_count() {
+        let start_byte = query.start_byte_for_pattern(i);
+        assert!(start_byte < source.len());
+        assert!(source[start_byte..].starts_with(|c| c == '"' || c == '('));
+    }
 }
 
 #[test]
@@ -1935,6 +2170,57 @@ fn test_query_capture_names() {
     });
 }
 
+#[test]
+fn test_query_capture_name_for_id() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_capture_name_for_id",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let query = Query::new(
+        language,
+        r#"
+        (program (word) @first-word (number) @number)
+        (number) @number
+        (word) @word
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(query.capture_names(), &["first-word", "number", "word"]);
+    assert_eq!(query.capture_name_for_id(0), Some("first-word"));
+    assert_eq!(query.capture_name_for_id(2), Some("word"));
+    assert_eq!(query.capture_name_for_id(3), None);
+
+    let source = "a 1";
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let mut cursor = QueryCursor::new();
+    let capture_names = cursor
+        .captures(&query, tree.root_node(), to_callback(source))
+        .map(|(m, i)| query.capture_name_for_id(m.captures[i].index).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(capture_names, &["first-word", "word", "number", "number"]);
+}
+
 #[test]
 fn test_query_with_no_patterns() {
     allocations::record(|| {
@@ -2013,6 +2299,50 @@ fn test_query_disable_pattern() {
     });
 }
 
+#[test]
+fn test_query_enable_pattern() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let mut query = Query::new(
+            language,
+            "
+                (function_declaration
+                    name: (identifier) @name)
+                (function_declaration
+                    body: (statement_block) @body)
+                (_ (number) @number)
+            ",
+        )
+        .unwrap();
+
+        let source = "function a() { return 1; }";
+
+        query.disable_pattern(0);
+        query.disable_pattern(2);
+        assert_query_matches(
+            language,
+            &query,
+            source,
+            &[(1, vec![("body", "{ return 1; }")])],
+        );
+
+        // re-enabling a pattern that is not disabled has no effect
+        query.enable_pattern(1);
+        query.enable_pattern(0);
+        query.enable_pattern(2);
+        assert_query_matches(
+            language,
+            &query,
+            source,
+            &[
+                (0, vec![("name", "a")]),
+                (1, vec![("body", "{ return 1; }")]),
+                (2, vec![("number", "1")]),
+            ],
+        );
+    });
+}
+
 fn assert_query_matches(
     language: Language,
     query: &Query,
@@ -2068,3 +2398,259 @@ fn format_captures<'a>(
 fn to_callback<'a>(source: &'a str) -> impl Fn(Node) -> &'a [u8] {
     move |n| &source.as_bytes()[n.byte_range()]
 }
+
+#[test]
+fn test_query_error_locations() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_error_locations",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let error = Query::new(language, "(word) @w\n(number) @n\n  (program (wrd))").unwrap_err();
+    assert_eq!(error.kind, QueryErrorKind::NodeType);
+    assert_eq!((error.row, error.column), (2, 12));
+    assert_eq!(error.message, "wrd");
+
+    let error = Query::new(language, "(word)\n\n(number) @n)").unwrap_err();
+    assert_eq!(error.kind, QueryErrorKind::Syntax);
+    assert_eq!((error.row, error.column), (2, 11));
+    assert_eq!(error.message, "(number) @n)\n           ^");
+
+    // Predicate errors point to the start of the pattern containing the predicate.
+    let error = Query::new(language, "(word) @w\n\n  ((number) @n (#eq? @n))").unwrap_err();
+    assert_eq!(error.kind, QueryErrorKind::Predicate);
+    assert_eq!((error.row, error.column), (2, 2));
+}
+
+#[test]
+fn test_language_create_query_checked() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_language_create_query_checked",
+            "rules": {
+                "program": {"type": "SYMBOL", "name": "word"},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let query = language
+        .create_query_checked("(program (word) @w)")
+        .unwrap();
+    assert_eq!(query.pattern_count(), 1);
+    assert_eq!(query.capture_names(), &["w"]);
+
+    assert_eq!(
+        language
+            .create_query_checked("(program (wrd))")
+            .unwrap_err(),
+        Query::new(language, "(program (wrd))").unwrap_err()
+    );
+}
+
+#[test]
+fn test_query_cursor_exec_with_context() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_cursor_exec_with_context",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let query = Query::new(language, "(number) @number (word) @word").unwrap();
+    let source = "one 2 three 4 five";
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.exec_with_context(
+        &query,
+        tree.root_node(),
+        to_callback(source),
+        RefCell::new(Vec::new()),
+        |m, matches| {
+            let node = m.captures[0].node;
+            matches
+                .borrow_mut()
+                .push((m.pattern_index, node.utf8_text(source.as_bytes()).unwrap()));
+        },
+    );
+    assert_eq!(
+        matches.into_inner(),
+        &[(1, "one"), (0, "2"), (1, "three"), (0, "4"), (1, "five")]
+    );
+}
+
+#[test]
+fn test_query_enable_pattern_preserves_match_order() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_enable_pattern_preserves_match_order",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "pair"},
+                            {"type": "SYMBOL", "name": "word"}
+                        ]
+                    }
+                },
+                "pair": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "STRING", "value": "="},
+                        {"type": "SYMBOL", "name": "number"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let mut query = Query::new(
+        language,
+        "
+        (pair) @pair
+        (_ (word) @child)
+        (pair (number) @number)
+        (_) @node
+        (pair) @whole_pair
+        ",
+    )
+    .unwrap();
+
+    let source = "a = 1 b c = 2";
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let get_matches = |query: &Query| {
+        let mut cursor = QueryCursor::new();
+        let matches = cursor.matches(query, tree.root_node(), to_callback(source));
+        format!("{:?}", collect_matches(matches, query, source))
+    };
+
+    let original_matches = get_matches(&query);
+    for index in 0..query.pattern_count() {
+        query.disable_pattern(index);
+    }
+    for &index in &[4, 3, 0, 1, 2] {
+        query.enable_pattern(index);
+    }
+    assert_eq!(get_matches(&query), original_matches);
+}
+
+#[test]
+fn test_query_lsp_diagnostics() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_query_lsp_diagnostics",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-zé]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let query = Query::new(
+        language,
+        r#"
+        ((number) @diagnostic.warning (#set! message "Numbers are deprecated"))
+        (word) @diagnostic.error
+        (word) @not-a-diagnostic
+        "#,
+    )
+    .unwrap();
+
+    // Columns are measured in UTF16 code units, so `é` only counts once.
+    let source = "é 12\nab";
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let diagnostics = lsp_diagnostics(&query, &mut QueryCursor::new(), &tree, source.as_bytes());
+    assert_eq!(
+        serde_json::to_value(&diagnostics).unwrap(),
+        serde_json::json!([
+            {
+                "range": {
+                    "start": {"line": 0, "character": 0},
+                    "end": {"line": 0, "character": 1}
+                },
+                "severity": 1,
+                "message": "word",
+                "source": "tree-sitter"
+            },
+            {
+                "range": {
+                    "start": {"line": 0, "character": 2},
+                    "end": {"line": 0, "character": 4}
+                },
+                "severity": 2,
+                "message": "Numbers are deprecated",
+                "source": "tree-sitter"
+            },
+            {
+                "range": {
+                    "start": {"line": 1, "character": 0},
+                    "end": {"line": 1, "character": 2}
+                },
+                "severity": 1,
+                "message": "word",
+                "source": "tree-sitter"
+            }
+        ])
+    );
+}
@@ -166,6 +166,37 @@ fn test_tags_javascript() {
     assert_eq!(tags[2].docs, None);
 }
 
+#[test]
+fn test_tags_with_docs() {
+    let language = get_language("javascript");
+    let tags_config = TagsConfiguration::new(language, JS_TAG_QUERY, "").unwrap();
+    let source = br#"
+    // Data about a customer.
+    class Customer {
+        getAge() {
+        }
+    }
+    "#;
+
+    let mut tag_context = TagsContext::new();
+    let tags = tag_context
+        .tag_with_docs(&tags_config, source, None)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        tags.iter()
+            .map(|t| (t.name.as_str(), t.tag.docs.as_ref().map(String::as_str)))
+            .collect::<Vec<_>>(),
+        &[
+            ("Customer", Some("Data about a customer.")),
+            ("getAge", None),
+        ]
+    );
+    assert_eq!(tags[0].tag.kind, TagKind::Class);
+}
+
 #[test]
 fn test_tags_ruby() {
     let language = get_language("ruby");
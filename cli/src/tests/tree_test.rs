@@ -1,8 +1,11 @@
 use super::helpers::edits::invert_edit;
-use super::helpers::fixtures::get_language;
-use crate::parse::{perform_edit, Edit};
-use std::str;
-use tree_sitter::{InputEdit, Parser, Point, Range, Tree};
+use super::helpers::fixtures::{fixtures_dir, get_language, get_test_language};
+use crate::generate::generate_parser_for_grammar;
+use crate::parse::{
+    find_ambiguities, perform_edit, pretty_print_sexp, Ambiguity, Edit, GrammarCoverage,
+};
+use std::{fs, str};
+use tree_sitter::{ChangeSet, ChangeSetError, InputEdit, Parser, Point, Range, Tree};
 
 #[test]
 fn test_tree_edit() {
@@ -262,6 +265,50 @@ fn test_tree_cursor_fields() {
     assert_eq!(cursor.field_name(), Some("parameters"));
 }
 
+#[test]
+fn test_tree_cursor_reset() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+
+    let tree = parser.parse("a(b); c.d(e);", None).unwrap();
+    let first_statement = tree.root_node().named_child(0).unwrap();
+    let second_statement = tree.root_node().named_child(1).unwrap();
+
+    let mut cursor = tree.walk();
+    assert!(cursor.goto_first_child());
+    assert!(cursor.goto_first_child());
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "identifier");
+    assert_eq!(cursor.field_name(), Some("function"));
+
+    // After a reset, the cursor's root is the given node, so it can't move to the
+    // node's parent or siblings.
+    cursor.reset(second_statement);
+    assert_eq!(cursor.node(), second_statement);
+    assert_eq!(cursor.field_name(), None);
+    assert!(!cursor.goto_parent());
+    assert!(!cursor.goto_next_sibling());
+    assert_eq!(cursor.node(), second_statement);
+
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "call_expression");
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "member_expression");
+    assert_eq!(cursor.field_name(), Some("function"));
+    assert!(cursor.goto_parent());
+    assert!(cursor.goto_parent());
+    assert_eq!(cursor.node(), second_statement);
+
+    // The cursor can be reset to a node that precedes its current node.
+    cursor.reset(first_statement);
+    assert_eq!(cursor.node(), first_statement);
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "call_expression");
+    assert!(cursor.goto_next_sibling());
+    assert_eq!(cursor.node().kind(), ";");
+    assert!(!cursor.goto_next_sibling());
+}
+
 #[test]
 fn test_tree_node_equality() {
     let mut parser = Parser::new();
@@ -274,6 +321,257 @@ fn test_tree_node_equality() {
     assert_ne!(node1.child(0).unwrap(), node2);
 }
 
+#[test]
+fn test_tree_is_valid() {
+    let grammar_json = r#"
+        {
+            "name": "test_grammar_for_tree_is_valid",
+            "rules": {
+                "program": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#;
+    let (parser_name, parser_code) = generate_parser_for_grammar(grammar_json).unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let (parser_name, parser_code) =
+        generate_parser_for_grammar(&grammar_json.replace("is_valid", "is_valid_2")).unwrap();
+    let other_language = get_test_language(&parser_name, &parser_code, None);
+
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("abc", None).unwrap();
+    assert!(tree.is_valid(&language));
+    assert!(!tree.is_valid(&other_language));
+
+    parser.set_language(other_language).unwrap();
+    let tree = parser.parse("abc", None).unwrap();
+    assert!(tree.is_valid(&other_language));
+    assert!(!tree.is_valid(&language));
+}
+
+#[test]
+fn test_tree_byte_range() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"
+        {
+            "name": "test_grammar_for_tree_byte_range",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "word"}
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(language).unwrap();
+
+    let source = "one two three four";
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.byte_range(), 0..source.len());
+
+    let text = source.as_bytes().to_vec();
+    parser
+        .set_included_ranges(&[range_of(&text, "two "), range_of(&text, "three")])
+        .unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program (word) (word))");
+    assert_eq!(tree.byte_range(), 4..13);
+
+    // The tree's range is unaffected by the parser's later configuration.
+    parser.set_included_ranges(&[]).unwrap();
+    assert_eq!(tree.byte_range(), 4..13);
+}
+
+#[test]
+fn test_tree_pretty_print_sexp() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_tree_pretty_print_sexp",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "list"}},
+                "list": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "["},
+                        {
+                            "type": "REPEAT",
+                            "content": {
+                                "type": "FIELD",
+                                "name": "item",
+                                "content": {
+                                    "type": "CHOICE",
+                                    "members": [
+                                        {"type": "SYMBOL", "name": "list"},
+                                        {"type": "SYMBOL", "name": "word"}
+                                    ]
+                                }
+                            }
+                        },
+                        {"type": "STRING", "value": "]"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let tree = parser.parse("[a [b c]] [d]", None).unwrap();
+    let root_node = tree.root_node();
+
+    assert_eq!(
+        pretty_print_sexp(root_node, false, true, 100),
+        root_node.to_sexp()
+    );
+    assert_eq!(
+        pretty_print_sexp(root_node, false, true, 60),
+        [
+            "(program",
+            "  (list item: (word) item: (list item: (word) item: (word)))",
+            "  (list item: (word)))",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        pretty_print_sexp(root_node, false, true, 50),
+        [
+            "(program",
+            "  (list",
+            "    item: (word)",
+            "    item: (list item: (word) item: (word)))",
+            "  (list item: (word)))",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        pretty_print_sexp(root_node.child(1).unwrap(), true, true, 0),
+        [
+            "(list [0, 10] - [0, 13]",
+            "  item: (word [0, 11] - [0, 12]))"
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_tree_grammar_coverage() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_tree_grammar_coverage",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_item"}},
+                "_item": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "word"},
+                        {"type": "SYMBOL", "name": "number"}
+                    ]
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "\\d+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let mut coverage = GrammarCoverage::new(
+        ["program", "_item", "word", "number"]
+            .iter()
+            .map(|name| name.to_string()),
+    );
+    coverage.add_tree(&parser.parse("a b", None).unwrap());
+    coverage.add_tree(&parser.parse("c", None).unwrap());
+    assert_eq!(coverage.unused_rule_names(), &["number"]);
+    assert_eq!(
+        coverage.to_string(),
+        concat!(
+            "program: 2\n",
+            "word: 3\n",
+            "number: 0\n",
+            "2 of 3 rules (66.67%) appear in the syntax trees\n",
+            "Rules that never appear: number\n",
+        )
+    );
+}
+
+#[test]
+fn test_tree_find_ambiguities() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_tree_find_ambiguities",
+            "conflicts": [["variable", "type"]],
+            "rules": {
+                "program": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "variable"},
+                        {"type": "SYMBOL", "name": "type"},
+                        {
+                            "type": "SEQ",
+                            "members": [
+                                {"type": "SYMBOL", "name": "type"},
+                                {"type": "STRING", "value": ":"}
+                            ]
+                        }
+                    ]
+                },
+                "variable": {"type": "SYMBOL", "name": "word"},
+                "type": {"type": "SYMBOL", "name": "word"},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(find_ambiguities(language, b"a:").unwrap(), &[]);
+    assert_eq!(
+        find_ambiguities(language, b"a").unwrap(),
+        &[Ambiguity {
+            kind: "program".to_string(),
+            resolved_by_precedence: false,
+            count: 1,
+        }]
+    );
+
+    let grammar_dir = fixtures_dir()
+        .join("test_grammars")
+        .join("dynamic_precedence");
+    let grammar_json = fs::read_to_string(grammar_dir.join("grammar.json")).unwrap();
+    let parser_code = generate_parser_for_grammar(&grammar_json).unwrap().1;
+    let language = get_test_language("dynamic_precedence", &parser_code, Some(&grammar_dir));
+    let ambiguities = find_ambiguities(language, b"int * x").unwrap();
+    assert_eq!(
+        ambiguities,
+        &[Ambiguity {
+            kind: "program".to_string(),
+            resolved_by_precedence: true,
+            count: 1,
+        }]
+    );
+    assert_eq!(
+        ambiguities[0].to_string(),
+        "program: 1 choice made by dynamic precedence"
+    );
+}
+
 #[test]
 fn test_get_changed_ranges() {
     let source_code = b"{a: null};\n".to_vec();
@@ -381,6 +679,96 @@ fn test_get_changed_ranges() {
     }
 }
 
+#[test]
+fn test_tree_edit_with_change_set() {
+    let (parser_name, parser_code) = generate_parser_for_grammar(
+        r#"{
+            "name": "test_tree_edit_with_change_set",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "CHOICE",
+                        "members": [
+                            {"type": "SYMBOL", "name": "word"},
+                            {"type": "SYMBOL", "name": "number"}
+                        ]
+                    }
+                },
+                "word": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "[0-9]+"}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let source = b"abc 12\nde 345\nf".to_vec();
+    let mut tree = parser.parse(&source, None).unwrap();
+
+    // Edits can be added in any order.
+    let mut changes = ChangeSet::new();
+    changes.add_edit(14, 15, b"ghi\n6").unwrap();
+    changes.add_edit(0, 0, b"7 ").unwrap();
+    changes.add_edit(7, 9, b"x").unwrap();
+    assert_eq!(changes.len(), 3);
+
+    // Edits cannot overlap.
+    assert_eq!(
+        changes.add_edit(8, 12, b""),
+        Err(ChangeSetError::Overlap(7..9))
+    );
+    assert_eq!(
+        changes.add_edit(0, 0, b"8"),
+        Err(ChangeSetError::Overlap(0..0))
+    );
+    assert_eq!(
+        changes.add_edit(3, 2, b""),
+        Err(ChangeSetError::InvalidRange)
+    );
+    assert_eq!(changes.len(), 3);
+
+    let new_source = changes.apply(&source);
+    assert_eq!(
+        str::from_utf8(&new_source).unwrap(),
+        "7 abc 12\nx 345\nghi\n6"
+    );
+
+    let edits = changes.to_input_edits(&source);
+    assert_eq!(
+        edits.iter().map(|e| e.start_byte).collect::<Vec<_>>(),
+        &[14, 7, 0]
+    );
+    assert_eq!(edits[0].start_position, Point::new(2, 0));
+    assert_eq!(edits[0].new_end_position, Point::new(3, 1));
+    for edit in &edits {
+        tree.edit(edit);
+    }
+
+    // The unchanged tokens have been moved to their new positions.
+    let number = tree.root_node().named_child(1).unwrap();
+    assert_eq!(number.byte_range(), 6..8);
+    assert_eq!(number.start_position(), Point::new(0, 6));
+
+    let new_tree = parser.parse(&new_source, Some(&tree)).unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        parser
+            .parse(&new_source, None)
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    );
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        "(program (number) (word) (number) (word) (number) (word) (number))"
+    );
+}
+
 fn index_of(text: &Vec<u8>, substring: &str) -> usize {
     str::from_utf8(text.as_slice())
         .unwrap()
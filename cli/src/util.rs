@@ -24,6 +24,63 @@ pub fn cancel_on_stdin() -> Arc<AtomicUsize> {
     });
     result
 }
+
+// Get the largest amount of physical memory that the process has used so far, in
+// bytes, or `None` if it can't be determined. This is a peak over the lifetime of
+// the whole process, so it never decreases: to measure the memory used by one
+// operation, compare the peak before and after it, which only shows an increase if
+// the operation uses more memory than anything else that ran before it.
+#[cfg(unix)]
+pub fn peak_memory_usage() -> Option<usize> {
+    let mut usage = unsafe { std::mem::zeroed::<libc::rusage>() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+
+    // macOS reports the maximum resident set size in bytes, and other systems
+    // report it in kilobytes.
+    if cfg!(target_os = "macos") {
+        Some(usage.ru_maxrss as usize)
+    } else {
+        Some(usage.ru_maxrss as usize * 1024)
+    }
+}
+
+#[cfg(windows)]
+pub fn peak_memory_usage() -> Option<usize> {
+    use std::os::raw::c_void;
+
+    #[repr(C)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn K32GetProcessMemoryInfo(
+            process: *mut c_void,
+            counters: *mut ProcessMemoryCounters,
+            size: u32,
+        ) -> i32;
+    }
+
+    let mut counters = unsafe { std::mem::zeroed::<ProcessMemoryCounters>() };
+    counters.cb = std::mem::size_of::<ProcessMemoryCounters>() as u32;
+    if unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) } == 0 {
+        return None;
+    }
+    Some(counters.peak_working_set_size)
+}
+
 #[cfg(windows)]
 pub struct LogSession();
 
@@ -1,9 +1,9 @@
 use super::error::{Error, Result};
 use super::generate::parse_grammar::GrammarJSON;
 use std::ffi::{OsStr, OsString};
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::{env, fs};
 
 pub fn get_grammar_name(src_dir: &Path) -> Result<String> {
     let grammar_json_path = src_dir.join("grammar.json");
@@ -22,7 +22,10 @@ pub fn compile_language_to_wasm(language_dir: &Path, force_docker: bool) -> Resu
     let output_filename = format!("tree-sitter-{}.wasm", grammar_name);
 
     let mut command;
-    if !force_docker && Command::new("emcc").output().is_ok() {
+    if let (false, Some(emcc_path)) = (force_docker, get_emsdk_emcc_path()) {
+        command = Command::new(emcc_path);
+        command.current_dir(language_dir);
+    } else if !force_docker && Command::new("emcc").output().is_ok() {
         command = Command::new("emcc");
         command.current_dir(&language_dir);
     } else if Command::new("docker").output().is_ok() {
@@ -60,7 +63,11 @@ pub fn compile_language_to_wasm(language_dir: &Path, force_docker: bool) -> Resu
         command.args(&["trzeci/emscripten-slim", "emcc"]);
     } else {
         return Error::err(
-            "You must have either emcc or docker on your PATH to run this command".to_string(),
+            concat!(
+                "You must either set the EMSDK environment variable to an Emscripten SDK, ",
+                "or have emcc or docker on your PATH to run this command"
+            )
+            .to_string(),
         );
     }
 
@@ -116,3 +123,18 @@ pub fn compile_language_to_wasm(language_dir: &Path, force_docker: bool) -> Resu
 
     Ok(())
 }
+
+// Find the `emcc` executable within the Emscripten SDK referenced by the `EMSDK`
+// environment variable, if there is one.
+fn get_emsdk_emcc_path() -> Option<PathBuf> {
+    let emsdk_dir = PathBuf::from(env::var_os("EMSDK")?);
+    let emcc_name = if cfg!(windows) { "emcc.bat" } else { "emcc" };
+    [
+        emsdk_dir.join("upstream").join("emscripten"),
+        emsdk_dir.join("fastcomp").join("emscripten"),
+        emsdk_dir.clone(),
+    ]
+    .iter()
+    .map(|dir| dir.join(emcc_name))
+    .find(|path| path.is_file())
+}
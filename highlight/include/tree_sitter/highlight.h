@@ -15,6 +15,7 @@ typedef enum {
   TSHighlightInvalidUtf8,
   TSHighlightInvalidRegex,
   TSHighlightInvalidQuery,
+  TSHighlightInvalidRange,
 } TSHighlightError;
 
 typedef struct TSHighlighter TSHighlighter;
@@ -29,6 +29,7 @@ pub enum ErrorCode {
     InvalidUtf8,
     InvalidRegex,
     InvalidQuery,
+    InvalidRange,
 }
 
 #[no_mangle]
@@ -231,6 +232,9 @@ impl TSHighlighter {
                 Err(Error::InvalidLanguage) => {
                     return ErrorCode::InvalidLanguage;
                 }
+                Err(Error::InvalidRange) => {
+                    return ErrorCode::InvalidRange;
+                }
                 Err(Error::Unknown) => {
                     return ErrorCode::Timeout;
                 }
@@ -2,6 +2,7 @@ pub mod c_lib;
 pub mod util;
 pub use c_lib as c;
 
+use std::borrow::Cow;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{iter, mem, ops, str, usize};
 use tree_sitter::{
@@ -20,6 +21,7 @@ pub struct Highlight(pub usize);
 pub enum Error {
     Cancelled,
     InvalidLanguage,
+    InvalidRange,
     Unknown,
 }
 
@@ -41,7 +43,7 @@ pub struct HighlightConfiguration {
     locals_pattern_index: usize,
     highlights_pattern_index: usize,
     highlight_indices: Vec<Option<Highlight>>,
-    non_local_variable_patterns: Vec<bool>,
+    local_variable_predicates: Vec<Box<[(LocalVariableProperty, bool)]>>,
     injection_content_capture_index: Option<u32>,
     injection_language_capture_index: Option<u32>,
     local_scope_capture_index: Option<u32>,
@@ -50,6 +52,16 @@ pub struct HighlightConfiguration {
     local_ref_capture_index: Option<u32>,
 }
 
+// The properties of a node that the `is?` and `is-not?` predicates of a highlighting
+// pattern can check: whether it is a local variable at all, whether it is the
+// definition of one, and whether it is a reference to one.
+#[derive(Clone, Copy, Debug)]
+enum LocalVariableProperty {
+    Local,
+    Definition,
+    Reference,
+}
+
 /// Performs syntax highlighting, recognizing a given list of highlight names.
 ///
 /// For the best performance `Highlighter` values should be reused between
@@ -58,6 +70,7 @@ pub struct HighlightConfiguration {
 pub struct Highlighter {
     parser: Parser,
     cursors: Vec<QueryCursor>,
+    highlight_names: Option<Vec<String>>,
 }
 
 /// Converts a general-purpose syntax highlighting iterator into a sequence of lines of HTML.
@@ -91,6 +104,7 @@ where
     injection_callback: F,
     cancellation_flag: Option<&'a AtomicUsize>,
     layers: Vec<HighlightIterLayer<'a>>,
+    forced_injection_ranges: Vec<ops::Range<usize>>,
     iter_count: usize,
     next_event: Option<HighlightEvent>,
     last_highlight_range: Option<(usize, usize, usize)>,
@@ -101,6 +115,7 @@ struct HighlightIterLayer<'a> {
     cursor: QueryCursor,
     captures: iter::Peekable<QueryCaptures<'a, &'a [u8]>>,
     config: &'a HighlightConfiguration,
+    highlight_indices: Cow<'a, [Option<Highlight>]>,
     highlight_end_stack: Vec<usize>,
     scope_stack: Vec<LocalScope<'a>>,
     ranges: Vec<Range>,
@@ -112,6 +127,7 @@ impl Highlighter {
         Highlighter {
             parser: Parser::new(),
             cursors: Vec::new(),
+            highlight_names: None,
         }
     }
 
@@ -119,15 +135,43 @@ impl Highlighter {
         &mut self.parser
     }
 
+    /// Set the list of recognized highlight names for all subsequent highlighting calls.
+    ///
+    /// These names take the place of the ones passed to `HighlightConfiguration::configure`
+    /// for every language, including injected languages, and are matched against capture
+    /// names in the same way. The returned `Highlight` values then refer to indices in this
+    /// list. The new mapping is computed for each language when highlighting starts, so
+    /// the names can be changed between calls without affecting an iteration in progress.
+    pub fn set_highlight_names(&mut self, names: &[&str]) {
+        self.highlight_names = Some(names.iter().map(|name| name.to_string()).collect());
+    }
+
     /// Iterate over the highlighted regions for a given slice of source code.
     pub fn highlight<'a>(
         &'a mut self,
         config: &'a HighlightConfiguration,
         source: &'a [u8],
         cancellation_flag: Option<&'a AtomicUsize>,
+        injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
+    ) -> Result<impl Iterator<Item = Result<HighlightEvent, Error>> + 'a, Error> {
+        self.highlight_with_injections(config, source, cancellation_flag, &[], injection_callback)
+    }
+
+    /// Iterate over the highlighted regions for a given slice of source code, treating
+    /// each of the given byte ranges as an injection of the given language.
+    ///
+    /// Within these ranges, the top-level language's injections query is ignored, so
+    /// this can be used to test how an embedded language is highlighted independently
+    /// of how its injections are detected.
+    pub fn highlight_with_injections<'a>(
+        &'a mut self,
+        config: &'a HighlightConfiguration,
+        source: &'a [u8],
+        cancellation_flag: Option<&'a AtomicUsize>,
+        forced_injections: &[(ops::Range<usize>, &'a HighlightConfiguration)],
         mut injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
     ) -> Result<impl Iterator<Item = Result<HighlightEvent, Error>> + 'a, Error> {
-        let layers = HighlightIterLayer::new(
+        let mut layers = HighlightIterLayer::new(
             source,
             self,
             cancellation_flag,
@@ -142,6 +186,25 @@ impl Highlighter {
             }],
         )?;
         assert_ne!(layers.len(), 0);
+        for (range, injected_config) in forced_injections {
+            if range.start > range.end || range.end > source.len() {
+                return Err(Error::InvalidRange);
+            }
+            layers.extend(HighlightIterLayer::new(
+                source,
+                self,
+                cancellation_flag,
+                &mut injection_callback,
+                injected_config,
+                1,
+                vec![Range {
+                    start_byte: range.start,
+                    end_byte: range.end,
+                    start_point: point_for_offset(source, range.start),
+                    end_point: point_for_offset(source, range.end),
+                }],
+            )?);
+        }
         let mut result = HighlightIter {
             source,
             byte_offset: 0,
@@ -150,6 +213,10 @@ impl Highlighter {
             highlighter: self,
             iter_count: 0,
             layers: layers,
+            forced_injection_ranges: forced_injections
+                .iter()
+                .map(|(range, _)| range.clone())
+                .collect(),
             next_event: None,
             last_highlight_range: None,
         };
@@ -223,14 +290,23 @@ impl HighlightConfiguration {
             None
         };
 
-        // Find all of the highlighting patterns that are disabled for nodes that
-        // have been identified as local variables.
-        let non_local_variable_patterns = (0..query.pattern_count())
+        // Find the `is?` and `is-not?` predicates that enable or disable each
+        // highlighting pattern for nodes that have been identified as local variables.
+        let local_variable_predicates = (0..query.pattern_count())
             .map(|i| {
                 query
                     .property_predicates(i)
                     .iter()
-                    .any(|(prop, positive)| !*positive && prop.key.as_ref() == "local")
+                    .filter_map(|(prop, is_positive)| {
+                        let property = match prop.key.as_ref() {
+                            "local" => LocalVariableProperty::Local,
+                            "local.definition" => LocalVariableProperty::Definition,
+                            "local.reference" => LocalVariableProperty::Reference,
+                            _ => return None,
+                        };
+                        Some((property, *is_positive))
+                    })
+                    .collect()
             })
             .collect();
 
@@ -262,7 +338,7 @@ impl HighlightConfiguration {
             locals_pattern_index,
             highlights_pattern_index,
             highlight_indices,
-            non_local_variable_patterns,
+            local_variable_predicates,
             injection_content_capture_index,
             injection_language_capture_index,
             local_def_capture_index,
@@ -288,32 +364,7 @@ impl HighlightConfiguration {
     /// When highlighting, results are returned as `Highlight` values, which contain the index
     /// of the matched highlight this list of highlight names.
     pub fn configure(&mut self, recognized_names: &[String]) {
-        let mut capture_parts = Vec::new();
-        self.highlight_indices.clear();
-        self.highlight_indices
-            .extend(self.query.capture_names().iter().map(move |capture_name| {
-                capture_parts.clear();
-                capture_parts.extend(capture_name.split('.'));
-
-                let mut best_index = None;
-                let mut best_match_len = 0;
-                for (i, recognized_name) in recognized_names.iter().enumerate() {
-                    let mut len = 0;
-                    let mut matches = true;
-                    for part in recognized_name.split('.') {
-                        len += 1;
-                        if !capture_parts.contains(&part) {
-                            matches = false;
-                            break;
-                        }
-                    }
-                    if matches && len > best_match_len {
-                        best_index = Some(i);
-                        best_match_len = len;
-                    }
-                }
-                best_index.map(Highlight)
-            }));
+        self.highlight_indices = highlight_indices(self.query.capture_names(), recognized_names);
     }
 }
 
@@ -398,6 +449,13 @@ impl<'a> HighlightIterLayer<'a> {
                     })
                     .peekable();
 
+                let highlight_indices = match &highlighter.highlight_names {
+                    Some(names) => {
+                        Cow::Owned(highlight_indices(config.query.capture_names(), names))
+                    }
+                    None => Cow::Borrowed(config.highlight_indices.as_slice()),
+                };
+
                 result.push(HighlightIterLayer {
                     highlight_end_stack: Vec::new(),
                     scope_stack: vec![LocalScope {
@@ -410,6 +468,7 @@ impl<'a> HighlightIterLayer<'a> {
                     _tree: tree,
                     captures,
                     config,
+                    highlight_indices,
                     ranges,
                 });
             }
@@ -550,6 +609,14 @@ impl<'a, F> HighlightIter<'a, F>
 where
     F: FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
 {
+    fn is_overridden_injection(&self, node: &Node) -> bool {
+        self.layers[0].depth == 0
+            && self
+                .forced_injection_ranges
+                .iter()
+                .any(|range| node.start_byte() < range.end && node.end_byte() > range.start)
+    }
+
     fn emit_event(
         &mut self,
         offset: usize,
@@ -700,6 +767,10 @@ where
 
                 // If a language is found with the given name, then add a new language layer
                 // to the highlighted document.
+                // Injections found by the top-level language are ignored wherever an
+                // injection has been explicitly requested.
+                let content_node = content_node.filter(|node| !self.is_overridden_injection(node));
+
                 if let (Some(language_name), Some(content_node)) = (language_name, content_node) {
                     if let Some(config) = (self.injection_callback)(language_name) {
                         let ranges = HighlightIterLayer::intersect_ranges(
@@ -839,11 +910,22 @@ where
                 }
             }
 
-            // If the current node was found to be a local variable, then skip over any
-            // highlighting patterns that are disabled for local variables.
+            // Skip over any highlighting patterns whose `is?` or `is-not?` predicates
+            // don't hold for the current node, based on whether it was found to be a
+            // local variable.
+            let is_definition = definition_highlight.is_some();
+            let is_reference = reference_highlight.is_some();
             while has_highlight
-                && (definition_highlight.is_some() || reference_highlight.is_some())
-                && layer.config.non_local_variable_patterns[pattern_index]
+                && !layer.config.local_variable_predicates[pattern_index]
+                    .iter()
+                    .all(|(property, is_positive)| {
+                        let value = match property {
+                            LocalVariableProperty::Local => is_definition || is_reference,
+                            LocalVariableProperty::Definition => is_definition,
+                            LocalVariableProperty::Reference => is_reference,
+                        };
+                        value == *is_positive
+                    })
             {
                 has_highlight = false;
                 if let Some((next_match, next_capture_index)) = layer.captures.peek() {
@@ -873,7 +955,7 @@ where
                     }
                 }
 
-                let current_highlight = layer.config.highlight_indices[capture.index as usize];
+                let current_highlight = layer.highlight_indices[capture.index as usize];
 
                 // If this node represents a local definition, then store the current
                 // highlight value on the local scope entry representing this node.
@@ -1031,6 +1113,54 @@ impl HtmlRenderer {
     }
 }
 
+// For each capture name, find the index of the longest recognized highlight name whose
+// dot-separated parts all appear in the capture name.
+fn highlight_indices(
+    capture_names: &[String],
+    recognized_names: &[String],
+) -> Vec<Option<Highlight>> {
+    let mut capture_parts = Vec::new();
+    capture_names
+        .iter()
+        .map(move |capture_name| {
+            capture_parts.clear();
+            capture_parts.extend(capture_name.split('.'));
+
+            let mut best_index = None;
+            let mut best_match_len = 0;
+            for (i, recognized_name) in recognized_names.iter().enumerate() {
+                let mut len = 0;
+                let mut matches = true;
+                for part in recognized_name.split('.') {
+                    len += 1;
+                    if !capture_parts.contains(&part) {
+                        matches = false;
+                        break;
+                    }
+                }
+                if matches && len > best_match_len {
+                    best_index = Some(i);
+                    best_match_len = len;
+                }
+            }
+            best_index.map(Highlight)
+        })
+        .collect()
+}
+
+fn point_for_offset(source: &[u8], offset: usize) -> Point {
+    let mut result = Point::new(0, 0);
+    for c in &source[0..offset] {
+        if *c == b'\n' {
+            result.row += 1;
+            result.column = 0;
+        } else {
+            result.column += 1;
+        }
+    }
+    result
+}
+
 fn injection_for_match<'a>(
     config: &HighlightConfiguration,
     query: &'a Query,
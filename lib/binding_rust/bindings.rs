@@ -29,6 +29,11 @@ pub struct TSQuery {
 pub struct TSQueryCursor {
     _unused: [u8; 0],
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSLookaheadIterator {
+    _unused: [u8; 0],
+}
 pub const TSInputEncoding_TSInputEncodingUTF8: TSInputEncoding = 0;
 pub const TSInputEncoding_TSInputEncodingUTF16: TSInputEncoding = 1;
 pub type TSInputEncoding = u32;
@@ -44,6 +49,25 @@ pub struct TSPoint {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct TSNodeTypeField {
+    pub field_id: TSFieldId,
+    pub multiple: bool,
+}
+pub const TSTableActionType_TSTableActionTypeError: TSTableActionType = 0;
+pub const TSTableActionType_TSTableActionTypeShift: TSTableActionType = 1;
+pub const TSTableActionType_TSTableActionTypeReduce: TSTableActionType = 2;
+pub const TSTableActionType_TSTableActionTypeAccept: TSTableActionType = 3;
+pub type TSTableActionType = u32;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSTableAction {
+    pub type_: TSTableActionType,
+    pub state: u16,
+    pub symbol: TSSymbol,
+    pub child_count: u16,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct TSRange {
     pub start_point: TSPoint,
     pub end_point: TSPoint,
@@ -282,6 +306,20 @@ extern "C" {
     #[doc = " Get the duration in microseconds that parsing is allowed to take."]
     pub fn ts_parser_timeout_micros(self_: *const TSParser) -> u64;
 }
+extern "C" {
+    #[doc = " Set the maximum number of *missing* nodes that the parser may insert while"]
+    #[doc = " recovering from errors during a single parse."]
+    #[doc = ""]
+    #[doc = " Once this limit is reached, the parser stops inserting missing tokens and"]
+    #[doc = " instead recovers by skipping trees, which produces a less detailed tree for"]
+    #[doc = " badly malformed input. By default, there is no limit."]
+    pub fn ts_parser_set_max_error_recovery_nodes(self_: *mut TSParser, count: u32);
+}
+extern "C" {
+    #[doc = " Get the maximum number of missing nodes that the parser may insert during"]
+    #[doc = " a single parse."]
+    pub fn ts_parser_max_error_recovery_nodes(self_: *const TSParser) -> u32;
+}
 extern "C" {
     #[doc = " Set the parser\'s current cancellation flag pointer."]
     #[doc = ""]
@@ -332,6 +370,14 @@ extern "C" {
     #[doc = " Get the language that was used to parse the syntax tree."]
     pub fn ts_tree_language(arg1: *const TSTree) -> *const TSLanguage;
 }
+extern "C" {
+    #[doc = " Get the ranges of text that were included when parsing the syntax tree."]
+    #[doc = ""]
+    #[doc = " The returned pointer is owned by the tree. The caller should not free it"]
+    #[doc = " or write to it. The length of the array will be written to the given"]
+    #[doc = " `length` pointer."]
+    pub fn ts_tree_included_ranges(arg1: *const TSTree, length: *mut u32) -> *const TSRange;
+}
 extern "C" {
     #[doc = " Edit the syntax tree to keep it in sync with source code that has been"]
     #[doc = " edited."]
@@ -464,6 +510,16 @@ extern "C" {
     #[doc = " `ts_language_field_id_for_name` function."]
     pub fn ts_node_child_by_field_id(arg1: TSNode, arg2: TSFieldId) -> TSNode;
 }
+extern "C" {
+    #[doc = " Check if the production that the node was parsed with assigns the given"]
+    #[doc = " numerical field id to any of its children."]
+    #[doc = ""]
+    #[doc = " This differs from the fields that the grammar declares for the node\'s type,"]
+    #[doc = " returned by `ts_language_node_type_fields`: a field that only appears in some"]
+    #[doc = " of the type\'s productions, such as an optional one, is only present if the"]
+    #[doc = " node was parsed with one of them."]
+    pub fn ts_node_has_field_id_in_production(arg1: TSNode, arg2: TSFieldId) -> bool;
+}
 extern "C" {
     #[doc = " Get the node\'s next / previous sibling."]
     pub fn ts_node_next_sibling(arg1: TSNode) -> TSNode;
@@ -682,9 +738,15 @@ extern "C" {
     #[doc = " Disable a certain pattern within a query."]
     #[doc = ""]
     #[doc = " This prevents the pattern from matching and removes most of the overhead"]
-    #[doc = " associated with the pattern. Currently, there is no way to undo this."]
+    #[doc = " associated with the pattern. It can be undone with `ts_query_enable_pattern`."]
     pub fn ts_query_disable_pattern(arg1: *mut TSQuery, arg2: u32);
 }
+extern "C" {
+    #[doc = " Re-enable a pattern that was previously disabled with"]
+    #[doc = " `ts_query_disable_pattern`. If the pattern is not disabled, this has no"]
+    #[doc = " effect."]
+    pub fn ts_query_enable_pattern(arg1: *mut TSQuery, arg2: u32);
+}
 extern "C" {
     #[doc = " Create a new cursor for executing a given query."]
     #[doc = ""]
@@ -745,6 +807,14 @@ extern "C" {
         capture_index: *mut u32,
     ) -> bool;
 }
+extern "C" {
+    #[doc = " Get the number of nodes that the cursor has visited since the query was"]
+    #[doc = " last started with `ts_query_cursor_exec`."]
+    #[doc = ""]
+    #[doc = " Nodes that are skipped because they are outside of the cursor\'s range are"]
+    #[doc = " not counted."]
+    pub fn ts_query_cursor_visited_node_count(arg1: *const TSQueryCursor) -> u32;
+}
 extern "C" {
     #[doc = " Get the number of distinct node types in the language."]
     pub fn ts_language_symbol_count(arg1: *const TSLanguage) -> u32;
@@ -769,6 +839,11 @@ extern "C" {
     #[doc = " Get the number of distinct field names in the language."]
     pub fn ts_language_field_count(arg1: *const TSLanguage) -> u32;
 }
+extern "C" {
+    #[doc = " Get the length of the language's alias sequences, which is the greatest"]
+    #[doc = " number of children of any of its productions."]
+    pub fn ts_language_max_alias_sequence_length(arg1: *const TSLanguage) -> u16;
+}
 extern "C" {
     #[doc = " Get the field name string for the given numerical id."]
     pub fn ts_language_field_name_for_id(
@@ -791,6 +866,16 @@ extern "C" {
     #[doc = " See also `ts_node_is_named`. Hidden nodes are never returned from the API."]
     pub fn ts_language_symbol_type(arg1: *const TSLanguage, arg2: TSSymbol) -> TSSymbolType;
 }
+extern "C" {
+    #[doc = " Check whether nodes with the given node type id appear in syntax trees. Unlike"]
+    #[doc = " `ts_language_symbol_type`, this distinguishes hidden rules from named nodes."]
+    pub fn ts_language_symbol_is_visible(arg1: *const TSLanguage, arg2: TSSymbol) -> bool;
+}
+extern "C" {
+    #[doc = " Check whether the given node type id belongs to one of the grammar\'s"]
+    #[doc = " supertypes."]
+    pub fn ts_language_symbol_is_supertype(arg1: *const TSLanguage, arg2: TSSymbol) -> bool;
+}
 extern "C" {
     #[doc = " Get the ABI version number for this language. This version number is used"]
     #[doc = " to ensure that languages were generated by a compatible version of"]
@@ -799,6 +884,126 @@ extern "C" {
     #[doc = " See also `ts_parser_set_language`."]
     pub fn ts_language_version(arg1: *const TSLanguage) -> u32;
 }
+extern "C" {
+    #[doc = " Get the name of this language, as specified in its grammar."]
+    #[doc = ""]
+    #[doc = " This returns `NULL` for languages that were generated with an older version"]
+    #[doc = " of Tree-sitter that did not store the language\'s name."]
+    pub fn ts_language_name(arg1: *const TSLanguage) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    #[doc = " Get the version of the grammar from which this language was generated."]
+    #[doc = ""]
+    #[doc = " This returns `NULL` if the grammar did not specify a version, or if the"]
+    #[doc = " language was generated with an older version of Tree-sitter that did not"]
+    #[doc = " store the grammar\'s version."]
+    pub fn ts_language_grammar_version(arg1: *const TSLanguage) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    #[doc = " Get the number of parse states in the language."]
+    #[doc = ""]
+    #[doc = " This returns zero if the language was generated with an older version of"]
+    #[doc = " Tree-sitter that did not store the number of parse states."]
+    pub fn ts_language_state_count(arg1: *const TSLanguage) -> u32;
+}
+extern "C" {
+    #[doc = " Get the fields that the grammar declares for the given node type, sorted by"]
+    #[doc = " field id, and whether each field can contain more than one node. The number"]
+    #[doc = " of fields is written to the `count` pointer."]
+    #[doc = ""]
+    #[doc = " This returns `NULL` if the node type has no fields, or if the language was"]
+    #[doc = " generated with an older version of Tree-sitter that did not store the fields"]
+    #[doc = " of each node type. Node types that only exist as aliases have no fields"]
+    #[doc = " stored, even if the rules that they alias have fields."]
+    pub fn ts_language_node_type_fields(
+        arg1: *const TSLanguage,
+        arg2: TSSymbol,
+        count: *mut u32,
+    ) -> *const TSNodeTypeField;
+}
+extern "C" {
+    #[doc = " Get the field that the grammar assigns to the child at the given index in"]
+    #[doc = " nodes of the given type. The index counts children the way `ts_node_child`"]
+    #[doc = " does, for a node that contains no extras."]
+    #[doc = ""]
+    #[doc = " This returns zero if no production of the node type has a field at that"]
+    #[doc = " index, if different productions assign different fields to it, if the index"]
+    #[doc = " comes after a hidden child that can contain any number of children, or if"]
+    #[doc = " the language was generated with an older version of Tree-sitter that did not"]
+    #[doc = " store the fields of each node type."]
+    pub fn ts_language_field_for_child_index(
+        arg1: *const TSLanguage,
+        arg2: TSSymbol,
+        child_index: u32,
+    ) -> TSFieldId;
+}
+extern "C" {
+    #[doc = " Get the action that the parse table of the language specifies for the given"]
+    #[doc = " symbol in the given parse state."]
+    #[doc = ""]
+    #[doc = " For a token, this is the first of the token's parse actions. A shift action's"]
+    #[doc = " `state` is the state that the parser moves to, and a reduce action's `symbol`"]
+    #[doc = " and `child_count` describe the node that it creates. For any other symbol, this"]
+    #[doc = " is a shift action whose `state` is the state that the parser moves to after"]
+    #[doc = " creating a node of that type."]
+    #[doc = ""]
+    #[doc = " This returns an action of type `TSTableActionTypeError` if the parser can't"]
+    #[doc = " handle the symbol in the state, if the state or the symbol is out of range, or"]
+    #[doc = " if the language was generated with an older version of Tree-sitter that did"]
+    #[doc = " not store the number of parse states."]
+    pub fn ts_language_table_action(
+        arg1: *const TSLanguage,
+        state: u16,
+        symbol: TSSymbol,
+    ) -> TSTableAction;
+}
+extern "C" {
+    #[doc = " Create a new iterator over the symbols that are valid in the given parse state:"]
+    #[doc = " the tokens that have a parse action and the nodes that have a goto action."]
+    #[doc = ""]
+    #[doc = " This returns `NULL` if the state is not one of the language\'s parse states,"]
+    #[doc = " which is always the case for languages that don\'t record their state count."]
+    #[doc = ""]
+    #[doc = " The iterator starts before the first symbol, so call"]
+    #[doc = " `ts_lookahead_iterator_next` to move to it."]
+    pub fn ts_lookahead_iterator_new(
+        arg1: *const TSLanguage,
+        state: u16,
+    ) -> *mut TSLookaheadIterator;
+}
+extern "C" {
+    #[doc = " Delete a lookahead iterator, freeing all of the memory that it used."]
+    pub fn ts_lookahead_iterator_delete(arg1: *mut TSLookaheadIterator);
+}
+extern "C" {
+    #[doc = " Move the iterator back to the start of the given parse state\'s symbols."]
+    #[doc = ""]
+    #[doc = " This returns `false`, leaving the iterator unchanged, if the state is not one"]
+    #[doc = " of the parse states of the iterator\'s language."]
+    pub fn ts_lookahead_iterator_reset_state(arg1: *mut TSLookaheadIterator, state: u16) -> bool;
+}
+extern "C" {
+    #[doc = " Get the language of a lookahead iterator."]
+    pub fn ts_lookahead_iterator_language(arg1: *const TSLookaheadIterator) -> *const TSLanguage;
+}
+extern "C" {
+    #[doc = " Move the iterator to the next valid symbol."]
+    #[doc = ""]
+    #[doc = " This returns `true` if the iterator moved to a symbol, and `false` if there"]
+    #[doc = " were no more symbols."]
+    pub fn ts_lookahead_iterator_next(arg1: *mut TSLookaheadIterator) -> bool;
+}
+extern "C" {
+    #[doc = " Get the current symbol of a lookahead iterator. This is only meaningful after"]
+    #[doc = " `ts_lookahead_iterator_next` has returned `true`."]
+    pub fn ts_lookahead_iterator_current_symbol(arg1: *const TSLookaheadIterator) -> TSSymbol;
+}
+extern "C" {
+    #[doc = " Get the name of the current symbol of a lookahead iterator."]
+    pub fn ts_lookahead_iterator_current_symbol_name(
+        arg1: *const TSLookaheadIterator,
+    ) -> *const ::std::os::raw::c_char;
+}
 
-pub const TREE_SITTER_LANGUAGE_VERSION: usize = 11;
+pub const TREE_SITTER_LANGUAGE_VERSION: usize = 12;
 pub const TREE_SITTER_MIN_COMPATIBLE_LANGUAGE_VERSION: usize = 9;
@@ -0,0 +1,140 @@
+use super::{InputEdit, Point};
+
+/// A set of non-overlapping edits to a source file, which can be applied to the
+/// file's text and to its syntax tree all at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChangeSet {
+    edits: Vec<Change>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Change {
+    start: usize,
+    end: usize,
+    replacement: Vec<u8>,
+}
+
+/// An error that occurred in `ChangeSet::add_edit`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChangeSetError {
+    /// The edit's start offset was greater than its end offset.
+    InvalidRange,
+    /// The edit overlapped the previously-added edit with the given byte range.
+    Overlap(std::ops::Range<usize>),
+}
+
+impl ChangeSet {
+    /// Create a new change set with no edits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an edit that replaces the bytes from `start` to `end` with `replacement`.
+    ///
+    /// Edits can be added in any order, but they cannot overlap each other. Edits
+    /// that insert text at the same offset are considered to overlap, because the
+    /// order of the inserted text would be ambiguous.
+    pub fn add_edit(
+        &mut self,
+        start: usize,
+        end: usize,
+        replacement: &[u8],
+    ) -> Result<(), ChangeSetError> {
+        if start > end {
+            return Err(ChangeSetError::InvalidRange);
+        }
+        let index = match self
+            .edits
+            .binary_search_by(|edit| (edit.start, edit.end).cmp(&(start, end)))
+        {
+            Ok(index) => return Err(self.edits[index].overlap()),
+            Err(index) => index,
+        };
+        let neighbors = index.checked_sub(1).into_iter().chain(Some(index));
+        for neighbor in neighbors.filter_map(|i| self.edits.get(i)) {
+            if start < neighbor.end && neighbor.start < end {
+                return Err(neighbor.overlap());
+            }
+        }
+        self.edits.insert(
+            index,
+            Change {
+                start,
+                end,
+                replacement: replacement.to_vec(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Get the number of edits in the change set.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Check if the change set has no edits.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Produce the text that results from applying every edit to `source`.
+    ///
+    /// Panics if any edit extends past the end of `source`.
+    pub fn apply(&self, source: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(source.len());
+        let mut offset = 0;
+        for edit in &self.edits {
+            result.extend_from_slice(&source[offset..edit.start]);
+            result.extend_from_slice(&edit.replacement);
+            offset = edit.end;
+        }
+        result.extend_from_slice(&source[offset..]);
+        result
+    }
+
+    /// Produce the `InputEdit`s that describe these edits to a tree that was parsed
+    /// from `source`.
+    ///
+    /// The edits are in order from the end of the file to the beginning, so that
+    /// each one's positions are still valid after the preceding ones have been
+    /// passed to `Tree::edit`.
+    pub fn to_input_edits(&self, source: &[u8]) -> Vec<InputEdit> {
+        let mut result = Vec::with_capacity(self.edits.len());
+        let mut position = Point::new(0, 0);
+        let mut offset = 0;
+        for edit in &self.edits {
+            let start_position = advance(position, &source[offset..edit.start]);
+            let old_end_position = advance(start_position, &source[edit.start..edit.end]);
+            result.push(InputEdit {
+                start_byte: edit.start,
+                old_end_byte: edit.end,
+                new_end_byte: edit.start + edit.replacement.len(),
+                start_position,
+                old_end_position,
+                new_end_position: advance(start_position, &edit.replacement),
+            });
+            position = old_end_position;
+            offset = edit.end;
+        }
+        result.reverse();
+        result
+    }
+}
+
+impl Change {
+    fn overlap(&self) -> ChangeSetError {
+        ChangeSetError::Overlap(self.start..self.end)
+    }
+}
+
+fn advance(mut position: Point, text: &[u8]) -> Point {
+    for c in text {
+        if *c == b'\n' {
+            position.row += 1;
+            position.column = 0;
+        } else {
+            position.column += 1;
+        }
+    }
+    position
+}
@@ -0,0 +1,58 @@
+use super::{Node, Point, TreeCursor};
+use serde_json::{Map, Value};
+
+impl<'tree> Node<'tree> {
+    /// Convert this node and all of its descendants to a JSON object.
+    ///
+    /// Each object has `type`, `named`, `startByte`, `endByte`, `startPoint`,
+    /// `endPoint` and `children` keys. Nodes without children also have a `text` key
+    /// with their source code, taken from `source`. The `fields` key maps the name of
+    /// each of the node's fields to the indices of the corresponding nodes in
+    /// `children`.
+    pub fn to_json(&self, source: &[u8]) -> Value {
+        node_to_json(&mut self.walk(), source)
+    }
+}
+
+fn node_to_json(cursor: &mut TreeCursor, source: &[u8]) -> Value {
+    let node = cursor.node();
+    let mut children = Vec::new();
+    let mut fields = Map::new();
+    if cursor.goto_first_child() {
+        loop {
+            if let Some(field_name) = cursor.field_name() {
+                let indices = fields
+                    .entry(field_name)
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(indices) = indices {
+                    indices.push(children.len().into());
+                }
+            }
+            children.push(node_to_json(cursor, source));
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+        cursor.goto_parent();
+    }
+
+    let mut result = json!({
+        "type": node.kind(),
+        "named": node.is_named(),
+        "startByte": node.start_byte(),
+        "endByte": node.end_byte(),
+        "startPoint": point_to_json(node.start_position()),
+        "endPoint": point_to_json(node.end_position()),
+    });
+    if children.is_empty() {
+        let text = source.get(node.byte_range()).unwrap_or(&[]);
+        result["text"] = String::from_utf8_lossy(text).into();
+    }
+    result["fields"] = Value::Object(fields);
+    result["children"] = Value::Array(children);
+    result
+}
+
+fn point_to_json(point: Point) -> Value {
+    json!({"row": point.row, "column": point.column})
+}
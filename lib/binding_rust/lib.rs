@@ -1,4 +1,12 @@
+#[cfg(feature = "serde_json")]
+#[macro_use]
+extern crate serde_json;
+
+mod change_set;
 mod ffi;
+#[cfg(feature = "serde_json")]
+mod json;
+mod serialize;
 mod util;
 
 #[cfg(unix)]
@@ -9,8 +17,14 @@ use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 use std::os::raw::{c_char, c_void};
 use std::ptr::NonNull;
-use std::sync::atomic::AtomicUsize;
-use std::{char, fmt, hash, iter, ptr, slice, str, u16};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{char, cmp, fmt, hash, iter, ptr, slice, str, u16};
+
+/// The C type of a language, as declared in `tree_sitter/api.h`.
+pub use ffi::TSLanguage;
+
+pub use change_set::{ChangeSet, ChangeSetError};
+pub use serialize::{DetachedNode, SERIALIZATION_VERSION};
 
 /// The latest ABI version that is supported by the current version of the
 /// library.
@@ -27,6 +41,12 @@ pub const MIN_COMPATIBLE_LANGUAGE_VERSION: usize = ffi::TREE_SITTER_MIN_COMPATIB
 
 pub const PARSER_HEADER: &'static str = include_str!("../include/tree_sitter/parser.h");
 
+thread_local! {
+    // The parsers that have been created by `Parser::with_thread_local` on the
+    // current thread and are not in use.
+    static THREAD_LOCAL_PARSERS: std::cell::RefCell<Vec<Parser>> = Default::default();
+}
+
 /// An opaque object that defines how to parse a particular language. The code for each
 /// `Language` is generated by the Tree-sitter CLI.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -66,11 +86,72 @@ pub struct InputEdit {
     pub new_end_position: Point,
 }
 
+/// The properties of one of a language's node kinds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SymbolMetadata {
+    /// Whether nodes of this kind appear in syntax trees, as opposed to being
+    /// hidden rules or auxiliary symbols.
+    pub visible: bool,
+    /// Whether this kind is named (as opposed to being an anonymous token).
+    pub named: bool,
+    /// Whether this kind is one of the grammar's supertypes.
+    pub supertype: bool,
+}
+
+/// An entry in a language's parse table, returned by [Language::parse_table].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseAction {
+    /// Move to the given parse state. For a token, the parser consumes it first.
+    Shift(u16),
+    /// Create a node of the given kind from the given number of nodes on the
+    /// top of the stack.
+    Reduce { symbol: u16, count: u16 },
+    /// Finish parsing.
+    Accept,
+    /// The symbol is not valid in the parse state.
+    Error,
+}
+
 /// A single node within a syntax `Tree`.
 #[derive(Clone, Copy)]
 #[repr(transparent)]
 pub struct Node<'a>(ffi::TSNode, PhantomData<&'a ()>);
 
+/// Options that control how [Node::format_sexp] formats a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SexpOptions<'a> {
+    /// Whether to annotate each node with its range, as `[row, column] - [row, column]`.
+    pub include_ranges: bool,
+    /// Whether to include anonymous nodes, which are written as quoted strings.
+    pub include_anonymous_nodes: bool,
+    /// Whether to prefix each node with the name of the field that it belongs to.
+    pub include_field_names: bool,
+    /// The source code that the tree was parsed from. If given, each named node
+    /// without children is followed by its text, as a quoted string.
+    pub source_code: Option<&'a [u8]>,
+    /// The number of spaces to indent each level of the tree by. If this is zero,
+    /// the whole S-expression is written on a single line.
+    pub indentation: usize,
+    /// Whether to label nodes that were inserted during error recovery as
+    /// `(MISSING ...)`. Otherwise, missing named nodes are written like any other
+    /// node, and missing anonymous nodes are only written with the other anonymous
+    /// nodes.
+    pub label_missing_nodes: bool,
+}
+
+impl<'a> Default for SexpOptions<'a> {
+    fn default() -> Self {
+        SexpOptions {
+            include_ranges: false,
+            include_anonymous_nodes: false,
+            include_field_names: true,
+            source_code: None,
+            indentation: 0,
+            label_missing_nodes: true,
+        }
+    }
+}
+
 /// A stateful object that this is used to produce a `Tree` based on some source code.
 pub struct Parser(NonNull<ffi::TSParser>);
 
@@ -81,6 +162,20 @@ pub enum LogType {
     Lex,
 }
 
+/// The outcome of [Parser::parse_bytes_with_timeout].
+#[derive(Debug)]
+pub enum ParseResult {
+    /// Parsing finished, producing a syntax tree.
+    Complete(Tree),
+    /// Parsing was halted because it took longer than the timeout.
+    Timeout,
+    /// Parsing was halted because the cancellation flag set with
+    /// [Parser::set_cancellation_flag] was flipped.
+    Cancelled,
+    /// The parser has not yet had a language assigned with [Parser::set_language].
+    NoLanguage,
+}
+
 /// A callback that receives log messages during parser.
 type Logger<'a> = Box<dyn FnMut(LogType, &str) + 'a>;
 
@@ -96,32 +191,69 @@ pub struct Query {
     property_settings: Vec<Box<[QueryProperty]>>,
     property_predicates: Vec<Box<[(QueryProperty, bool)]>>,
     general_predicates: Vec<Box<[QueryPredicate]>>,
+    predicates: Vec<Box<[QueryPatternPredicate]>>,
 }
 
 /// A stateful object for executing a `Query` on a syntax `Tree`.
 pub struct QueryCursor(NonNull<ffi::TSQueryCursor>);
 
+/// An iterator over the symbols that are valid in a particular parse state,
+/// created with [Language::lookahead_iterator].
+pub struct LookaheadIterator(NonNull<ffi::TSLookaheadIterator>);
+
 /// A key-value pair associated with a particular pattern in a `Query`.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct QueryProperty {
     pub key: Box<str>,
     pub value: Option<Box<str>>,
     pub capture_id: Option<usize>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// An argument to a predicate in a `Query`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum QueryPredicateArg {
+    /// A capture, such as `@name`, identified by its index in
+    /// [capture_names](Query::capture_names).
     Capture(u32),
+    /// A string literal, or a bare identifier.
     String(Box<str>),
 }
 
-/// A key-value pair associated with a particular pattern in a `Query`.
-#[derive(Debug, PartialEq, Eq)]
+/// A predicate associated with a particular pattern in a `Query`, consisting of an
+/// operator and its already-parsed arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct QueryPredicate {
     pub operator: Box<str>,
     pub args: Vec<QueryPredicateArg>,
 }
 
+/// A predicate associated with a particular pattern in a `Query`, identified by
+/// its operator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryPatternPredicate {
+    /// An `eq?` predicate, comparing a capture's text to a string or to the text
+    /// of another capture.
+    Eq {
+        capture: u32,
+        value: QueryPredicateArg,
+    },
+    /// A `not-eq?` predicate.
+    NotEq {
+        capture: u32,
+        value: QueryPredicateArg,
+    },
+    /// A `match?` predicate, matching a capture's text against a regex.
+    Match { capture: u32, regex: Box<str> },
+    /// A `set!` predicate.
+    Set(QueryProperty),
+    /// An `is?` predicate.
+    Is(QueryProperty),
+    /// An `is-not?` predicate.
+    IsNot(QueryProperty),
+    /// A predicate with any other operator.
+    Other(QueryPredicate),
+}
+
 /// A match of a `Query` to a particular set of `Node`s.
 pub struct QueryMatch<'a> {
     pub pattern_index: usize,
@@ -138,13 +270,25 @@ pub struct QueryCaptures<'a, T: AsRef<[u8]>> {
 }
 
 /// A particular `Node` that has been captured with a particular name within a `Query`.
-#[derive(Clone, Copy)]
+///
+/// Captures are ordered by the start byte of their nodes. Captures that start at the
+/// same byte are ordered by end byte, then by capture index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct QueryCapture<'a> {
     pub node: Node<'a>,
     pub index: u32,
 }
 
+/// A `QueryCapture` along with its query and source code, which is displayed as
+/// the capture's name followed by the text of its node. Created with
+/// [QueryCapture::display].
+pub struct QueryCaptureDisplay<'a, 'b> {
+    capture: QueryCapture<'a>,
+    query: &'b Query,
+    source: &'b [u8],
+}
+
 /// An error that occurred when trying to assign an incompatible `Language` to a `Parser`.
 #[derive(Debug, PartialEq, Eq)]
 pub struct LanguageError {
@@ -157,12 +301,28 @@ pub struct IncludedRangesError(pub usize);
 
 /// An error that occurred when trying to create a `Query`.
 #[derive(Debug, PartialEq, Eq)]
-pub enum QueryError {
-    Syntax(usize, String),
-    NodeType(usize, String),
-    Field(usize, String),
-    Capture(usize, String),
-    Predicate(String),
+pub struct QueryError {
+    pub kind: QueryErrorKind,
+    /// The zero-based row of the query source where the error was found.
+    pub row: usize,
+    /// The zero-based column, in bytes, of the query source where the error was found.
+    pub column: usize,
+    /// For syntax errors, the line containing the error with a caret pointing to the
+    /// error's position. For invalid names, the name. For invalid predicates, a
+    /// description of the problem.
+    pub message: String,
+}
+
+/// The kind of problem that prevented a `Query` from being created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    Syntax,
+    NodeType,
+    Field,
+    Capture,
+    /// A predicate was malformed. The error's location is the start of the pattern
+    /// that contains the predicate.
+    Predicate,
 }
 
 #[derive(Debug)]
@@ -175,8 +335,72 @@ enum TextPredicate {
 impl Language {
     /// Get the ABI version number that indicates which version of the Tree-sitter CLI
     /// that was used to generate this `Language`.
+    ///
+    /// This is unrelated to the [grammar_version](Language::grammar_version), which is
+    /// chosen by the grammar's author.
+    pub fn abi_version(&self) -> u32 {
+        unsafe { ffi::ts_language_version(self.0) }
+    }
+
+    /// Get the ABI version number that indicates which version of the Tree-sitter CLI
+    /// that was used to generate this `Language`.
+    #[deprecated(note = "Use `Language::abi_version` instead")]
     pub fn version(&self) -> usize {
-        unsafe { ffi::ts_language_version(self.0) as usize }
+        self.abi_version() as usize
+    }
+
+    /// Get the raw pointer to the underlying C language object, for passing this
+    /// language to C APIs that accept a `const TSLanguage *`.
+    ///
+    /// The returned pointer is valid for the lifetime of this `Language` value, and
+    /// must not be used after the `Language` is dropped.
+    pub fn as_raw(&self) -> *const TSLanguage {
+        self.0
+    }
+
+    /// Check if this language's ABI [version](Language::abi_version) is within the
+    /// given inclusive range.
+    ///
+    /// This is useful for applications that load languages dynamically and need
+    /// to reject languages that they don't support, before assigning them to a
+    /// `Parser`.
+    pub fn check_version_compatibility(&self, min_version: u32, max_version: u32) -> bool {
+        let version = self.abi_version();
+        version >= min_version && version <= max_version
+    }
+
+    /// Check if this language's ABI [version](Language::abi_version) is between the
+    /// given minimum and maximum ABI versions, inclusive.
+    ///
+    /// This is the same check as [Language::check_version_compatibility].
+    pub fn compatible_with_version(&self, min_abi: u32, max_abi: u32) -> bool {
+        self.check_version_compatibility(min_abi, max_abi)
+    }
+
+    /// Get the name of this language, as specified in its grammar.
+    ///
+    /// Returns `None` for languages that were generated by a version of the
+    /// Tree-sitter CLI that did not record the language's name.
+    pub fn name(&self) -> Option<&'static str> {
+        let ptr = unsafe { ffi::ts_language_name(self.0) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(ptr) }.to_str().unwrap())
+        }
+    }
+
+    /// Get the version of the grammar from which this language was generated.
+    ///
+    /// Returns `None` if the grammar did not specify a version, or if the language
+    /// was generated by a version of the Tree-sitter CLI that did not record it.
+    pub fn grammar_version(&self) -> Option<&'static str> {
+        let ptr = unsafe { ffi::ts_language_grammar_version(self.0) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(ptr) }.to_str().unwrap())
+        }
     }
 
     /// Get the number of distinct node types in this language.
@@ -184,6 +408,24 @@ impl Language {
         unsafe { ffi::ts_language_symbol_count(self.0) as usize }
     }
 
+    /// Get the number of node kinds in this language that are named, according to
+    /// [Language::node_kind_is_named]. This includes the kinds of hidden rules.
+    pub fn symbol_count_named(&self) -> usize {
+        self.count_symbols_of_type(ffi::TSSymbolType_TSSymbolTypeRegular)
+    }
+
+    /// Get the number of node kinds in this language that are anonymous: the visible
+    /// kinds that are not named, such as string tokens.
+    pub fn symbol_count_anonymous(&self) -> usize {
+        self.count_symbols_of_type(ffi::TSSymbolType_TSSymbolTypeAnonymous)
+    }
+
+    fn count_symbols_of_type(&self, symbol_type: ffi::TSSymbolType) -> usize {
+        (0..self.node_kind_count() as u16)
+            .filter(|id| unsafe { ffi::ts_language_symbol_type(self.0, *id) } == symbol_type)
+            .count()
+    }
+
     /// Get the name of the node kind for the given numerical id.
     pub fn node_kind_for_id(&self, id: u16) -> Option<&'static str> {
         let ptr = unsafe { ffi::ts_language_symbol_name(self.0, id) };
@@ -195,6 +437,10 @@ impl Language {
     }
 
     /// Get the numeric id for the given node kind.
+    ///
+    /// Returns `0` if the language has no such node kind, which is ambiguous with the
+    /// id of the builtin `end` symbol.
+    #[deprecated(note = "Use `node_kind_id_for_type_checked` instead")]
     pub fn id_for_node_kind(&self, kind: &str, named: bool) -> u16 {
         unsafe {
             ffi::ts_language_symbol_for_name(
@@ -206,6 +452,24 @@ impl Language {
         }
     }
 
+    /// Get the numeric id for the given node kind, or `None` if the language has
+    /// no such node kind.
+    pub fn node_kind_id_for_type_checked(&self, kind: &str, named: bool) -> Option<u16> {
+        let id = unsafe {
+            ffi::ts_language_symbol_for_name(
+                self.0,
+                kind.as_bytes().as_ptr() as *const c_char,
+                kind.len() as u32,
+                named,
+            )
+        };
+        if id == 0 {
+            None
+        } else {
+            Some(id)
+        }
+    }
+
     /// Check if the node type for the given numerical id is named (as opposed
     /// to an anonymous node type).
     pub fn node_kind_is_named(&self, id: u16) -> bool {
@@ -218,11 +482,110 @@ impl Language {
         }
     }
 
+    /// Get the properties of the node kind for the given numerical id.
+    pub fn symbol_metadata(&self, id: u16) -> SymbolMetadata {
+        unsafe {
+            SymbolMetadata {
+                visible: ffi::ts_language_symbol_is_visible(self.0, id),
+                named: ffi::ts_language_symbol_type(self.0, id)
+                    == ffi::TSSymbolType_TSSymbolTypeRegular,
+                supertype: ffi::ts_language_symbol_is_supertype(self.0, id),
+            }
+        }
+    }
+
+    /// Create an iterator over the symbols that are valid in the given parse state:
+    /// the tokens that the parser can consume and the nodes that it can complete.
+    ///
+    /// Returns `None` if the state is not less than the language's
+    /// [parse_state_count](Language::parse_state_count), which is always the case
+    /// for languages that did not record their state count.
+    pub fn lookahead_iterator(&self, state: u16) -> Option<LookaheadIterator> {
+        let ptr = unsafe { ffi::ts_lookahead_iterator_new(self.0, state) };
+        NonNull::new(ptr).map(LookaheadIterator)
+    }
+
     /// Get the number of distinct field names in this language.
     pub fn field_count(&self) -> usize {
         unsafe { ffi::ts_language_field_count(self.0) as usize }
     }
 
+    /// Get the length of this language's alias sequences, which is the greatest
+    /// number of children of any of its productions. A buffer of this length can
+    /// hold the aliases of any node's children.
+    pub fn max_alias_sequence_length(&self) -> u16 {
+        unsafe { ffi::ts_language_max_alias_sequence_length(self.0) }
+    }
+
+    /// Get the number of parse states in this language.
+    ///
+    /// Returns zero if the language was generated by a version of the Tree-sitter
+    /// CLI that did not record it.
+    pub fn parse_state_count(&self) -> usize {
+        unsafe { ffi::ts_language_state_count(self.0) as usize }
+    }
+
+    /// Get the action that this language's parse table specifies for the symbol
+    /// with the given numerical id in the given parse state.
+    ///
+    /// For a token, this is the first of the token's actions, so if the grammar has
+    /// an unresolved conflict in that state, the other actions are not returned. For
+    /// a node kind, this is a `Shift` to the state that the parser moves to after
+    /// creating the node. Returns `Error` if the state or the symbol is out of range,
+    /// or if the language was generated by a version of the Tree-sitter CLI that did
+    /// not record its parse state count.
+    pub fn parse_table(&self, state: u16, symbol: u16) -> ParseAction {
+        let action = unsafe { ffi::ts_language_table_action(self.0, state, symbol) };
+        match action.type_ {
+            ffi::TSTableActionType_TSTableActionTypeShift => ParseAction::Shift(action.state),
+            ffi::TSTableActionType_TSTableActionTypeReduce => ParseAction::Reduce {
+                symbol: action.symbol,
+                count: action.child_count,
+            },
+            ffi::TSTableActionType_TSTableActionTypeAccept => ParseAction::Accept,
+            _ => ParseAction::Error,
+        }
+    }
+
+    /// Get the fields that the grammar declares for the node kind with the given
+    /// numerical id, as pairs of a field id and whether the field can contain more
+    /// than one node. The fields are sorted by id.
+    ///
+    /// Returns an empty list for node kinds without fields, and for languages that
+    /// were generated by a version of the Tree-sitter CLI that did not record them.
+    /// Node kinds that only exist as aliases also have no fields recorded, even if
+    /// the rules that they alias have fields.
+    pub fn fields_for_node_type(&self, id: u16) -> Vec<(u16, bool)> {
+        let mut count = 0u32;
+        let ptr = unsafe { ffi::ts_language_node_type_fields(self.0, id, &mut count) };
+        if ptr.is_null() {
+            return Vec::new();
+        }
+        unsafe { slice::from_raw_parts(ptr, count as usize) }
+            .iter()
+            .map(|field| (field.field_id, field.multiple))
+            .collect()
+    }
+
+    /// Get the id of the field that the grammar assigns to the child at the given
+    /// index in nodes of the kind with the given numerical id. The index counts
+    /// children the way [Node::child] does, for a node that contains no extras.
+    ///
+    /// Returns `None` if no production of the node kind has a field at that index,
+    /// if different productions assign different fields to it, if the index comes
+    /// after a hidden child that can contain any number of children, or if the
+    /// language was generated by a version of the Tree-sitter CLI that did not
+    /// record the fields of each node kind.
+    pub fn field_for_child_at_index(&self, type_id: u16, child_index: usize) -> Option<u16> {
+        let id =
+            unsafe { ffi::ts_language_field_for_child_index(self.0, type_id, child_index as u32) };
+        if id == 0 {
+            None
+        } else {
+            Some(id)
+        }
+    }
+
     /// Get the field names for the given numerical id.
     pub fn field_name_for_id(&self, field_id: u16) -> Option<&'static str> {
         let ptr = unsafe { ffi::ts_language_field_name_for_id(self.0, field_id) };
@@ -233,7 +596,8 @@ impl Language {
         }
     }
 
-    /// Get the numerical id for the given field name.
+    /// Get the numerical id for the given field name, or `None` if the language has
+    /// no such field. Field ids start at `1`.
     pub fn field_id_for_name(&self, field_name: impl AsRef<[u8]>) -> Option<u16> {
         let field_name = field_name.as_ref();
         let id = unsafe {
@@ -249,6 +613,14 @@ impl Language {
             Some(id)
         }
     }
+
+    /// Create a new query for this language from a string containing one or more
+    /// S-expression patterns.
+    ///
+    /// This is equivalent to [Query::new].
+    pub fn create_query_checked(&self, source: &str) -> Result<Query, QueryError> {
+        Query::new(*self, source)
+    }
 }
 
 impl fmt::Display for LanguageError {
@@ -270,16 +642,62 @@ impl Parser {
         }
     }
 
+    /// Run the given function with the current thread's parser for the given
+    /// language, creating the parser the first time that it's needed.
+    ///
+    /// This saves programs that parse on many threads from declaring their own
+    /// thread-local parsers. Each call gets a parser in the same state as a new one:
+    /// after the function returns, the parser is reset, and its timeout, included
+    /// ranges, cancellation flag, logger and other settings are restored to their
+    /// defaults. If the function calls `with_thread_local` for the same language,
+    /// the inner call uses a separate parser.
+    pub fn with_thread_local<R>(
+        language: Language,
+        f: impl FnOnce(&mut Parser) -> R,
+    ) -> Result<R, LanguageError> {
+        let cached_parser = THREAD_LOCAL_PARSERS.with(|parsers| {
+            let mut parsers = parsers.borrow_mut();
+            let index = parsers
+                .iter()
+                .position(|parser| parser.language() == Some(language))?;
+            Some(parsers.swap_remove(index))
+        });
+        let mut parser = match cached_parser {
+            Some(parser) => parser,
+            None => {
+                let mut parser = Parser::new();
+                parser.set_language(language)?;
+                parser
+            }
+        };
+        let result = f(&mut parser);
+        parser.restore_defaults();
+        THREAD_LOCAL_PARSERS.with(|parsers| parsers.borrow_mut().push(parser));
+        Ok(result)
+    }
+
+    // Put the parser back into the state of a new parser for its language, so that
+    // it can be cached without keeping any references that its last user passed in.
+    fn restore_defaults(&mut self) {
+        self.reset();
+        unsafe { self.set_cancellation_flag(None) };
+        self.set_included_ranges(&[]).unwrap();
+        self.set_timeout_micros(0);
+        self.set_max_error_recovery_nodes(u32::MAX);
+        self.set_logger(None);
+        self.stop_printing_dot_graphs();
+    }
+
     /// Set the language that the parser should use for parsing.
     ///
     /// Returns a Result indicating whether or not the language was successfully
     /// assigned. True means assignment succeeded. False means there was a version
     /// mismatch: the language was generated with an incompatible version of the
-    /// Tree-sitter CLI. Check the language's version using [Language::version]
+    /// Tree-sitter CLI. Check the language's version using [Language::abi_version]
     /// and compare it to this library's [LANGUAGE_VERSION](LANGUAGE_VERSION) and
     /// [MIN_COMPATIBLE_LANGUAGE_VERSION](MIN_COMPATIBLE_LANGUAGE_VERSION) constants.
     pub fn set_language(&mut self, language: Language) -> Result<(), LanguageError> {
-        let version = language.version();
+        let version = language.abi_version() as usize;
         if version < MIN_COMPATIBLE_LANGUAGE_VERSION || version > LANGUAGE_VERSION {
             Err(LanguageError { version })
         } else {
@@ -291,6 +709,10 @@ impl Parser {
     }
 
     /// Get the parser's current language.
+    ///
+    /// This returns `None` if no language has been assigned. It does not affect the
+    /// state of the parser, so it can be used to check whether the language needs
+    /// to be changed before calling [set_language](Parser::set_language).
     pub fn language(&self) -> Option<Language> {
         let ptr = unsafe { ffi::ts_parser_language(self.0.as_ptr()) };
         if ptr.is_null() {
@@ -359,6 +781,15 @@ impl Parser {
         unsafe { ffi::ts_parser_print_dot_graphs(self.0.as_ptr(), ffi::dup(fd)) }
     }
 
+    /// Write debugging graphs to the file at the given path, which is created if it
+    /// does not exist, and truncated if it does. See [Parser::print_dot_graphs].
+    #[cfg(unix)]
+    pub fn print_dot_graphs_to(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.print_dot_graphs(&file);
+        Ok(())
+    }
+
     /// Stop the parser from printing debugging graphs while parsing.
     pub fn stop_printing_dot_graphs(&mut self) {
         unsafe { ffi::ts_parser_print_dot_graphs(self.0.as_ptr(), -1) }
@@ -386,10 +817,56 @@ impl Parser {
         )
     }
 
+    /// Parse a slice of UTF8 text, halting if parsing takes longer than the given
+    /// number of milliseconds, and report why parsing stopped.
+    ///
+    /// Unlike [parse](Parser::parse), this never resumes a halted parse: the parser is
+    /// reset before parsing, and again if parsing halts, so it can be used to parse
+    /// another document right away. The parser's own timeout, set with
+    /// [Parser::set_timeout_micros], is restored afterwards. A timeout of zero means
+    /// that there is no time limit.
+    pub fn parse_bytes_with_timeout(&mut self, source: &[u8], timeout_ms: u64) -> ParseResult {
+        if self.language().is_none() {
+            return ParseResult::NoLanguage;
+        }
+        self.reset();
+        let previous_timeout = self.timeout_micros();
+        self.set_timeout_micros(timeout_ms.saturating_mul(1000));
+        let tree = self.parse(source, None);
+        self.set_timeout_micros(previous_timeout);
+        match tree {
+            Some(tree) => ParseResult::Complete(tree),
+            None => {
+                self.reset();
+                let flag = unsafe { self.cancellation_flag() };
+                if matches!(flag, Some(flag) if flag.load(Ordering::SeqCst) != 0) {
+                    ParseResult::Cancelled
+                } else {
+                    ParseResult::Timeout
+                }
+            }
+        }
+    }
+
+    /// Parse a slice of UTF8 text, and find how far into the text the resulting tree
+    /// is free of errors.
+    ///
+    /// The returned offset is the start byte of the first `ERROR` or `MISSING` node
+    /// in the tree, or the length of `source` if the tree has no errors. Returns
+    /// `None` in the same cases as [parse](Parser::parse).
+    pub fn parse_until_error(&mut self, source: &[u8]) -> Option<(usize, Tree)> {
+        let tree = self.parse(source, None)?;
+        let offset = first_error_offset(&mut tree.walk()).unwrap_or(source.len());
+        Some((offset, tree))
+    }
+
     /// Parse a slice of UTF16 text.
     ///
+    /// The byte offsets and columns in the resulting tree count the bytes of the UTF16
+    /// encoding, so they are twice the number of UTF16 code units.
+    ///
     /// # Arguments:
-    /// * `text` The UTF16-encoded text to parse.
+    /// * `input` The UTF16-encoded text to parse.
     /// * `old_tree` A previous syntax tree parsed from the same document.
     ///   If the text of the document has changed since `old_tree` was
     ///   created, then you must edit `old_tree` to match the new text using
@@ -539,6 +1016,24 @@ impl Parser {
         unsafe { ffi::ts_parser_set_timeout_micros(self.0.as_ptr(), timeout_micros) }
     }
 
+    /// Get the maximum number of *missing* nodes that the parser may insert while
+    /// recovering from errors during a single parse.
+    ///
+    /// This is set via [set_max_error_recovery_nodes](Parser::set_max_error_recovery_nodes).
+    pub fn max_error_recovery_nodes(&self) -> u32 {
+        unsafe { ffi::ts_parser_max_error_recovery_nodes(self.0.as_ptr()) }
+    }
+
+    /// Set the maximum number of *missing* nodes that the parser may insert while
+    /// recovering from errors during a single parse.
+    ///
+    /// Once this limit is reached, the parser stops inserting missing tokens, and
+    /// recovers from any further errors by skipping over trees instead. By default,
+    /// there is no limit.
+    pub fn set_max_error_recovery_nodes(&mut self, count: u32) {
+        unsafe { ffi::ts_parser_set_max_error_recovery_nodes(self.0.as_ptr(), count) }
+    }
+
     /// Set the ranges of text that the parser should include when parsing.
     ///
     /// By default, the parser will always include entire documents. This function
@@ -604,6 +1099,29 @@ impl Parser {
     }
 }
 
+// Find the start byte of the first error or missing node under the cursor's node,
+// skipping the subtrees that have no errors.
+fn first_error_offset(cursor: &mut TreeCursor) -> Option<usize> {
+    let node = cursor.node();
+    if node.is_error() || node.is_missing() {
+        return Some(node.start_byte());
+    }
+    if !node.has_error() {
+        return None;
+    }
+    let mut result = None;
+    if cursor.goto_first_child() {
+        loop {
+            result = first_error_offset(cursor);
+            if result.is_some() || !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+        cursor.goto_parent();
+    }
+    result
+}
+
 impl Drop for Parser {
     fn drop(&mut self) {
         self.stop_printing_dot_graphs();
@@ -623,6 +1141,43 @@ impl Tree {
         Language(unsafe { ffi::ts_tree_language(self.0.as_ptr()) })
     }
 
+    /// Check whether the syntax tree was produced by the given language.
+    ///
+    /// The tree's node kind ids only have meaning for the language that produced it,
+    /// so a tree that was parsed before its language was regenerated or reloaded
+    /// should not be passed to [Parser::parse] as the old tree. Languages are compared
+    /// by identity, so two separately loaded copies of a language are considered
+    /// different.
+    pub fn is_valid(&self, language: &Language) -> bool {
+        self.language() == *language
+    }
+
+    /// Get the range of bytes that was parsed to produce the syntax tree.
+    ///
+    /// If the tree was parsed with [Parser::set_included_ranges], this spans from the
+    /// start of the first included range to the end of the last one. Otherwise, it
+    /// spans the entire document.
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        let mut count = 0u32;
+        let ranges = unsafe {
+            let ptr = ffi::ts_tree_included_ranges(self.0.as_ptr(), &mut count);
+            slice::from_raw_parts(ptr, count as usize)
+        };
+        let root_end = self.root_node().end_byte();
+        match (ranges.first(), ranges.last()) {
+            (Some(first), Some(last)) => {
+                let start = first.start_byte as usize;
+                let end = if last.end_byte == u32::MAX {
+                    root_end.max(start)
+                } else {
+                    last.end_byte as usize
+                };
+                start..end
+            }
+            _ => 0..root_end,
+        }
+    }
+
     /// Edit the syntax tree to keep it in sync with source code that has been
     /// edited.
     ///
@@ -739,6 +1294,74 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_has_error(self.0) }
     }
 
+    /// Check if this node represents a syntax error or contains any syntax errors anywhere
+    /// within it. This is the same as [Node::has_error].
+    pub fn has_errors(&self) -> bool {
+        self.has_error()
+    }
+
+    /// Get all of the *error* and *missing* nodes within this node, including the node
+    /// itself, in the order that they appear in the tree.
+    ///
+    /// Subtrees that don't contain any syntax errors are skipped without visiting their
+    /// descendants.
+    pub fn all_errors(&self) -> Vec<Node<'tree>> {
+        let mut result = Vec::new();
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_error() || node.is_missing() {
+                result.push(node);
+            }
+            if node.has_error() && cursor.goto_first_child() {
+                continue;
+            }
+            loop {
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Get the fraction of this node's source code that was parsed without errors,
+    /// for tracking the quality of a grammar across many files.
+    ///
+    /// This is the number of bytes that are not within an `ERROR` node, divided by the
+    /// total number of bytes in the node. Whitespace is not counted, so a node with no
+    /// other text has a coverage of `1.0`.
+    pub fn coverage(&self, source: &[u8]) -> f64 {
+        let count_bytes = |range: std::ops::Range<usize>| {
+            source.get(range).map_or(0, |text| {
+                text.iter()
+                    .filter(|byte| !byte.is_ascii_whitespace())
+                    .count()
+            })
+        };
+        let total_bytes = count_bytes(self.byte_range());
+        if total_bytes == 0 {
+            return 1.0;
+        }
+        let mut error_bytes = 0;
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_error() {
+                error_bytes += count_bytes(node.byte_range());
+            } else if node.has_error() && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return (total_bytes - error_bytes) as f64 / total_bytes as f64;
+                }
+            }
+        }
+    }
+
     /// Check if this node represents a syntax error.
     ///
     /// Syntax errors represent parts of the code that could not be incorporated into a
@@ -755,6 +1378,11 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_is_missing(self.0) }
     }
 
+    /// Check if this node is a *leaf*, meaning that it has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.child_count() == 0
+    }
+
     /// Get the byte offsets where this node starts.
     pub fn start_byte(&self) -> usize {
         unsafe { ffi::ts_node_start_byte(self.0) as usize }
@@ -770,6 +1398,20 @@ impl<'tree> Node<'tree> {
         self.start_byte()..self.end_byte()
     }
 
+    /// Get the number of bytes between the end of this node and the start of the
+    /// given node, which is expected to come after it.
+    ///
+    /// Returns `None` if the given node starts before this node ends.
+    pub fn byte_gap_to(&self, next: Node) -> Option<usize> {
+        next.start_byte().checked_sub(self.end_byte())
+    }
+
+    /// Check if there are any bytes between the end of this node and the start of
+    /// the given node, according to [Node::byte_gap_to].
+    pub fn has_gap_to(&self, next: Node) -> bool {
+        self.byte_gap_to(next).unwrap_or(0) > 0
+    }
+
     /// Get the range of source code that this node represents, both in terms of raw bytes
     /// and of row/column coordinates.
     pub fn range(&self) -> Range {
@@ -781,6 +1423,33 @@ impl<'tree> Node<'tree> {
         }
     }
 
+    /// Check if the given byte offset is within this node. A node's range includes
+    /// its start byte but not its end byte, so an empty node contains no bytes.
+    pub fn contains_byte(&self, byte: usize) -> bool {
+        self.start_byte() <= byte && byte < self.end_byte()
+    }
+
+    /// Check if the given position is within this node. Like
+    /// [contains_byte](Node::contains_byte), this includes the node's start position
+    /// but not its end position.
+    pub fn contains_point(&self, point: Point) -> bool {
+        self.start_position() <= point && point < self.end_position()
+    }
+
+    /// Check if the given node's byte range is within this node's byte range. Both
+    /// ranges include their start byte but not their end byte, so a node contains
+    /// itself, and contains an empty node at its end.
+    pub fn contains(&self, other: Node) -> bool {
+        self.start_byte() <= other.start_byte() && other.end_byte() <= self.end_byte()
+    }
+
+    /// Check if this node and the given node have any bytes in common, treating
+    /// both byte ranges the same way as [contains](Node::contains). Nodes that
+    /// are merely adjacent don't overlap, and an empty node overlaps nothing.
+    pub fn overlaps(&self, other: Node) -> bool {
+        self.start_byte() < other.end_byte() && other.start_byte() < self.end_byte()
+    }
+
     /// Get this node's start position in terms of rows and columns.
     pub fn start_position(&self) -> Point {
         let result = unsafe { ffi::ts_node_start_point(self.0) };
@@ -825,6 +1494,26 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_named_child_count(self.0) as usize }
     }
 
+    /// Get the number of this node's children for which the given predicate returns
+    /// `true`.
+    ///
+    /// The children are visited with a tree cursor, without being collected.
+    pub fn child_count_filtered(&self, predicate: impl Fn(Node<'tree>) -> bool) -> usize {
+        let mut cursor = self.walk();
+        let mut count = 0;
+        if cursor.goto_first_child() {
+            loop {
+                if predicate(cursor.node()) {
+                    count += 1;
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        count
+    }
+
     /// Get the first child with the given field name.
     ///
     /// If multiple children may have the same field name, access them using
@@ -848,6 +1537,36 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_child_by_field_id(self.0, field_id) })
     }
 
+    /// Check if the grammar declares the given field for this node's kind.
+    ///
+    /// This consults the kind's fields, as listed by [Language::fields_for_node_type],
+    /// so it is true even if this particular node has no child with that field, such
+    /// as when the field is optional. Use [child_by_field_name](Node::child_by_field_name)
+    /// to get the child itself.
+    pub fn has_field(&self, field_name: &str) -> bool {
+        let language = self.language();
+        match language.field_id_for_name(field_name) {
+            Some(field_id) => language
+                .fields_for_node_type(self.kind_id())
+                .iter()
+                .any(|(id, _)| *id == field_id),
+            None => false,
+        }
+    }
+
+    /// Check if the production that this node was parsed with assigns the given
+    /// field name to any of its children.
+    ///
+    /// Unlike [has_field](Node::has_field), this is specific to the production:
+    /// a field that only appears in some of the kind's productions, such as an
+    /// optional one, is only present if this node was parsed with one of them.
+    pub fn has_field_in_production(&self, field_name: &str) -> bool {
+        match self.language().field_id_for_name(field_name) {
+            Some(field_id) => unsafe { ffi::ts_node_has_field_id_in_production(self.0, field_id) },
+            None => false,
+        }
+    }
+
     /// Iterate over this node's children.
     ///
     /// A [TreeCursor] is used to retrieve the children efficiently. Obtain
@@ -870,6 +1589,32 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Iterate over this node's children that overlap the given range of bytes.
+    ///
+    /// Children that end before `start_byte` are skipped over using the sizes that
+    /// are stored in the tree, without visiting each one, and the iteration stops at
+    /// the first child that starts at or after `end_byte`.
+    pub fn children_in_range(
+        &self,
+        start_byte: usize,
+        end_byte: usize,
+    ) -> impl Iterator<Item = Node<'tree>> {
+        let mut cursor = self.walk();
+        let mut has_child = cursor.goto_first_child_for_byte(start_byte).is_some();
+        iter::from_fn(move || {
+            if !has_child {
+                return None;
+            }
+            let child = cursor.node();
+            if child.start_byte() >= end_byte {
+                has_child = false;
+                return None;
+            }
+            has_child = cursor.goto_next_sibling();
+            Some(child)
+        })
+    }
+
     /// Iterate over this node's named children.
     ///
     /// See also [Node::children].
@@ -931,11 +1676,155 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Iterate over this node's named children, along with the names of the
+    /// fields that they belong to, if any.
+    ///
+    /// Unlike [Node::iter_fields], this includes the named children that don't
+    /// belong to a field, with a field name of `None`.
+    pub fn named_children_with_fields<'a>(
+        &self,
+        cursor: &'a mut TreeCursor<'tree>,
+    ) -> impl Iterator<Item = (Option<&'static str>, Node<'tree>)> + 'a {
+        cursor.reset(*self);
+        let mut has_next = cursor.goto_first_child();
+        iter::from_fn(move || {
+            while has_next {
+                let field_name = cursor.field_name();
+                let node = cursor.node();
+                has_next = cursor.goto_next_sibling();
+                if node.is_named() {
+                    return Some((field_name, node));
+                }
+            }
+            None
+        })
+    }
+
+    /// Iterate over this node's children that belong to fields, along with the
+    /// names of their fields, in the order that the children appear.
+    ///
+    /// Fields that have no child in this node are skipped, and fields with several
+    /// children appear once for each child. Children that don't belong to a field
+    /// are not included.
+    pub fn iter_fields<'a>(
+        &self,
+        cursor: &'a mut TreeCursor<'tree>,
+    ) -> impl Iterator<Item = (&'static str, Node<'tree>)> + 'a {
+        cursor.reset(*self);
+        let mut has_next = cursor.goto_first_child();
+        iter::from_fn(move || {
+            while has_next {
+                let field_name = cursor.field_name();
+                let node = cursor.node();
+                has_next = cursor.goto_next_sibling();
+                if let Some(field_name) = field_name {
+                    return Some((field_name, node));
+                }
+            }
+            None
+        })
+    }
+
     /// Get this node's immediate parent.
     pub fn parent(&self) -> Option<Self> {
         Self::new(unsafe { ffi::ts_node_parent(self.0) })
     }
 
+    /// Get the closest ancestor of this node for which the given predicate returns
+    /// `true`, starting with the node's parent and walking up to the root.
+    ///
+    /// The node itself is not checked. If the predicate panics, the panic is
+    /// propagated to the caller.
+    pub fn closest_ancestor_matching(
+        &self,
+        predicate: impl Fn(Node<'tree>) -> bool,
+    ) -> Option<Self> {
+        let mut ancestor = self.parent();
+        while let Some(node) = ancestor {
+            if predicate(node) {
+                return Some(node);
+            }
+            ancestor = node.parent();
+        }
+        None
+    }
+
+    /// Get the name of the field under which this node appears in its parent, if
+    /// any.
+    pub fn parent_field_name(&self) -> Option<&'static str> {
+        self.parent()
+            .and_then(|parent| self.field_name_in_parent(parent))
+    }
+
+    /// Check if this node is either named or appears under a field in its parent.
+    ///
+    /// This is useful for walking a tree while skipping anonymous punctuation nodes,
+    /// without losing anonymous nodes that are meaningful because of their field,
+    /// such as an operator.
+    pub fn is_named_or_field_named(&self) -> bool {
+        self.is_named() || self.parent_field_name().is_some()
+    }
+
+    /// Get the chain of ancestors of this node, starting with the root node and
+    /// ending with this node itself.
+    ///
+    /// Each node is paired with the name of the field under which it appears in
+    /// its parent, as returned by [parent_field_name](Node::parent_field_name).
+    pub fn path_from_root(&self) -> Vec<(Self, Option<&'static str>)> {
+        let mut result = Vec::new();
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            result.push((node, node.field_name_in_parent(parent)));
+            node = parent;
+        }
+        result.push((node, None));
+        result.reverse();
+        result
+    }
+
+    /// Get the chain of nodes from this node down to the given descendant, starting
+    /// with this node itself and ending with the descendant.
+    ///
+    /// Returns `None` if the given node is not this node or one of its descendants.
+    pub fn path_to(&self, descendant: Self) -> Option<Vec<Self>> {
+        let mut result = vec![descendant];
+        let mut node = descendant;
+        while node != *self {
+            node = node.parent()?;
+            result.push(node);
+        }
+        result.reverse();
+        Some(result)
+    }
+
+    /// Get the number of ancestors of this node. The root node has a depth of zero,
+    /// and each of its children has a depth of one, and so on. Hidden nodes, which
+    /// are never returned by [parent](Node::parent), are not counted.
+    pub fn depth_from_root(&self) -> usize {
+        let mut result = 0;
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            result += 1;
+            node = parent;
+        }
+        result
+    }
+
+    fn field_name_in_parent(&self, parent: Self) -> Option<&'static str> {
+        let mut cursor = parent.walk();
+        if cursor.goto_first_child() {
+            loop {
+                if cursor.node().id() == self.id() {
+                    return cursor.field_name();
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        None
+    }
+
     /// Get this node's next sibling.
     pub fn next_sibling(&self) -> Option<Self> {
         Self::new(unsafe { ffi::ts_node_next_sibling(self.0) })
@@ -956,6 +1845,51 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_prev_named_sibling(self.0) })
     }
 
+    /// Get the closest sibling after this node whose type is `kind`, skipping over any
+    /// siblings of other types.
+    ///
+    /// `ERROR` and `MISSING` nodes are skipped as well, even if their type matches.
+    pub fn next_sibling_of_type(&self, kind: &str) -> Option<Self> {
+        let mut sibling = self.next_sibling();
+        while let Some(node) = sibling {
+            if node.kind() == kind && !node.is_error() && !node.is_missing() {
+                return Some(node);
+            }
+            sibling = node.next_sibling();
+        }
+        None
+    }
+
+    /// Get the closest sibling before this node whose type is `kind`, skipping over any
+    /// siblings of other types.
+    ///
+    /// `ERROR` and `MISSING` nodes are skipped as well, even if their type matches.
+    pub fn prev_sibling_of_type(&self, kind: &str) -> Option<Self> {
+        let mut sibling = self.prev_sibling();
+        while let Some(node) = sibling {
+            if node.kind() == kind && !node.is_error() && !node.is_missing() {
+                return Some(node);
+            }
+            sibling = node.prev_sibling();
+        }
+        None
+    }
+
+    /// Get the number of children of this node's parent, including this node.
+    ///
+    /// Returns `0` for the root node, which has no parent.
+    pub fn sibling_count(&self) -> usize {
+        self.parent().map_or(0, |parent| parent.child_count())
+    }
+
+    /// Get the number of *named* children of this node's parent. This includes this
+    /// node if it is named.
+    ///
+    /// Returns `0` for the root node, which has no parent.
+    pub fn named_sibling_count(&self) -> usize {
+        self.parent().map_or(0, |parent| parent.named_child_count())
+    }
+
     /// Get the smallest node within this node that spans the given range.
     pub fn descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
         Self::new(unsafe {
@@ -984,6 +1918,22 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Check if this node is identical to another node.
+    ///
+    /// Unlike the [PartialEq] implementation, which only compares the nodes' ids, this
+    /// also requires that the two nodes belong to the same tree.
+    pub fn equals(&self, other: Node) -> bool {
+        unsafe { ffi::ts_node_eq(self.0, other.0) }
+    }
+
+    /// Check if this node spans the same range of the source code as another node.
+    ///
+    /// This does not require the two nodes to belong to the same tree, so it can be
+    /// used to find the node at the same location in a tree that has been re-parsed.
+    pub fn is_same_position_as(&self, other: Node) -> bool {
+        self.start_byte() == other.start_byte() && self.end_byte() == other.end_byte()
+    }
+
     pub fn to_sexp(&self) -> String {
         let c_string = unsafe { ffi::ts_node_string(self.0) };
         let result = unsafe { CStr::from_ptr(c_string) }
@@ -994,12 +1944,126 @@ impl<'tree> Node<'tree> {
         result
     }
 
+    /// Format this node and its descendants as an S-expression, according to the
+    /// given options. This is the same as [Node::format_sexp].
+    pub fn sexp(&self, options: &SexpOptions) -> String {
+        self.format_sexp(options)
+    }
+
+    /// Format this node and its descendants as an S-expression, according to the
+    /// given options. With the default options, this is the same as [Node::to_sexp],
+    /// which is the format that is used in corpus tests.
+    pub fn format_sexp(&self, options: &SexpOptions) -> String {
+        // The library's own printer also describes the unexpected characters within
+        // `ERROR` nodes, which can't be recovered from the tree here.
+        if *options == SexpOptions::default() {
+            return self.to_sexp();
+        }
+
+        let mut result = String::new();
+        let mut cursor = self.walk();
+        let mut indent_level = 0;
+        let mut did_visit_children = false;
+        loop {
+            let node = cursor.node();
+            let is_labeled_missing = node.is_missing() && options.label_missing_nodes;
+            let is_delimited = node.is_named() || is_labeled_missing;
+            let is_visible = is_delimited || options.include_anonymous_nodes || indent_level == 0;
+            if did_visit_children {
+                if is_visible && is_delimited {
+                    result.push(')');
+                }
+                if cursor.goto_next_sibling() {
+                    did_visit_children = false;
+                } else if cursor.goto_parent() {
+                    did_visit_children = true;
+                    indent_level -= 1;
+                } else {
+                    break;
+                }
+            } else {
+                if is_visible {
+                    if !result.is_empty() {
+                        if options.indentation > 0 {
+                            result.push('\n');
+                            result.push_str(&" ".repeat(indent_level * options.indentation));
+                        } else {
+                            result.push(' ');
+                        }
+                    }
+                    if options.include_field_names {
+                        if let Some(field_name) = cursor.field_name() {
+                            result.push_str(field_name);
+                            result.push_str(": ");
+                        }
+                    }
+                    if !is_labeled_missing {
+                        if node.is_named() {
+                            result.push('(');
+                            result.push_str(node.kind());
+                        } else {
+                            result.push_str(&format!("{:?}", node.kind()));
+                        }
+                    } else if node.is_named() {
+                        result.push_str(&format!("(MISSING {}", node.kind()));
+                    } else {
+                        result.push_str(&format!("(MISSING {:?}", node.kind()));
+                    }
+                    if options.include_ranges {
+                        let start = node.start_position();
+                        let end = node.end_position();
+                        result.push_str(&format!(
+                            " [{}, {}] - [{}, {}]",
+                            start.row, start.column, end.row, end.column
+                        ));
+                    }
+                    if let Some(source_code) = options.source_code {
+                        if node.is_named() && !node.is_missing() && node.child_count() == 0 {
+                            let text = String::from_utf8_lossy(&source_code[node.byte_range()]);
+                            result.push_str(&format!(" {:?}", text));
+                        }
+                    }
+                }
+                if cursor.goto_first_child() {
+                    did_visit_children = false;
+                    indent_level += 1;
+                } else {
+                    did_visit_children = true;
+                }
+            }
+        }
+        result
+    }
+
     pub fn utf8_text<'a>(&self, source: &'a [u8]) -> Result<&'a str, str::Utf8Error> {
         str::from_utf8(&source[self.start_byte()..self.end_byte()])
     }
 
+    /// Get the number of Unicode code points in this node's text within the given
+    /// source code, as opposed to its number of bytes. If the text is not valid UTF8,
+    /// each invalid sequence is counted as one code point.
+    pub fn text_length(&self, source: &[u8]) -> usize {
+        let text = &source[self.start_byte()..self.end_byte()];
+        match str::from_utf8(text) {
+            Ok(text) => text.chars().count(),
+            Err(_) => String::from_utf8_lossy(text).chars().count(),
+        }
+    }
+
+    /// Check if the given regex matches this node's text within the given source code.
+    ///
+    /// The node's bytes are passed to the regex directly, so the text doesn't need to
+    /// be valid UTF8. The text of a node that spans several lines includes the line
+    /// breaks, so use the regex's multi-line flag (`(?m)`) if `^` and `$` should match
+    /// at the start and end of each line rather than of the whole node.
+    pub fn text_matches_regex(&self, regex: &regex::bytes::Regex, source: &[u8]) -> bool {
+        regex.is_match(&source[self.start_byte()..self.end_byte()])
+    }
+
+    /// Get the text of this node within a UTF16-encoded document that was parsed with
+    /// [Parser::parse_utf16]. The node's byte offsets count two bytes per UTF16 code unit.
     pub fn utf16_text<'a>(&self, source: &'a [u16]) -> &'a [u16] {
-        &source.as_ref()[self.start_byte()..self.end_byte()]
+        &source.as_ref()[self.start_byte() / 2..self.end_byte() / 2]
     }
 
     /// Create a new [TreeCursor] starting from this node.
@@ -1007,6 +2071,44 @@ impl<'tree> Node<'tree> {
         TreeCursor(unsafe { ffi::ts_tree_cursor_new(self.0) }, PhantomData)
     }
 
+    /// Check if this node matches the given query pattern.
+    ///
+    /// The pattern must consist of a single pattern whose outermost node is matched
+    /// against this node itself, not against its descendants. Returns `false` if the
+    /// pattern is invalid, or if it uses predicates like `#eq?` that compare the text
+    /// of nodes, because a node doesn't have access to its source code.
+    ///
+    /// This compiles a new [Query] on every call, which is much slower than matching
+    /// the node against a [Query] that is created once and reused. It is intended for
+    /// one-off checks; code that checks many nodes should use a [QueryCursor].
+    pub fn matches_pattern(&self, pattern: &str, language: &Language) -> bool {
+        const ROOT_CAPTURE_NAME: &str = "__matches_pattern_root";
+        // The capture goes on its own line so that a trailing comment in the pattern
+        // can't comment it out.
+        let query = match Query::new(*language, &format!("{}\n@{}", pattern, ROOT_CAPTURE_NAME)) {
+            Ok(query) => query,
+            Err(_) => return false,
+        };
+        if query.pattern_count() != 1 || !query.text_predicates[0].is_empty() {
+            return false;
+        }
+        let root_capture_index = match query
+            .capture_names()
+            .iter()
+            .position(|name| name == ROOT_CAPTURE_NAME)
+        {
+            Some(index) => index as u32,
+            None => return false,
+        };
+        let mut cursor = QueryCursor::new();
+        let result = cursor.matches(&query, *self, |_| "").any(|m| {
+            m.captures
+                .iter()
+                .any(|c| c.index == root_capture_index && c.node.id() == self.id())
+        });
+        result
+    }
+
     /// Edit this node to keep it in-sync with source code that has been edited.
     ///
     /// This function is only rarely needed. When you edit a syntax tree with the
@@ -1125,6 +2227,11 @@ impl<'a> TreeCursor<'a> {
     }
 
     /// Re-initialize this tree cursor to start at a different node.
+    ///
+    /// The given node becomes the cursor's new root, so the cursor won't move to
+    /// the node's parent or siblings. Resetting a cursor reuses its allocated
+    /// memory, so traversing many subtrees with a single cursor is cheaper than
+    /// creating a new cursor for each of them.
     pub fn reset(&mut self, node: Node<'a>) {
         unsafe { ffi::ts_tree_cursor_reset(&mut self.0, node.0) };
     }
@@ -1163,9 +2270,7 @@ impl Query {
         if ptr.is_null() {
             let offset = error_offset as usize;
             let mut line_start = 0;
-            let mut row = 0;
             let line_containing_error = source.split("\n").find_map(|line| {
-                row += 1;
                 let line_end = line_start + line.len() + 1;
                 if line_end > offset {
                     Some(line)
@@ -1181,22 +2286,20 @@ impl Query {
                 "Unexpected EOF".to_string()
             };
 
-            // if line_containing_error
-            return if error_type != ffi::TSQueryError_TSQueryErrorSyntax {
+            let name = || {
                 let suffix = source.split_at(offset).1;
                 let end_offset = suffix
                     .find(|c| !char::is_alphanumeric(c) && c != '_' && c != '-')
-                    .unwrap_or(source.len());
-                let name = suffix.split_at(end_offset).0.to_string();
-                match error_type {
-                    ffi::TSQueryError_TSQueryErrorNodeType => Err(QueryError::NodeType(row, name)),
-                    ffi::TSQueryError_TSQueryErrorField => Err(QueryError::Field(row, name)),
-                    ffi::TSQueryError_TSQueryErrorCapture => Err(QueryError::Capture(row, name)),
-                    _ => Err(QueryError::Syntax(row, message)),
-                }
-            } else {
-                Err(QueryError::Syntax(row, message))
+                    .unwrap_or(suffix.len());
+                suffix.split_at(end_offset).0.to_string()
+            };
+            let (kind, message) = match error_type {
+                ffi::TSQueryError_TSQueryErrorNodeType => (QueryErrorKind::NodeType, name()),
+                ffi::TSQueryError_TSQueryErrorField => (QueryErrorKind::Field, name()),
+                ffi::TSQueryError_TSQueryErrorCapture => (QueryErrorKind::Capture, name()),
+                _ => (QueryErrorKind::Syntax, message),
             };
+            return Err(QueryError::new(kind, source, offset, message));
         }
 
         let string_count = unsafe { ffi::ts_query_string_count(ptr) };
@@ -1209,6 +2312,7 @@ impl Query {
             property_predicates: Vec::with_capacity(pattern_count),
             property_settings: Vec::with_capacity(pattern_count),
             general_predicates: Vec::with_capacity(pattern_count),
+            predicates: Vec::with_capacity(pattern_count),
         };
 
         // Build a vector of strings to store the capture names.
@@ -1242,7 +2346,21 @@ impl Query {
                 let mut length = 0u32;
                 let raw_predicates =
                     ffi::ts_query_predicates_for_pattern(ptr, i as u32, &mut length as *mut u32);
-                slice::from_raw_parts(raw_predicates, length as usize)
+                if length > 0 {
+                    slice::from_raw_parts(raw_predicates, length as usize)
+                } else {
+                    &[]
+                }
+            };
+
+            let pattern_start = unsafe { ffi::ts_query_start_byte_for_pattern(ptr, i as u32) };
+            let predicate_error = |message: String| {
+                QueryError::new(
+                    QueryErrorKind::Predicate,
+                    source,
+                    pattern_start as usize,
+                    message,
+                )
             };
 
             let type_done = ffi::TSQueryPredicateStepType_TSQueryPredicateStepTypeDone;
@@ -1253,13 +2371,14 @@ impl Query {
             let mut property_predicates = Vec::new();
             let mut property_settings = Vec::new();
             let mut general_predicates = Vec::new();
+            let mut predicates = Vec::new();
             for p in predicate_steps.split(|s| s.type_ == type_done) {
                 if p.is_empty() {
                     continue;
                 }
 
                 if p[0].type_ != type_string {
-                    return Err(QueryError::Predicate(format!(
+                    return Err(predicate_error(format!(
                         "Expected predicate to start with a function name. Got @{}.",
                         result.capture_names[p[0].value_id as usize],
                     )));
@@ -1270,19 +2389,39 @@ impl Query {
                 match operator_name.as_str() {
                     "eq?" | "not-eq?" => {
                         if p.len() != 3 {
-                            return Err(QueryError::Predicate(format!(
+                            return Err(predicate_error(format!(
                                 "Wrong number of arguments to #eq? predicate. Expected 2, got {}.",
                                 p.len() - 1
                             )));
                         }
                         if p[1].type_ != type_capture {
-                            return Err(QueryError::Predicate(format!(
+                            return Err(predicate_error(format!(
                                 "First argument to #eq? predicate must be a capture name. Got literal \"{}\".",
                                 string_values[p[1].value_id as usize],
                             )));
                         }
 
                         let is_positive = operator_name == "eq?";
+                        let value = if p[2].type_ == type_capture {
+                            QueryPredicateArg::Capture(p[2].value_id)
+                        } else {
+                            QueryPredicateArg::String(
+                                string_values[p[2].value_id as usize]
+                                    .clone()
+                                    .into_boxed_str(),
+                            )
+                        };
+                        predicates.push(if is_positive {
+                            QueryPatternPredicate::Eq {
+                                capture: p[1].value_id,
+                                value,
+                            }
+                        } else {
+                            QueryPatternPredicate::NotEq {
+                                capture: p[1].value_id,
+                                value,
+                            }
+                        });
                         text_predicates.push(if p[2].type_ == type_capture {
                             TextPredicate::CaptureEqCapture(
                                 p[1].value_id,
@@ -1300,65 +2439,87 @@ impl Query {
 
                     "match?" => {
                         if p.len() != 3 {
-                            return Err(QueryError::Predicate(format!(
+                            return Err(predicate_error(format!(
                                 "Wrong number of arguments to #match? predicate. Expected 2, got {}.",
                                 p.len() - 1
                             )));
                         }
                         if p[1].type_ != type_capture {
-                            return Err(QueryError::Predicate(format!(
+                            return Err(predicate_error(format!(
                                 "First argument to #match? predicate must be a capture name. Got literal \"{}\".",
                                 string_values[p[1].value_id as usize],
                             )));
                         }
                         if p[2].type_ == type_capture {
-                            return Err(QueryError::Predicate(format!(
+                            return Err(predicate_error(format!(
                                 "Second argument to #match? predicate must be a literal. Got capture @{}.",
                                 result.capture_names[p[2].value_id as usize],
                             )));
                         }
 
                         let regex = &string_values[p[2].value_id as usize];
+                        predicates.push(QueryPatternPredicate::Match {
+                            capture: p[1].value_id,
+                            regex: regex.clone().into_boxed_str(),
+                        });
                         text_predicates.push(TextPredicate::CaptureMatchString(
                             p[1].value_id,
                             regex::bytes::Regex::new(regex).map_err(|_| {
-                                QueryError::Predicate(format!("Invalid regex '{}'", regex))
+                                predicate_error(format!("Invalid regex '{}'", regex))
                             })?,
                         ));
                     }
 
-                    "set!" => property_settings.push(Self::parse_property(
-                        "set!",
-                        &result.capture_names,
-                        &string_values,
-                        &p[1..],
-                    )?),
+                    "set!" => {
+                        let property = Self::parse_property(
+                            "set!",
+                            &result.capture_names,
+                            &string_values,
+                            &p[1..],
+                        )
+                        .map_err(predicate_error)?;
+                        predicates.push(QueryPatternPredicate::Set(property.clone()));
+                        property_settings.push(property);
+                    }
 
-                    "is?" | "is-not?" => property_predicates.push((
-                        Self::parse_property(
+                    "is?" | "is-not?" => {
+                        let property = Self::parse_property(
                             &operator_name,
                             &result.capture_names,
                             &string_values,
                             &p[1..],
-                        )?,
-                        operator_name == "is?",
-                    )),
-
-                    _ => general_predicates.push(QueryPredicate {
-                        operator: operator_name.clone().into_boxed_str(),
-                        args: p[1..]
-                            .iter()
-                            .map(|a| {
-                                if a.type_ == type_capture {
-                                    QueryPredicateArg::Capture(a.value_id)
-                                } else {
-                                    QueryPredicateArg::String(
-                                        string_values[a.value_id as usize].clone().into_boxed_str(),
-                                    )
-                                }
-                            })
-                            .collect(),
-                    }),
+                        )
+                        .map_err(predicate_error)?;
+                        let is_positive = operator_name == "is?";
+                        predicates.push(if is_positive {
+                            QueryPatternPredicate::Is(property.clone())
+                        } else {
+                            QueryPatternPredicate::IsNot(property.clone())
+                        });
+                        property_predicates.push((property, is_positive));
+                    }
+
+                    _ => {
+                        let predicate = QueryPredicate {
+                            operator: operator_name.clone().into_boxed_str(),
+                            args: p[1..]
+                                .iter()
+                                .map(|a| {
+                                    if a.type_ == type_capture {
+                                        QueryPredicateArg::Capture(a.value_id)
+                                    } else {
+                                        QueryPredicateArg::String(
+                                            string_values[a.value_id as usize]
+                                                .clone()
+                                                .into_boxed_str(),
+                                        )
+                                    }
+                                })
+                                .collect(),
+                        };
+                        predicates.push(QueryPatternPredicate::Other(predicate.clone()));
+                        general_predicates.push(predicate);
+                    }
                 }
             }
 
@@ -1374,11 +2535,19 @@ impl Query {
             result
                 .general_predicates
                 .push(general_predicates.into_boxed_slice());
+            result.predicates.push(predicates.into_boxed_slice());
         }
         Ok(result)
     }
 
     /// Get the byte offset where the given pattern starts in the query's source.
+    ///
+    /// This can be used to show the user where a matching pattern was written, or to
+    /// map patterns back to their source when queries are built by concatenating
+    /// several query files.
+    ///
+    /// Panics if `pattern_index` is not less than the query's
+    /// [pattern_count](Query::pattern_count).
     pub fn start_byte_for_pattern(&self, pattern_index: usize) -> usize {
         if pattern_index >= self.text_predicates.len() {
             panic!(
@@ -1402,6 +2571,12 @@ impl Query {
         &self.capture_names
     }
 
+    /// Get the name of the capture with the given index, as found in a
+    /// [QueryCapture](QueryCapture::index).
+    pub fn capture_name_for_id(&self, id: u32) -> Option<&str> {
+        self.capture_names.get(id as usize).map(String::as_str)
+    }
+
     /// Get the properties that are checked for the given pattern index.
     ///
     /// This includes predicates with the operators `is?` and `is-not?`.
@@ -1427,6 +2602,17 @@ impl Query {
         &self.general_predicates[index]
     }
 
+    /// Get all of the predicates associated with the given pattern index, in the
+    /// order that they appear in the query's source.
+    ///
+    /// Unlike [property_predicates](Query::property_predicates),
+    /// [property_settings](Query::property_settings), and
+    /// [general_predicates](Query::general_predicates), this includes the `eq?`,
+    /// `not-eq?`, and `match?` predicates that the `QueryCursor` evaluates itself.
+    pub fn predicates_for_pattern(&self, index: usize) -> &[QueryPatternPredicate] {
+        &self.predicates[index]
+    }
+
     /// Disable a certain capture within a query.
     ///
     /// This prevents the capture from being returned in matches, and also avoids any
@@ -1444,23 +2630,36 @@ impl Query {
     /// Disable a certain pattern within a query.
     ///
     /// This prevents the pattern from matching, and also avoids any resource usage
-    /// associated with the pattern.
+    /// associated with the pattern. The pattern can be re-enabled with
+    /// [enable_pattern](Query::enable_pattern).
+    ///
+    /// This modifies the query itself, so it affects every subsequent execution of
+    /// the query. It must not be called while the query is being used on another thread.
     pub fn disable_pattern(&mut self, index: usize) {
         unsafe { ffi::ts_query_disable_pattern(self.ptr.as_ptr(), index as u32) }
     }
 
+    /// Re-enable a pattern that was previously disabled with
+    /// [disable_pattern](Query::disable_pattern).
+    ///
+    /// Like `disable_pattern`, this modifies the query itself, and must not be called
+    /// while the query is being used on another thread.
+    pub fn enable_pattern(&mut self, index: usize) {
+        unsafe { ffi::ts_query_enable_pattern(self.ptr.as_ptr(), index as u32) }
+    }
+
     fn parse_property(
         function_name: &str,
         capture_names: &[String],
         string_values: &[String],
         args: &[ffi::TSQueryPredicateStep],
-    ) -> Result<QueryProperty, QueryError> {
+    ) -> Result<QueryProperty, String> {
         if args.len() == 0 || args.len() > 3 {
-            return Err(QueryError::Predicate(format!(
+            return Err(format!(
                 "Wrong number of arguments to {} predicate. Expected 1 to 3, got {}.",
                 function_name,
                 args.len(),
-            )));
+            ));
         }
 
         let mut capture_id = None;
@@ -1470,10 +2669,10 @@ impl Query {
         for arg in args {
             if arg.type_ == ffi::TSQueryPredicateStepType_TSQueryPredicateStepTypeCapture {
                 if capture_id.is_some() {
-                    return Err(QueryError::Predicate(format!(
+                    return Err(format!(
                         "Invalid arguments to {} predicate. Unexpected second capture name @{}",
                         function_name, capture_names[arg.value_id as usize]
-                    )));
+                    ));
                 }
                 capture_id = Some(arg.value_id as usize);
             } else if key.is_none() {
@@ -1481,20 +2680,20 @@ impl Query {
             } else if value.is_none() {
                 value = Some(string_values[arg.value_id as usize].as_str());
             } else {
-                return Err(QueryError::Predicate(format!(
+                return Err(format!(
                     "Invalid arguments to {} predicate. Unexpected third argument @{}",
                     function_name, string_values[arg.value_id as usize]
-                )));
+                ));
             }
         }
 
         if let Some(key) = key {
             Ok(QueryProperty::new(key, value, capture_id))
         } else {
-            return Err(QueryError::Predicate(format!(
+            return Err(format!(
                 "Invalid arguments to {} predicate. Missing key argument",
                 function_name,
-            )));
+            ));
         }
     }
 }
@@ -1535,6 +2734,27 @@ impl QueryCursor {
         })
     }
 
+    /// Call the given callback with each of the matches, in the same order as
+    /// [QueryCursor::matches], along with a shared context value.
+    ///
+    /// This avoids holding on to the match iterator, and with it the borrow of
+    /// the cursor. The context is returned once all of the matches have been
+    /// handled, so that a callback can gather results in it through interior
+    /// mutability.
+    pub fn exec_with_context<'a, T: AsRef<[u8]>, C>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'a>,
+        text_callback: impl FnMut(Node<'a>) -> T + 'a,
+        context: C,
+        callback: impl Fn(&QueryMatch<'a>, &C),
+    ) -> C {
+        for m in self.matches(query, node, text_callback) {
+            callback(&m, &context);
+        }
+        context
+    }
+
     /// Iterate over all of the individual captures in the order that they appear.
     ///
     /// This is useful if don't care about which pattern matched, and just want a single,
@@ -1569,6 +2789,12 @@ impl QueryCursor {
         }
         self
     }
+
+    /// Get the number of nodes that this cursor has visited while executing the
+    /// most recent query. Nodes outside of the cursor's range are not counted.
+    pub fn visited_node_count(&self) -> usize {
+        unsafe { ffi::ts_query_cursor_visited_node_count(self.0.as_ptr()) as usize }
+    }
 }
 
 impl<'a> QueryMatch<'a> {
@@ -1624,6 +2850,71 @@ impl<'a> QueryMatch<'a> {
     }
 }
 
+impl<'a> QueryCapture<'a> {
+    /// Get the captured node.
+    pub fn to_node(&self) -> Node<'a> {
+        self.node
+    }
+
+    /// Get a value that displays this capture as `@name: "text"`, using the given
+    /// query for its name and the given source code for its node's text.
+    pub fn display<'b>(&self, query: &'b Query, source: &'b [u8]) -> QueryCaptureDisplay<'a, 'b> {
+        QueryCaptureDisplay {
+            capture: *self,
+            query,
+            source,
+        }
+    }
+}
+
+impl<'a> PartialOrd for QueryCapture<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for QueryCapture<'a> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.node
+            .start_byte()
+            .cmp(&other.node.start_byte())
+            .then_with(|| self.node.end_byte().cmp(&other.node.end_byte()))
+            .then_with(|| self.index.cmp(&other.index))
+            .then_with(|| self.node.id().cmp(&other.node.id()))
+    }
+}
+
+impl<'a, 'b> fmt::Display for QueryCaptureDisplay<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self
+            .query
+            .capture_name_for_id(self.capture.index)
+            .unwrap_or("");
+        let text = self
+            .source
+            .get(self.capture.node.byte_range())
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        write!(f, "@{}: {:?}", name, text)
+    }
+}
+
+impl QueryError {
+    fn new(kind: QueryErrorKind, source: &str, offset: usize, message: String) -> Self {
+        let preceding_text = &source.as_bytes()[..offset.min(source.len())];
+        let line_start = preceding_text
+            .iter()
+            .rposition(|c| *c == b'\n')
+            .map_or(0, |i| i + 1);
+        QueryError {
+            kind,
+            row: preceding_text.iter().filter(|c| **c == b'\n').count(),
+            column: preceding_text.len() - line_start,
+            message,
+        }
+    }
+}
+
 impl QueryProperty {
     pub fn new(key: &str, value: Option<&str>, capture_id: Option<usize>) -> Self {
         QueryProperty {
@@ -1679,6 +2970,75 @@ impl Drop for QueryCursor {
     }
 }
 
+impl LookaheadIterator {
+    /// Get the language that this iterator's states belong to.
+    pub fn language(&self) -> Language {
+        Language(unsafe { ffi::ts_lookahead_iterator_language(self.0.as_ptr()) })
+    }
+
+    /// Move to the next valid symbol, returning `false` if there are no more.
+    pub fn advance_lookahead(&mut self) -> bool {
+        unsafe { ffi::ts_lookahead_iterator_next(self.0.as_ptr()) }
+    }
+
+    /// Get the numerical id of the current symbol, without advancing. This is only
+    /// meaningful after the iterator has been advanced at least once.
+    pub fn current_symbol(&self) -> u16 {
+        unsafe { ffi::ts_lookahead_iterator_current_symbol(self.0.as_ptr()) }
+    }
+
+    /// Get the name of the current symbol, without advancing. This is empty if the
+    /// iterator has passed the last symbol.
+    pub fn current_symbol_name(&self) -> &'static str {
+        let ptr = unsafe { ffi::ts_lookahead_iterator_current_symbol_name(self.0.as_ptr()) };
+        if ptr.is_null() {
+            ""
+        } else {
+            unsafe { CStr::from_ptr(ptr) }.to_str().unwrap()
+        }
+    }
+
+    /// Move back to the start of the given parse state's symbols, so that the
+    /// iterator can be reused without allocating a new one.
+    ///
+    /// Returns `false`, leaving the iterator unchanged, if the state is not one of
+    /// the language's parse states.
+    pub fn reset_to(&mut self, state: u16) -> bool {
+        unsafe { ffi::ts_lookahead_iterator_reset_state(self.0.as_ptr(), state) }
+    }
+
+    /// Iterate over the remaining symbols' ids along with their names and whether
+    /// they are named.
+    pub fn with_names(&mut self) -> impl Iterator<Item = (u16, &'static str, bool)> + '_ {
+        let language = self.language();
+        self.map(move |symbol| {
+            (
+                symbol,
+                language.node_kind_for_id(symbol).unwrap_or(""),
+                language.node_kind_is_named(symbol),
+            )
+        })
+    }
+}
+
+impl Iterator for LookaheadIterator {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.advance_lookahead() {
+            Some(self.current_symbol())
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for LookaheadIterator {
+    fn drop(&mut self) {
+        unsafe { ffi::ts_lookahead_iterator_delete(self.0.as_ptr()) }
+    }
+}
+
 impl Point {
     pub fn new(row: usize, column: usize) -> Self {
         Point { row, column }
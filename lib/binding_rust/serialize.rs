@@ -0,0 +1,260 @@
+use super::{Language, Node, Point, Range, TreeCursor};
+
+/// The version of the format written by [Node::serialize]. Serialized nodes with a
+/// different version are rejected by [DetachedNode::deserialize].
+pub const SERIALIZATION_VERSION: u8 = 1;
+
+const MAGIC: &[u8] = b"TSN";
+
+const FLAG_NAMED: u8 = 1 << 0;
+const FLAG_EXTRA: u8 = 1 << 1;
+const FLAG_MISSING: u8 = 1 << 2;
+const FLAG_HAS_ERROR: u8 = 1 << 3;
+
+/// A copy of a syntax node and its descendants that does not refer to a `Tree`,
+/// produced by deserializing the output of [Node::serialize].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetachedNode {
+    pub kind_id: u16,
+    pub kind: &'static str,
+    pub is_named: bool,
+    pub is_extra: bool,
+    pub is_missing: bool,
+    pub has_error: bool,
+    /// The name of the field that this node belongs to within its parent.
+    pub field_name: Option<&'static str>,
+    pub range: Range,
+    /// The source code of the node, if it has no children.
+    pub text: Option<Vec<u8>>,
+    pub children: Vec<DetachedNode>,
+}
+
+impl<'tree> Node<'tree> {
+    /// Encode this node and all of its descendants as a compact byte string, which
+    /// can be sent to another process and decoded with [DetachedNode::deserialize].
+    ///
+    /// The `source` is the text that the tree was parsed from. The text of every node
+    /// without children is included in the output.
+    pub fn serialize(&self, source: &[u8]) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(MAGIC);
+        result.push(SERIALIZATION_VERSION);
+        serialize_node(&mut self.walk(), source, &mut result);
+        result
+    }
+}
+
+impl DetachedNode {
+    /// Decode a node that was encoded with [Node::serialize], using the language
+    /// that the node was parsed with.
+    ///
+    /// Returns `None` if the bytes were written with a different format version, or
+    /// if they are not a valid serialized node for the given language.
+    pub fn deserialize(bytes: &[u8], language: &Language) -> Option<DetachedNode> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.read_bytes(MAGIC.len())? != MAGIC || reader.read_u8()? != SERIALIZATION_VERSION {
+            return None;
+        }
+        let result = deserialize_node(&mut reader, language)?;
+        if reader.offset == bytes.len() {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Get this node's start position in terms of rows and columns.
+    pub fn start_position(&self) -> Point {
+        self.range.start_point
+    }
+
+    /// Get this node's end position in terms of rows and columns.
+    pub fn end_position(&self) -> Point {
+        self.range.end_point
+    }
+
+    /// Get the byte range of source code that this node represents.
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.range.start_byte..self.range.end_byte
+    }
+}
+
+// The node and its descendants are written in pre-order, walking the tree with the
+// cursor rather than recursing, so that deeply nested trees can't overflow the stack.
+fn serialize_node(cursor: &mut TreeCursor, source: &[u8], result: &mut Vec<u8>) {
+    loop {
+        serialize_node_header(cursor, source, result);
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return;
+            }
+        }
+    }
+}
+
+fn serialize_node_header(cursor: &TreeCursor, source: &[u8], result: &mut Vec<u8>) {
+    let node = cursor.node();
+    let mut flags = 0;
+    if node.is_named() {
+        flags |= FLAG_NAMED;
+    }
+    if node.is_extra() {
+        flags |= FLAG_EXTRA;
+    }
+    if node.is_missing() {
+        flags |= FLAG_MISSING;
+    }
+    if node.has_error() {
+        flags |= FLAG_HAS_ERROR;
+    }
+    write_varint(result, node.kind_id() as usize);
+    result.push(flags);
+    write_varint(result, cursor.field_id().unwrap_or(0) as usize);
+    write_varint(result, node.start_byte());
+    write_varint(result, node.end_byte());
+    write_point(result, node.start_position());
+    write_point(result, node.end_position());
+
+    let child_count = node.child_count();
+    write_varint(result, child_count);
+    if child_count == 0 {
+        let text = source.get(node.byte_range()).unwrap_or(&[]);
+        write_varint(result, text.len());
+        result.extend_from_slice(text);
+    }
+}
+
+// Like serialization, this keeps its own stack of the nodes whose children are still
+// being read, so that malformed input with deep nesting returns `None` instead of
+// overflowing the stack.
+fn deserialize_node(reader: &mut Reader, language: &Language) -> Option<DetachedNode> {
+    let mut stack: Vec<(DetachedNode, usize)> = Vec::new();
+    loop {
+        let (mut node, child_count) = deserialize_node_header(reader, language)?;
+        if child_count > 0 {
+            stack.push((node, child_count));
+            continue;
+        }
+        loop {
+            match stack.last_mut() {
+                None => return Some(node),
+                Some((parent, remaining_child_count)) => {
+                    parent.children.push(node);
+                    *remaining_child_count -= 1;
+                    if *remaining_child_count > 0 {
+                        break;
+                    }
+                }
+            }
+            node = stack.pop().unwrap().0;
+        }
+    }
+}
+
+// Read a node without its children, along with the number of children that follow it.
+fn deserialize_node_header(
+    reader: &mut Reader,
+    language: &Language,
+) -> Option<(DetachedNode, usize)> {
+    let kind_id = reader.read_varint()?;
+    if kind_id > u16::MAX as usize {
+        return None;
+    }
+    let kind_id = kind_id as u16;
+    let kind = language.node_kind_for_id(kind_id)?;
+    let flags = reader.read_u8()?;
+    let field_name = match reader.read_varint()? {
+        0 => None,
+        field_id if field_id <= language.field_count() => {
+            Some(language.field_name_for_id(field_id as u16)?)
+        }
+        _ => return None,
+    };
+    let range = Range {
+        start_byte: reader.read_varint()?,
+        end_byte: reader.read_varint()?,
+        start_point: reader.read_point()?,
+        end_point: reader.read_point()?,
+    };
+
+    let child_count = reader.read_varint()?;
+    let mut text = None;
+    if child_count == 0 {
+        let length = reader.read_varint()?;
+        text = Some(reader.read_bytes(length)?.to_vec());
+    }
+
+    let node = DetachedNode {
+        kind_id,
+        kind,
+        is_named: flags & FLAG_NAMED != 0,
+        is_extra: flags & FLAG_EXTRA != 0,
+        is_missing: flags & FLAG_MISSING != 0,
+        has_error: flags & FLAG_HAS_ERROR != 0,
+        field_name,
+        range,
+        text,
+        children: Vec::new(),
+    };
+    Some((node, child_count))
+}
+
+// Numbers are written as LEB128 variable-length integers, so that the small
+// values that make up most of a tree only take up a single byte.
+fn write_varint(result: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            result.push(byte);
+            break;
+        } else {
+            result.push(byte | 0x80);
+        }
+    }
+}
+
+fn write_point(result: &mut Vec<u8>, point: Point) {
+    write_varint(result, point.row);
+    write_varint(result, point.column);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_u8(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.offset)?;
+        self.offset += 1;
+        Some(byte)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(length)?;
+        let result = self.bytes.get(self.offset..end)?;
+        self.offset = end;
+        Some(result)
+    }
+
+    fn read_varint(&mut self) -> Option<usize> {
+        let mut result = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            result |= ((byte & 0x7f) as usize).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_point(&mut self) -> Option<Point> {
+        Some(Point::new(self.read_varint()?, self.read_varint()?))
+    }
+}
@@ -21,7 +21,7 @@ extern "C" {
  * The Tree-sitter library is generally backwards-compatible with languages
  * generated using older CLI versions, but is not forwards-compatible.
  */
-#define TREE_SITTER_LANGUAGE_VERSION 11
+#define TREE_SITTER_LANGUAGE_VERSION 12
 
 /**
  * The earliest ABI version that is supported by the current version of the
@@ -40,6 +40,7 @@ typedef struct TSParser TSParser;
 typedef struct TSTree TSTree;
 typedef struct TSQuery TSQuery;
 typedef struct TSQueryCursor TSQueryCursor;
+typedef struct TSLookaheadIterator TSLookaheadIterator;
 
 typedef enum {
   TSInputEncodingUTF8,
@@ -57,6 +58,29 @@ typedef struct {
   uint32_t column;
 } TSPoint;
 
+/* This is also defined in `parser.h`, so only one definition is used when both
+ * headers are included. */
+#ifndef TREE_SITTER_PARSER_H_
+typedef struct {
+  TSFieldId field_id;
+  bool multiple;
+} TSNodeTypeField;
+#endif
+
+typedef enum {
+  TSTableActionTypeError,
+  TSTableActionTypeShift,
+  TSTableActionTypeReduce,
+  TSTableActionTypeAccept,
+} TSTableActionType;
+
+typedef struct {
+  TSTableActionType type;
+  uint16_t state;
+  TSSymbol symbol;
+  uint16_t child_count;
+} TSTableAction;
+
 typedef struct {
   TSPoint start_point;
   TSPoint end_point;
@@ -300,6 +324,22 @@ void ts_parser_set_timeout_micros(TSParser *self, uint64_t timeout);
  */
 uint64_t ts_parser_timeout_micros(const TSParser *self);
 
+/**
+ * Set the maximum number of *missing* nodes that the parser may insert while
+ * recovering from errors during a single parse.
+ *
+ * Once this limit is reached, the parser stops inserting missing tokens and
+ * instead recovers by skipping trees, which produces a less detailed tree for
+ * badly malformed input. By default, there is no limit.
+ */
+void ts_parser_set_max_error_recovery_nodes(TSParser *self, uint32_t count);
+
+/**
+ * Get the maximum number of missing nodes that the parser may insert during
+ * a single parse.
+ */
+uint32_t ts_parser_max_error_recovery_nodes(const TSParser *self);
+
 /**
  * Set the parser's current cancellation flag pointer.
  *
@@ -363,6 +403,15 @@ TSNode ts_tree_root_node(const TSTree *self);
  */
 const TSLanguage *ts_tree_language(const TSTree *);
 
+/**
+ * Get the ranges of text that were included when parsing the syntax tree.
+ *
+ * The returned pointer is owned by the tree. The caller should not free it
+ * or write to it. The length of the array will be written to the given
+ * `length` pointer.
+ */
+const TSRange *ts_tree_included_ranges(const TSTree *, uint32_t *length);
+
 /**
  * Edit the syntax tree to keep it in sync with source code that has been
  * edited.
@@ -522,6 +571,17 @@ TSNode ts_node_child_by_field_name(
  */
 TSNode ts_node_child_by_field_id(TSNode, TSFieldId);
 
+/**
+ * Check if the production that the node was parsed with assigns the given
+ * numerical field id to any of its children.
+ *
+ * This differs from the fields that the grammar declares for the node's type,
+ * returned by `ts_language_node_type_fields`: a field that only appears in some
+ * of the type's productions, such as an optional one, is only present if the
+ * node was parsed with one of them.
+ */
+bool ts_node_has_field_id_in_production(TSNode, TSFieldId);
+
 /**
  * Get the node's next / previous sibling.
  */
@@ -747,10 +807,17 @@ void ts_query_disable_capture(TSQuery *, const char *, uint32_t);
  * Disable a certain pattern within a query.
  *
  * This prevents the pattern from matching and removes most of the overhead
- * associated with the pattern. Currently, there is no way to undo this.
+ * associated with the pattern. It can be undone with `ts_query_enable_pattern`.
  */
 void ts_query_disable_pattern(TSQuery *, uint32_t);
 
+/**
+ * Re-enable a pattern that was previously disabled with
+ * `ts_query_disable_pattern`. If the pattern is not disabled, this has no
+ * effect.
+ */
+void ts_query_enable_pattern(TSQuery *, uint32_t);
+
 /**
  * Create a new cursor for executing a given query.
  *
@@ -813,6 +880,15 @@ bool ts_query_cursor_next_capture(
   uint32_t *capture_index
 );
 
+/**
+ * Get the number of nodes that the cursor has visited since the query was
+ * last started with `ts_query_cursor_exec`.
+ *
+ * Nodes that are skipped because they are outside of the cursor's range are
+ * not counted.
+ */
+uint32_t ts_query_cursor_visited_node_count(const TSQueryCursor *);
+
 /**********************/
 /* Section - Language */
 /**********************/
@@ -842,6 +918,12 @@ TSSymbol ts_language_symbol_for_name(
  */
 uint32_t ts_language_field_count(const TSLanguage *);
 
+/**
+ * Get the length of the language's alias sequences, which is the greatest
+ * number of children of any of its productions.
+ */
+uint16_t ts_language_max_alias_sequence_length(const TSLanguage *);
+
 /**
  * Get the field name string for the given numerical id.
  */
@@ -860,6 +942,18 @@ TSFieldId ts_language_field_id_for_name(const TSLanguage *, const char *, uint32
  */
 TSSymbolType ts_language_symbol_type(const TSLanguage *, TSSymbol);
 
+/**
+ * Check whether nodes with the given node type id appear in syntax trees. Unlike
+ * `ts_language_symbol_type`, this distinguishes hidden rules from named nodes.
+ */
+bool ts_language_symbol_is_visible(const TSLanguage *, TSSymbol);
+
+/**
+ * Check whether the given node type id belongs to one of the grammar's
+ * supertypes.
+ */
+bool ts_language_symbol_is_supertype(const TSLanguage *, TSSymbol);
+
 /**
  * Get the ABI version number for this language. This version number is used
  * to ensure that languages were generated by a compatible version of
@@ -869,6 +963,138 @@ TSSymbolType ts_language_symbol_type(const TSLanguage *, TSSymbol);
  */
 uint32_t ts_language_version(const TSLanguage *);
 
+/**
+ * Get the name of this language, as specified in its grammar.
+ *
+ * This returns `NULL` for languages that were generated with an older version
+ * of Tree-sitter that did not store the language's name.
+ */
+const char *ts_language_name(const TSLanguage *);
+
+/**
+ * Get the version of the grammar from which this language was generated.
+ *
+ * This returns `NULL` if the grammar did not specify a version, or if the
+ * language was generated with an older version of Tree-sitter that did not
+ * store the grammar's version.
+ */
+const char *ts_language_grammar_version(const TSLanguage *);
+
+/**
+ * Get the number of parse states in the language.
+ *
+ * This returns zero if the language was generated with an older version of
+ * Tree-sitter that did not store the number of parse states.
+ */
+uint32_t ts_language_state_count(const TSLanguage *);
+
+/**
+ * Get the fields that the grammar declares for the given node type, sorted by
+ * field id, and whether each field can contain more than one node. The number
+ * of fields is written to the `count` pointer.
+ *
+ * This returns `NULL` if the node type has no fields, or if the language was
+ * generated with an older version of Tree-sitter that did not store the fields
+ * of each node type. Node types that only exist as aliases have no fields
+ * stored, even if the rules that they alias have fields.
+ */
+const TSNodeTypeField *ts_language_node_type_fields(
+  const TSLanguage *,
+  TSSymbol,
+  uint32_t *count
+);
+
+/**
+ * Get the field that the grammar assigns to the child at the given index in
+ * nodes of the given type. The index counts children the way `ts_node_child`
+ * does, for a node that contains no extras.
+ *
+ * This returns zero if no production of the node type has a field at that
+ * index, if different productions assign different fields to it, if the index
+ * comes after a hidden child that can contain any number of children, or if
+ * the language was generated with an older version of Tree-sitter that did not
+ * store the fields of each node type.
+ */
+TSFieldId ts_language_field_for_child_index(
+  const TSLanguage *,
+  TSSymbol,
+  uint32_t child_index
+);
+
+/**
+ * Get the action that the parse table of the language specifies for the given
+ * symbol in the given parse state.
+ *
+ * For a token, this is the first of the token's parse actions. A shift action's
+ * `state` is the state that the parser moves to, and a reduce action's `symbol`
+ * and `child_count` describe the node that it creates. For any other symbol, this
+ * is a shift action whose `state` is the state that the parser moves to after
+ * creating a node of that type.
+ *
+ * This returns an action of type `TSTableActionTypeError` if the parser can't
+ * handle the symbol in the state, if the state or the symbol is out of range, or
+ * if the language was generated with an older version of Tree-sitter that did
+ * not store the number of parse states.
+ */
+TSTableAction ts_language_table_action(
+  const TSLanguage *,
+  uint16_t state,
+  TSSymbol symbol
+);
+
+/********************************/
+/* Section - Lookahead Iterator */
+/********************************/
+
+/**
+ * Create a new iterator over the symbols that are valid in the given parse state:
+ * the tokens that have a parse action and the nodes that have a goto action.
+ *
+ * This returns `NULL` if the state is not one of the language's parse states,
+ * which is always the case for languages that don't record their state count.
+ *
+ * The iterator starts before the first symbol, so call
+ * `ts_lookahead_iterator_next` to move to it.
+ */
+TSLookaheadIterator *ts_lookahead_iterator_new(const TSLanguage *, uint16_t state);
+
+/**
+ * Delete a lookahead iterator, freeing all of the memory that it used.
+ */
+void ts_lookahead_iterator_delete(TSLookaheadIterator *);
+
+/**
+ * Move the iterator back to the start of the given parse state's symbols.
+ *
+ * This returns `false`, leaving the iterator unchanged, if the state is not one
+ * of the parse states of the iterator's language.
+ */
+bool ts_lookahead_iterator_reset_state(TSLookaheadIterator *, uint16_t state);
+
+/**
+ * Get the language of a lookahead iterator.
+ */
+const TSLanguage *ts_lookahead_iterator_language(const TSLookaheadIterator *);
+
+/**
+ * Move the iterator to the next valid symbol.
+ *
+ * This returns `true` if the iterator moved to a symbol, and `false` if there
+ * were no more symbols.
+ */
+bool ts_lookahead_iterator_next(TSLookaheadIterator *);
+
+/**
+ * Get the current symbol of a lookahead iterator. This is only meaningful after
+ * `ts_lookahead_iterator_next` has returned `true`.
+ */
+TSSymbol ts_lookahead_iterator_current_symbol(const TSLookaheadIterator *);
+
+/**
+ * Get the name of the current symbol of a lookahead iterator.
+ */
+const char *ts_lookahead_iterator_current_symbol_name(const TSLookaheadIterator *);
+
 #ifdef __cplusplus
 }
 #endif
@@ -17,6 +17,11 @@ extern "C" {
 typedef uint16_t TSSymbol;
 typedef uint16_t TSFieldId;
 typedef struct TSLanguage TSLanguage;
+
+typedef struct {
+  TSFieldId field_id;
+  bool multiple;
+} TSNodeTypeField;
 #endif
 
 typedef struct {
@@ -35,6 +40,7 @@ typedef uint16_t TSStateId;
 typedef struct {
   bool visible : 1;
   bool named : 1;
+  bool supertype : 1;
 } TSSymbolMetadata;
 
 typedef struct TSLexer TSLexer;
@@ -119,6 +125,13 @@ struct TSLanguage {
   const uint16_t *small_parse_table;
   const uint32_t *small_parse_table_map;
   const TSSymbol *public_symbol_map;
+  const char *name;
+  const char *grammar_version;
+  uint32_t state_count;
+  const TSFieldMapSlice *node_type_field_slices;
+  const TSNodeTypeField *node_type_fields;
+  const TSFieldMapSlice *node_type_child_field_slices;
+  const TSFieldId *node_type_child_fields;
 };
 
 /*
@@ -1,6 +1,7 @@
 #include "./language.h"
 #include "./subtree.h"
 #include "./error_costs.h"
+#include "./alloc.h"
 #include <string.h>
 
 uint32_t ts_language_symbol_count(const TSLanguage *self) {
@@ -11,6 +12,109 @@ uint32_t ts_language_version(const TSLanguage *self) {
   return self->version;
 }
 
+const char *ts_language_name(const TSLanguage *self) {
+  if (self->version >= TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO) {
+    return self->name;
+  } else {
+    return NULL;
+  }
+}
+
+const char *ts_language_grammar_version(const TSLanguage *self) {
+  if (self->version >= TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO) {
+    return self->grammar_version;
+  } else {
+    return NULL;
+  }
+}
+
+uint32_t ts_language_state_count(const TSLanguage *self) {
+  if (self->version >= TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO) {
+    return self->state_count;
+  } else {
+    return 0;
+  }
+}
+
+const TSNodeTypeField *ts_language_node_type_fields(
+  const TSLanguage *self,
+  TSSymbol symbol,
+  uint32_t *count
+) {
+  *count = 0;
+  if (
+    self->version < TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO ||
+    !self->node_type_field_slices ||
+    symbol >= ts_language_symbol_count(self)
+  ) return NULL;
+
+  TSFieldMapSlice slice = self->node_type_field_slices[symbol];
+  if (slice.length == 0) return NULL;
+  *count = slice.length;
+  return &self->node_type_fields[slice.index];
+}
+
+TSFieldId ts_language_field_for_child_index(
+  const TSLanguage *self,
+  TSSymbol symbol,
+  uint32_t child_index
+) {
+  if (
+    self->version < TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO ||
+    !self->node_type_child_field_slices ||
+    symbol >= ts_language_symbol_count(self)
+  ) return 0;
+
+  TSFieldMapSlice slice = self->node_type_child_field_slices[symbol];
+  if (child_index >= slice.length) return 0;
+  return self->node_type_child_fields[slice.index + child_index];
+}
+
+TSTableAction ts_language_table_action(
+  const TSLanguage *self,
+  uint16_t state,
+  TSSymbol symbol
+) {
+  TSTableAction result = {.type = TSTableActionTypeError};
+  if (
+    state >= ts_language_state_count(self) ||
+    symbol >= self->symbol_count ||
+    symbol == ts_builtin_sym_error ||
+    symbol == ts_builtin_sym_error_repeat
+  ) return result;
+
+  if (symbol >= self->token_count) {
+    TSStateId next_state = ts_language_lookup(self, state, symbol);
+    if (next_state != 0) {
+      result.type = TSTableActionTypeShift;
+      result.state = next_state;
+    }
+    return result;
+  }
+
+  uint32_t action_count;
+  const TSParseAction *actions = ts_language_actions(self, state, symbol, &action_count);
+  if (action_count == 0) return result;
+  const TSParseAction *action = &actions[0];
+  switch (action->type) {
+    case TSParseActionTypeShift:
+      result.type = TSTableActionTypeShift;
+      result.state = action->params.shift.extra ? state : action->params.shift.state;
+      break;
+    case TSParseActionTypeReduce:
+      result.type = TSTableActionTypeReduce;
+      result.symbol = ts_language_public_symbol(self, action->params.reduce.symbol);
+      result.child_count = action->params.reduce.child_count;
+      break;
+    case TSParseActionTypeAccept:
+      result.type = TSTableActionTypeAccept;
+      break;
+    default:
+      break;
+  }
+  return result;
+}
+
 uint32_t ts_language_field_count(const TSLanguage *self) {
   if (self->version >= TREE_SITTER_LANGUAGE_VERSION_WITH_FIELDS) {
     return self->field_count;
@@ -19,6 +123,10 @@ uint32_t ts_language_field_count(const TSLanguage *self) {
   }
 }
 
+uint16_t ts_language_max_alias_sequence_length(const TSLanguage *self) {
+  return self->max_alias_sequence_length;
+}
+
 void ts_language_table_entry(
   const TSLanguage *self,
   TSStateId state,
@@ -116,6 +224,20 @@ TSSymbolType ts_language_symbol_type(
   }
 }
 
+bool ts_language_symbol_is_visible(
+  const TSLanguage *self,
+  TSSymbol symbol
+) {
+  return ts_language_symbol_metadata(self, symbol).visible;
+}
+
+bool ts_language_symbol_is_supertype(
+  const TSLanguage *self,
+  TSSymbol symbol
+) {
+  return ts_language_symbol_metadata(self, symbol).supertype;
+}
+
 const char *ts_language_field_name_for_id(
   const TSLanguage *self,
   TSFieldId id
@@ -147,3 +269,61 @@ TSFieldId ts_language_field_id_for_name(
   }
   return 0;
 }
+
+struct TSLookaheadIterator {
+  const TSLanguage *language;
+  TSStateId state;
+  uint32_t symbol;
+  bool started;
+};
+
+TSLookaheadIterator *ts_lookahead_iterator_new(const TSLanguage *language, uint16_t state) {
+  if (state >= ts_language_state_count(language)) return NULL;
+  TSLookaheadIterator *self = ts_malloc(sizeof(TSLookaheadIterator));
+  self->language = language;
+  ts_lookahead_iterator_reset_state(self, state);
+  return self;
+}
+
+void ts_lookahead_iterator_delete(TSLookaheadIterator *self) {
+  ts_free(self);
+}
+
+bool ts_lookahead_iterator_reset_state(TSLookaheadIterator *self, uint16_t state) {
+  if (state >= ts_language_state_count(self->language)) return false;
+  self->state = state;
+  self->symbol = 0;
+  self->started = false;
+  return true;
+}
+
+const TSLanguage *ts_lookahead_iterator_language(const TSLookaheadIterator *self) {
+  return self->language;
+}
+
+bool ts_lookahead_iterator_next(TSLookaheadIterator *self) {
+  const TSLanguage *language = self->language;
+  uint32_t symbol = self->started ? self->symbol + 1 : 0;
+  self->started = true;
+  for (; symbol < language->symbol_count; symbol++) {
+    bool is_valid = symbol < language->token_count
+      ? ts_language_has_actions(language, self->state, symbol)
+      : ts_language_lookup(language, self->state, symbol) != 0;
+    if (is_valid) {
+      self->symbol = symbol;
+      return true;
+    }
+  }
+  self->symbol = language->symbol_count;
+  return false;
+}
+
+TSSymbol ts_lookahead_iterator_current_symbol(const TSLookaheadIterator *self) {
+  if (self->symbol >= self->language->symbol_count) return 0;
+  return ts_language_public_symbol(self->language, self->symbol);
+}
+
+const char *ts_lookahead_iterator_current_symbol_name(const TSLookaheadIterator *self) {
+  if (self->symbol >= self->language->symbol_count) return NULL;
+  return ts_language_symbol_name(self->language, self->symbol);
+}
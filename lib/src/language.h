@@ -12,6 +12,7 @@ extern "C" {
 #define TREE_SITTER_LANGUAGE_VERSION_WITH_FIELDS 10
 #define TREE_SITTER_LANGUAGE_VERSION_WITH_SYMBOL_DEDUPING 11
 #define TREE_SITTER_LANGUAGE_VERSION_WITH_SMALL_STATES 11
+#define TREE_SITTER_LANGUAGE_VERSION_WITH_LANGUAGE_INFO 12
 
 typedef struct {
   const TSParseAction *actions;
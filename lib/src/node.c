@@ -585,6 +585,22 @@ TSNode ts_node_child_by_field_name(
   return ts_node_child_by_field_id(self, field_id);
 }
 
+bool ts_node_has_field_id_in_production(TSNode self, TSFieldId field_id) {
+  if (!field_id || ts_node_child_count(self) == 0) return false;
+
+  const TSFieldMapEntry *field_map, *field_map_end;
+  ts_language_field_map(
+    self.tree->language,
+    ts_node__subtree(self).ptr->production_id,
+    &field_map,
+    &field_map_end
+  );
+  for (; field_map < field_map_end; field_map++) {
+    if (field_map->field_id == field_id) return true;
+  }
+  return false;
+}
+
 uint32_t ts_node_child_count(TSNode self) {
   Subtree tree = ts_node__subtree(self);
   if (ts_subtree_child_count(tree) > 0) {
@@ -74,6 +74,8 @@ struct TSParser {
   Subtree old_tree;
   TSRangeArray included_range_differences;
   unsigned included_range_difference_index;
+  unsigned missing_token_count;
+  unsigned max_error_recovery_nodes;
 };
 
 typedef struct {
@@ -1017,7 +1019,7 @@ static void ts_parser__handle_error(
   // were created in the previous step.
   bool did_insert_missing_token = false;
   for (StackVersion v = version; v < version_count;) {
-    if (!did_insert_missing_token) {
+    if (!did_insert_missing_token && self->missing_token_count < self->max_error_recovery_nodes) {
       TSStateId state = ts_stack_state(self->stack, v);
       for (TSSymbol missing_symbol = 1;
            missing_symbol < self->language->token_count;
@@ -1061,6 +1063,7 @@ static void ts_parser__handle_error(
               ts_stack_state(self->stack, version_with_missing_tree)
             );
             did_insert_missing_token = true;
+            self->missing_token_count++;
             break;
           }
         }
@@ -1647,6 +1650,8 @@ TSParser *ts_parser_new(void) {
   self->scratch_tree.ptr = &self->scratch_tree_data;
   self->included_range_differences = (TSRangeArray) array_new();
   self->included_range_difference_index = 0;
+  self->missing_token_count = 0;
+  self->max_error_recovery_nodes = UINT32_MAX;
   ts_parser__set_cached_token(self, 0, NULL_SUBTREE, NULL_SUBTREE);
   return self;
 }
@@ -1734,6 +1739,14 @@ void ts_parser_set_timeout_micros(TSParser *self, uint64_t timeout_micros) {
   self->timeout_duration = duration_from_micros(timeout_micros);
 }
 
+uint32_t ts_parser_max_error_recovery_nodes(const TSParser *self) {
+  return self->max_error_recovery_nodes;
+}
+
+void ts_parser_set_max_error_recovery_nodes(TSParser *self, uint32_t count) {
+  self->max_error_recovery_nodes = count;
+}
+
 bool ts_parser_set_included_ranges(
   TSParser *self,
   const TSRange *ranges,
@@ -1765,6 +1778,7 @@ void ts_parser_reset(TSParser *self) {
     self->finished_tree = NULL_SUBTREE;
   }
   self->accept_count = 0;
+  self->missing_token_count = 0;
 }
 
 TSTree *ts_parser_parse(
@@ -148,6 +148,7 @@ struct TSQuery {
   SymbolTable predicate_values;
   Array(QueryStep) steps;
   Array(PatternEntry) pattern_map;
+  Array(PatternEntry) disabled_pattern_entries;
   Array(TSQueryPredicateStep) predicate_steps;
   Array(Slice) predicates_by_pattern;
   Array(uint32_t) start_bytes_by_pattern;
@@ -169,6 +170,7 @@ struct TSQueryCursor {
   uint32_t start_byte;
   uint32_t end_byte;
   uint32_t next_state_id;
+  uint32_t visited_node_count;
   TSPoint start_point;
   TSPoint end_point;
   bool ascending;
@@ -552,6 +554,34 @@ static inline void ts_query__pattern_map_insert(
   }));
 }
 
+// Insert a pattern map entry that was previously removed, at the same position
+// that `ts_query__pattern_map_insert` gave it when the query was created. Entries
+// with the same symbol were inserted in the order of their step indices: each
+// wildcard entry after the existing ones, and each other entry before them.
+static inline void ts_query__pattern_map_restore(
+  TSQuery *self,
+  TSSymbol symbol,
+  PatternEntry entry
+) {
+  uint32_t index;
+  if (symbol == WILDCARD_SYMBOL) {
+    index = 0;
+    while (
+      index < self->wildcard_root_pattern_count &&
+      self->pattern_map.contents[index].step_index < entry.step_index
+    ) index++;
+  } else {
+    ts_query__pattern_map_search(self, symbol, &index);
+    while (index < self->pattern_map.size) {
+      PatternEntry *existing = &self->pattern_map.contents[index];
+      if (self->steps.contents[existing->step_index].symbol != symbol) break;
+      if (existing->step_index < entry.step_index) break;
+      index++;
+    }
+  }
+  array_insert(&self->pattern_map, index, entry);
+}
+
 static void ts_query__finalize_steps(TSQuery *self) {
   for (unsigned i = 0; i < self->steps.size; i++) {
     QueryStep *step = &self->steps.contents[i];
@@ -1105,6 +1135,7 @@ TSQuery *ts_query_new(
   *self = (TSQuery) {
     .steps = array_new(),
     .pattern_map = array_new(),
+    .disabled_pattern_entries = array_new(),
     .captures = symbol_table_new(),
     .predicate_values = symbol_table_new(),
     .predicate_steps = array_new(),
@@ -1176,6 +1207,7 @@ void ts_query_delete(TSQuery *self) {
   if (self) {
     array_delete(&self->steps);
     array_delete(&self->pattern_map);
+    array_delete(&self->disabled_pattern_entries);
     array_delete(&self->predicate_steps);
     array_delete(&self->predicates_by_pattern);
     array_delete(&self->start_bytes_by_pattern);
@@ -1253,16 +1285,41 @@ void ts_query_disable_pattern(
   uint32_t pattern_index
 ) {
   // Remove the given pattern from the pattern map. Its steps will still
-  // be in the `steps` array, but they will never be read.
+  // be in the `steps` array, but they will never be read. The removed
+  // entries are retained so that the pattern can be re-enabled later.
   for (unsigned i = 0; i < self->pattern_map.size; i++) {
-    PatternEntry *pattern = &self->pattern_map.contents[i];
-    if (pattern->pattern_index == pattern_index) {
+    PatternEntry pattern = self->pattern_map.contents[i];
+    if (pattern.pattern_index == pattern_index) {
+      if (self->steps.contents[pattern.step_index].symbol == WILDCARD_SYMBOL) {
+        self->wildcard_root_pattern_count--;
+      }
+      array_push(&self->disabled_pattern_entries, pattern);
       array_erase(&self->pattern_map, i);
       i--;
     }
   }
 }
 
+void ts_query_enable_pattern(
+  TSQuery *self,
+  uint32_t pattern_index
+) {
+  // Restore any pattern map entries that were removed when the given
+  // pattern was disabled, at the positions that they had originally.
+  for (unsigned i = 0; i < self->disabled_pattern_entries.size; i++) {
+    PatternEntry pattern = self->disabled_pattern_entries.contents[i];
+    if (pattern.pattern_index == pattern_index) {
+      TSSymbol symbol = self->steps.contents[pattern.step_index].symbol;
+      ts_query__pattern_map_restore(self, symbol, pattern);
+      if (symbol == WILDCARD_SYMBOL) {
+        self->wildcard_root_pattern_count++;
+      }
+      array_erase(&self->disabled_pattern_entries, i);
+      i--;
+    }
+  }
+}
+
 /***************
  * QueryCursor
  ***************/
@@ -1278,6 +1335,7 @@ TSQueryCursor *ts_query_cursor_new(void) {
     .end_byte = UINT32_MAX,
     .start_point = {0, 0},
     .end_point = POINT_MAX,
+    .visited_node_count = 0,
   };
   array_reserve(&self->states, MAX_STATE_COUNT);
   array_reserve(&self->finished_states, MAX_CAPTURE_LIST_COUNT);
@@ -1302,11 +1360,16 @@ void ts_query_cursor_exec(
   ts_tree_cursor_reset(&self->cursor, node);
   capture_list_pool_reset(&self->capture_list_pool);
   self->next_state_id = 0;
+  self->visited_node_count = 0;
   self->depth = 0;
   self->ascending = false;
   self->query = query;
 }
 
+uint32_t ts_query_cursor_visited_node_count(const TSQueryCursor *self) {
+  return self->visited_node_count;
+}
+
 void ts_query_cursor_set_byte_range(
   TSQueryCursor *self,
   uint32_t start_byte,
@@ -1583,6 +1646,8 @@ static inline bool ts_query_cursor__advance(TSQueryCursor *self) {
         point_lte(self->end_point, ts_node_start_point(node))
       ) return false;
 
+      self->visited_node_count++;
+
       // Get the properties of the current node.
       TSSymbol symbol = ts_node_symbol(node);
       bool is_named = ts_node_is_named(node);
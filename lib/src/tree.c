@@ -47,6 +47,11 @@ const TSLanguage *ts_tree_language(const TSTree *self) {
   return self->language;
 }
 
+const TSRange *ts_tree_included_ranges(const TSTree *self, uint32_t *length) {
+  *length = self->included_range_count;
+  return self->included_ranges;
+}
+
 void ts_tree_edit(TSTree *self, const TSInputEdit *edit) {
   for (unsigned i = 0; i < self->included_range_count; i++) {
     TSRange *range = &self->included_ranges[i];
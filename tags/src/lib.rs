@@ -46,6 +46,14 @@ pub struct Tag {
     pub docs: Option<String>,
 }
 
+/// A `Tag` along with the text of its name. Its documentation is in the tag's own
+/// `docs` field.
+#[derive(Debug, Clone)]
+pub struct TagWithDocs {
+    pub tag: Tag,
+    pub name: String,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TagKind {
     Function,
@@ -239,6 +247,24 @@ impl TagsContext {
             }],
         })
     }
+
+    /// Generate tags for the given source code, pairing each tag with the text
+    /// of its name. The doc string that was extracted via the query's `@doc`
+    /// capture is in the tag's `docs` field.
+    pub fn tag_with_docs<'a>(
+        &'a mut self,
+        config: &'a TagsConfiguration,
+        source: &'a [u8],
+        cancellation_flag: Option<&'a AtomicUsize>,
+    ) -> Result<impl Iterator<Item = Result<TagWithDocs, Error>> + 'a, Error> {
+        let tags = self.generate_tags(config, source, cancellation_flag)?;
+        Ok(tags.map(move |tag| {
+            tag.map(|tag| {
+                let name = String::from_utf8_lossy(&source[tag.name_range.clone()]).to_string();
+                TagWithDocs { tag, name }
+            })
+        }))
+    }
 }
 
 impl<'a, I> Iterator for TagsIter<'a, I>